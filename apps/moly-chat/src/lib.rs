@@ -7,7 +7,7 @@ pub mod screen;
 use makepad_widgets::Cx;
 use moly_widgets::{MolyApp, AppInfo};
 
-pub use screen::{ChatApp, ChatAppRef, ChatHistoryAction};
+pub use screen::{ChatApp, ChatAppRef, ChatHistoryAction, ChatWelcomeAction, FavoritePromptsAction};
 
 /// Main app struct for MolyApp trait implementation
 pub struct MolyChatApp;
@@ -4,7 +4,7 @@
 
 use makepad_widgets::*;
 
-use super::{ChatApp, ChatHistoryItem, ChatHistoryPanel};
+use super::{ChatApp, ChatHistoryItem, ChatHistoryPanel, ChatWelcome, FavoritePromptsBar};
 
 live_design! {
     use link::theme::*;
@@ -104,6 +104,31 @@ live_design! {
                 text: "New Chat"
             }
 
+            // Shown instead of title_label while renaming
+            title_input = <TextInput> {
+                width: Fill, height: Fit
+                visible: false
+                padding: {left: 4, right: 4, top: 2, bottom: 2}
+                empty_text: "Chat name"
+                draw_bg: {
+                    instance dark_mode: 0.0
+                    fn pixel(self) -> vec4 {
+                        let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                        sdf.box(0.5, 0.5, self.rect_size.x - 1.0, self.rect_size.y - 1.0, 3.0);
+                        sdf.fill(mix(#ffffff, #0f172a, self.dark_mode));
+                        sdf.stroke(mix(#3b82f6, #60a5fa, self.dark_mode), 1.0);
+                        return sdf.result;
+                    }
+                }
+                draw_text: {
+                    instance dark_mode: 0.0
+                    fn get_color(self) -> vec4 {
+                        return mix(#1f2937, #f1f5f9, self.dark_mode);
+                    }
+                    text_style: { font_size: 12.0 }
+                }
+            }
+
             date_label = <Label> {
                 width: Fill
                 draw_text: {
@@ -117,6 +142,42 @@ live_design! {
             }
         }
 
+        // Pin toggle: a filled dot when pinned, a ring otherwise
+        pin_button = <View> {
+            width: 24, height: 24
+            cursor: Hand
+            show_bg: true
+            draw_bg: {
+                instance hover: 0.0
+                instance pinned: 0.0
+                instance dark_mode: 0.0
+                fn pixel(self) -> vec4 {
+                    let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                    let center = self.rect_size / 2.0;
+                    let accent = mix(#3b82f6, #60a5fa, self.dark_mode);
+                    let idle = mix(#d1d5db, #4b5563, self.dark_mode);
+                    sdf.circle(center.x, center.y, 4.0);
+                    sdf.fill_keep(mix(vec4(0.0, 0.0, 0.0, 0.0), accent, self.pinned));
+                    sdf.stroke(mix(mix(idle, accent, self.hover), accent, self.pinned), 1.2);
+                    return sdf.result;
+                }
+            }
+
+            animator: {
+                hover = {
+                    default: off
+                    off = {
+                        from: {all: Forward {duration: 0.1}}
+                        apply: { draw_bg: {hover: 0.0} }
+                    }
+                    on = {
+                        from: {all: Forward {duration: 0.1}}
+                        apply: { draw_bg: {hover: 1.0} }
+                    }
+                }
+            }
+        }
+
         // Right side: delete button (visible on hover)
         delete_button = <View> {
             width: 24, height: 24
@@ -159,9 +220,164 @@ live_design! {
         }
     }
 
+    // Small text-only button used in the chat header
+    HeaderLinkButton = <Button> {
+        width: Fit, height: Fit
+        padding: {left: 4, right: 4, top: 2, bottom: 2}
+        draw_bg: {
+            fn pixel(self) -> vec4 {
+                return vec4(0.0, 0.0, 0.0, 0.0);
+            }
+        }
+        draw_text: {
+            instance dark_mode: 0.0
+            instance hover: 0.0
+            fn get_color(self) -> vec4 {
+                let base = mix(#3b82f6, #60a5fa, self.dark_mode);
+                let hover = mix(#1d4ed8, #93c5fd, self.dark_mode);
+                return mix(base, hover, self.hover);
+            }
+            text_style: <THEME_FONT_REGULAR>{ font_size: 11.0 }
+        }
+    }
+
+    // Strip under each moly-kit message row with its number, link, copy
+    // and edit buttons and model
+    MessageGutter = <View> {
+        width: Fill, height: Fit
+        visible: false
+        flow: Right
+        align: {y: 0.5}
+        spacing: 8
+        padding: {top: 2, bottom: 2}
+
+        message_number = <Label> {
+            text: ""
+            draw_text: {
+                instance dark_mode: 0.0
+                fn get_color(self) -> vec4 {
+                    return mix(#6b7280, #9ca3af, self.dark_mode);
+                }
+                text_style: <THEME_FONT_REGULAR>{ font_size: 10.0 }
+            }
+        }
+
+        copy_anchor_button = <HeaderLinkButton> { text: "Copy link" }
+
+        copy_message_button = <HeaderLinkButton> { text: "Copy" }
+
+        // Only shown under user messages
+        edit_message_button = <HeaderLinkButton> { text: "Edit", visible: false }
+
+        // Model that produced an assistant message
+        message_model_label = <RoundedView> {
+            visible: false
+            width: Fit, height: Fit
+            padding: {left: 6, right: 6, top: 2, bottom: 2}
+            show_bg: true
+            draw_bg: {
+                instance dark_mode: 0.0
+                border_radius: 4.0
+                fn get_color(self) -> vec4 {
+                    return mix(#eef2ff, #1e1b4b, self.dark_mode);
+                }
+            }
+
+            model_name = <Label> {
+                text: ""
+                draw_text: {
+                    instance dark_mode: 0.0
+                    fn get_color(self) -> vec4 {
+                        return mix(#4338ca, #a5b4fc, self.dark_mode);
+                    }
+                    text_style: <THEME_FONT_REGULAR>{ font_size: 10.0 }
+                }
+            }
+        }
+    }
+
+    // Collapsible card with the MCP tool calls of an assistant message,
+    // added to the moly-kit message rows
+    MessageToolCalls = <View> {
+        width: Fill, height: Fit
+        visible: false
+        flow: Down
+        spacing: 4
+        padding: {top: 4, bottom: 4}
+
+        tool_calls_button = <HeaderLinkButton> { text: "Tool calls" }
+
+        tool_calls_details = <RoundedView> {
+            visible: false
+            width: Fill, height: Fit
+            padding: 8
+            show_bg: true
+            draw_bg: {
+                instance dark_mode: 0.0
+                border_radius: 4.0
+                fn get_color(self) -> vec4 {
+                    return mix(#f3f4f6, #1f2937, self.dark_mode);
+                }
+            }
+
+            tool_calls_text = <Label> {
+                width: Fill
+                text: ""
+                draw_text: {
+                    wrap: Word
+                    instance dark_mode: 0.0
+                    fn get_color(self) -> vec4 {
+                        return mix(#374151, #d1d5db, self.dark_mode);
+                    }
+                    text_style: <THEME_FONT_CODE>{ font_size: 10.0 }
+                }
+            }
+        }
+    }
+
+    // Compact input for one generation parameter
+    GenerationParamInput = <TextInput> {
+        width: 90, height: Fit
+        padding: {left: 8, right: 8, top: 4, bottom: 4}
+        draw_bg: {
+            instance dark_mode: 0.0
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                sdf.box(0.5, 0.5, self.rect_size.x - 1.0, self.rect_size.y - 1.0, 4.0);
+                sdf.fill(mix(#ffffff, #1e293b, self.dark_mode));
+                sdf.stroke(mix(#d1d5db, #475569, self.dark_mode), 1.0);
+                return sdf.result;
+            }
+        }
+        draw_text: {
+            instance dark_mode: 0.0
+            fn get_color(self) -> vec4 {
+                return mix(#1f2937, #f1f5f9, self.dark_mode);
+            }
+            text_style: <THEME_FONT_REGULAR>{ font_size: 10.0 }
+        }
+    }
+
     // Template alias for PortalList
     ChatHistoryItemTemplate = <ChatHistoryItem> {}
 
+    // Date group heading in the history list ("Today", "Older", ...)
+    HistoryGroupHeader = <View> {
+        width: Fill, height: Fit
+        padding: {left: 12, right: 12, top: 10, bottom: 4}
+
+        group_label = <Label> {
+            text: ""
+            draw_text: {
+                instance dark_mode: 0.0
+                fn get_color(self) -> vec4 {
+                    return mix(#9ca3af, #64748b, self.dark_mode);
+                }
+                text_style: <THEME_FONT_BOLD>{ font_size: 10.0 }
+            }
+        }
+    }
+
     // Chat history panel as a separate widget
     pub ChatHistoryPanel = {{ChatHistoryPanel}} {
         width: 220, height: Fill
@@ -177,6 +393,8 @@ live_design! {
         // New chat button
         new_chat_container = <View> {
             width: Fill, height: Fit
+            flow: Down
+            spacing: 4
             padding: 12
 
             new_chat_button = <Button> {
@@ -200,6 +418,38 @@ live_design! {
                     }
                 }
             }
+
+            // Throwaway chat that is never saved
+            new_ephemeral_chat_button = <HeaderLinkButton> { text: "+ New ephemeral chat" }
+        }
+
+        // Search box filtering chats by title and message content
+        history_search_container = <View> {
+            width: Fill, height: Fit
+            padding: {left: 12, right: 12}
+
+            history_search_input = <TextInput> {
+                width: Fill, height: Fit
+                padding: {left: 8, right: 8, top: 6, bottom: 6}
+                empty_text: "Search chats..."
+                draw_bg: {
+                    instance dark_mode: 0.0
+                    fn pixel(self) -> vec4 {
+                        let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                        sdf.box(0.5, 0.5, self.rect_size.x - 1.0, self.rect_size.y - 1.0, 4.0);
+                        sdf.fill(mix(#ffffff, #1e293b, self.dark_mode));
+                        sdf.stroke(mix(#d1d5db, #475569, self.dark_mode), 1.0);
+                        return sdf.result;
+                    }
+                }
+                draw_text: {
+                    instance dark_mode: 0.0
+                    fn get_color(self) -> vec4 {
+                        return mix(#1f2937, #f1f5f9, self.dark_mode);
+                    }
+                    text_style: { font_size: 11.0 }
+                }
+            }
         }
 
         // History header
@@ -224,10 +474,91 @@ live_design! {
             width: Fill, height: Fill
             flow: Down
 
+            HistoryGroupHeader = <HistoryGroupHeader> {}
             ChatHistoryItem = <ChatHistoryItem> {}
         }
     }
 
+    // Chip for a single favorite prompt
+    FavoritePromptChip = <Button> {
+        width: Fit, height: 28
+        padding: {left: 12, right: 12}
+        margin: {right: 8}
+        draw_bg: {
+            instance dark_mode: 0.0
+            instance hover: 0.0
+            instance pressed: 0.0
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                let sz = self.rect_size - 2.0;
+                sdf.box(1.0, 1.0, sz.x, sz.y, 13.0);
+                let bg = mix(#eff6ff, #1e293b, self.dark_mode);
+                let hover_bg = mix(#dbeafe, #334155, self.dark_mode);
+                let border = mix(#bfdbfe, #475569, self.dark_mode);
+                sdf.fill(mix(bg, hover_bg, self.hover));
+                sdf.stroke(border, 1.0);
+                return sdf.result;
+            }
+        }
+        draw_text: {
+            instance dark_mode: 0.0
+            fn get_color(self) -> vec4 {
+                return mix(#1d4ed8, #bfdbfe, self.dark_mode);
+            }
+            text_style: <THEME_FONT_REGULAR>{ font_size: 11.0 }
+        }
+    }
+
+    // Quick-access bar with favorite prompts (hidden when empty)
+    pub FavoritePromptsBar = {{FavoritePromptsBar}} {
+        width: Fill, height: Fit
+        padding: {left: 16, right: 16, top: 8, bottom: 8}
+        show_bg: true
+        draw_bg: {
+            instance dark_mode: 0.0
+            fn pixel(self) -> vec4 {
+                return mix(#f5f7fa, #0f172a, self.dark_mode);
+            }
+        }
+
+        favorites_list = <PortalList> {
+            width: Fill, height: 28
+            flow: Right
+            drag_scrolling: false
+
+            FavoritePromptChip = <FavoritePromptChip> {}
+        }
+    }
+
+    // Greeting and suggested prompts shown in an empty chat
+    pub ChatWelcome = {{ChatWelcome}} {
+        width: Fill, height: Fit
+        flow: Down
+        align: {x: 0.5}
+        spacing: 16
+        padding: {left: 32, right: 32, top: 80}
+
+        greeting_label = <Label> {
+            text: ""
+            draw_text: {
+                instance dark_mode: 0.0
+                fn get_color(self) -> vec4 {
+                    return mix(#1f2937, #f1f5f9, self.dark_mode);
+                }
+                text_style: <THEME_FONT_BOLD>{ font_size: 18.0 }
+            }
+        }
+
+        suggestions_list = <PortalList> {
+            width: Fill, height: 28
+            flow: Right
+            align: {x: 0.5}
+            drag_scrolling: false
+
+            SuggestionChip = <FavoritePromptChip> {}
+        }
+    }
+
     pub ChatApp = {{ChatApp}} {
         width: Fill, height: Fill
         flow: Down
@@ -260,14 +591,44 @@ live_design! {
             padding: 16
             spacing: 4
 
-            title_label = <Label> {
-                text: "Chat"
-                draw_text: {
-                    instance dark_mode: 0.0
-                    fn get_color(self) -> vec4 {
-                        return mix(#1f2937, #f1f5f9, self.dark_mode);
+            <View> {
+                width: Fill, height: Fit
+                flow: Right
+                align: {y: 0.5}
+                spacing: 8
+
+                title_label = <Label> {
+                    text: "Chat"
+                    draw_text: {
+                        instance dark_mode: 0.0
+                        fn get_color(self) -> vec4 {
+                            return mix(#1f2937, #f1f5f9, self.dark_mode);
+                        }
+                        text_style: <THEME_FONT_BOLD>{ font_size: 20.0 }
+                    }
+                }
+
+                // Whether the selected model's provider is reachable; details go to status_label
+                provider_health_dot = <View> {
+                    width: 8, height: 8
+                    show_bg: true
+                    draw_bg: {
+                        // status: 0=unknown (gray), 1=connecting (yellow), 2=connected (green), 3=error (red)
+                        instance status: 0.0
+                        instance dark_mode: 0.0
+                        fn pixel(self) -> vec4 {
+                            let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                            let center = self.rect_size / 2.0;
+                            sdf.circle(center.x, center.y, min(center.x, center.y));
+                            let gray = mix(#9ca3af, #64748b, self.dark_mode);
+                            let yellow = mix(#f59e0b, #fbbf24, self.dark_mode);
+                            let green = mix(#22c55e, #4ade80, self.dark_mode);
+                            let red = mix(#ef4444, #f87171, self.dark_mode);
+                            let color = mix(mix(gray, yellow, step(0.5, self.status)), mix(green, red, step(2.5, self.status)), step(1.5, self.status));
+                            sdf.fill(color);
+                            return sdf.result;
+                        }
                     }
-                    text_style: <THEME_FONT_BOLD>{ font_size: 20.0 }
                 }
             }
 
@@ -275,12 +636,81 @@ live_design! {
                 text: "No provider configured - Go to Settings to add an API key"
                 draw_text: {
                     instance dark_mode: 0.0
+                    // Amber for problems, muted for plain connection info
+                    instance warning: 1.0
                     fn get_color(self) -> vec4 {
-                        return mix(#f59e0b, #fbbf24, self.dark_mode);
+                        let normal = mix(#6b7280, #9ca3af, self.dark_mode);
+                        let warning = mix(#f59e0b, #fbbf24, self.dark_mode);
+                        return mix(normal, warning, self.warning);
                     }
                     text_style: <THEME_FONT_REGULAR>{ font_size: 11.0 }
                 }
             }
+
+            // Narrows the model selector's list; provider groups still apply
+            model_search_input = <TextInput> {
+                width: 260, height: Fit
+                padding: {left: 8, right: 8, top: 6, bottom: 6}
+                empty_text: "Filter models, e.g. gpt-4o"
+                draw_bg: {
+                    instance dark_mode: 0.0
+                    fn pixel(self) -> vec4 {
+                        let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                        sdf.box(0.5, 0.5, self.rect_size.x - 1.0, self.rect_size.y - 1.0, 4.0);
+                        sdf.fill(mix(#ffffff, #1e293b, self.dark_mode));
+                        sdf.stroke(mix(#d1d5db, #475569, self.dark_mode), 1.0);
+                        return sdf.result;
+                    }
+                }
+                draw_text: {
+                    instance dark_mode: 0.0
+                    fn get_color(self) -> vec4 {
+                        return mix(#1f2937, #f1f5f9, self.dark_mode);
+                    }
+                    text_style: { font_size: 11.0 }
+                }
+            }
+
+            // Message numbering and anchor links
+            message_nav = <View> {
+                width: Fill, height: Fit
+                flow: Right
+                align: {y: 0.5}
+                spacing: 8
+
+                numbering_button = <HeaderLinkButton> { text: "Show message numbers" }
+
+                reuse_prompt_button = <HeaderLinkButton> { visible: false, text: "Reuse last prompt" }
+
+                generation_button = <HeaderLinkButton> { text: "Parameters" }
+
+                raw_text_button = <HeaderLinkButton> { text: "Show raw text" }
+
+                <View> { width: Fill, height: 1 }
+
+                anchor_input = <TextInput> {
+                    width: 260, height: Fit
+                    padding: {left: 8, right: 8, top: 4, bottom: 4}
+                    empty_text: "Paste a message link to jump to it"
+                    draw_bg: {
+                        instance dark_mode: 0.0
+                        fn pixel(self) -> vec4 {
+                            let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                            sdf.box(0.5, 0.5, self.rect_size.x - 1.0, self.rect_size.y - 1.0, 4.0);
+                            sdf.fill(mix(#ffffff, #1e293b, self.dark_mode));
+                            sdf.stroke(mix(#d1d5db, #475569, self.dark_mode), 1.0);
+                            return sdf.result;
+                        }
+                    }
+                    draw_text: {
+                        instance dark_mode: 0.0
+                        fn get_color(self) -> vec4 {
+                            return mix(#1f2937, #f1f5f9, self.dark_mode);
+                        }
+                        text_style: <THEME_FONT_REGULAR>{ font_size: 10.0 }
+                    }
+                }
+            }
         }
 
         // Main content area with history panel and chat
@@ -303,9 +733,345 @@ live_design! {
                 }
             }
 
-            // Chat widget from moly-kit
-            chat = <Chat> {
+            chat_column = <View> {
                 width: Fill, height: Fill
+                flow: Down
+
+                // Shown while the current chat is ephemeral
+                ephemeral_bar = <View> {
+                    width: Fill, height: Fit
+                    visible: false
+                    flow: Right
+                    align: {y: 0.5}
+                    spacing: 8
+                    padding: {left: 16, right: 16, top: 6, bottom: 6}
+                    show_bg: true
+                    draw_bg: {
+                        instance dark_mode: 0.0
+                        fn pixel(self) -> vec4 {
+                            return mix(#fef3c7, #422006, self.dark_mode);
+                        }
+                    }
+
+                    ephemeral_label = <Label> {
+                        width: Fill
+                        text: "Ephemeral chat: nothing is saved, and it is discarded when you leave it."
+                        draw_text: {
+                            wrap: Word
+                            instance dark_mode: 0.0
+                            fn get_color(self) -> vec4 {
+                                return mix(#92400e, #fcd34d, self.dark_mode);
+                            }
+                            text_style: <THEME_FONT_REGULAR>{ font_size: 11.0 }
+                        }
+                    }
+
+                    save_ephemeral_button = <FavoritePromptChip> { margin: 0, text: "Save chat" }
+                }
+
+                // Favorite prompts quick-access bar
+                favorites_bar = <FavoritePromptsBar> {}
+
+                // Generation parameters of the current chat (blank fields use the defaults)
+                generation_bar = <View> {
+                    width: Fill, height: Fit
+                    visible: false
+                    flow: Right
+                    align: {y: 0.5}
+                    spacing: 8
+                    padding: {left: 16, right: 16, top: 6, bottom: 6}
+
+                    temperature_input = <GenerationParamInput> { empty_text: "Temperature" }
+                    top_p_input = <GenerationParamInput> { empty_text: "Top P" }
+                    max_tokens_input = <GenerationParamInput> { empty_text: "Max tokens" }
+
+                    generation_hint = <Label> {
+                        width: Fill
+                        text: ""
+                        draw_text: {
+                            wrap: Word
+                            instance dark_mode: 0.0
+                            fn get_color(self) -> vec4 {
+                                return mix(#6b7280, #9ca3af, self.dark_mode);
+                            }
+                            text_style: <THEME_FONT_REGULAR>{ font_size: 10.0 }
+                        }
+                    }
+
+                    save_generation_default_button = <HeaderLinkButton> { text: "Use as default" }
+                }
+
+                // Editor for re-running the conversation from an earlier user message
+                edit_bar = <View> {
+                    width: Fill, height: Fit
+                    visible: false
+                    flow: Right
+                    align: {y: 0.5}
+                    spacing: 8
+                    padding: {left: 16, right: 16, top: 8, bottom: 8}
+
+                    edit_message_input = <TextInput> {
+                        width: Fill, height: Fit
+                        padding: {left: 10, right: 10, top: 8, bottom: 8}
+                        empty_text: "Edit message"
+                        draw_bg: {
+                            instance dark_mode: 0.0
+                            fn pixel(self) -> vec4 {
+                                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                                sdf.box(0.5, 0.5, self.rect_size.x - 1.0, self.rect_size.y - 1.0, 6.0);
+                                sdf.fill(mix(#ffffff, #1e293b, self.dark_mode));
+                                sdf.stroke(mix(#3b82f6, #60a5fa, self.dark_mode), 1.0);
+                                return sdf.result;
+                            }
+                        }
+                        draw_text: {
+                            instance dark_mode: 0.0
+                            fn get_color(self) -> vec4 {
+                                return mix(#1f2937, #f1f5f9, self.dark_mode);
+                            }
+                            text_style: <THEME_FONT_REGULAR>{ font_size: 12.0 }
+                        }
+                    }
+
+                    resend_edit_button = <FavoritePromptChip> { margin: 0, text: "Resend" }
+                    cancel_edit_button = <HeaderLinkButton> { text: "Cancel" }
+                }
+
+                // Confirmation before replacing a draft with the last prompt
+                reuse_confirm_bar = <View> {
+                    width: Fill, height: Fit
+                    visible: false
+                    flow: Right
+                    align: {y: 0.5}
+                    spacing: 8
+                    padding: {left: 16, right: 16, top: 8, bottom: 8}
+
+                    reuse_confirm_label = <Label> {
+                        text: "Replace your current draft with the last prompt?"
+                        draw_text: {
+                            instance dark_mode: 0.0
+                            fn get_color(self) -> vec4 {
+                                return mix(#374151, #e2e8f0, self.dark_mode);
+                            }
+                            text_style: <THEME_FONT_REGULAR>{ font_size: 11.0 }
+                        }
+                    }
+
+                    confirm_reuse_button = <FavoritePromptChip> { margin: 0, text: "Replace" }
+                    cancel_reuse_button = <HeaderLinkButton> { text: "Keep draft" }
+                }
+
+                // Confirmation before sending a very large context (see Settings)
+                large_send_confirm_bar = <View> {
+                    width: Fill, height: Fit
+                    visible: false
+                    flow: Right
+                    align: {y: 0.5}
+                    spacing: 8
+                    padding: {left: 16, right: 16, top: 8, bottom: 8}
+
+                    large_send_label = <Label> {
+                        draw_text: {
+                            instance dark_mode: 0.0
+                            fn get_color(self) -> vec4 {
+                                return mix(#374151, #e2e8f0, self.dark_mode);
+                            }
+                            text_style: <THEME_FONT_REGULAR>{ font_size: 11.0 }
+                        }
+                    }
+
+                    send_anyway_button = <FavoritePromptChip> { margin: 0, text: "Send anyway" }
+                    cancel_large_send_button = <HeaderLinkButton> { text: "Cancel" }
+                }
+
+                // Shown only while a reply is streaming
+                stop_bar = <View> {
+                    width: Fill, height: Fit
+                    visible: false
+                    padding: {top: 8, bottom: 4}
+                    align: {x: 0.5}
+
+                    stop_button = <Button> {
+                        width: Fit, height: 28
+                        padding: {left: 14, right: 14}
+                        text: "Stop generating"
+                        draw_bg: {
+                            instance dark_mode: 0.0
+                            instance hover: 0.0
+                            instance pressed: 0.0
+                            fn pixel(self) -> vec4 {
+                                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                                sdf.box(0.5, 0.5, self.rect_size.x - 1.0, self.rect_size.y - 1.0, 14.0);
+                                let base = mix(#ffffff, #1e293b, self.dark_mode);
+                                let hover = mix(#fee2e2, #7f1d1d, self.dark_mode);
+                                sdf.fill(mix(base, hover, self.hover));
+                                sdf.stroke(mix(#fca5a5, #b91c1c, self.dark_mode), 1.0);
+                                return sdf.result;
+                            }
+                        }
+                        draw_text: {
+                            instance dark_mode: 0.0
+                            fn get_color(self) -> vec4 {
+                                return mix(#b91c1c, #fca5a5, self.dark_mode);
+                            }
+                            text_style: <THEME_FONT_REGULAR>{ font_size: 11.0 }
+                        }
+                    }
+                }
+
+                // Shown when a streaming reply grows below a scrolled-up view
+                jump_to_latest_bar = <View> {
+                    width: Fill, height: Fit
+                    visible: false
+                    padding: {top: 8, bottom: 4}
+                    align: {x: 0.5}
+
+                    jump_to_latest_button = <FavoritePromptChip> { margin: 0, text: "Jump to latest ↓" }
+                }
+
+                // Shown when the chat ends with a failed request
+                retry_bar = <View> {
+                    width: Fill, height: Fit
+                    visible: false
+                    padding: {top: 8, bottom: 4}
+                    align: {x: 0.5}
+
+                    retry_request_button = <FavoritePromptChip> { margin: 0, text: "Retry request" }
+                }
+
+                // Messages as verbatim text, shown instead of the rendered ones when
+                // the chat is in raw mode (the prompt below stays in place)
+                raw_messages = <ScrollYView> {
+                    visible: false
+                    width: Fill, height: Fill
+                    padding: {left: 16, right: 16, top: 8, bottom: 8}
+
+                    raw_messages_text = <Label> {
+                        width: Fill
+                        text: ""
+                        draw_text: {
+                            wrap: Word
+                            instance dark_mode: 0.0
+                            fn get_color(self) -> vec4 {
+                                return mix(#1f2937, #e2e8f0, self.dark_mode);
+                            }
+                            text_style: <THEME_FONT_CODE>{ font_size: 10.0 }
+                        }
+                    }
+                }
+
+                chat_area = <View> {
+                    width: Fill, height: Fill
+                    flow: Overlay
+
+                    // Chat widget from moly-kit
+                    chat = <Chat> {
+                        width: Fill, height: Fill
+
+                        // Additions to the message rows, filled in by ChatApp once drawn
+                        messages = {
+                            list = {
+                                UserLine = {
+                                    message_gutter = <MessageGutter> {}
+                                }
+                                BotLine = {
+                                    message_tool_calls = <MessageToolCalls> {}
+                                    message_gutter = <MessageGutter> {}
+                                }
+                            }
+                        }
+                    }
+
+                    // Empty-chat greeting, drawn over the (empty) messages area
+                    welcome = <ChatWelcome> { visible: false }
+                }
+
+                // Length of the latest reply, updated when it finishes
+                response_stats_bar = <View> {
+                    width: Fill, height: Fit
+                    visible: false
+                    padding: {left: 16, right: 16, top: 2}
+                    align: {x: 1.0}
+
+                    response_stats_label = <Label> {
+                        text: ""
+                        draw_text: {
+                            instance dark_mode: 0.0
+                            fn get_color(self) -> vec4 {
+                                return mix(#9ca3af, #64748b, self.dark_mode);
+                            }
+                            text_style: <THEME_FONT_REGULAR>{ font_size: 9.0 }
+                        }
+                    }
+                }
+
+                // Images attached to the draft (only for models that accept images)
+                image_bar = <View> {
+                    width: Fill, height: Fit
+                    visible: false
+                    flow: Right
+                    align: {y: 0.5}
+                    spacing: 8
+                    padding: {left: 16, right: 16, top: 4}
+
+                    attach_image_button = <HeaderLinkButton> { text: "Attach image" }
+
+                    image_path_input = <TextInput> {
+                        visible: false
+                        width: Fill, height: Fit
+                        padding: {left: 8, right: 8, top: 4, bottom: 4}
+                        empty_text: "Path to a PNG, JPEG, GIF or WebP image, then Enter"
+                        draw_bg: {
+                            instance dark_mode: 0.0
+                            fn pixel(self) -> vec4 {
+                                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                                sdf.box(0.5, 0.5, self.rect_size.x - 1.0, self.rect_size.y - 1.0, 4.0);
+                                sdf.fill(mix(#ffffff, #1e293b, self.dark_mode));
+                                sdf.stroke(mix(#d1d5db, #475569, self.dark_mode), 1.0);
+                                return sdf.result;
+                            }
+                        }
+                        draw_text: {
+                            instance dark_mode: 0.0
+                            fn get_color(self) -> vec4 {
+                                return mix(#1f2937, #f1f5f9, self.dark_mode);
+                            }
+                            text_style: <THEME_FONT_REGULAR>{ font_size: 10.0 }
+                        }
+                    }
+
+                    attached_images_label = <Label> {
+                        text: ""
+                        draw_text: {
+                            instance dark_mode: 0.0
+                            fn get_color(self) -> vec4 {
+                                return mix(#374151, #e2e8f0, self.dark_mode);
+                            }
+                            text_style: <THEME_FONT_REGULAR>{ font_size: 10.0 }
+                        }
+                    }
+
+                    clear_images_button = <HeaderLinkButton> { visible: false, text: "Remove" }
+                }
+
+                // Size of the draft and the conversation it would be sent with
+                draft_size_bar = <View> {
+                    width: Fill, height: Fit
+                    visible: false
+                    padding: {left: 16, right: 16, bottom: 6}
+                    align: {x: 1.0}
+
+                    draft_size_label = <Label> {
+                        text: ""
+                        draw_text: {
+                            instance dark_mode: 0.0
+                            fn get_color(self) -> vec4 {
+                                return mix(#6b7280, #9ca3af, self.dark_mode);
+                            }
+                            text_style: <THEME_FONT_REGULAR>{ font_size: 10.0 }
+                        }
+                    }
+                }
             }
         }
     }
@@ -5,20 +5,70 @@ pub mod design;
 use makepad_widgets::*;
 use moly_kit::prelude::*;
 use moly_kit::aitk::controllers::chat::{ChatStateMutation, ChatTask};
-use moly_kit::aitk::protocol::{Bot, BotId, EntityAvatar};
+use moly_kit::aitk::protocol::{Attachment, Bot, BotId, EntityAvatar};
 use moly_kit::widgets::model_selector::BotGroup;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use moly_data::{ChatId, Store};
+use moly_data::{is_local_url, ActiveModel, ChatId, GenerationParams, MessageAnchor, ProviderConnectionStatus, ProviderType, ProvidersManager, ServerConnectionStatus, Store, StoreAction, ToolCallRecord};
+use moly_data::shortcuts::SHORTCUT_REUSE_LAST_PROMPT;
+use moly_data::tokens::estimate_tokens;
+use moly_data::vision::{image_mime_type, MAX_IMAGE_BYTES};
+use moly_widgets::toast::Toast;
+use serde::{Deserialize, Serialize};
+
+/// Retries for a provider whose model list times out or fails transiently
+const MAX_FETCH_RETRIES: u32 = 2;
+
+/// Model selector group holding the recommended model of each provider
+const RECOMMENDED_GROUP_ID: &str = "recommended";
+
+/// Minimum time between writes of a chat while a reply is streaming
+const STREAMING_SYNC_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Share of the context window at which the status warns that it's nearly full
+const CONTEXT_WARNING_RATIO: f64 = 0.9;
+
+/// Seconds the "previously selected model is unavailable" notice stays in the status line
+const MODEL_FALLBACK_NOTICE_SECS: f64 = 8.0;
+
+/// Start of the message that replaces a reply whose request failed
+const REQUEST_FAILED_PREFIX: &str = "Request failed: ";
+
+/// Chat screen state restored on the next launch
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ChatSessionState {
+    /// Chat that was open
+    pub chat_id: Option<ChatId>,
+    /// Index of the topmost visible message
+    pub first_message: usize,
+}
 
 // Actions emitted by ChatHistoryPanel
 #[derive(Clone, Debug, DefaultNone)]
 pub enum ChatHistoryAction {
     None,
     NewChat,
+    NewEphemeralChat,
     SelectChat(ChatId),
     DeleteChat(ChatId),
+    RenameChat(ChatId, String),
+    TogglePin(ChatId),
+}
+
+// Actions emitted by FavoritePromptsBar
+#[derive(Clone, Debug, DefaultNone)]
+pub enum FavoritePromptsAction {
+    None,
+    Insert(String),
+}
+
+// Actions emitted by ChatWelcome
+#[derive(Clone, Debug, DefaultNone)]
+pub enum ChatWelcomeAction {
+    None,
+    UseSuggestion(String),
 }
 
 /// ChatHistoryItem Widget - handles its own click events
@@ -29,6 +79,10 @@ pub struct ChatHistoryItem {
 
     #[rust]
     chat_id: Option<ChatId>,
+
+    /// Whether the title is currently being edited
+    #[rust]
+    editing: bool,
 }
 
 impl Widget for ChatHistoryItem {
@@ -46,10 +100,10 @@ impl ChatHistoryItem {
         self.chat_id = Some(id);
     }
 
-    /// Check if this item was clicked (but not the delete button)
+    /// Check if this item was clicked (but not the delete or pin button)
     pub fn clicked(&self, actions: &Actions) -> bool {
-        // Don't count as clicked if delete button was clicked
-        if self.delete_clicked(actions) {
+        // Don't count as clicked if delete or pin button was clicked
+        if self.delete_clicked(actions) || self.pin_clicked(actions) {
             return false;
         }
         if let Some(item) = actions.find_widget_action(self.view.widget_uid()) {
@@ -70,9 +124,56 @@ impl ChatHistoryItem {
         false
     }
 
+    /// Check if the pin button was clicked
+    pub fn pin_clicked(&self, actions: &Actions) -> bool {
+        if let Some(item) = actions.find_widget_action(self.view.view(ids!(pin_button)).widget_uid()) {
+            if let ViewAction::FingerDown(fd) = item.cast() {
+                return fd.tap_count == 1;
+            }
+        }
+        false
+    }
+
+    /// Check if this item was double-clicked (starts renaming)
+    pub fn double_clicked(&self, actions: &Actions) -> bool {
+        if self.delete_clicked(actions) || self.pin_clicked(actions) {
+            return false;
+        }
+        if let Some(item) = actions.find_widget_action(self.view.widget_uid()) {
+            if let ViewAction::FingerDown(fd) = item.cast() {
+                return fd.tap_count == 2;
+            }
+        }
+        false
+    }
+
     pub fn get_chat_id(&self) -> Option<ChatId> {
         self.chat_id
     }
+
+    pub fn is_editing(&self) -> bool {
+        self.editing
+    }
+
+    /// Swap the title label for an editable input
+    pub fn start_editing(&mut self, cx: &mut Cx) {
+        self.editing = true;
+        let title = self.view.label(ids!(content.title_label)).text();
+        let title_input = self.view.text_input(ids!(content.title_input));
+        title_input.set_text(cx, &title);
+        title_input.set_visible(cx, true);
+        title_input.set_key_focus(cx);
+        self.view.label(ids!(content.title_label)).set_visible(cx, false);
+        self.view.redraw(cx);
+    }
+
+    /// Go back to showing the title label
+    pub fn stop_editing(&mut self, cx: &mut Cx) {
+        self.editing = false;
+        self.view.text_input(ids!(content.title_input)).set_visible(cx, false);
+        self.view.label(ids!(content.title_label)).set_visible(cx, true);
+        self.view.redraw(cx);
+    }
 }
 
 impl ChatHistoryItemRef {
@@ -98,6 +199,22 @@ impl ChatHistoryItemRef {
         }
     }
 
+    pub fn pin_clicked(&self, actions: &Actions) -> bool {
+        if let Some(inner) = self.borrow() {
+            inner.pin_clicked(actions)
+        } else {
+            false
+        }
+    }
+
+    pub fn double_clicked(&self, actions: &Actions) -> bool {
+        if let Some(inner) = self.borrow() {
+            inner.double_clicked(actions)
+        } else {
+            false
+        }
+    }
+
     pub fn get_chat_id(&self) -> Option<ChatId> {
         if let Some(inner) = self.borrow() {
             inner.get_chat_id()
@@ -105,6 +222,34 @@ impl ChatHistoryItemRef {
             None
         }
     }
+
+    pub fn is_editing(&self) -> bool {
+        if let Some(inner) = self.borrow() {
+            inner.is_editing()
+        } else {
+            false
+        }
+    }
+
+    pub fn start_editing(&self, cx: &mut Cx) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.start_editing(cx);
+        }
+    }
+
+    pub fn stop_editing(&self, cx: &mut Cx) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.stop_editing(cx);
+        }
+    }
+}
+
+/// One row of the history list: a date group heading or a chat
+#[derive(Clone, Copy, Debug)]
+enum HistoryRow {
+    Header(&'static str),
+    /// Index into `saved_chats`
+    Chat(usize),
 }
 
 /// Separate widget for chat history panel - handles its own PortalList drawing
@@ -121,6 +266,23 @@ pub struct ChatHistoryPanel {
 
     #[rust]
     dark_mode: f64,
+
+    /// Current search query (lowercased)
+    #[rust]
+    search_query: String,
+
+    /// Chats matching the search query: pinned ones first, then most recent first
+    /// under date headings
+    #[rust]
+    history_rows: Vec<HistoryRow>,
+
+    /// Whether `history_rows` must be recomputed
+    #[rust]
+    filter_dirty: bool,
+
+    /// Chat list revision the filter was last computed for
+    #[rust]
+    filtered_revision: Option<u64>,
 }
 
 impl Widget for ChatHistoryPanel {
@@ -136,7 +298,17 @@ impl Widget for ChatHistoryPanel {
         // Get data from store
         if let Some(store) = scope.data.get::<Store>() {
             self.dark_mode = if store.is_dark_mode() { 1.0 } else { 0.0 };
-            self.chat_count = store.chats.saved_chats.len();
+
+            // Only recompute the filter when the query or the chats changed,
+            // including renames and new messages
+            let revision = store.chats.revision();
+            if self.filter_dirty || self.filtered_revision != Some(revision) {
+                let matching = filter_chats(&store.chats.saved_chats, &self.search_query);
+                self.history_rows = group_by_date(&store.chats.saved_chats, matching);
+                self.filtered_revision = Some(revision);
+                self.filter_dirty = false;
+            }
+            self.chat_count = self.history_rows.len();
         }
 
         // Apply dark mode to panel
@@ -146,6 +318,13 @@ impl Widget for ChatHistoryPanel {
         self.view.button(ids!(new_chat_button)).apply_over(cx, live! {
             draw_bg: { dark_mode: (self.dark_mode) }
         });
+        self.view.button(ids!(new_ephemeral_chat_button)).apply_over(cx, live! {
+            draw_text: { dark_mode: (self.dark_mode) }
+        });
+        self.view.text_input(ids!(history_search_input)).apply_over(cx, live! {
+            draw_bg: { dark_mode: (self.dark_mode) }
+            draw_text: { dark_mode: (self.dark_mode) }
+        });
 
         // Get the history_list PortalList
         let history_list = self.view.portal_list(ids!(history_list));
@@ -159,14 +338,27 @@ impl Widget for ChatHistoryPanel {
 
                     while let Some(item_id) = list.next_visible_item(cx) {
                         if item_id < self.chat_count {
+                            let chat_index = match self.history_rows[item_id] {
+                                HistoryRow::Header(title) => {
+                                    let item_widget = list.item(cx, item_id, live_id!(HistoryGroupHeader));
+                                    item_widget.label(ids!(group_label)).set_text(cx, title);
+                                    item_widget.label(ids!(group_label)).apply_over(cx, live! {
+                                        draw_text: { dark_mode: (self.dark_mode) }
+                                    });
+                                    item_widget.draw_all(cx, scope);
+                                    continue;
+                                }
+                                HistoryRow::Chat(chat_index) => chat_index,
+                            };
+
                             // Get chat data
-                            let (chat_id, title, date_str, is_selected) = if let Some(store) = scope.data.get::<Store>() {
-                                if let Some(chat) = store.chats.saved_chats.get(item_id) {
+                            let (chat_id, title, date_str, is_selected, is_pinned) = if let Some(store) = scope.data.get::<Store>() {
+                                if let Some(chat) = store.chats.saved_chats.get(chat_index) {
                                     let id = chat.id;
                                     let title = chat.title.clone();
                                     let date = chat.accessed_at.format("%b %d").to_string();
                                     let selected = self.current_chat_id == Some(chat.id);
-                                    (id, title, date, selected)
+                                    (id, title, date, selected, chat.pinned)
                                 } else {
                                     continue;
                                 }
@@ -194,11 +386,21 @@ impl Widget for ChatHistoryPanel {
                                 draw_text: { dark_mode: (self.dark_mode) }
                             });
 
+                            item_widget.text_input(ids!(content.title_input)).apply_over(cx, live! {
+                                draw_bg: { dark_mode: (self.dark_mode) }
+                                draw_text: { dark_mode: (self.dark_mode) }
+                            });
+
                             item_widget.label(ids!(content.date_label)).set_text(cx, &date_str);
                             item_widget.label(ids!(content.date_label)).apply_over(cx, live! {
                                 draw_text: { dark_mode: (self.dark_mode) }
                             });
 
+                            let pinned_value = if is_pinned { 1.0 } else { 0.0 };
+                            item_widget.view(ids!(pin_button)).apply_over(cx, live! {
+                                draw_bg: { dark_mode: (self.dark_mode), pinned: (pinned_value) }
+                            });
+
                             // Apply dark mode to delete button
                             item_widget.view(ids!(delete_button)).apply_over(cx, live! {
                                 draw_bg: { dark_mode: (self.dark_mode) }
@@ -220,12 +422,77 @@ impl Widget for ChatHistoryPanel {
 
 impl ChatHistoryPanel {
     pub fn set_current_chat(&mut self, chat_id: Option<ChatId>) {
+        // Opening a chat bumps its access time, which can move it to another group
+        if self.current_chat_id != chat_id {
+            self.filter_dirty = true;
+        }
         self.current_chat_id = chat_id;
     }
 }
 
+/// Order chats by most recent access and insert a heading before each date group.
+/// Pinned chats come first under their own heading, whatever their access time.
+fn group_by_date(chats: &[moly_data::ChatData], mut indices: Vec<usize>) -> Vec<HistoryRow> {
+    use chrono::Local;
+
+    indices.sort_by(|a, b| chats[*b].accessed_at.cmp(&chats[*a].accessed_at));
+    // Stable, so pinned chats keep their recency order among themselves
+    indices.sort_by_key(|&index| !chats[index].pinned);
+
+    let today = Local::now().date_naive();
+    let group_of = |index: usize| {
+        if chats[index].pinned {
+            return "Pinned";
+        }
+        let date = chats[index].accessed_at.with_timezone(&Local).date_naive();
+        if date >= today {
+            "Today"
+        } else if date == today - chrono::Duration::days(1) {
+            "Yesterday"
+        } else if date > today - chrono::Duration::days(7) {
+            "Previous 7 days"
+        } else {
+            "Older"
+        }
+    };
+
+    let mut rows = Vec::with_capacity(indices.len() + 4);
+    let mut current_group = None;
+    for index in indices {
+        let group = group_of(index);
+        if current_group != Some(group) {
+            rows.push(HistoryRow::Header(group));
+            current_group = Some(group);
+        }
+        rows.push(HistoryRow::Chat(index));
+    }
+    rows
+}
+
+/// Indices of saved chats whose title or message content contains the query (case-insensitive).
+/// Ephemeral chats are never listed.
+fn filter_chats(chats: &[moly_data::ChatData], query: &str) -> Vec<usize> {
+    chats.iter()
+        .enumerate()
+        .filter(|(_, chat)| !chat.ephemeral)
+        .filter(|(_, chat)| {
+            query.is_empty()
+                || chat.title.to_lowercase().contains(query)
+                || chat.messages.iter().any(|m| m.content.text.to_lowercase().contains(query))
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
 impl WidgetMatchEvent for ChatHistoryPanel {
     fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, _scope: &mut Scope) {
+        // Handle search input changes
+        if let Some(text) = self.text_input(ids!(history_search_input)).changed(actions) {
+            self.search_query = text.trim().to_lowercase();
+            self.filter_dirty = true;
+            self.redraw(cx);
+        }
+
         // Handle new chat button click
         let btn = self.button(ids!(new_chat_button));
         if btn.clicked(actions) {
@@ -233,12 +500,40 @@ impl WidgetMatchEvent for ChatHistoryPanel {
             cx.action(ChatHistoryAction::NewChat);
         }
 
+        if self.button(ids!(new_ephemeral_chat_button)).clicked(actions) {
+            cx.action(ChatHistoryAction::NewEphemeralChat);
+        }
+
         // Handle chat history item clicks from PortalList
         // Use the ChatHistoryItem widget's clicked() method (like moly-ai's EntityButton pattern)
         let history_list = self.portal_list(ids!(history_list));
         for (_item_id, item) in history_list.items_with_actions(actions) {
             let history_item = item.as_chat_history_item();
 
+            // Handle an in-progress rename: Enter or losing focus commits, Escape cancels
+            if history_item.is_editing() {
+                let title_input = item.text_input(ids!(content.title_input));
+                if title_input.escaped(actions) {
+                    history_item.stop_editing(cx);
+                    continue;
+                }
+                let committed = if let Some((text, _)) = title_input.returned(actions) {
+                    Some(text)
+                } else if title_input.key_focus_lost(actions) {
+                    Some(title_input.text())
+                } else {
+                    None
+                };
+                if let Some(text) = committed {
+                    history_item.stop_editing(cx);
+                    let title = text.trim();
+                    if let (Some(chat_id), false) = (history_item.get_chat_id(), title.is_empty()) {
+                        cx.action(ChatHistoryAction::RenameChat(chat_id, title.to_string()));
+                    }
+                    continue;
+                }
+            }
+
             // Check for delete button click first
             if history_item.delete_clicked(actions) {
                 if let Some(chat_id) = history_item.get_chat_id() {
@@ -246,6 +541,18 @@ impl WidgetMatchEvent for ChatHistoryPanel {
                     cx.action(ChatHistoryAction::DeleteChat(chat_id));
                 }
             }
+            // Pinning moves the chat, so the rows are rebuilt on the next draw
+            else if history_item.pin_clicked(actions) {
+                if let Some(chat_id) = history_item.get_chat_id() {
+                    cx.action(ChatHistoryAction::TogglePin(chat_id));
+                    self.filter_dirty = true;
+                    self.redraw(cx);
+                }
+            }
+            // Double-click renames the chat
+            else if history_item.double_clicked(actions) {
+                history_item.start_editing(cx);
+            }
             // Then check for item click (select chat)
             else if history_item.clicked(actions) {
                 if let Some(chat_id) = history_item.get_chat_id() {
@@ -265,6 +572,289 @@ impl ChatHistoryPanelRef {
     }
 }
 
+/// Quick-access bar showing the user's favorite prompts as chips
+#[derive(Live, LiveHook, Widget)]
+pub struct FavoritePromptsBar {
+    #[deref]
+    view: View,
+
+    #[rust]
+    prompts: Vec<String>,
+
+    #[rust]
+    dark_mode: f64,
+}
+
+impl Widget for FavoritePromptsBar {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+        self.widget_match_event(cx, event, scope);
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        if let Some(store) = scope.data.get::<Store>() {
+            self.dark_mode = if store.is_dark_mode() { 1.0 } else { 0.0 };
+            self.prompts = store.preferences.favorite_prompts.clone();
+        }
+
+        // Hide the bar entirely when there are no favorites
+        if self.prompts.is_empty() {
+            return DrawStep::done();
+        }
+
+        self.view.apply_over(cx, live! {
+            draw_bg: { dark_mode: (self.dark_mode) }
+        });
+
+        let favorites_list = self.view.portal_list(ids!(favorites_list));
+        let favorites_list_uid = favorites_list.widget_uid();
+
+        while let Some(widget) = self.view.draw_walk(cx, scope, walk).step() {
+            if widget.widget_uid() == favorites_list_uid {
+                if let Some(mut list) = widget.as_portal_list().borrow_mut() {
+                    list.set_item_range(cx, 0, self.prompts.len());
+
+                    while let Some(item_id) = list.next_visible_item(cx) {
+                        if item_id < self.prompts.len() {
+                            let chip = list.item(cx, item_id, live_id!(FavoritePromptChip));
+                            chip.set_text(cx, &favorite_chip_label(&self.prompts[item_id]));
+                            chip.apply_over(cx, live! {
+                                draw_bg: { dark_mode: (self.dark_mode) }
+                                draw_text: { dark_mode: (self.dark_mode) }
+                            });
+                            chip.draw_all(cx, scope);
+                        }
+                    }
+                }
+            }
+        }
+
+        DrawStep::done()
+    }
+}
+
+impl WidgetMatchEvent for FavoritePromptsBar {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, _scope: &mut Scope) {
+        let favorites_list = self.portal_list(ids!(favorites_list));
+        for (item_id, item) in favorites_list.items_with_actions(actions) {
+            if item.as_button().clicked(actions) {
+                if let Some(prompt) = self.prompts.get(item_id) {
+                    cx.action(FavoritePromptsAction::Insert(prompt.clone()));
+                }
+            }
+        }
+    }
+}
+
+/// Greeting and clickable suggested prompts for an empty chat
+#[derive(Live, LiveHook, Widget)]
+pub struct ChatWelcome {
+    #[deref]
+    view: View,
+
+    #[rust]
+    suggestions: Vec<String>,
+
+    #[rust]
+    dark_mode: f64,
+}
+
+impl Widget for ChatWelcome {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+        self.widget_match_event(cx, event, scope);
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        if let Some(store) = scope.data.get::<Store>() {
+            self.dark_mode = if store.is_dark_mode() { 1.0 } else { 0.0 };
+            self.suggestions = store.preferences.suggested_prompts.clone();
+            self.view.label(ids!(greeting_label)).set_text(cx, &store.preferences.empty_chat_greeting);
+        }
+
+        self.view.label(ids!(greeting_label)).apply_over(cx, live! {
+            draw_text: { dark_mode: (self.dark_mode) }
+        });
+        self.view.portal_list(ids!(suggestions_list)).set_visible(cx, !self.suggestions.is_empty());
+
+        let suggestions_list = self.view.portal_list(ids!(suggestions_list));
+        let suggestions_list_uid = suggestions_list.widget_uid();
+
+        while let Some(widget) = self.view.draw_walk(cx, scope, walk).step() {
+            if widget.widget_uid() == suggestions_list_uid {
+                if let Some(mut list) = widget.as_portal_list().borrow_mut() {
+                    list.set_item_range(cx, 0, self.suggestions.len());
+
+                    while let Some(item_id) = list.next_visible_item(cx) {
+                        if item_id < self.suggestions.len() {
+                            let chip = list.item(cx, item_id, live_id!(SuggestionChip));
+                            chip.set_text(cx, &favorite_chip_label(&self.suggestions[item_id]));
+                            chip.apply_over(cx, live! {
+                                draw_bg: { dark_mode: (self.dark_mode) }
+                                draw_text: { dark_mode: (self.dark_mode) }
+                            });
+                            chip.draw_all(cx, scope);
+                        }
+                    }
+                }
+            }
+        }
+
+        DrawStep::done()
+    }
+}
+
+impl WidgetMatchEvent for ChatWelcome {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, _scope: &mut Scope) {
+        let suggestions_list = self.portal_list(ids!(suggestions_list));
+        for (item_id, item) in suggestions_list.items_with_actions(actions) {
+            if item.as_button().clicked(actions) {
+                if let Some(prompt) = self.suggestions.get(item_id) {
+                    cx.action(ChatWelcomeAction::UseSuggestion(prompt.clone()));
+                }
+            }
+        }
+    }
+}
+
+/// App message shown in place of a reply whose request failed
+fn failed_request_message(error: &str) -> Message {
+    use moly_kit::aitk::protocol::{EntityId, MessageContent};
+
+    // The controller's own error text may already carry an "Error:" label
+    let error = error.trim();
+    let error = error.strip_prefix("Error:").map_or(error, str::trim);
+    Message {
+        from: EntityId::App,
+        content: MessageContent {
+            text: format!("{}{}", REQUEST_FAILED_PREFIX, error),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// First line of a tool result, shortened for display
+fn summarize_tool_result(content: &str) -> String {
+    const MAX_CHARS: usize = 160;
+    let first_line = content.trim().lines().next().unwrap_or("");
+    if first_line.chars().count() > MAX_CHARS || content.trim().lines().count() > 1 {
+        let truncated: String = first_line.chars().take(MAX_CHARS).collect();
+        format!("{}…", truncated)
+    } else {
+        first_line.to_string()
+    }
+}
+
+/// Hint listing the defaults used for blank generation parameters
+fn generation_defaults_hint(defaults: &GenerationParams) -> String {
+    let describe = |value: Option<String>| value.unwrap_or_else(|| "provider default".to_string());
+    format!(
+        "Blank fields use the defaults: temperature {}, top P {}, max tokens {}",
+        describe(defaults.temperature.map(|v| v.to_string())),
+        describe(defaults.top_p.map(|v| v.to_string())),
+        describe(defaults.max_tokens.map(|v| v.to_string())),
+    )
+}
+
+/// Word and character count and reading time of a reply, plus the word count of all replies in the chat
+fn response_stats_text(reply: &str, chat_words: usize) -> String {
+    const WORDS_PER_MINUTE: usize = 200;
+    let words = reply.split_whitespace().count();
+    let chars = reply.chars().count();
+    let minutes = words.div_ceil(WORDS_PER_MINUTE).max(1);
+    let mut text = format!(
+        "{} word{} · {} character{} · ~{} min read",
+        words,
+        if words == 1 { "" } else { "s" },
+        chars,
+        if chars == 1 { "" } else { "s" },
+        minutes,
+    );
+    if chat_words > words {
+        text.push_str(&format!(" · {} words across replies", chat_words));
+    }
+    text
+}
+
+/// Shorten a favorite prompt so it fits in a chip
+fn favorite_chip_label(prompt: &str) -> String {
+    const MAX_CHARS: usize = 32;
+    let prompt = prompt.trim();
+    if prompt.chars().count() > MAX_CHARS {
+        let truncated: String = prompt.chars().take(MAX_CHARS).collect();
+        format!("{}...", truncated.trim_end())
+    } else {
+        prompt.to_string()
+    }
+}
+
+/// Top message of each chat when it was last left (session only)
+#[derive(Default)]
+struct ScrollPositions(HashMap<ChatId, usize>);
+
+impl ScrollPositions {
+    /// Note where a chat is left, with `top` the first visible of `count` messages.
+    /// A chat left while a reply is streaming has new content below, so it
+    /// reopens at the bottom instead.
+    fn leave(&mut self, chat_id: ChatId, top: usize, count: usize, writing: bool) {
+        if count == 0 || writing {
+            self.0.remove(&chat_id);
+        } else {
+            self.0.insert(chat_id, top.min(count - 1));
+        }
+    }
+
+    /// Message to scroll back to when the chat is shown again with `count`
+    /// messages, if it was left and that message still exists
+    fn restore(&self, chat_id: ChatId, count: usize) -> Option<usize> {
+        self.0.get(&chat_id).copied().filter(|index| *index < count)
+    }
+
+    fn forget(&mut self, chat_id: ChatId) {
+        self.0.remove(&chat_id);
+    }
+}
+
+/// Prompt input events that matter for IME composition tracking
+#[derive(Clone, Copy, Debug)]
+enum ImeInput {
+    /// Text input; `preedit` while it is the uncommitted text of a composition
+    Text { preedit: bool },
+    Enter,
+    Escape,
+}
+
+impl ImeInput {
+    fn from_event(event: &Event) -> Option<Self> {
+        match event {
+            // While composing, the platform reports preedit text with `replace_last` set
+            Event::TextInput(text_event) => Some(ImeInput::Text {
+                preedit: text_event.replace_last && !text_event.was_paste && !text_event.input.is_empty(),
+            }),
+            Event::KeyDown(key_event) if key_event.key_code == KeyCode::ReturnKey => Some(ImeInput::Enter),
+            Event::KeyDown(key_event) if key_event.key_code == KeyCode::Escape => Some(ImeInput::Escape),
+            _ => None,
+        }
+    }
+
+    /// Update `composing` for this input. Returns true for an Enter that
+    /// commits the composition.
+    fn apply(self, composing: &mut bool) -> bool {
+        match self {
+            ImeInput::Text { preedit } => {
+                *composing = preedit;
+                false
+            }
+            ImeInput::Enter => std::mem::take(composing),
+            ImeInput::Escape => {
+                *composing = false;
+                false
+            }
+        }
+    }
+}
+
 #[derive(Live, Widget)]
 pub struct ChatApp {
     #[deref]
@@ -284,9 +874,19 @@ pub struct ChatApp {
     #[rust]
     providers_configured: bool,
 
+    /// Remote providers whose models weren't fetched because the network was
+    /// down; fetched once it's back
+    #[rust]
+    offline_skipped_provider_ids: Vec<String>,
+
     #[rust]
     current_provider_id: Option<String>,
 
+    /// Provider whose client the controller sends with. Model fetches swap in
+    /// the clients of the providers being fetched.
+    #[rust]
+    chat_client_provider_id: Option<String>,
+
     /// Track which providers we've already fetched models from
     #[rust]
     fetched_provider_ids: Vec<String>,
@@ -303,10 +903,41 @@ pub struct ChatApp {
     #[rust]
     fetch_in_progress: bool,
 
-    /// Number of bots we last saw from the current fetch
+    /// Fires when the current provider takes too long to return its models
+    #[rust]
+    fetch_timer: Timer,
+
+    /// Retries made for the current provider
+    #[rust]
+    fetch_attempts: u32,
+
+    /// Messages in the controller when the current fetch was dispatched. App
+    /// messages added after them report that fetch's errors
+    #[rust]
+    fetch_message_count: usize,
+
+    /// Providers whose models could not be loaded
+    #[rust]
+    failed_provider_ids: Vec<String>,
+
+    /// Model lists fetched in the background for providers loaded from the cache
+    #[rust]
+    refreshed_bots: Arc<Mutex<Vec<(String, Vec<Bot>)>>>,
+
+    /// Number of bots we last saw from the current fetch
     #[rust]
     last_bots_count: usize,
 
+    /// `bots_revision` the model selector grouping was built for
+    #[rust]
+    grouping_revision: Option<u64>,
+
+    /// `clients_revision` of the providers manager when we last configured it.
+    /// Settings rebuild the clients on their own (e.g. for a new proxy), and
+    /// the controller's clients must then be replaced.
+    #[rust]
+    clients_revision: u64,
+
     /// Track the last saved bot_id to detect changes
     #[rust]
     last_saved_bot_id: Option<String>,
@@ -319,6 +950,10 @@ pub struct ChatApp {
     #[rust]
     needs_controller_reset: bool,
 
+    /// Text the model selector's list is narrowed by
+    #[rust]
+    model_search: String,
+
     /// Current chat ID being edited
     #[rust]
     current_chat_id: Option<ChatId>,
@@ -335,9 +970,139 @@ pub struct ChatApp {
     #[rust]
     last_synced_content_len: usize,
 
+    /// When the current chat was last written (throttles writes while streaming)
+    #[rust]
+    last_synced_at: Option<Instant>,
+
     /// Whether we've initialized the chat from persistence
     #[rust]
     chat_initialized: bool,
+
+    /// Whether positional message numbers are shown (session only)
+    #[rust]
+    show_message_numbers: bool,
+
+    /// Message index to scroll to on the next draw (after navigating to an anchor)
+    #[rust]
+    pending_scroll_to_message: Option<usize>,
+
+    #[rust]
+    scroll_positions: ScrollPositions,
+
+    /// Session state to apply once the current chat is loaded
+    #[rust]
+    restored_session: Option<ChatSessionState>,
+
+    /// Index of the user message being edited for resend
+    #[rust]
+    editing_message_index: Option<usize>,
+
+    /// Whether we're asking to replace a non-empty draft with the last prompt
+    #[rust]
+    confirm_reuse_prompt: bool,
+
+    /// Estimated tokens of a send held back for confirmation, and their cost in
+    /// US dollars when the selected model's price is known
+    #[rust]
+    pending_large_send: Option<(usize, Option<f64>)>,
+
+    /// Draft, message count and streaming state the size indicator was last computed for
+    #[rust]
+    draft_size_key: (String, usize, bool),
+
+    /// Estimated tokens of the conversation plus draft, as of the last `update_draft_size`
+    #[rust]
+    estimated_tokens: usize,
+
+    /// Clears the "Copied!" confirmation after a second
+    #[rust]
+    copy_feedback_timer: Timer,
+
+    /// Message whose "Copied!" confirmation is showing
+    #[rust]
+    copied_message_index: Option<usize>,
+
+    /// Whether the selected model's provider is known to be unreachable.
+    /// Sends wait until a re-test passes
+    #[rust]
+    send_blocked: bool,
+
+    /// Whether an IME composition (e.g. CJK input) is in progress in the prompt
+    #[rust]
+    ime_composing: bool,
+
+    /// Messages of the current chat whose tool calls are expanded
+    #[rust]
+    expanded_tool_calls: HashSet<usize>,
+
+    /// What the visible message rows were last filled in with, to redraw rows
+    /// that were drawn before they were filled in
+    #[rust]
+    message_rows_signature: u64,
+
+    /// Whether the generation parameters bar is open (session only)
+    #[rust]
+    show_generation_params: bool,
+
+    /// Chat whose parameters the generation inputs currently show
+    #[rust]
+    generation_inputs_chat: Option<ChatId>,
+
+    /// Images attached to the draft, sent with it
+    #[rust]
+    pending_images: Vec<Attachment>,
+
+    /// Whether the image path input is open
+    #[rust]
+    show_image_path_input: bool,
+
+    /// Last Moly Server status reported by `StoreAction::ServerStatusChanged`
+    #[rust]
+    moly_server_status: ServerConnectionStatus,
+
+    /// Shown once when the saved model's provider is unavailable and another model was picked
+    #[rust]
+    model_fallback_notice: Option<String>,
+
+    /// Clears `model_fallback_notice`
+    #[rust]
+    model_fallback_timer: Timer,
+
+    /// Chat and provider of a rate-limited request waiting to be retried
+    #[rust]
+    rate_limit_retry: Option<(ChatId, String)>,
+
+    /// Ticks every second while `rate_limit_retry` waits
+    #[rust]
+    rate_limit_timer: Timer,
+
+    /// Whether a reply was streaming at the last check for finished replies
+    #[rust]
+    reply_in_progress: bool,
+
+    /// Whether the messages list was scrolled to the end before the latest content arrived
+    #[rust]
+    at_latest_message: bool,
+
+    /// A streaming reply grew while the list was scrolled up
+    #[rust]
+    new_content_below: bool,
+
+    /// Message count and last message length the list was last followed for
+    #[rust]
+    followed_content_key: (usize, usize),
+
+    /// Chat, message count and last message length the raw transcript was built for
+    #[rust]
+    raw_text_key: (Option<ChatId>, usize, usize),
+
+    /// Footer text describing the latest reply's length
+    #[rust]
+    response_stats: Option<String>,
+
+    /// Chat `response_stats` was computed for
+    #[rust]
+    response_stats_chat: Option<ChatId>,
 }
 
 impl LiveHook for ChatApp {
@@ -349,6 +1114,36 @@ impl LiveHook for ChatApp {
 }
 
 impl ChatApp {
+    /// Controller driving the chat, for embedders and scripted conversations.
+    ///
+    /// Change messages and bots only through `dispatch_mutation` (and requests
+    /// through `dispatch_task`): the Chat widget and persistence follow those,
+    /// so state edited in place would fall out of sync with what's shown.
+    pub fn controller(&self) -> Arc<Mutex<ChatController>> {
+        self.chat_controller.clone()
+    }
+
+    /// Append a system message to the current chat, e.g. to set up a scripted
+    /// conversation. It's saved with the chat like any other message.
+    pub fn push_system_message(&mut self, cx: &mut Cx, text: &str) {
+        use moly_kit::aitk::protocol::{EntityId, MessageContent};
+
+        {
+            let mut ctrl = self.chat_controller.lock().unwrap();
+            let mut messages = ctrl.state().messages.clone();
+            messages.push(Message {
+                from: EntityId::System,
+                content: MessageContent {
+                    text: text.to_string(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+            ctrl.dispatch_mutation(VecMutation::Set(messages));
+        }
+        self.view.redraw(cx);
+    }
+
     /// Get provider icon LiveDependency from the loaded list
     fn get_provider_icon(&self, provider_id: &str) -> Option<&LiveDependency> {
         // Icons are stored in order: openai, anthropic, gemini, ollama, deepseek, openrouter, siliconflow, nvidia, groq
@@ -388,14 +1183,38 @@ impl ChatApp {
         }
     }
 
-    /// Set up the grouping function for the model selector
-    fn setup_model_selector_grouping(&mut self, scope: &mut Scope) {
-        let Some(store) = scope.data.get::<Store>() else { return };
+    /// Set up the grouping function for the model selector.
+    /// The lookup table is only rebuilt when the set of bots changed; returns
+    /// whether it was.
+    ///
+    /// The selector's list itself is moly-kit's widget, which builds a row per
+    /// listed model; the model search keeps long lists (e.g. OpenRouter's) short.
+    fn setup_model_selector_grouping(&mut self, scope: &mut Scope) -> bool {
+        let Some(store) = scope.data.get::<Store>() else { return false };
+
+        let revision = store.providers_manager.bots_revision();
+        if !Self::take_grouping_revision(&mut self.grouping_revision, revision) {
+            return false;
+        }
 
         // Build lookup table: BotId -> BotGroup
         let mut bot_groups: HashMap<BotId, BotGroup> = HashMap::new();
+        // Recommended among the enabled models, matching the order filter_enabled_bots
+        // gives the selector, so a disabled model never takes a provider's slot
+        let enabled_bots = Self::filter_enabled_bots(store.providers_manager.get_all_bots(), store);
+        let recommended = Self::recommended_bot_ids(&enabled_bots, store);
 
         for bot in store.providers_manager.get_all_bots() {
+            // Recommended models are listed in their own group at the top
+            if recommended.contains(&bot.id) {
+                bot_groups.insert(bot.id.clone(), BotGroup {
+                    id: RECOMMENDED_GROUP_ID.to_string(),
+                    label: "Recommended".to_string(),
+                    icon: None,
+                });
+                continue;
+            }
+
             // Get provider ID from ProvidersManager
             let provider_id = store.providers_manager.get_provider_for_bot(&bot.id)
                 .unwrap_or("unknown"); // fallback if not found
@@ -424,12 +1243,23 @@ impl ChatApp {
         };
 
         // Set grouping on the ModelSelector inside PromptInput
-        let chat = self.view.chat(ids!(chat));
-        chat.read()
-            .prompt_input_ref()
-            .widget(ids!(model_selector))
-            .as_model_selector()
-            .set_grouping(grouping_fn);
+        if let Some(chat) = self.view.chat(ids!(chat)).borrow() {
+            chat.prompt_input_ref()
+                .widget(ids!(model_selector))
+                .as_model_selector()
+                .set_grouping(grouping_fn);
+        }
+        true
+    }
+
+    /// Record `revision` as grouped, returning false if it already was, so the
+    /// grouping is built once per bot list
+    fn take_grouping_revision(grouping_revision: &mut Option<u64>, revision: u64) -> bool {
+        if *grouping_revision == Some(revision) {
+            return false;
+        }
+        *grouping_revision = Some(revision);
+        true
     }
 
     /// Set our controller on the Chat widget if not already done
@@ -446,16 +1276,84 @@ impl ChatApp {
     /// Force re-set the controller on the Chat widget
     /// This handles visibility changes and ensures bots are properly propagated
     fn force_reset_controller_on_widget(&mut self, cx: &mut Cx) {
-        let mut chat_ref = self.view.chat(ids!(chat));
+        let Some(mut chat) = self.view.chat(ids!(chat)).borrow_mut() else { return };
         // Set to None first to bypass the same-pointer check
-        chat_ref.write().set_chat_controller(cx, None);
-        chat_ref.write().set_chat_controller(cx, Some(self.chat_controller.clone()));
+        chat.set_chat_controller(cx, None);
+        chat.set_chat_controller(cx, Some(self.chat_controller.clone()));
+    }
+
+    /// Re-set the controller on the Chat widget and hand it the selector's bots.
+    ///
+    /// Also re-filters bots in case model settings changed in Settings. Until
+    /// the providers' models are stored, the controller's own bots are handed
+    /// back instead, so the selector isn't left empty.
+    fn reset_controller_bots(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        self.needs_controller_reset = false;
+        let Some(store) = scope.data.get::<Store>() else { return };
+
+        let all_bots = store.providers_manager.get_all_bots();
+        let bots = Self::bots_for_selector(
+            all_bots.len(),
+            // Re-filter bots based on current settings and the model search
+            || self.selector_bots(store),
+            || self.chat_controller.lock().unwrap().state().bots.clone(),
+        );
+        ::log::info!("Controller reset: dispatching {} bots ({} stored)", bots.len(), all_bots.len());
+
+        self.force_reset_controller_on_widget(cx);
+        // Re-dispatch bots so the new plugin sees them
+        self.chat_controller.lock().unwrap().dispatch_mutation(VecMutation::Set(bots));
+        // Picks up bots refreshed in the background; a no-op if unchanged
+        self.setup_model_selector_grouping(scope);
+        self.view.redraw(cx);
+        self.view.chat(ids!(chat)).redraw(cx);
+    }
+
+    /// Bots to hand the model selector on a reset: the `stored` providers' bots
+    /// as filtered for the selector, or the controller's own until any are stored
+    fn bots_for_selector<T>(
+        stored: usize,
+        selector_bots: impl FnOnce() -> Vec<T>,
+        controller_bots: impl FnOnce() -> Vec<T>,
+    ) -> Vec<T> {
+        if stored == 0 {
+            controller_bots()
+        } else {
+            selector_bots()
+        }
     }
 
     /// Called by the parent App when this view becomes visible
     /// This triggers a controller reset to ensure the model list is populated
     pub fn on_become_visible(&mut self) {
+        ::log::info!("Chat view visible: repopulating the model selector");
         self.needs_controller_reset = true;
+        // The model selector may have been rebuilt while hidden, so set its grouping again
+        self.grouping_revision = None;
+
+        // The reset re-propagates the controller, which can move the message list,
+        // so come back to the same chat at the message that was on top
+        self.remember_scroll_position();
+        if let Some(chat_id) = self.current_chat_id {
+            if let Some(index) = self.scroll_positions.restore(chat_id, self.message_count()) {
+                self.pending_scroll_to_message = Some(index);
+            }
+        }
+    }
+
+    /// Current view state, for restoring it on the next launch
+    pub fn session_state(&self) -> ChatSessionState {
+        let count = self.message_count();
+        ChatSessionState {
+            chat_id: self.current_chat_id,
+            first_message: if count > 0 { self.messages_list().first_id().min(count - 1) } else { 0 },
+        }
+    }
+
+    /// Reopen at the saved scroll position once the chat loads. Ignored if
+    /// another chat is current by then or the message no longer exists.
+    pub fn restore_session_state(&mut self, state: ChatSessionState) {
+        self.restored_session = Some(state);
     }
 
     /// Initialize the chat from persistence (load or create the current chat)
@@ -481,6 +1379,8 @@ impl ChatApp {
 
         self.current_chat_id = Some(chat_id);
 
+        let initial_messages = Self::initial_messages(store);
+
         // Load messages from the chat into the controller
         if let Some(chat) = store.chats.get_chat_by_id(chat_id) {
             let messages = chat.messages.clone();
@@ -490,10 +1390,20 @@ impl ChatApp {
                 ::log::info!("Loading {} messages from chat {}", message_count, chat_id);
                 let mut ctrl = self.chat_controller.lock().unwrap();
                 ctrl.dispatch_mutation(VecMutation::Set(messages));
+            } else if !initial_messages.is_empty() {
+                // An empty chat starts with the global system prompt; the sync writes it to the chat
+                let mut ctrl = self.chat_controller.lock().unwrap();
+                ctrl.dispatch_mutation(VecMutation::Set(initial_messages));
             }
 
             self.last_synced_message_count = message_count;
 
+            if let Some(state) = self.restored_session.take() {
+                if state.chat_id == Some(chat_id) && state.first_message < message_count {
+                    self.pending_scroll_to_message = Some(state.first_message);
+                }
+            }
+
             // Also restore the bot_id if it was saved with the chat
             if let Some(ref bot_id) = chat.bot_id {
                 ::log::info!("Chat {} has saved bot_id: {}", chat_id, bot_id.as_str());
@@ -509,16 +1419,21 @@ impl ChatApp {
     fn sync_messages_to_persistence(&mut self, scope: &mut Scope) {
         let Some(chat_id) = self.current_chat_id else { return };
 
-        // Get current messages from controller
-        let (messages, message_count, has_writing_message, last_msg_content_len) = {
+        // Ephemeral chats live only in the controller until they're saved
+        if scope.data.get::<Store>().map_or(false, |store| store.chats.is_ephemeral(chat_id)) {
+            return;
+        }
+
+        // Inspect the controller's messages without copying them
+        let (message_count, has_writing_message, last_msg_content_len) = {
             let ctrl = self.chat_controller.lock().unwrap();
-            let msgs = ctrl.state().messages.clone();
+            let msgs = &ctrl.state().messages;
             let count = msgs.len();
             // Check if any message is still being written
             let writing = msgs.iter().any(|m| m.metadata.is_writing);
             // Get the content length of the last message (to detect content changes)
             let last_len = msgs.last().map(|m| m.content.text.len()).unwrap_or(0);
-            (msgs, count, writing, last_len)
+            (count, writing, last_len)
         };
 
         // Sync if:
@@ -533,6 +1448,14 @@ impl ChatApp {
             return;
         }
 
+        // Streamed tokens are written at most every STREAMING_SYNC_INTERVAL; the
+        // final content is always written once the reply finishes
+        let throttled = !count_changed && !writing_finished && has_writing_message
+            && self.last_synced_at.is_some_and(|at| at.elapsed() < STREAMING_SYNC_INTERVAL);
+        if throttled {
+            return;
+        }
+
         if count_changed {
             ::log::debug!("Messages count changed: {} -> {}, syncing to persistence",
                 self.last_synced_message_count, message_count);
@@ -546,10 +1469,12 @@ impl ChatApp {
         }
 
         // Update the chat in persistence
+        let messages = self.chat_controller.lock().unwrap().state().messages.clone();
         if let Some(store) = scope.data.get_mut::<Store>() {
             store.chats.update_chat_messages(chat_id, messages);
         }
 
+        self.last_synced_at = Some(Instant::now());
         self.last_synced_message_count = message_count;
         self.had_writing_message = has_writing_message;
         self.last_synced_content_len = last_msg_content_len;
@@ -578,7 +1503,18 @@ impl ChatApp {
 
     /// Create a new chat session
     pub fn create_new_chat(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        self.start_new_chat(cx, scope, false);
+    }
+
+    /// Create a throwaway chat that is never saved unless the user asks to
+    pub fn create_ephemeral_chat(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        self.start_new_chat(cx, scope, true);
+    }
+
+    fn start_new_chat(&mut self, cx: &mut Cx, scope: &mut Scope, ephemeral: bool) {
+        self.remember_scroll_position();
         let Some(store) = scope.data.get_mut::<Store>() else { return };
+        let initial_messages = Self::initial_messages(store);
 
         // Get current bot_id and all bots to use for new chat
         let (current_bot_id, all_bots) = {
@@ -587,7 +1523,11 @@ impl ChatApp {
         };
 
         // Create new chat
-        let chat_id = store.chats.create_chat(current_bot_id.clone());
+        let chat_id = if ephemeral {
+            store.chats.create_ephemeral_chat(current_bot_id.clone())
+        } else {
+            store.chats.create_chat(current_bot_id.clone())
+        };
         self.current_chat_id = Some(chat_id);
 
         // Force reset the controller on the Chat widget to ensure clean state
@@ -598,10 +1538,10 @@ impl ChatApp {
             chat_ref.write().set_chat_controller(cx, Some(self.chat_controller.clone()));
         }
 
-        // Clear messages in controller and re-set bots (since set_chat_controller may clear them)
+        // Reset messages in controller and re-set bots (since set_chat_controller may clear them)
         {
             let mut ctrl = self.chat_controller.lock().unwrap();
-            ctrl.dispatch_mutation(VecMutation::<Message>::Set(vec![]));
+            ctrl.dispatch_mutation(VecMutation::<Message>::Set(initial_messages));
             ctrl.dispatch_mutation(VecMutation::Set(all_bots));
             // Re-set the bot_id
             if let Some(bot_id) = current_bot_id {
@@ -627,6 +1567,7 @@ impl ChatApp {
             return;
         }
 
+        self.remember_scroll_position();
         let Some(store) = scope.data.get_mut::<Store>() else { return };
 
         // Set as current chat in persistence
@@ -658,6 +1599,7 @@ impl ChatApp {
             }
 
             // Reset all sync tracking state for the loaded chat
+            self.expanded_tool_calls.clear();
             self.last_synced_message_count = message_count;
             self.had_writing_message = false;
             self.last_synced_content_len = last_content_len;
@@ -666,165 +1608,1456 @@ impl ChatApp {
             // This is needed because switching from a chat with many messages to one with fewer
             // can leave the scroll position pointing to a non-existent message index
             self.view.chat(ids!(chat)).write().messages_ref().write().instant_scroll_to_bottom(cx);
+
+            // Then return to where the chat was left, if that's still a valid message
+            if let Some(index) = self.scroll_positions.restore(chat_id, message_count) {
+                self.pending_scroll_to_message = Some(index);
+            }
         }
 
         self.view.redraw(cx);
     }
 
-    /// Delete a chat session
-    pub fn delete_chat(&mut self, cx: &mut Cx, scope: &mut Scope, chat_id: ChatId) {
-        let Some(store) = scope.data.get_mut::<Store>() else { return };
-
-        // Check if we're deleting the current chat
-        let is_current = self.current_chat_id == Some(chat_id);
-
-        // Delete from storage (this also updates current_chat_id if needed)
-        store.chats.delete_chat(chat_id);
+    /// Load the store's current chat if ours no longer exists
+    fn follow_removed_chat(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        let Some(chat_id) = self.current_chat_id else { return };
+        let Some(store) = scope.data.get::<Store>() else { return };
+        if store.chats.get_chat_by_id(chat_id).is_some() {
+            return;
+        }
+        let Some(next_id) = store.chats.current_chat_id else { return };
 
-        ::log::info!("Deleted chat {}", chat_id);
+        ::log::info!("Chat {} was removed, switching to {}", chat_id, next_id);
+        self.scroll_positions.forget(chat_id);
+        self.current_chat_id = None;
+        self.switch_to_chat(cx, scope, next_id);
+    }
 
-        // If we deleted the current chat, we need to switch to another chat or create a new one
-        if is_current {
-            if let Some(next_chat) = store.chats.saved_chats.first() {
-                // Switch to the next available chat
-                let next_id = next_chat.id;
-                self.current_chat_id = Some(next_id);
-                store.chats.set_current_chat(Some(next_id));
+    /// Remember the top message of the chat being left
+    fn remember_scroll_position(&mut self) {
+        let Some(chat_id) = self.current_chat_id else { return };
+        let count = self.message_count();
+        let top = if count > 0 { self.messages_list().first_id() } else { 0 };
+        let writing = self.is_writing();
+        self.scroll_positions.leave(chat_id, top, count, writing);
+    }
 
-                // Load the chat's messages into controller
-                if let Some(chat) = store.chats.get_chat_by_id(next_id) {
-                    let mut messages = chat.messages.clone();
-                    for msg in &mut messages {
-                        msg.metadata.is_writing = false;
-                    }
-                    let message_count = messages.len();
-                    let last_content_len = messages.last().map(|m| m.content.text.len()).unwrap_or(0);
+    /// Insert text into the prompt input (used by favorite prompts; empty-chat suggestions also send it)
+    fn insert_prompt_text(&mut self, cx: &mut Cx, text: &str) {
+        let chat = self.view.chat(ids!(chat));
+        chat.read().prompt_input_ref().set_text(cx, text);
+        self.view.redraw(cx);
+    }
 
-                    {
-                        let mut ctrl = self.chat_controller.lock().unwrap();
-                        ctrl.dispatch_mutation(VecMutation::Set(messages));
+    /// Text of the most recent user message
+    fn last_user_prompt(&self) -> Option<String> {
+        use moly_kit::aitk::protocol::EntityId;
 
-                        if let Some(ref bot_id) = chat.bot_id {
-                            ctrl.dispatch_mutation(ChatStateMutation::SetBotId(Some(bot_id.clone())));
-                        }
-                    }
+        let ctrl = self.chat_controller.lock().unwrap();
+        ctrl.state().messages.iter()
+            .rev()
+            .find(|m| matches!(m.from, EntityId::User))
+            .map(|m| m.content.text.clone())
+    }
 
-                    self.last_synced_message_count = message_count;
-                    self.had_writing_message = false;
-                    self.last_synced_content_len = last_content_len;
-                }
-            } else {
-                // No chats left, create a new one
-                self.create_new_chat(cx, scope);
-                return; // create_new_chat handles redraw
-            }
+    /// Put the last user prompt back in the input without sending it.
+    /// Only touches the input, so it is safe while a reply is streaming.
+    fn reuse_last_prompt(&mut self, cx: &mut Cx, confirmed: bool) {
+        let Some(prompt) = self.last_user_prompt() else { return };
 
-            // Reset scroll position
-            self.view.chat(ids!(chat)).write().messages_ref().write().instant_scroll_to_bottom(cx);
+        let chat = self.view.chat(ids!(chat));
+        let draft = chat.read().prompt_input_ref().text();
+        if !confirmed && !draft.trim().is_empty() && draft != prompt {
+            self.confirm_reuse_prompt = true;
+            self.view.redraw(cx);
+            return;
         }
 
+        self.confirm_reuse_prompt = false;
+        chat.read().prompt_input_ref().set_text(cx, &prompt);
         self.view.redraw(cx);
     }
-}
 
-impl Widget for ChatApp {
-    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
-        // Set controller on Chat widget early (required for Messages widget)
-        self.maybe_set_controller_on_widget(cx);
+    /// Text of the message at `index` if it was sent by the user
+    fn user_message_text(&self, index: usize) -> Option<String> {
+        use moly_kit::aitk::protocol::EntityId;
 
-        // Handle pending controller reset (e.g., after models load or view becomes visible)
-        // This ensures the model list is properly populated after visibility changes
-        // Also re-filters bots in case model settings changed in Settings
-        if self.needs_controller_reset {
-            if let Some(store) = scope.data.get::<Store>() {
-                let all_bots = store.providers_manager.get_all_bots();
-                if !all_bots.is_empty() {
-                    // Re-filter bots based on current settings
-                    let enabled_bots = Self::filter_enabled_bots(all_bots, store);
-                    ::log::info!("Controller reset: filtering {} enabled bots from {} total",
-                        enabled_bots.len(), all_bots.len());
-
-                    self.force_reset_controller_on_widget(cx);
-                    // Re-dispatch filtered bots mutation so the new plugin sees them
-                    self.chat_controller.lock().unwrap().dispatch_mutation(VecMutation::Set(enabled_bots));
-                    self.view.redraw(cx);
-                    self.view.chat(ids!(chat)).redraw(cx);
-                }
-            }
-            self.needs_controller_reset = false;
-        }
+        let ctrl = self.chat_controller.lock().unwrap();
+        let message = ctrl.state().messages.get(index)?;
+        matches!(message.from, EntityId::User).then(|| message.content.text.clone())
+    }
 
-        // Check and configure providers from Store
-        self.maybe_configure_providers(cx, scope);
+    /// Open the editor for the user message at `index`
+    fn start_editing_message(&mut self, cx: &mut Cx, index: usize) {
+        let Some(text) = self.user_message_text(index) else { return };
 
-        // Check for loaded bots from the ChatController
-        self.check_for_loaded_bots(cx, scope);
+        self.editing_message_index = Some(index);
+        let input = self.view.text_input(ids!(edit_message_input));
+        input.set_text(cx, &text);
+        input.set_key_focus(cx);
+        self.view.redraw(cx);
+    }
 
-        // Initialize chat from persistence (load or create)
-        self.maybe_initialize_chat(cx, scope);
+    /// Drop the edited message and everything after it, then re-run the turn with the new text
+    fn resend_edited_message(&mut self, cx: &mut Cx, scope: &mut Scope, text: String) {
+        let Some(index) = self.editing_message_index.take() else { return };
+        let text = text.trim().to_string();
+        if text.is_empty() {
+            return;
+        }
 
-        // Track model selection changes and save to preferences
-        self.track_model_selection(scope);
+        // Never truncate underneath a live stream
+        if self.is_writing() {
+            self.chat_controller.lock().unwrap().dispatch_task(ChatTask::Stop);
+        }
 
-        // Sync messages to persistence when they change
-        self.sync_messages_to_persistence(scope);
+        let messages = {
+            let mut ctrl = self.chat_controller.lock().unwrap();
+            let mut messages: Vec<Message> = ctrl.state().messages.iter().take(index + 1).cloned().collect();
+            let Some(edited) = messages.get_mut(index) else { return };
+            edited.content.text = text;
+            for msg in &mut messages {
+                msg.metadata.is_writing = false;
+            }
+            ctrl.dispatch_mutation(VecMutation::Set(messages.clone()));
+            messages
+        };
 
-        // Sync bot selection to current chat
-        self.sync_bot_to_chat(scope);
+        // Tell persistence the conversation shrank so the next sync compares against the truncated state
+        let last_content_len = messages.last().map(|m| m.content.text.len()).unwrap_or(0);
+        self.last_synced_message_count = messages.len();
+        self.last_synced_content_len = last_content_len;
+        self.had_writing_message = false;
+        if let (Some(chat_id), Some(store)) = (self.current_chat_id, scope.data.get_mut::<Store>()) {
+            store.chats.update_chat_messages(chat_id, messages);
+        }
 
-        // Delegate events directly to view (like moly-ai does)
-        // Don't use capture_actions as it can interfere with nested widget event handling
-        self.view.handle_event(cx, event, scope);
+        ::log::info!("Resending edited message {}", index + 1);
+        self.chat_controller.lock().unwrap().dispatch_task(ChatTask::Send);
+        self.view.chat(ids!(chat)).write().messages_ref().write().instant_scroll_to_bottom(cx);
+        self.view.redraw(cx);
+    }
 
-        // Use WidgetMatchEvent pattern for handling actions
-        self.widget_match_event(cx, event, scope);
+    /// Track IME composition state from text input events.
+    ///
+    /// Returns true for an Enter that commits the composition, which should be
+    /// swallowed instead of sending the message.
+    fn track_ime_composition(&mut self, event: &Event) -> bool {
+        ImeInput::from_event(event).is_some_and(|input| input.apply(&mut self.ime_composing))
     }
 
-    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
-        // Get dark mode state
-        let dark_mode_value = if let Some(store) = scope.data.get::<Store>() {
-            if store.is_dark_mode() { 1.0 } else { 0.0 }
-        } else {
-            0.0
+    /// Hold back a send whose estimated size is over the configured threshold
+    /// and ask first. Returns true when the event that would send was swallowed.
+    fn hold_large_send(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) -> bool {
+        self.is_send_event(cx, event) && self.hold_large_draft(cx, scope)
+    }
+
+    /// Hold back the draft for confirmation when its estimated size is over the
+    /// configured threshold. Returns true when it was held.
+    fn hold_large_draft(&mut self, cx: &mut Cx, scope: &mut Scope) -> bool {
+        let Some(threshold) = scope.data.get::<Store>()
+            .and_then(|store| store.preferences.send_confirmation_threshold()) else {
+            return false;
         };
 
-        // Apply dark mode to background
-        self.view.apply_over(cx, live! {
-            draw_bg: { dark_mode: (dark_mode_value) }
+        let draft = self.view.chat(ids!(chat)).read().prompt_input_ref().text();
+        if draft.trim().is_empty() {
+            return false;
+        }
+        let tokens = self.estimated_send_tokens(&draft);
+        if tokens <= threshold {
+            return false;
+        }
+
+        let cost = self.chat_controller.lock().unwrap().state().bot_id.clone().and_then(|bot_id| {
+            scope.data.get::<Store>()?.pricing.estimate_cost(bot_id.id(), tokens)
+        });
+        ::log::info!("Holding a send of about {} tokens (threshold {})", tokens, threshold);
+        self.pending_large_send = Some((tokens, cost));
+        self.view.redraw(cx);
+        true
+    }
+
+    /// Send a draft held for confirmation the way it would have gone
+    fn send_held_draft(&mut self, cx: &mut Cx) {
+        self.pending_large_send = None;
+        self.submit_draft(cx);
+    }
+
+    /// Send the draft as a typed prompt is sent: while the provider is reachable,
+    /// after confirmation when it's large, and through the regular submit
+    fn send_draft_as_typed(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        if self.send_blocked || self.is_writing() || self.hold_large_draft(cx, scope) {
+            return;
+        }
+        self.submit_draft(cx);
+    }
+
+    /// Send the draft with images through send_draft, otherwise through moly-kit's
+    /// own submit, which also takes the files attached in the prompt input
+    fn submit_draft(&mut self, cx: &mut Cx) {
+        if !self.pending_images.is_empty() {
+            self.send_draft(cx);
+            return;
+        }
+        let submit = self.view.chat(ids!(chat)).read().prompt_input_ref().button(ids!(submit));
+        cx.widget_action(submit.widget_uid(), ButtonAction::Clicked(KeyModifiers::default()));
+        self.view.redraw(cx);
+    }
+
+    /// Whether `event` would send the draft: Enter in the prompt input or a click
+    /// on its submit button. False while streaming, when the button stops instead.
+    fn is_send_event(&self, cx: &Cx, event: &Event) -> bool {
+        let prompt_input = self.view.chat(ids!(chat)).read().prompt_input_ref();
+        let is_send = match event {
+            Event::KeyDown(key_event) => {
+                key_event.key_code == KeyCode::ReturnKey
+                    && !key_event.modifiers.shift
+                    && cx.has_key_focus(prompt_input.text_input(ids!(text_input)).area())
+            }
+            Event::FingerDown(finger_event) => {
+                prompt_input.button(ids!(submit)).area().rect(cx).contains(finger_event.abs)
+            }
+            _ => false,
+        };
+        is_send && !self.is_writing()
+    }
+
+    /// Read an image file and attach it to the draft
+    fn attach_image(&mut self, cx: &mut Cx, path: &str) {
+        let path = std::path::Path::new(path.trim());
+        let Some(mime_type) = image_mime_type(path) else {
+            cx.action(Toast::error("Only PNG, JPEG, GIF and WebP images can be attached"));
+            return;
+        };
+        let bytes = match std::fs::metadata(path) {
+            Ok(metadata) if metadata.len() > MAX_IMAGE_BYTES => {
+                cx.action(Toast::error(format!("Images can be at most {} MB", MAX_IMAGE_BYTES / (1024 * 1024))));
+                return;
+            }
+            Ok(_) => std::fs::read(path),
+            Err(e) => Err(e),
+        };
+        match bytes {
+            Ok(bytes) => {
+                let name = path.file_name().map_or_else(|| "image".to_string(), |n| n.to_string_lossy().into_owned());
+                ::log::info!("Attached image {} ({} bytes)", name, bytes.len());
+                self.pending_images.push(Attachment::from_bytes(name, Some(mime_type.to_string()), &bytes));
+                self.show_image_path_input = false;
+                self.view.text_input(ids!(image_path_input)).set_text(cx, "");
+            }
+            Err(e) => {
+                cx.action(Toast::error(format!("Could not read image: {}", e)));
+            }
+        }
+        self.view.redraw(cx);
+    }
+
+    /// Offer image attachments when the selected model accepts images. Switching
+    /// to a text-only model drops images already attached.
+    fn update_image_bar(&mut self, cx: &mut Cx2d, scope: &mut Scope, dark_mode: f64) {
+        let bot_id = self.chat_controller.lock().unwrap().state().bot_id.clone();
+        let supported = bot_id.is_some_and(|bot_id| {
+            scope.data.get::<Store>().map_or(false, |store| store.providers_manager.supports_image_input(&bot_id))
+        });
+        if !supported {
+            self.pending_images.clear();
+            self.show_image_path_input = false;
+        }
+        self.view.view(ids!(image_bar)).set_visible(cx, supported);
+        if !supported {
+            return;
+        }
+
+        let names: Vec<&str> = self.pending_images.iter().map(|image| image.name.as_str()).collect();
+        self.view.text_input(ids!(image_path_input)).set_visible(cx, self.show_image_path_input);
+        self.view.label(ids!(attached_images_label)).set_text(cx, &names.join(", "));
+        self.view.button(ids!(clear_images_button)).set_visible(cx, !names.is_empty());
+
+        self.view.button(ids!(attach_image_button)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.button(ids!(clear_images_button)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.label(ids!(attached_images_label)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.text_input(ids!(image_path_input)).apply_over(cx, live! {
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+    }
+
+    /// Show the draft's character count and the estimated tokens of sending it.
+    /// Only recomputed when the draft or the conversation changes.
+    fn update_draft_size(&mut self, cx: &mut Cx) {
+        let draft = self.view.chat(ids!(chat)).read().prompt_input_ref().text();
+        let key = (draft, self.message_count(), self.is_writing());
+        if key == self.draft_size_key {
+            return;
+        }
+
+        let (draft, message_count, _) = &key;
+        let bar = self.view.view(ids!(draft_size_bar));
+        if draft.is_empty() && *message_count == 0 {
+            self.estimated_tokens = 0;
+            bar.set_visible(cx, false);
+        } else {
+            let chars = draft.chars().count();
+            let tokens = self.estimated_send_tokens(draft);
+            self.estimated_tokens = tokens;
+            bar.set_visible(cx, true);
+            self.view.label(ids!(draft_size_label)).set_text(cx,
+                &format!("{} characters · ~{} tokens with the conversation", chars, tokens));
+        }
+        self.draft_size_key = key;
+        self.view.redraw(cx);
+    }
+
+    /// "Context nearly full" note when the estimate is close to the selected model's
+    /// context window (as reported by its provider, or the configured default)
+    fn context_warning(&self, scope: &mut Scope) -> Option<String> {
+        let store = scope.data.get::<Store>()?;
+        let bot_id = self.chat_controller.lock().unwrap().state().bot_id.clone();
+        let limit = bot_id
+            .and_then(|bot_id| store.providers_manager.context_window(&bot_id))
+            .unwrap_or(store.preferences.default_context_window_tokens);
+
+        let nearly_full = self.estimated_tokens as f64 >= limit as f64 * CONTEXT_WARNING_RATIO;
+        nearly_full.then(|| format!("Context nearly full (~{} of {} tokens)", self.estimated_tokens, limit))
+    }
+
+    /// "Loading models from X (2/5)…" while providers are fetched one after another
+    fn fetch_progress(&self, scope: &mut Scope) -> Option<String> {
+        if !self.fetch_in_progress || self.providers_to_fetch.is_empty() {
+            return None;
+        }
+        let provider_id = self.current_provider_id.as_ref()?;
+        let name = scope.data.get::<Store>()
+            .and_then(|store| store.preferences.get_provider(provider_id))
+            .map_or_else(|| provider_id.clone(), |provider| provider.name.clone());
+        Some(format!("Loading models from {} ({}/{})…", name, self.fetch_index + 1, self.providers_to_fetch.len()))
+    }
+
+    /// Estimated tokens sent for the current conversation plus `draft`
+    fn estimated_send_tokens(&self, draft: &str) -> usize {
+        let ctrl = self.chat_controller.lock().unwrap();
+        let history: usize = ctrl.state().messages.iter().map(|m| estimate_tokens(&m.content.text)).sum();
+        history + estimate_tokens(draft)
+    }
+
+    /// Send the draft with its attached images, bypassing moly-kit's own send
+    /// (used whenever images are attached)
+    fn send_draft(&mut self, cx: &mut Cx) {
+        use moly_kit::aitk::protocol::{EntityId, MessageContent};
+
+        self.pending_large_send = None;
+        let chat = self.view.chat(ids!(chat));
+        let text = chat.read().prompt_input_ref().text();
+        if text.trim().is_empty() && self.pending_images.is_empty() {
+            self.view.redraw(cx);
+            return;
+        }
+
+        {
+            let mut ctrl = self.chat_controller.lock().unwrap();
+            let mut messages = ctrl.state().messages.clone();
+            messages.push(Message {
+                from: EntityId::User,
+                content: MessageContent {
+                    text,
+                    attachments: std::mem::take(&mut self.pending_images),
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+            ctrl.dispatch_mutation(VecMutation::Set(messages));
+            ctrl.dispatch_task(ChatTask::Send);
+        }
+
+        chat.read().prompt_input_ref().set_text(cx, "");
+        chat.write().messages_ref().write().instant_scroll_to_bottom(cx);
+        self.view.redraw(cx);
+    }
+
+    /// Whether any message is still being streamed
+    fn is_writing(&self) -> bool {
+        self.chat_controller.lock().unwrap().state().messages.iter().any(|m| m.metadata.is_writing)
+    }
+
+    /// Interrupt the streaming reply, keeping the partial text
+    fn stop_generation(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        if !self.is_writing() {
+            return;
+        }
+        ::log::info!("Stopping generation");
+        self.chat_controller.lock().unwrap().dispatch_task(ChatTask::Stop);
+
+        // Persist the partial reply right away. had_writing_message is still true here,
+        // so this sync consumes the writing -> finished transition and no extra sync follows.
+        self.sync_messages_to_persistence(scope);
+        self.view.redraw(cx);
+    }
+
+    /// Replace the in-progress reply with an error message when its request fails.
+    /// The controller reports the error as an app message but leaves the reply
+    /// marked as writing, which would otherwise keep the stop bar up for good.
+    fn handle_stream_error(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        use moly_kit::aitk::protocol::EntityId;
+
+        let messages = {
+            let mut ctrl = self.chat_controller.lock().unwrap();
+            let current = &ctrl.state().messages;
+            let Some(writing_index) = current.iter().position(|m| m.metadata.is_writing) else { return };
+            let Some(error) = current[writing_index + 1..].iter()
+                .find(|m| matches!(m.from, EntityId::App))
+                .map(|m| m.content.text.clone())
+            else {
+                return;
+            };
+
+            let mut messages: Vec<Message> = current[..writing_index].to_vec();
+            for msg in &mut messages {
+                msg.metadata.is_writing = false;
+            }
+            messages.push(failed_request_message(&error));
+            ctrl.dispatch_mutation(VecMutation::Set(messages.clone()));
+            messages
+        };
+
+        if let Some(failed) = messages.last() {
+            ::log::warn!("{}", failed.content.text);
+            if moly_data::http::is_rate_limit_error(&failed.content.text) {
+                self.schedule_rate_limit_retry(cx, scope);
+            } else if moly_data::http::is_connection_error(&failed.content.text) {
+                let error = failed.content.text.trim_start_matches(REQUEST_FAILED_PREFIX).to_string();
+                self.set_selected_provider_status(scope, ProviderConnectionStatus::Error(error));
+            }
+        }
+        self.persist_messages_now(scope, messages);
+        self.view.redraw(cx);
+    }
+
+    /// Scroll along with a streaming reply only while the list is at its end.
+    /// Scrolled up, the view stays put and the "Jump to latest" pill appears instead.
+    fn follow_streaming_reply(&mut self, cx: &mut Cx) {
+        let key = {
+            let ctrl = self.chat_controller.lock().unwrap();
+            let messages = &ctrl.state().messages;
+            (messages.len(), messages.last().map_or(0, |m| m.content.text.len()))
+        };
+
+        if key == self.followed_content_key {
+            // Only the scroll position can have changed
+            let at_end = self.messages_list().is_at_end();
+            if at_end != self.at_latest_message || (at_end && self.new_content_below) {
+                self.at_latest_message = at_end;
+                self.new_content_below &= !at_end;
+                self.view.redraw(cx);
+            }
+            return;
+        }
+        // A new message means a send, which scrolls to the end
+        if key.0 > self.followed_content_key.0 {
+            self.at_latest_message = true;
+        }
+        self.followed_content_key = key;
+
+        if !self.is_writing() {
+            // Chat switched or reply finished; nothing to catch up with
+            self.new_content_below = false;
+            return;
+        }
+        if self.at_latest_message {
+            self.view.chat(ids!(chat)).write().messages_ref().write().instant_scroll_to_bottom(cx);
+        } else if !self.new_content_below {
+            self.new_content_below = true;
+            self.view.redraw(cx);
+        }
+    }
+
+    /// Scroll to the newest message and follow the reply again
+    fn jump_to_latest(&mut self, cx: &mut Cx) {
+        self.view.chat(ids!(chat)).write().messages_ref().write().instant_scroll_to_bottom(cx);
+        self.at_latest_message = true;
+        self.new_content_below = false;
+        self.view.redraw(cx);
+    }
+
+    /// Provider of the selected model
+    fn selected_provider_id(&self, store: &Store) -> Option<String> {
+        let bot_id = self.chat_controller.lock().unwrap().state().bot_id.clone()?;
+        store.providers_manager.get_provider_for_bot(&bot_id).map(str::to_string)
+    }
+
+    /// Record the selected model's provider status, shared with Settings
+    fn set_selected_provider_status(&mut self, scope: &mut Scope, status: ProviderConnectionStatus) {
+        let Some(store) = scope.data.get_mut::<Store>() else { return };
+        let Some(provider_id) = self.selected_provider_id(store) else { return };
+        store.preferences.set_provider_connection_status(&provider_id, status);
+    }
+
+    /// Mark the selected provider connected once a reply finishes without error.
+    /// Returns whether a reply finished since the last call.
+    fn track_provider_health(&mut self, scope: &mut Scope) -> bool {
+        use moly_kit::aitk::protocol::EntityId;
+
+        let (writing, replied) = {
+            let ctrl = self.chat_controller.lock().unwrap();
+            let messages = &ctrl.state().messages;
+            let writing = messages.iter().any(|m| m.metadata.is_writing);
+            let replied = messages.last().is_some_and(|m| matches!(m.from, EntityId::Bot(_)) && !m.content.text.is_empty());
+            (writing, replied)
+        };
+        let finished = self.reply_in_progress && !writing;
+        self.reply_in_progress = writing;
+        if finished && replied {
+            self.set_selected_provider_status(scope, ProviderConnectionStatus::Connected);
+        }
+        finished
+    }
+
+    /// Recount the latest bot reply for the footer
+    fn update_response_stats(&mut self, cx: &mut Cx) {
+        use moly_kit::aitk::protocol::EntityId;
+
+        self.response_stats_chat = self.current_chat_id;
+        let stats = {
+            let ctrl = self.chat_controller.lock().unwrap();
+            let messages = &ctrl.state().messages;
+            let chat_words = messages.iter()
+                .filter(|m| matches!(m.from, EntityId::Bot(_)))
+                .map(|m| m.content.text.split_whitespace().count())
+                .sum();
+            messages.iter()
+                .rev()
+                .find(|m| matches!(m.from, EntityId::Bot(_)) && !m.metadata.is_writing && !m.content.text.trim().is_empty())
+                .map(|m| response_stats_text(&m.content.text, chat_words))
+        };
+        if stats != self.response_stats {
+            self.response_stats = stats;
+            self.view.redraw(cx);
+        }
+    }
+
+    /// Retry a rate-limited request after the provider's backoff, unless it
+    /// was retried too often already (the error then stays with its Retry button)
+    fn schedule_rate_limit_retry(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        let Some(chat_id) = self.current_chat_id else { return };
+        let bot_id = self.chat_controller.lock().unwrap().state().bot_id.clone();
+        let Some(store) = scope.data.get_mut::<Store>() else { return };
+        let Some(provider_id) = bot_id.and_then(|bot_id| store.providers_manager.get_provider_for_bot(&bot_id).map(str::to_string)) else {
+            return;
+        };
+
+        if store.providers_manager.note_rate_limited(&provider_id, None).is_some() {
+            self.rate_limit_retry = Some((chat_id, provider_id));
+            cx.stop_timer(self.rate_limit_timer);
+            self.rate_limit_timer = cx.start_interval(1.0);
+        }
+    }
+
+    /// Seconds left before the pending rate-limited retry
+    fn rate_limit_retry_secs(&self, scope: &Scope) -> Option<u64> {
+        let (_, provider_id) = self.rate_limit_retry.as_ref()?;
+        let remaining = scope.data.get::<Store>()
+            .and_then(|store| store.providers_manager.rate_limit_remaining(provider_id))
+            .unwrap_or_default();
+        Some(remaining.as_secs_f64().ceil() as u64)
+    }
+
+    fn tick_rate_limit_retry(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        if self.rate_limit_retry_secs(scope).is_some_and(|secs| secs > 0) {
+            self.view.redraw(cx);
+            return;
+        }
+
+        cx.stop_timer(self.rate_limit_timer);
+        // Only retry in the chat that was rate-limited
+        if let Some((chat_id, _)) = self.rate_limit_retry.take() {
+            if self.current_chat_id == Some(chat_id) {
+                self.retry_failed_request(cx, scope);
+            }
+        }
+        self.view.redraw(cx);
+    }
+
+    /// Whether the chat ends with a failed request that can be retried
+    fn has_failed_request(&self) -> bool {
+        use moly_kit::aitk::protocol::EntityId;
+
+        let ctrl = self.chat_controller.lock().unwrap();
+        ctrl.state().messages.last().is_some_and(|m| {
+            matches!(m.from, EntityId::App) && m.content.text.starts_with(REQUEST_FAILED_PREFIX)
+        })
+    }
+
+    /// Drop the error message and send the conversation again
+    fn retry_failed_request(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        if self.is_writing() || !self.has_failed_request() {
+            return;
+        }
+
+        let messages = {
+            let mut ctrl = self.chat_controller.lock().unwrap();
+            let mut messages = ctrl.state().messages.clone();
+            messages.pop();
+            ctrl.dispatch_mutation(VecMutation::Set(messages.clone()));
+            messages
+        };
+        self.persist_messages_now(scope, messages);
+
+        ::log::info!("Retrying failed request");
+        self.chat_controller.lock().unwrap().dispatch_task(ChatTask::Send);
+        self.view.chat(ids!(chat)).write().messages_ref().write().instant_scroll_to_bottom(cx);
+        self.view.redraw(cx);
+    }
+
+    /// Write messages that replaced the controller's state, and continue syncing from them
+    fn persist_messages_now(&mut self, scope: &mut Scope, messages: Vec<Message>) {
+        self.last_synced_message_count = messages.len();
+        self.last_synced_content_len = messages.last().map(|m| m.content.text.len()).unwrap_or(0);
+        self.had_writing_message = false;
+
+        let Some(chat_id) = self.current_chat_id else { return };
+        if let Some(store) = scope.data.get_mut::<Store>() {
+            if !store.chats.is_ephemeral(chat_id) {
+                store.chats.update_chat_messages(chat_id, messages);
+            }
+        }
+    }
+
+    /// The PortalList inside moly-kit's Messages widget
+    fn messages_list(&self) -> PortalListRef {
+        self.view.chat(ids!(chat)).read().messages_ref().portal_list(ids!(list))
+    }
+
+    /// Messages a new chat starts with: the global system prompt, if set.
+    /// The prompt is stored in the chat, so later edits don't change existing chats.
+    fn initial_messages(store: &Store) -> Vec<Message> {
+        use moly_kit::aitk::protocol::{EntityId, MessageContent};
+
+        let prompt = store.preferences.system_prompt.trim();
+        if prompt.is_empty() {
+            return Vec::new();
+        }
+        vec![Message {
+            from: EntityId::System,
+            content: MessageContent {
+                text: prompt.to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }]
+    }
+
+    /// Whether the conversation has anything besides a system prompt
+    fn has_conversation(&self) -> bool {
+        use moly_kit::aitk::protocol::EntityId;
+
+        let ctrl = self.chat_controller.lock().unwrap();
+        ctrl.state().messages.iter().any(|m| !matches!(m.from, EntityId::System))
+    }
+
+    /// Number of messages in the current chat
+    fn message_count(&self) -> usize {
+        self.chat_controller.lock().unwrap().state().messages.len()
+    }
+
+    /// Update the numbering toggle and the message bar buttons
+    fn update_message_nav(&mut self, cx: &mut Cx2d, dark_mode: f64) {
+        self.view.view(ids!(edit_bar)).set_visible(cx, self.editing_message_index.is_some());
+
+        self.view.view(ids!(large_send_confirm_bar)).set_visible(cx, self.pending_large_send.is_some());
+        if let Some((tokens, cost)) = self.pending_large_send {
+            let cost = match cost {
+                Some(cost) if cost < 0.01 => " (under $0.01)".to_string(),
+                Some(cost) => format!(" (about ${:.2})", cost),
+                None => String::new(),
+            };
+            let text = format!("This send is about {} tokens{} with the conversation so far. Send it?", tokens, cost);
+            self.view.label(ids!(large_send_label)).set_text(cx, &text);
+        }
+
+        let has_last_prompt = self.last_user_prompt().is_some();
+        self.view.button(ids!(reuse_prompt_button)).set_visible(cx, has_last_prompt);
+        self.view.view(ids!(reuse_confirm_bar)).set_visible(cx, self.confirm_reuse_prompt);
+
+        let toggle_text = if self.show_message_numbers { "Hide message numbers" } else { "Show message numbers" };
+        self.view.button(ids!(numbering_button)).set_text(cx, toggle_text);
+
+        self.view.button(ids!(numbering_button)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.button(ids!(reuse_prompt_button)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.button(ids!(cancel_reuse_button)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.button(ids!(confirm_reuse_button)).apply_over(cx, live! {
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.label(ids!(reuse_confirm_label)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.label(ids!(large_send_label)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.button(ids!(send_anyway_button)).apply_over(cx, live! {
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.button(ids!(cancel_large_send_button)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.button(ids!(cancel_edit_button)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.button(ids!(resend_edit_button)).apply_over(cx, live! {
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.text_input(ids!(edit_message_input)).apply_over(cx, live! {
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.text_input(ids!(anchor_input)).apply_over(cx, live! {
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+    }
+
+    /// Show the generation parameters of the current chat, refilling the
+    /// inputs when another chat became current since they were last filled
+    fn update_generation_bar(&mut self, cx: &mut Cx2d, scope: &mut Scope, dark_mode: f64) {
+        let toggle_text = if self.show_generation_params { "Hide parameters" } else { "Parameters" };
+        self.view.button(ids!(generation_button)).set_text(cx, toggle_text);
+        self.view.button(ids!(generation_button)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.view(ids!(generation_bar)).set_visible(cx, self.show_generation_params);
+        if !self.show_generation_params {
+            self.generation_inputs_chat = None;
+            return;
+        }
+
+        let Some(store) = scope.data.get::<Store>() else { return };
+        if self.generation_inputs_chat != self.current_chat_id {
+            self.generation_inputs_chat = self.current_chat_id;
+            let params = self.current_chat_id
+                .and_then(|chat_id| store.chats.get_chat_by_id(chat_id))
+                .map(|chat| chat.generation_params.clone())
+                .unwrap_or_default();
+            let temperature = params.temperature.map(|v| v.to_string()).unwrap_or_default();
+            let top_p = params.top_p.map(|v| v.to_string()).unwrap_or_default();
+            let max_tokens = params.max_tokens.map(|v| v.to_string()).unwrap_or_default();
+            self.view.text_input(ids!(temperature_input)).set_text(cx, &temperature);
+            self.view.text_input(ids!(top_p_input)).set_text(cx, &top_p);
+            self.view.text_input(ids!(max_tokens_input)).set_text(cx, &max_tokens);
+        }
+
+        let hint = generation_defaults_hint(&store.preferences.generation_params);
+        self.view.label(ids!(generation_hint)).set_text(cx, &hint);
+
+        for input in [ids!(temperature_input), ids!(top_p_input), ids!(max_tokens_input)] {
+            self.view.text_input(input).apply_over(cx, live! {
+                draw_bg: { dark_mode: (dark_mode) }
+                draw_text: { dark_mode: (dark_mode) }
+            });
+        }
+        self.view.label(ids!(generation_hint)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.button(ids!(save_generation_default_button)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode) }
+        });
+    }
+
+    /// Have the provider clients send chats with the current chat's generation
+    /// parameters over the defaults
+    fn sync_generation_params(&self, scope: &mut Scope) {
+        let Some(store) = scope.data.get::<Store>() else { return };
+        let chat_params = self.current_chat_id
+            .and_then(|chat_id| store.chats.get_chat_by_id(chat_id))
+            .map(|chat| chat.generation_params.clone())
+            .unwrap_or_default();
+        let params = store.preferences.generation_params.overridden_by(&chat_params);
+        store.providers_manager.set_generation_params(params);
+    }
+
+    /// Parameters typed into the generation inputs. Blank or unparsable
+    /// fields are left unset, so the default applies to them.
+    fn generation_inputs(&self) -> GenerationParams {
+        let value = |id| self.view.text_input(id).text().trim().parse().ok();
+        GenerationParams {
+            temperature: value(ids!(temperature_input)),
+            top_p: value(ids!(top_p_input)),
+            max_tokens: self.view.text_input(ids!(max_tokens_input)).text().trim().parse().ok(),
+        }
+    }
+
+    /// Swap the rendered messages for a verbatim transcript when the current chat
+    /// is in raw mode. Returns whether raw mode is on.
+    fn update_raw_messages(&mut self, cx: &mut Cx2d, scope: &mut Scope, dark_mode: f64) -> bool {
+        let raw = self.current_chat_id
+            .and_then(|chat_id| scope.data.get::<Store>()?.chats.get_chat_by_id(chat_id).map(|chat| chat.raw_text))
+            .unwrap_or(false);
+
+        let toggle_text = if raw { "Show rendered" } else { "Show raw text" };
+        self.view.button(ids!(raw_text_button)).set_text(cx, toggle_text);
+        self.view.button(ids!(raw_text_button)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode) }
+        });
+
+        // The prompt input lives inside the Chat widget, so in raw mode only its
+        // messages are hidden and the chat shrinks to fit the prompt
+        self.view.view(ids!(raw_messages)).set_visible(cx, raw);
+        self.view.chat(ids!(chat)).read().messages_ref().set_visible(cx, !raw);
+        if raw {
+            self.view.view(ids!(chat_area)).apply_over(cx, live! { height: Fit });
+            self.view.chat(ids!(chat)).apply_over(cx, live! { height: Fit });
+        } else {
+            self.view.view(ids!(chat_area)).apply_over(cx, live! { height: Fill });
+            self.view.chat(ids!(chat)).apply_over(cx, live! { height: Fill });
+            self.raw_text_key = (None, 0, 0);
+            return false;
+        }
+
+        let transcript = {
+            use moly_kit::aitk::protocol::EntityId;
+
+            let ctrl = self.chat_controller.lock().unwrap();
+            let messages = &ctrl.state().messages;
+            let key = (
+                self.current_chat_id,
+                messages.len(),
+                messages.last().map_or(0, |m| m.content.text.len()),
+            );
+            if key == self.raw_text_key {
+                None
+            } else {
+                self.raw_text_key = key;
+                let sections: Vec<String> = messages.iter().map(|message| {
+                    let role = match &message.from {
+                        EntityId::User => "User",
+                        EntityId::System => "System",
+                        EntityId::Bot(_) => "Assistant",
+                        _ => "Tool",
+                    };
+                    format!("{}:\n{}", role, message.content.text)
+                }).collect();
+                Some(sections.join("\n\n"))
+            }
+        };
+        if let Some(text) = transcript {
+            self.view.label(ids!(raw_messages_text)).set_text(cx, &text);
+        }
+        self.view.label(ids!(raw_messages_text)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        true
+    }
+
+    /// Fill in Moly's additions to the visible moly-kit message rows. Rows only
+    /// exist once the list has drawn them, so this runs after the draw and asks
+    /// for another one when any row changed.
+    fn decorate_message_rows(&mut self, cx: &mut Cx2d, scope: &mut Scope, dark_mode: f64) {
+        use std::hash::{Hash, Hasher};
+
+        let count = self.message_count();
+        let rows: Vec<(usize, WidgetRef)> = {
+            let list = self.messages_list();
+            let Some(list) = list.borrow() else { return };
+            (list.first_id()..count)
+                .map_while(|index| list.get_item(index).map(|(_, row)| (index, row.clone())))
+                .collect()
+        };
+
+        let show_tool_calls = scope.data.get::<Store>().map_or(false, |store| store.preferences.show_tool_calls);
+        let tool_calls = if show_tool_calls { self.tool_call_records(scope) } else { Vec::new() };
+
+        let user_rows: HashSet<usize> = {
+            use moly_kit::aitk::protocol::EntityId;
+
+            let ctrl = self.chat_controller.lock().unwrap();
+            let messages = &ctrl.state().messages;
+            rows.iter()
+                .map(|(index, _)| *index)
+                .filter(|index| messages.get(*index).is_some_and(|m| matches!(m.from, EntityId::User)))
+                .collect()
+        };
+
+        let mut signature = std::collections::hash_map::DefaultHasher::new();
+        (dark_mode.to_bits(), self.show_message_numbers, self.copied_message_index).hash(&mut signature);
+        for (index, row) in &rows {
+            let editable = user_rows.contains(index);
+            editable.hash(&mut signature);
+            (index, row.widget_uid().0).hash(&mut signature);
+            let calls: Vec<&ToolCallRecord> = tool_calls.iter().filter(|call| call.message_index == *index).collect();
+            let expanded = self.expanded_tool_calls.contains(index);
+            (calls.len(), expanded).hash(&mut signature);
+            Self::decorate_tool_calls(cx, row, &calls, expanded, dark_mode);
+            let model_label = self.model_label_for_message(scope, *index);
+            model_label.hash(&mut signature);
+            self.decorate_gutter(cx, row, *index, model_label.as_deref(), editable, dark_mode);
+        }
+
+        // Rows drawn for the first time get their additions on the next draw
+        let signature = signature.finish();
+        if signature != self.message_rows_signature {
+            self.message_rows_signature = signature;
+            self.view.chat(ids!(chat)).redraw(cx);
+        }
+    }
+
+    /// Copy button under a message, its number and link while numbering is
+    /// on, the edit button of a user message, and the model that produced it
+    fn decorate_gutter(
+        &self,
+        cx: &mut Cx,
+        row: &WidgetRef,
+        index: usize,
+        model_label: Option<&str>,
+        editable: bool,
+        dark_mode: f64,
+    ) {
+        let gutter = row.view(ids!(message_gutter));
+        gutter.set_visible(cx, true);
+        let copy_text = if self.copied_message_index == Some(index) { "Copied!" } else { "Copy" };
+        gutter.button(ids!(copy_message_button)).set_text(cx, copy_text);
+        gutter.label(ids!(message_number)).set_visible(cx, self.show_message_numbers);
+        gutter.label(ids!(message_number)).set_text(cx, &format!("#{}", index + 1));
+        gutter.button(ids!(copy_anchor_button)).set_visible(cx, self.show_message_numbers);
+        gutter.button(ids!(edit_message_button)).set_visible(cx, editable);
+        gutter.view(ids!(message_model_label)).set_visible(cx, model_label.is_some());
+        if let Some(name) = model_label {
+            gutter.label(ids!(message_model_label.model_name)).set_text(cx, name);
+        }
+
+        gutter.label(ids!(message_number)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        gutter.button(ids!(copy_anchor_button)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        gutter.button(ids!(copy_message_button)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        gutter.button(ids!(edit_message_button)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        gutter.view(ids!(message_model_label)).apply_over(cx, live! {
+            draw_bg: { dark_mode: (dark_mode) }
+        });
+        gutter.label(ids!(message_model_label.model_name)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode) }
+        });
+    }
+
+    /// Collapsible card with the MCP tool calls made by one message. Calls still
+    /// awaiting approval or execution show as pending.
+    fn decorate_tool_calls(cx: &mut Cx, row: &WidgetRef, calls: &[&ToolCallRecord], expanded: bool, dark_mode: f64) {
+        let card = row.view(ids!(message_tool_calls));
+        card.set_visible(cx, !calls.is_empty());
+        if calls.is_empty() {
+            return;
+        }
+
+        let arrow = if expanded { "▾" } else { "▸" };
+        card.button(ids!(tool_calls_button)).set_text(cx, &format!("{} Tool calls ({})", arrow, calls.len()));
+        card.view(ids!(tool_calls_details)).set_visible(cx, expanded);
+        if expanded {
+            let text = calls.iter().map(|call| {
+                let outcome = match &call.result {
+                    Some(result) if call.is_error => format!("Error: {}", summarize_tool_result(result)),
+                    Some(result) => format!("Result: {}", summarize_tool_result(result)),
+                    None => "Pending".to_string(),
+                };
+                format!("{}\n  Arguments: {}\n  {}", call.name, call.arguments, outcome)
+            }).collect::<Vec<_>>().join("\n\n");
+            card.label(ids!(tool_calls_text)).set_text(cx, &text);
+        }
+
+        card.button(ids!(tool_calls_button)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        card.view(ids!(tool_calls_details)).apply_over(cx, live! {
+            draw_bg: { dark_mode: (dark_mode) }
+        });
+        card.label(ids!(tool_calls_text)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode) }
+        });
+    }
+
+    /// Tool calls of the current chat: from the messages, or as recorded with
+    /// the chat when its messages were restored without them
+    fn tool_call_records(&self, scope: &mut Scope) -> Vec<ToolCallRecord> {
+        let calls = ToolCallRecord::from_messages(&self.chat_controller.lock().unwrap().state().messages);
+        if !calls.is_empty() {
+            return calls;
+        }
+        self.current_chat_id
+            .and_then(|chat_id| scope.data.get::<Store>()?.chats.get_chat_by_id(chat_id).map(|chat| chat.tool_calls.clone()))
+            .unwrap_or_default()
+    }
+
+    /// Display name of the model that produced an assistant message, from the
+    /// bot the message records in `from`
+    fn model_label_for_message(&self, scope: &mut Scope, index: usize) -> Option<String> {
+        let store = scope.data.get::<Store>()?;
+        let chat = store.chats.get_chat_by_id(self.current_chat_id?)?;
+        let ctrl = self.chat_controller.lock().unwrap();
+        let bot_id = chat.message_bot_id(ctrl.state().messages.get(index)?)?;
+
+        // Prefer the user's alias, then the bot's display name when it is still available
+        let name = store.preferences.model_alias(bot_id.as_str()).map(str::to_string)
+            .or_else(|| ctrl.state().bots.iter().find(|bot| &bot.id == bot_id).map(|bot| bot.name.clone()))
+            .unwrap_or_else(|| bot_id.as_str().to_string());
+        Some(name)
+    }
+
+    /// Copy the text of the message at `index`.
+    /// Streaming messages copy whatever has arrived so far.
+    fn copy_message_text(&mut self, cx: &mut Cx, index: usize) {
+        let Some(text) = self.chat_controller.lock().unwrap().state().messages.get(index)
+            .map(|message| message.content.text.clone()) else { return };
+
+        cx.copy_to_clipboard(&text);
+        self.copied_message_index = Some(index);
+        self.copy_feedback_timer = cx.start_timeout(1.0);
+        self.view.redraw(cx);
+    }
+
+    /// Copy a link to a message of the current chat
+    fn copy_message_anchor(&mut self, cx: &mut Cx, index: usize) {
+        let Some(chat_id) = self.current_chat_id else { return };
+        let anchor = MessageAnchor::new(chat_id, index).to_string();
+        ::log::info!("Copied message anchor {}", anchor);
+        cx.copy_to_clipboard(&anchor);
+    }
+
+    /// Navigate to the chat and message referenced by an anchor link
+    pub fn open_message_anchor(&mut self, cx: &mut Cx, scope: &mut Scope, link: &str) {
+        let Some(anchor) = MessageAnchor::parse(link) else {
+            ::log::warn!("Invalid message link: {}", link);
+            return;
+        };
+
+        let exists = scope.data.get::<Store>()
+            .map_or(false, |store| store.chats.get_chat_by_id(anchor.chat_id).is_some());
+        if !exists {
+            ::log::warn!("Message link points to a missing chat: {}", anchor.chat_id);
+            return;
+        }
+
+        self.switch_to_chat(cx, scope, anchor.chat_id);
+
+        let count = self.message_count();
+        if count > 0 {
+            self.pending_scroll_to_message = Some(anchor.message_index.min(count - 1));
+        }
+        self.show_message_numbers = true;
+        self.view.text_input(ids!(anchor_input)).set_text(cx, "");
+        self.view.redraw(cx);
+    }
+
+    /// Turn the current ephemeral chat into a saved one
+    fn save_ephemeral_chat(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        let Some(chat_id) = self.current_chat_id else { return };
+        let Some(store) = scope.data.get_mut::<Store>() else { return };
+        if !store.chats.is_ephemeral(chat_id) {
+            return;
+        }
+
+        let (messages, bot_id) = {
+            let ctrl = self.chat_controller.lock().unwrap();
+            (ctrl.state().messages.clone(), ctrl.state().bot_id.clone())
+        };
+        let message_count = messages.len();
+        let last_content_len = messages.last().map(|m| m.content.text.len()).unwrap_or(0);
+        let has_writing_message = messages.iter().any(|m| m.metadata.is_writing);
+
+        store.chats.persist_chat(chat_id, messages);
+        store.chats.update_chat_bot(chat_id, bot_id);
+
+        // Continue syncing from what was just saved
+        self.last_synced_message_count = message_count;
+        self.had_writing_message = has_writing_message;
+        self.last_synced_content_len = last_content_len;
+
+        cx.action(Toast::success("Chat saved to history"));
+        self.view.redraw(cx);
+    }
+
+    /// Delete a chat session
+    pub fn delete_chat(&mut self, cx: &mut Cx, scope: &mut Scope, chat_id: ChatId) {
+        let Some(store) = scope.data.get_mut::<Store>() else { return };
+
+        // Check if we're deleting the current chat
+        let is_current = self.current_chat_id == Some(chat_id);
+
+        // Delete from storage (this also updates current_chat_id if needed)
+        store.chats.delete_chat(chat_id);
+
+        ::log::info!("Deleted chat {}", chat_id);
+
+        // If we deleted the current chat, we need to switch to another chat or create a new one
+        if is_current {
+            if let Some(next_chat) = store.chats.saved_chats.first() {
+                // Switch to the next available chat
+                let next_id = next_chat.id;
+                self.current_chat_id = Some(next_id);
+                store.chats.set_current_chat(Some(next_id));
+
+                // Load the chat's messages into controller
+                if let Some(chat) = store.chats.get_chat_by_id(next_id) {
+                    let mut messages = chat.messages.clone();
+                    for msg in &mut messages {
+                        msg.metadata.is_writing = false;
+                    }
+                    let message_count = messages.len();
+                    let last_content_len = messages.last().map(|m| m.content.text.len()).unwrap_or(0);
+
+                    {
+                        let mut ctrl = self.chat_controller.lock().unwrap();
+                        ctrl.dispatch_mutation(VecMutation::Set(messages));
+
+                        if let Some(ref bot_id) = chat.bot_id {
+                            ctrl.dispatch_mutation(ChatStateMutation::SetBotId(Some(bot_id.clone())));
+                        }
+                    }
+
+                    self.last_synced_message_count = message_count;
+                    self.had_writing_message = false;
+                    self.last_synced_content_len = last_content_len;
+                }
+            } else {
+                // No chats left, create a new one
+                self.create_new_chat(cx, scope);
+                return; // create_new_chat handles redraw
+            }
+
+            // Reset scroll position
+            self.view.chat(ids!(chat)).write().messages_ref().write().instant_scroll_to_bottom(cx);
+        }
+
+        self.view.redraw(cx);
+    }
+}
+
+impl Widget for ChatApp {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        // Set controller on Chat widget early (required for Messages widget)
+        self.maybe_set_controller_on_widget(cx);
+
+        // Check and configure providers from Store
+        self.maybe_configure_providers(cx, scope);
+        self.sync_generation_params(scope);
+
+        // Check for loaded bots from the ChatController
+        self.check_for_loaded_bots(cx, scope);
+        self.check_for_fetch_error(cx, scope);
+        if self.fetch_timer.is_event(event).is_some() && self.fetch_in_progress {
+            self.retry_fetch_or_skip(cx, scope, "timed out", true);
+        }
+        self.apply_refreshed_bots(cx, scope);
+
+        // Handle pending controller reset (e.g., after models load or view becomes visible)
+        // Runs after the loaded-bots checks above so models that arrived while the
+        // view was hidden are already stored when the selector is repopulated
+        if self.needs_controller_reset {
+            self.reset_controller_bots(cx, scope);
+        }
+
+        // Initialize chat from persistence (load or create)
+        self.maybe_initialize_chat(cx, scope);
+
+        // Move on if the current chat was deleted elsewhere (e.g. history cleared in Settings)
+        self.follow_removed_chat(cx, scope);
+
+        // Track model selection changes and save to preferences
+        self.track_model_selection(cx, scope);
+
+        // Replace a reply that errored mid-stream before it gets persisted
+        self.handle_stream_error(cx, scope);
+
+        // A finished reply shows the provider is reachable again
+        let reply_finished = self.track_provider_health(scope);
+
+        // Reply length is only counted once it's complete, or when another chat opens
+        if reply_finished || self.response_stats_chat != self.current_chat_id {
+            self.update_response_stats(cx);
+        }
+
+        // Keep up with a streaming reply unless scrolled up to read
+        self.follow_streaming_reply(cx);
+
+        // Sync messages to persistence when they change
+        self.sync_messages_to_persistence(scope);
+
+        // Sync bot selection to current chat
+        self.sync_bot_to_chat(scope);
+
+        // Clear the model fallback notice
+        if self.model_fallback_timer.is_event(event).is_some() {
+            self.model_fallback_notice = None;
+            self.view.redraw(cx);
+        }
+
+        // Count down a rate-limit backoff, then retry
+        if self.rate_limit_timer.is_event(event).is_some() {
+            self.tick_rate_limit_retry(cx, scope);
+        }
+
+        // Clear the copy confirmation
+        if self.copy_feedback_timer.is_event(event).is_some() {
+            self.copied_message_index = None;
+            self.view.redraw(cx);
+        }
+
+        // Reuse the last prompt (Ctrl/Cmd+Shift+Up unless rebound)
+        if let Event::KeyDown(key_event) = event {
+            let pressed = scope.data.get::<Store>()
+                .map_or(false, |store| store.preferences.shortcut_pressed(SHORTCUT_REUSE_LAST_PROMPT, key_event));
+            if pressed {
+                self.reuse_last_prompt(cx, false);
+            }
+        }
+
+        // An Enter that commits an IME composition must not reach the prompt input,
+        // otherwise the half-composed text would be sent
+        if self.track_ime_composition(event) {
+            return;
+        }
+
+        // Nothing is sent while the provider is unreachable (see draw_walk)
+        if self.send_blocked && self.is_send_event(cx, event) {
+            return;
+        }
+
+        // A send over the configured size waits for confirmation
+        if self.hold_large_send(cx, event, scope) {
+            return;
+        }
+
+        // moly-kit's own send only takes the prompt text, so sends with images go through ours
+        if !self.pending_images.is_empty() && self.is_send_event(cx, event) {
+            self.send_draft(cx);
+            return;
+        }
+
+        // Delegate events directly to view (like moly-ai does)
+        // Don't use capture_actions as it can interfere with nested widget event handling
+        self.view.handle_event(cx, event, scope);
+
+        // Use WidgetMatchEvent pattern for handling actions
+        self.widget_match_event(cx, event, scope);
+
+        self.update_draft_size(cx);
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        // Get dark mode state
+        let dark_mode_value = if let Some(store) = scope.data.get::<Store>() {
+            if store.is_dark_mode() { 1.0 } else { 0.0 }
+        } else {
+            0.0
+        };
+
+        // Apply dark mode to background
+        self.view.apply_over(cx, live! {
+            draw_bg: { dark_mode: (dark_mode_value) }
+        });
+
+        // Apply dark mode to header labels
+        self.view.label(ids!(title_label)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode_value) }
+        });
+        self.view.label(ids!(status_label)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode_value) }
+        });
+        self.view.text_input(ids!(model_search_input)).apply_over(cx, live! {
+            draw_bg: { dark_mode: (dark_mode_value) }
+            draw_text: { dark_mode: (dark_mode_value) }
+        });
+        self.view.label(ids!(draft_size_label)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode_value) }
+        });
+
+        // Apply dark mode to separator
+        self.view.view(ids!(separator)).apply_over(cx, live! {
+            draw_bg: { dark_mode: (dark_mode_value) }
+        });
+
+        // Update status label based on provider configuration
+        self.view.view(ids!(provider_health_dot)).set_visible(cx, self.providers_configured);
+        let mut send_blocked = false;
+        if self.providers_configured {
+            // A failed re-test or send of the selected model's provider (or Moly Server
+            // going down) takes precedence, and sets the health dot next to the title
+            let (health, active_error) = scope.data.get::<Store>().and_then(|store| {
+                let provider_id = self.selected_provider_id(store)
+                    .or_else(|| store.providers_manager.active_provider_id().map(str::to_string))?;
+                let provider = store.preferences.get_provider(&provider_id)?;
+                Some(match (&provider.last_connection_status, &self.moly_server_status) {
+                    (ProviderConnectionStatus::Error(e), _) => {
+                        (3.0, Some(format!("{} unreachable — check Settings ({})", provider.name, e)))
+                    }
+                    (_, ServerConnectionStatus::Error(e)) if provider.provider_type == ProviderType::MolyServer => {
+                        (3.0, Some(format!("{} unreachable — check Settings ({})", provider.name, e)))
+                    }
+                    (ProviderConnectionStatus::Connecting, _) => (1.0, None),
+                    (ProviderConnectionStatus::Connected, _) => (2.0, None),
+                    (ProviderConnectionStatus::NotConnected, _) => (0.0, None),
+                })
+            }).unwrap_or((0.0, None));
+            self.view.view(ids!(provider_health_dot)).apply_over(cx, live! {
+                draw_bg: { status: (health), dark_mode: (dark_mode_value) }
+            });
+            send_blocked = active_error.is_some();
+
+            // Providers that timed out while loading models
+            let failed_note = if self.failed_provider_ids.is_empty() {
+                String::new()
+            } else {
+                let names: Vec<String> = self.failed_provider_ids.iter().map(|id| {
+                    scope.data.get::<Store>()
+                        .and_then(|store| store.preferences.get_provider(id))
+                        .map_or_else(|| id.clone(), |provider| provider.name.clone())
+                }).collect();
+                format!(" ({} failed to load)", names.join(", "))
+            };
+
+            let fetch_progress = self.fetch_progress(scope);
+            let num_providers = self.fetched_provider_ids.len();
+            let context_warning = self.context_warning(scope);
+            // Plain connection info and loading progress are muted; everything else is a warning
+            let is_warning = active_error.is_some() || self.rate_limit_retry.is_some() || (fetch_progress.is_none()
+                && (self.model_fallback_notice.is_some() || context_warning.is_some()
+                    || !failed_note.is_empty() || num_providers == 0));
+            let warning_value = if is_warning { 1.0 } else { 0.0 };
+            self.view.label(ids!(status_label)).apply_over(cx, live! {
+                draw_text: { warning: (warning_value) }
+            });
+
+            let rate_limit_secs = self.rate_limit_retry_secs(scope);
+            if let Some(error) = active_error {
+                self.view.label(ids!(status_label)).set_text(cx, &error);
+            } else if let Some(secs) = rate_limit_secs {
+                self.view.label(ids!(status_label)).set_text(cx, &format!("Rate limited, retrying in {}s", secs));
+            } else if let Some(progress) = fetch_progress {
+                self.view.label(ids!(status_label)).set_text(cx, &progress);
+            } else if let Some(notice) = &self.model_fallback_notice {
+                self.view.label(ids!(status_label)).set_text(cx, notice);
+            } else if let Some(warning) = context_warning {
+                self.view.label(ids!(status_label)).set_text(cx, &warning);
+            } else if num_providers == 1 {
+                let provider_name = self.fetched_provider_ids[0].as_str();
+                self.view.label(ids!(status_label)).set_text(cx,
+                    &format!("Connected to {}{}", provider_name, failed_note));
+            } else if num_providers > 1 {
+                self.view.label(ids!(status_label)).set_text(cx,
+                    &format!("Connected to {} providers{}", num_providers, failed_note));
+            } else if !self.failed_provider_ids.is_empty() && !self.fetch_in_progress {
+                self.view.label(ids!(status_label)).set_text(cx,
+                    &format!("No models loaded{}", failed_note));
+            }
+        }
+
+        // Sending to an unreachable provider would only fail
+        if send_blocked != self.send_blocked {
+            self.send_blocked = send_blocked;
+            self.view.chat(ids!(chat)).read().prompt_input_ref().button(ids!(submit)).set_enabled(cx, !send_blocked);
+        }
+
+        // Update history panel's current chat
+        self.view.chat_history_panel(ids!(history_panel)).set_current_chat(self.current_chat_id);
+
+        // Apply a pending anchor navigation before the messages list draws
+        if let Some(index) = self.pending_scroll_to_message.take() {
+            self.messages_list().set_first_id_and_scroll(index, 0.0);
+        }
+
+        self.update_message_nav(cx, dark_mode_value);
+        self.update_generation_bar(cx, scope, dark_mode_value);
+        self.update_image_bar(cx, scope, dark_mode_value);
+        let raw_text = self.update_raw_messages(cx, scope, dark_mode_value);
+
+        // Greeting for empty chats (the no-provider state is reported by status_label instead)
+        let show_welcome = self.providers_configured && !raw_text && !self.has_conversation();
+        self.view.widget(ids!(welcome)).set_visible(cx, show_welcome);
+
+        // Ephemeral chats get a banner with the option to keep them
+        let is_ephemeral = self.current_chat_id.is_some_and(|chat_id| {
+            scope.data.get::<Store>().map_or(false, |store| store.chats.is_ephemeral(chat_id))
         });
+        self.view.view(ids!(ephemeral_bar)).set_visible(cx, is_ephemeral);
+        if is_ephemeral {
+            self.view.view(ids!(ephemeral_bar)).apply_over(cx, live! {
+                draw_bg: { dark_mode: (dark_mode_value) }
+            });
+            self.view.label(ids!(ephemeral_label)).apply_over(cx, live! {
+                draw_text: { dark_mode: (dark_mode_value) }
+            });
+            self.view.button(ids!(save_ephemeral_button)).apply_over(cx, live! {
+                draw_bg: { dark_mode: (dark_mode_value) }
+                draw_text: { dark_mode: (dark_mode_value) }
+            });
+        }
 
-        // Apply dark mode to header labels
-        self.view.label(ids!(title_label)).apply_over(cx, live! {
+        // Stop control is only available while a reply is streaming
+        let is_writing = self.is_writing();
+        self.view.view(ids!(stop_bar)).set_visible(cx, is_writing);
+        self.view.button(ids!(stop_button)).apply_over(cx, live! {
+            draw_bg: { dark_mode: (dark_mode_value) }
             draw_text: { dark_mode: (dark_mode_value) }
         });
-        self.view.label(ids!(status_label)).apply_over(cx, live! {
+
+        self.view.view(ids!(jump_to_latest_bar)).set_visible(cx, self.new_content_below);
+        self.view.button(ids!(jump_to_latest_button)).apply_over(cx, live! {
+            draw_bg: { dark_mode: (dark_mode_value) }
             draw_text: { dark_mode: (dark_mode_value) }
         });
 
-        // Apply dark mode to separator
-        self.view.view(ids!(separator)).apply_over(cx, live! {
+        // Retry is offered while the chat ends with a failed request
+        let awaiting_retry = self.rate_limit_retry.is_some();
+        self.view.view(ids!(retry_bar)).set_visible(cx, !is_writing && !awaiting_retry && self.has_failed_request());
+        self.view.button(ids!(retry_request_button)).apply_over(cx, live! {
             draw_bg: { dark_mode: (dark_mode_value) }
+            draw_text: { dark_mode: (dark_mode_value) }
         });
 
-        // Update status label based on provider configuration
-        if self.providers_configured {
-            let num_providers = self.fetched_provider_ids.len();
-            if num_providers == 1 {
-                let provider_name = self.current_provider_id.as_deref().unwrap_or("Unknown");
-                self.view.label(ids!(status_label)).set_text(cx,
-                    &format!("Connected to {}", provider_name));
-            } else if num_providers > 1 {
-                self.view.label(ids!(status_label)).set_text(cx,
-                    &format!("Connected to {} providers", num_providers));
-            }
+        // Reply length is shown once a reply has finished, not while the next one streams
+        let stats = self.response_stats.as_deref().filter(|_| !is_writing);
+        self.view.view(ids!(response_stats_bar)).set_visible(cx, stats.is_some());
+        if let Some(stats) = stats {
+            self.view.label(ids!(response_stats_label)).set_text(cx, stats);
+            self.view.label(ids!(response_stats_label)).apply_over(cx, live! {
+                draw_text: { dark_mode: (dark_mode_value) }
+            });
         }
 
-        // Update history panel's current chat
-        self.view.chat_history_panel(ids!(history_panel)).set_current_chat(self.current_chat_id);
-
         // Simply delegate to view's draw_walk - no step() pattern needed
         // ChatHistoryPanel handles its own PortalList, Chat handles its own
-        self.view.draw_walk(cx, scope, walk)
+        let step = self.view.draw_walk(cx, scope, walk);
+        self.decorate_message_rows(cx, scope, dark_mode_value);
+        step
+    }
+}
+
+impl ChatAppRef {
+    /// See [`ChatApp::controller`]
+    pub fn controller(&self) -> Option<Arc<Mutex<ChatController>>> {
+        self.borrow().map(|inner| inner.controller())
+    }
+
+    pub fn push_system_message(&self, cx: &mut Cx, text: &str) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.push_system_message(cx, text);
+        }
     }
 }
 
@@ -835,12 +3068,174 @@ impl WidgetMatchEvent for ChatApp {
             if let ChatHistoryAction::NewChat = action.cast() {
                 self.create_new_chat(cx, scope);
             }
+            if let ChatHistoryAction::NewEphemeralChat = action.cast() {
+                self.create_ephemeral_chat(cx, scope);
+            }
             if let ChatHistoryAction::SelectChat(chat_id) = action.cast() {
                 self.switch_to_chat(cx, scope, chat_id);
             }
             if let ChatHistoryAction::DeleteChat(chat_id) = action.cast() {
                 self.delete_chat(cx, scope, chat_id);
             }
+            if let ChatHistoryAction::RenameChat(chat_id, title) = action.cast() {
+                if let Some(store) = scope.data.get_mut::<Store>() {
+                    store.chats.rename_chat(chat_id, title);
+                }
+                self.view.redraw(cx);
+            }
+            if let ChatHistoryAction::TogglePin(chat_id) = action.cast() {
+                if let Some(store) = scope.data.get_mut::<Store>() {
+                    let pinned = store.chats.get_chat_by_id(chat_id).is_some_and(|c| c.pinned);
+                    store.chats.set_chat_pinned(chat_id, !pinned);
+                }
+                self.view.redraw(cx);
+            }
+            if let FavoritePromptsAction::Insert(text) = action.cast() {
+                self.insert_prompt_text(cx, &text);
+            }
+            if let ChatWelcomeAction::UseSuggestion(text) = action.cast() {
+                // A suggestion is sent as is; the welcome hides once it's in the chat
+                self.insert_prompt_text(cx, &text);
+                self.send_draft_as_typed(cx, scope);
+            }
+            if let StoreAction::ServerStatusChanged(status) = action.cast() {
+                self.moly_server_status = status;
+                self.view.redraw(cx);
+            }
+        }
+
+        if self.view.button(ids!(stop_button)).clicked(actions) {
+            self.stop_generation(cx, scope);
+        }
+
+        if let Some(text) = self.view.text_input(ids!(model_search_input)).changed(actions) {
+            self.model_search = text;
+            self.needs_controller_reset = true;
+            self.view.redraw(cx);
+        }
+
+        if self.view.button(ids!(jump_to_latest_button)).clicked(actions) {
+            self.jump_to_latest(cx);
+        }
+
+        if self.view.button(ids!(retry_request_button)).clicked(actions) {
+            self.retry_failed_request(cx, scope);
+        }
+
+        if self.view.button(ids!(save_ephemeral_button)).clicked(actions) {
+            self.save_ephemeral_chat(cx, scope);
+        }
+
+        // Message row additions
+        for (index, row) in self.messages_list().items_with_actions(actions) {
+            if row.button(ids!(copy_message_button)).clicked(actions) {
+                self.copy_message_text(cx, index);
+            }
+            if row.button(ids!(copy_anchor_button)).clicked(actions) {
+                self.copy_message_anchor(cx, index);
+            }
+            // Only the explicit button edits, so selecting text or tapping
+            // next to the other gutter buttons leaves the message alone
+            if row.button(ids!(edit_message_button)).clicked(actions) && self.editing_message_index.is_none() {
+                self.start_editing_message(cx, index);
+            }
+            if row.button(ids!(tool_calls_button)).clicked(actions) {
+                if !self.expanded_tool_calls.remove(&index) {
+                    self.expanded_tool_calls.insert(index);
+                }
+                self.view.redraw(cx);
+            }
+        }
+
+        // Reuse last prompt
+        if self.view.button(ids!(reuse_prompt_button)).clicked(actions) {
+            self.reuse_last_prompt(cx, false);
+        }
+        if self.view.button(ids!(confirm_reuse_button)).clicked(actions) {
+            self.reuse_last_prompt(cx, true);
+        }
+        if self.view.button(ids!(cancel_reuse_button)).clicked(actions) {
+            self.confirm_reuse_prompt = false;
+            self.view.redraw(cx);
+        }
+
+        // Confirm or cancel a large send
+        if self.view.button(ids!(send_anyway_button)).clicked(actions) {
+            self.send_held_draft(cx);
+        }
+        if self.view.button(ids!(cancel_large_send_button)).clicked(actions) {
+            self.pending_large_send = None;
+            self.view.redraw(cx);
+        }
+
+        // Edit & resend
+        if self.view.button(ids!(cancel_edit_button)).clicked(actions) {
+            self.editing_message_index = None;
+            self.view.redraw(cx);
+        }
+        let edit_input = self.view.text_input(ids!(edit_message_input));
+        let edited_text = if let Some((text, _)) = edit_input.returned(actions) {
+            Some(text)
+        } else if self.view.button(ids!(resend_edit_button)).clicked(actions) {
+            Some(edit_input.text())
+        } else {
+            None
+        };
+        if let Some(text) = edited_text {
+            self.resend_edited_message(cx, scope, text);
+        }
+
+        // Raw or rendered messages, remembered per chat
+        if self.view.button(ids!(raw_text_button)).clicked(actions) {
+            if let (Some(chat_id), Some(store)) = (self.current_chat_id, scope.data.get_mut::<Store>()) {
+                let raw_text = store.chats.get_chat_by_id(chat_id).map_or(false, |chat| chat.raw_text);
+                store.chats.set_chat_raw_text(chat_id, !raw_text);
+            }
+            self.view.redraw(cx);
+        }
+
+        // Image attachments
+        if self.view.button(ids!(attach_image_button)).clicked(actions) {
+            self.show_image_path_input = !self.show_image_path_input;
+            self.view.redraw(cx);
+        }
+        if let Some((path, _)) = self.view.text_input(ids!(image_path_input)).returned(actions) {
+            self.attach_image(cx, &path);
+        }
+        if self.view.button(ids!(clear_images_button)).clicked(actions) {
+            self.pending_images.clear();
+            self.view.redraw(cx);
+        }
+
+        // Generation parameters
+        if self.view.button(ids!(generation_button)).clicked(actions) {
+            self.show_generation_params = !self.show_generation_params;
+            self.view.redraw(cx);
+        }
+        let generation_changed = [ids!(temperature_input), ids!(top_p_input), ids!(max_tokens_input)]
+            .into_iter()
+            .any(|input| self.view.text_input(input).changed(actions).is_some());
+        if generation_changed {
+            let params = self.generation_inputs();
+            if let (Some(chat_id), Some(store)) = (self.current_chat_id, scope.data.get_mut::<Store>()) {
+                store.chats.update_chat_generation_params(chat_id, params);
+            }
+        }
+        if self.view.button(ids!(save_generation_default_button)).clicked(actions) {
+            if let Some(store) = scope.data.get_mut::<Store>() {
+                let params = store.preferences.generation_params.overridden_by(&self.generation_inputs());
+                store.preferences.set_generation_params(params);
+            }
+            self.view.redraw(cx);
+        }
+
+        // Message numbering and anchors
+        if self.view.button(ids!(numbering_button)).clicked(actions) {
+            self.show_message_numbers = !self.show_message_numbers;
+            self.view.redraw(cx);
+        }
+        if let Some((link, _)) = self.view.text_input(ids!(anchor_input)).returned(actions) {
+            self.open_message_anchor(cx, scope, &link);
         }
     }
 }
@@ -854,6 +3249,7 @@ impl ChatApp {
         }
 
         let Some(store) = scope.data.get_mut::<Store>() else { return };
+        let online = store.is_online();
 
         // Get all enabled providers with API keys - clone to avoid borrow issues
         let enabled_providers: Vec<_> = store.preferences.get_enabled_providers()
@@ -867,9 +3263,18 @@ impl ChatApp {
 
         // Check if the set of providers has changed
         if self.providers_configured {
-            let old_set: std::collections::HashSet<_> = self.fetched_provider_ids.iter().collect();
+            // Providers skipped while offline count as configured, or they'd look new on every event
+            let old_set: std::collections::HashSet<_> = self.fetched_provider_ids.iter()
+                .chain(&self.offline_skipped_provider_ids)
+                .collect();
             let new_set: std::collections::HashSet<_> = current_provider_ids.iter().collect();
-            needs_reconfigure = old_set != new_set;
+            needs_reconfigure = old_set != new_set
+                || store.providers_manager.clients_revision() != self.clients_revision;
+            // Back online: fetch the models skipped while offline
+            if !self.offline_skipped_provider_ids.is_empty() && online {
+                ::log::info!("Network reachable again, fetching provider models");
+                needs_reconfigure = true;
+            }
         }
 
         // Skip if already configured and no changes
@@ -906,11 +3311,18 @@ impl ChatApp {
             self.restored_saved_model = false;  // Allow model selection after reload
         }
         self.fetched_provider_ids.clear();
+        self.failed_provider_ids.clear();
+        self.offline_skipped_provider_ids.clear();
         self.providers_to_fetch.clear();
         self.fetch_index = 0;
 
         // Configure all provider clients in ProvidersManager
         store.reconfigure_providers();
+        self.clients_revision = store.providers_manager.clients_revision();
+
+        // Populate the selector from the model cache; cached providers are refreshed in the background
+        let cache_ttl = std::time::Duration::from_secs(store.preferences.model_cache_ttl_hours * 3600);
+        let cached_provider_ids = store.providers_manager.load_cache(cache_ttl);
 
         // Build list of providers to fetch
         for provider in &enabled_providers {
@@ -929,11 +3341,28 @@ impl ChatApp {
             };
             ::log::info!("Will fetch models from provider {} with API key: {}", provider.id, key_preview);
 
-            self.providers_to_fetch.push(provider.id.clone());
+            if cached_provider_ids.contains(&provider.id) {
+                self.fetched_provider_ids.push(provider.id.clone());
+            } else if online || is_local_url(&provider.url) {
+                self.providers_to_fetch.push(provider.id.clone());
+            } else {
+                ::log::info!("Offline, not fetching models from provider {}", provider.id);
+                self.offline_skipped_provider_ids.push(provider.id.clone());
+            }
         }
 
         self.providers_configured = true;
 
+        // Offline, cached models of remote providers are still listed but not refreshed
+        if !cached_provider_ids.is_empty() {
+            let reachable_provider_ids: Vec<String> = cached_provider_ids.iter()
+                .filter(|id| online || enabled_providers.iter().any(|p| &p.id == *id && is_local_url(&p.url)))
+                .cloned()
+                .collect();
+            self.finish_fetching(cx, scope);
+            self.refresh_cached_providers(scope, reachable_provider_ids);
+        }
+
         // Start fetching from the first provider
         if !self.providers_to_fetch.is_empty() {
             self.start_fetch_for_provider(cx, scope, 0);
@@ -944,15 +3373,21 @@ impl ChatApp {
     fn start_fetch_for_provider(&mut self, cx: &mut Cx, scope: &mut Scope, index: usize) {
         if index >= self.providers_to_fetch.len() {
             ::log::info!("Finished fetching from all {} providers", self.fetched_provider_ids.len());
-            self.fetch_in_progress = false;
-            self.view.redraw(cx);
+            self.finish_fetching(cx, scope);
             return;
         }
 
+        self.fetch_attempts = 0;
+        self.dispatch_fetch(cx, scope, index);
+    }
+
+    /// Dispatch the model Load task for a provider and arm its timeout
+    fn dispatch_fetch(&mut self, cx: &mut Cx, scope: &mut Scope, index: usize) {
         let provider_id = &self.providers_to_fetch[index];
         ::log::info!("Starting fetch for provider {} (index {})", provider_id, index);
 
         let Some(store) = scope.data.get::<Store>() else { return };
+        let timeout_secs = store.preferences.model_fetch_timeout_secs;
 
         // Get client for this provider from ProvidersManager
         let Some(client) = store.providers_manager.clone_client(provider_id) else {
@@ -971,12 +3406,14 @@ impl ChatApp {
         {
             let mut ctrl = self.chat_controller.lock().unwrap();
             ctrl.set_client(Some(Box::new(client)));
+            self.chat_client_provider_id = None;
 
             // Don't set a default bot_id here - we'll restore the saved model
             // or select first available after models are loaded
 
             // Dispatch Load task to fetch models
             ::log::info!("Dispatching ChatTask::Load for provider {}", provider_id);
+            self.fetch_message_count = ctrl.state().messages.len();
             ctrl.dispatch_task(ChatTask::Load);
         }
 
@@ -985,6 +3422,9 @@ impl ChatApp {
         self.fetch_in_progress = true;
         self.last_bots_count = 0;
 
+        cx.stop_timer(self.fetch_timer);
+        self.fetch_timer = cx.start_timeout(timeout_secs as f64);
+
         self.view.redraw(cx);
     }
 
@@ -1032,62 +3472,206 @@ impl ChatApp {
             }
         }
 
-        // Move to next provider
+        self.advance_fetch(cx, scope);
+    }
+
+    /// Move on to the next provider, or finish once all have been tried
+    fn advance_fetch(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        cx.stop_timer(self.fetch_timer);
+
         let next_index = self.fetch_index + 1;
         if next_index < self.providers_to_fetch.len() {
             self.start_fetch_for_provider(cx, scope, next_index);
         } else {
-            // All providers fetched - combine bots into ChatController
-            ::log::info!("All providers fetched, {} total bots available", store.providers_manager.get_all_bots().len());
-            self.fetch_in_progress = false;
-
-            // Update ChatController with filtered bots (only enabled models)
-            let all_bots = store.providers_manager.get_all_bots();
-            let enabled_bots = Self::filter_enabled_bots(all_bots, store);
-            let num_bots = enabled_bots.len();
-            ::log::info!("Setting {} enabled bots on ChatController (out of {} total)", num_bots, all_bots.len());
-            {
-                let mut ctrl = self.chat_controller.lock().unwrap();
-                // VecMutation::Set automatically converts to ChatStateMutation::MutateBots
-                ctrl.dispatch_mutation(VecMutation::Set(enabled_bots.clone()));
+            self.finish_fetching(cx, scope);
+        }
+    }
+
+    /// Combine the bots from all fetched providers into the ChatController
+    fn finish_fetching(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        let Some(store) = scope.data.get_mut::<Store>() else { return };
+
+        // All providers fetched - combine bots into ChatController
+        ::log::info!("All providers fetched, {} total bots available", store.providers_manager.get_all_bots().len());
+        self.fetch_in_progress = false;
+        store.providers_manager.save_cache();
+
+        // Update ChatController with filtered bots (only enabled models)
+        let all_bots = store.providers_manager.get_all_bots();
+        let enabled_bots = Self::filter_enabled_bots(all_bots, store);
+        let num_bots = enabled_bots.len();
+        ::log::info!("Setting {} enabled bots on ChatController (out of {} total)", num_bots, all_bots.len());
+        {
+            let mut ctrl = self.chat_controller.lock().unwrap();
+            // VecMutation::Set automatically converts to ChatStateMutation::MutateBots
+            ctrl.dispatch_mutation(VecMutation::Set(enabled_bots.clone()));
+
+            // Verify bots were set
+            let controller_bots = ctrl.state().bots.len();
+            ::log::info!("ChatController now has {} bots", controller_bots);
+        }
+
+        // Get filtered bots before restore (restore may clear them due to set_client)
+        let all_bots_for_reset = enabled_bots;
+
+        // Restore the saved model selection (this may switch client which clears bots)
+        self.restore_saved_model(cx, scope);
+
+        // Fetching left a models listing client in place; send with the selected
+        // model's chat client again
+        let selected_bot_id = self.chat_controller.lock().unwrap().state().bot_id.clone();
+        if let Some(bot_id) = selected_bot_id {
+            self.activate_provider_for_bot(&bot_id, scope);
+        }
+
+        // Force re-setting the controller on the Chat widget now that bots are loaded
+        // The Chat widget's set_chat_controller has an early return if the Arc pointer
+        // is the same, so we need to set it to None first to force re-propagation
+        // IMPORTANT: Do this BEFORE dispatching mutations so the new plugin receives them
+        {
+            let mut chat_ref = self.view.chat(ids!(chat));
+            // First set to None to clear the existing controller
+            chat_ref.write().set_chat_controller(cx, None);
+            // Then set to our controller again to force propagation to child widgets
+            chat_ref.write().set_chat_controller(cx, Some(self.chat_controller.clone()));
+        }
+
+        // Re-set the bots after restore (set_client clears them)
+        // Do this AFTER force re-setting controller so the new plugin sees the mutation
+        {
+            let mut ctrl = self.chat_controller.lock().unwrap();
+            ctrl.dispatch_mutation(VecMutation::Set(all_bots_for_reset.clone()));
+        }
+
+        // Set up grouping with provider icons for the model selector
+        self.setup_model_selector_grouping(scope);
+
+        // Models arriving while a search is typed are narrowed on the next reset
+        if !self.model_search.trim().is_empty() {
+            self.needs_controller_reset = true;
+        }
+
+        // Redraw both the view and explicitly the chat widget
+        self.view.redraw(cx);
+        self.view.chat(ids!(chat)).redraw(cx);
+    }
 
-                // Verify bots were set
-                let controller_bots = ctrl.state().bots.len();
-                ::log::info!("ChatController now has {} bots", controller_bots);
+    /// Re-fetch the models of providers loaded from the cache without touching the controller
+    fn refresh_cached_providers(&mut self, scope: &mut Scope, provider_ids: Vec<String>) {
+        let Some(store) = scope.data.get::<Store>() else { return };
+
+        // One runtime for all providers; a current-thread runtime can be
+        // blocked on from several threads at once
+        let rt = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+            Ok(rt) => Arc::new(rt),
+            Err(e) => {
+                ::log::warn!("Skipping background model refresh, could not start a runtime: {}", e);
+                return;
             }
+        };
+
+        for provider_id in provider_ids {
+            let Some(client) = store.providers_manager.clone_client(&provider_id) else { continue };
+            let results = self.refreshed_bots.clone();
+            let rt = rt.clone();
+
+            std::thread::spawn(move || {
+                match rt.block_on(ProvidersManager::fetch_bots(&client)) {
+                    Ok(bots) => {
+                        if let Ok(mut guard) = results.lock() {
+                            guard.push((provider_id, bots));
+                        }
+                    }
+                    Err(e) => ::log::warn!("Background model refresh for {} failed: {}", provider_id, e),
+                }
+            });
+        }
+    }
 
-            // Get filtered bots before restore (restore may clear them due to set_client)
-            let all_bots_for_reset = enabled_bots;
+    /// Apply model lists refreshed in the background and update the cache
+    fn apply_refreshed_bots(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        let refreshed = match self.refreshed_bots.lock() {
+            Ok(mut guard) if !guard.is_empty() => std::mem::take(&mut *guard),
+            _ => return,
+        };
 
-            // Restore the saved model selection (this may switch client which clears bots)
-            self.restore_saved_model(scope);
+        let mut updated = false;
+        for (provider_id, mut bots) in refreshed {
+            let icon_path = self.get_provider_icon_path(&provider_id);
+            Self::apply_provider_icon_to_bots(&mut bots, icon_path);
 
-            // Force re-setting the controller on the Chat widget now that bots are loaded
-            // The Chat widget's set_chat_controller has an early return if the Arc pointer
-            // is the same, so we need to set it to None first to force re-propagation
-            // IMPORTANT: Do this BEFORE dispatching mutations so the new plugin receives them
-            {
-                let mut chat_ref = self.view.chat(ids!(chat));
-                // First set to None to clear the existing controller
-                chat_ref.write().set_chat_controller(cx, None);
-                // Then set to our controller again to force propagation to child widgets
-                chat_ref.write().set_chat_controller(cx, Some(self.chat_controller.clone()));
+            let Some(store) = scope.data.get_mut::<Store>() else { return };
+            // Ignore results for providers removed since the refresh started
+            if store.providers_manager.get_client(&provider_id).is_none() {
+                continue;
             }
+            store.providers_manager.set_provider_bots(&provider_id, bots);
+            updated = true;
+        }
 
-            // Re-set the bots after restore (set_client clears them)
-            // Do this AFTER force re-setting controller so the new plugin sees the mutation
-            {
-                let mut ctrl = self.chat_controller.lock().unwrap();
-                ctrl.dispatch_mutation(VecMutation::Set(all_bots_for_reset.clone()));
+        if updated {
+            if let Some(store) = scope.data.get::<Store>() {
+                store.providers_manager.save_cache();
+            }
+            // A running sequential fetch picks the new lists up when it finishes
+            if !self.fetch_in_progress {
+                self.needs_controller_reset = true;
+                self.view.redraw(cx);
             }
+        }
+    }
+
+    /// Retry a model fetch that failed with a timeout, connection error or 5xx
+    fn check_for_fetch_error(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        if !self.fetch_in_progress {
+            return;
+        }
+        let Some(error) = self.take_fetch_error() else { return };
+        let transient = moly_data::http::is_transient_error(&error);
+        self.retry_fetch_or_skip(cx, scope, &error, transient);
+    }
 
-            // Set up grouping with provider icons for the model selector
-            self.setup_model_selector_grouping(scope);
+    /// Take the error the controller reported for the running model fetch, if
+    /// any, dropping it from the messages so it doesn't end up in the chat
+    fn take_fetch_error(&mut self) -> Option<String> {
+        use moly_kit::aitk::protocol::EntityId;
 
-            // Redraw both the view and explicitly the chat widget
-            self.view.redraw(cx);
-            self.view.chat(ids!(chat)).redraw(cx);
+        let mut ctrl = self.chat_controller.lock().unwrap();
+        let current = &ctrl.state().messages;
+        // Errors of a streaming reply are handled by handle_stream_error
+        if current.iter().any(|m| m.metadata.is_writing) {
+            return None;
+        }
+        let start = self.fetch_message_count.min(current.len());
+        let offset = current[start..].iter().position(|m| {
+            matches!(m.from, EntityId::App) && !m.content.text.starts_with(REQUEST_FAILED_PREFIX)
+        })?;
+
+        let mut messages = current.clone();
+        let error = messages.remove(start + offset).content.text;
+        ctrl.dispatch_mutation(VecMutation::Set(messages));
+        Some(error)
+    }
+
+    /// A provider's model fetch failed: retry transient failures, then give up
+    /// on the provider and move on
+    fn retry_fetch_or_skip(&mut self, cx: &mut Cx, scope: &mut Scope, error: &str, transient: bool) {
+        let Some(provider_id) = self.current_provider_id.clone() else { return };
+
+        if transient && self.fetch_attempts < MAX_FETCH_RETRIES {
+            self.fetch_attempts += 1;
+            ::log::warn!("Fetching models from {} failed ({}), retrying ({}/{})",
+                provider_id, error, self.fetch_attempts, MAX_FETCH_RETRIES);
+            self.dispatch_fetch(cx, scope, self.fetch_index);
+            return;
+        }
+
+        ::log::warn!("Fetching models from {} failed after {} attempts ({}), skipping",
+            provider_id, self.fetch_attempts + 1, error);
+        if !self.failed_provider_ids.contains(&provider_id) {
+            self.failed_provider_ids.push(provider_id);
         }
+        self.advance_fetch(cx, scope);
     }
 
     /// Parse a BotId string into (model_name, provider) tuple
@@ -1110,7 +3694,7 @@ impl ChatApp {
 
     /// Track model selection changes and save to preferences
     /// Only tracks changes after the saved model has been restored
-    fn track_model_selection(&mut self, scope: &mut Scope) {
+    fn track_model_selection(&mut self, cx: &mut Cx, scope: &mut Scope) {
         // Don't track until we've restored the saved model
         // This prevents the initial load from overwriting the user's saved selection
         if !self.restored_saved_model {
@@ -1132,28 +3716,83 @@ impl ChatApp {
                 ::log::info!("Model selection changed to: {}", bot_id_str);
 
                 // Switch to the correct provider's client for this model
-                self.switch_to_provider_for_bot(bot_id, scope);
+                self.activate_provider_for_bot(bot_id, scope);
 
                 // Save to preferences
                 if let Some(store) = scope.data.get_mut::<Store>() {
                     store.preferences.set_current_chat_model(Some(bot_id_str.clone()));
                 }
+                // A model picked by the user replaces the fallback
+                self.model_fallback_notice = None;
 
                 self.last_saved_bot_id = Some(bot_id_str);
             } else {
                 self.last_saved_bot_id = None;
             }
+
+            // Let the shell show the active model on every screen
+            let active_model = current_bot_id.as_ref().map(|bot_id| self.active_model_for_bot(bot_id, scope));
+            cx.action(StoreAction::ActiveModelChanged(active_model));
+        }
+    }
+
+    /// Describe the selected bot for the shell's active model indicator
+    fn active_model_for_bot(&self, bot_id: &BotId, scope: &mut Scope) -> ActiveModel {
+        let name = self.chat_controller.lock().unwrap().state().bots.iter()
+            .find(|bot| &bot.id == bot_id)
+            .map(|bot| bot.name.clone())
+            .unwrap_or_else(|| bot_id.as_str().to_string());
+        let provider_id = scope.data.get::<Store>()
+            .and_then(|store| store.providers_manager.get_provider_for_bot(bot_id).map(str::to_string))
+            .or_else(|| self.current_provider_id.clone())
+            .unwrap_or_default();
+        let icon_path = self.get_provider_icon_path(&provider_id);
+
+        ActiveModel { name, provider_id, icon_path }
+    }
+
+    /// Activate the provider serving `bot_id` and return the bot to select: the
+    /// provider's default model when the current chat has no model of its own,
+    /// otherwise `bot_id`. Explicit picks use `activate_provider_for_bot` instead.
+    fn switch_to_provider_for_bot(&mut self, bot_id: &BotId, scope: &mut Scope) -> BotId {
+        self.activate_provider_for_bot(bot_id, scope);
+        self.default_bot_for_chat(bot_id, scope).unwrap_or_else(|| bot_id.clone())
+    }
+
+    /// The default model of `bot_id`'s provider, if the current chat has no saved
+    /// model (so per-chat restoration always wins) and the default is enabled
+    fn default_bot_for_chat(&self, bot_id: &BotId, scope: &mut Scope) -> Option<BotId> {
+        let store = scope.data.get::<Store>()?;
+        let chat_has_model = self.current_chat_id
+            .and_then(|chat_id| store.chats.get_chat_by_id(chat_id))
+            .map_or(false, |chat| chat.bot_id.is_some());
+        if chat_has_model {
+            return None;
         }
+
+        let provider_id = store.providers_manager.get_provider_for_bot(bot_id)?;
+        let default_model = store.preferences.get_provider(provider_id)?.default_model.clone()?;
+        let enabled_bots = Self::filter_enabled_bots(store.providers_manager.get_all_bots(), store);
+        let default_bot = enabled_bots.iter()
+            .filter(|bot| store.providers_manager.get_provider_for_bot(&bot.id) == Some(provider_id))
+            .find(|bot| {
+                // Model lists may report ids with a "models/" prefix (e.g. Gemini)
+                let model_name = Self::parse_bot_id_string(bot.id.as_str()).0;
+                model_name == default_model || model_name == format!("models/{}", default_model)
+            })?;
+
+        ::log::info!("Using default model {} for provider {}", default_model, provider_id);
+        Some(default_bot.id.clone())
     }
 
-    /// Switch to the correct provider's client for a given bot
-    fn switch_to_provider_for_bot(&mut self, bot_id: &BotId, scope: &mut Scope) {
+    /// Point the controller at the client of the provider serving `bot_id`
+    fn activate_provider_for_bot(&mut self, bot_id: &BotId, scope: &mut Scope) {
         let Some(store) = scope.data.get::<Store>() else { return };
 
         // Find which provider this bot belongs to
         if let Some(provider_id) = store.providers_manager.get_provider_for_bot(bot_id) {
             // Only switch if it's a different provider
-            if self.current_provider_id.as_deref() != Some(provider_id) {
+            if self.chat_client_provider_id.as_deref() != Some(provider_id) {
                 if let Some(client) = store.providers_manager.clone_client(provider_id) {
                     // Get filtered bots before switching (set_client clears them)
                     let all_bots = store.providers_manager.get_all_bots();
@@ -1165,6 +3804,7 @@ impl ChatApp {
                     }
 
                     self.current_provider_id = Some(provider_id.to_string());
+                    self.chat_client_provider_id = Some(provider_id.to_string());
                     ::log::info!("Switched to provider: {} for model", provider_id);
 
                     // Re-set the filtered bots after set_client cleared them
@@ -1179,12 +3819,28 @@ impl ChatApp {
         }
     }
 
+    /// Enabled bots matching the model search, in selector order. The selected
+    /// bot is always kept so filtering never changes the selection.
+    fn selector_bots(&self, store: &Store) -> Vec<Bot> {
+        let mut bots = Self::filter_enabled_bots(store.providers_manager.get_all_bots(), store);
+        let search = self.model_search.trim().to_lowercase();
+        if !search.is_empty() {
+            let selected = self.chat_controller.lock().unwrap().state().bot_id.clone();
+            bots.retain(|bot| {
+                selected.as_ref() == Some(&bot.id)
+                    || bot.name.to_lowercase().contains(&search)
+                    || bot.id.id().to_lowercase().contains(&search)
+            });
+        }
+        bots
+    }
+
     /// Filter bots based on enabled status in provider preferences
     /// Returns only bots that are either:
     /// 1. Not in the provider's models list (default to enabled)
     /// 2. Explicitly enabled in the provider's models list
     fn filter_enabled_bots(all_bots: &[Bot], store: &Store) -> Vec<Bot> {
-        all_bots.iter()
+        let mut bots: Vec<Bot> = all_bots.iter()
             .filter(|bot| {
                 // Find which provider this bot belongs to
                 let provider_id = store.providers_manager.get_provider_for_bot(&bot.id);
@@ -1210,11 +3866,41 @@ impl ChatApp {
                 true
             })
             .cloned()
+            .collect();
+
+        // Recommended models first, so their group leads the model selector
+        let recommended = Self::recommended_bot_ids(&bots, store);
+        bots.sort_by_key(|bot| !recommended.contains(&bot.id));
+
+        // Aliases only rename; the id used for requests is unchanged
+        for bot in &mut bots {
+            if let Some(alias) = store.preferences.model_alias(bot.id.as_str()) {
+                bot.name = alias.to_string();
+            }
+        }
+        bots
+    }
+
+    /// One recommended bot per provider, picked from `bots` by the store's recommendation table
+    fn recommended_bot_ids(bots: &[Bot], store: &Store) -> HashSet<BotId> {
+        let mut bots_by_provider: HashMap<&str, Vec<&Bot>> = HashMap::new();
+        for bot in bots {
+            if let Some(provider_id) = store.providers_manager.get_provider_for_bot(&bot.id) {
+                bots_by_provider.entry(provider_id).or_default().push(bot);
+            }
+        }
+
+        bots_by_provider.into_iter()
+            .filter_map(|(provider_id, bots)| {
+                let names: Vec<&str> = bots.iter().map(|bot| bot.id.id()).collect();
+                let name = store.recommendations.recommend(provider_id, &names)?;
+                bots.iter().find(|bot| bot.id.id() == name).map(|bot| bot.id.clone())
+            })
             .collect()
     }
 
     /// Restore the saved model selection from preferences
-    fn restore_saved_model(&mut self, scope: &mut Scope) {
+    fn restore_saved_model(&mut self, cx: &mut Cx, scope: &mut Scope) {
         if self.restored_saved_model {
             return;
         }
@@ -1241,8 +3927,8 @@ impl ChatApp {
 
             ::log::info!("No saved model, selecting first available: {}", first_bot_name);
 
-            // Switch to the correct provider for this bot
-            self.switch_to_provider_for_bot(&first_bot_id, scope);
+            // Switch to the correct provider for this bot (or its default model)
+            let first_bot_id = self.switch_to_provider_for_bot(&first_bot_id, scope);
 
             {
                 let mut ctrl = self.chat_controller.lock().unwrap();
@@ -1284,10 +3970,10 @@ impl ChatApp {
             ::log::info!("Found saved model, selecting: {}", bot.name);
 
             let matched_bot_id = bot.id.clone();
-            let matched_bot_id_str = bot.id.as_str().to_string();
 
-            // Switch to the correct provider for this bot
-            self.switch_to_provider_for_bot(&matched_bot_id, scope);
+            // Switch to the correct provider for this bot (or its default model)
+            let matched_bot_id = self.switch_to_provider_for_bot(&matched_bot_id, scope);
+            let matched_bot_id_str = matched_bot_id.as_str().to_string();
 
             // Set the bot_id on the controller
             {
@@ -1305,20 +3991,175 @@ impl ChatApp {
                 }
             }
         } else {
-            // Saved model not found, select first available
+            // Saved model not found (e.g. its provider is disabled), select first available.
+            // Preferences keep the saved model, so re-enabling its provider restores it.
             ::log::warn!("Saved model '{}' not found, selecting first available", saved_model);
             let first_bot_id = all_bots[0].id.clone();
 
-            // Switch to the correct provider for this bot
-            self.switch_to_provider_for_bot(&first_bot_id, scope);
+            // Switch to the correct provider for this bot (or its default model)
+            let first_bot_id = self.switch_to_provider_for_bot(&first_bot_id, scope);
 
             {
                 let mut ctrl = self.chat_controller.lock().unwrap();
                 ctrl.dispatch_mutation(ChatStateMutation::SetBotId(Some(first_bot_id.clone())));
             }
             self.last_saved_bot_id = Some(first_bot_id.as_str().to_string());
+
+            // Name the model actually selected, which may be the provider's default
+            let selected_name = all_bots.iter()
+                .find(|bot| bot.id == first_bot_id)
+                .map_or_else(|| first_bot_id.id().to_string(), |bot| bot.name.clone());
+            self.model_fallback_notice = Some(format!(
+                "Previously selected model {} is unavailable — switched to {}",
+                saved_model_name, selected_name
+            ));
+            cx.stop_timer(self.model_fallback_timer);
+            self.model_fallback_timer = cx.start_timeout(MODEL_FALLBACK_NOTICE_SECS);
         }
 
         self.restored_saved_model = true;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use moly_data::{Chats, Preferences};
+
+    /// Feed inputs in order, returning which ones were swallowed
+    fn swallowed(inputs: &[ImeInput]) -> Vec<bool> {
+        let mut composing = false;
+        inputs.iter().map(|input| input.apply(&mut composing)).collect()
+    }
+
+    #[test]
+    fn enter_committing_a_composition_is_swallowed() {
+        let inputs = [ImeInput::Text { preedit: true }, ImeInput::Enter];
+        assert_eq!(swallowed(&inputs), vec![false, true]);
+    }
+
+    #[test]
+    fn enter_after_committed_text_sends() {
+        let inputs = [ImeInput::Text { preedit: true }, ImeInput::Text { preedit: false }, ImeInput::Enter];
+        assert_eq!(swallowed(&inputs), vec![false, false, false]);
+    }
+
+    #[test]
+    fn only_the_committing_enter_is_swallowed() {
+        let inputs = [ImeInput::Text { preedit: true }, ImeInput::Enter, ImeInput::Enter];
+        assert_eq!(swallowed(&inputs), vec![false, true, false]);
+    }
+
+    #[test]
+    fn escape_cancels_the_composition() {
+        let inputs = [ImeInput::Text { preedit: true }, ImeInput::Escape, ImeInput::Enter];
+        assert_eq!(swallowed(&inputs), vec![false, false, false]);
+    }
+
+    #[test]
+    fn returns_to_the_message_on_top_after_navigating_back() {
+        let mut positions = ScrollPositions::default();
+        positions.leave(1, 12, 40, false);
+        positions.leave(2, 3, 5, false);
+
+        assert_eq!(positions.restore(1, 40), Some(12));
+        assert_eq!(positions.restore(2, 5), Some(3));
+        // A chat never left opens at the bottom
+        assert_eq!(positions.restore(3, 10), None);
+    }
+
+    #[test]
+    fn chat_left_while_streaming_reopens_at_the_bottom() {
+        let mut positions = ScrollPositions::default();
+        positions.leave(1, 12, 40, false);
+        positions.leave(1, 30, 41, true);
+
+        assert_eq!(positions.restore(1, 41), None);
+    }
+
+    #[test]
+    fn ignores_positions_past_the_last_message() {
+        let mut positions = ScrollPositions::default();
+        positions.leave(1, 50, 20, false);
+        assert_eq!(positions.restore(1, 20), Some(19));
+
+        // Messages were deleted since the chat was left
+        assert_eq!(positions.restore(1, 10), None);
+
+        positions.forget(1);
+        assert_eq!(positions.restore(1, 20), None);
+    }
+
+    /// `count` models served by OpenRouter
+    fn openrouter_bots(count: usize) -> Vec<Bot> {
+        (0..count)
+            .map(|i| {
+                let name = format!("vendor/model-{}", i);
+                Bot {
+                    id: BotId::new(&name, "https://openrouter.ai/api/v1"),
+                    name,
+                    avatar: EntityAvatar::Text("O".to_string()),
+                    capabilities: Default::default(),
+                }
+            })
+            .collect()
+    }
+
+    /// A chat view without its widgets built, and a store without any data on disk
+    fn chat_app_and_store() -> (Cx, ChatApp, Store) {
+        let mut cx = Cx::new(Box::new(|_, _| {}));
+        let app = ChatApp::new(&mut cx);
+        (cx, app, Store::new(Preferences::default(), Chats::new()))
+    }
+
+    #[test]
+    fn groups_hundreds_of_models_once_per_change() {
+        let (_cx, mut app, mut store) = chat_app_and_store();
+        store.providers_manager.set_provider_bots("openrouter", openrouter_bots(500));
+        let scope = &mut Scope::with_data(&mut store);
+
+        assert!(app.setup_model_selector_grouping(scope));
+        // Each reset asks for the grouping again; an unchanged list isn't regrouped
+        for _ in 0..100 {
+            assert!(!app.setup_model_selector_grouping(scope));
+        }
+
+        let store = scope.data.get_mut::<Store>().unwrap();
+        store.providers_manager.set_provider_bots("openrouter", openrouter_bots(501));
+        assert!(app.setup_model_selector_grouping(scope));
+        assert!(!app.setup_model_selector_grouping(scope));
+    }
+
+    #[test]
+    fn selector_is_repopulated_with_models_loaded_while_hidden() {
+        let (mut cx, mut app, mut store) = chat_app_and_store();
+        let scope = &mut Scope::with_data(&mut store);
+        app.setup_model_selector_grouping(scope);
+
+        // The models arrive while the view is hidden and handles no events
+        let store = scope.data.get_mut::<Store>().unwrap();
+        store.providers_manager.set_provider_bots("openrouter", openrouter_bots(3));
+        let revision = store.providers_manager.bots_revision();
+
+        app.on_become_visible();
+        assert!(app.needs_controller_reset);
+        // The next event runs the reset
+        app.reset_controller_bots(&mut cx, scope);
+
+        let mut names: Vec<String> = app.controller().lock().unwrap().state().bots.iter()
+            .map(|bot| bot.name.clone())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["vendor/model-0", "vendor/model-1", "vendor/model-2"]);
+        assert_eq!(app.grouping_revision, Some(revision));
+        assert!(!app.needs_controller_reset);
+
+        // Shown again with the same models, the grouping is set on the selector
+        // again, since it may have been rebuilt while hidden
+        app.on_become_visible();
+        assert_eq!(app.grouping_revision, None);
+        app.reset_controller_bots(&mut cx, scope);
+        assert_eq!(app.grouping_revision, Some(revision));
+        assert_eq!(app.controller().lock().unwrap().state().bots.len(), 3);
+    }
+}
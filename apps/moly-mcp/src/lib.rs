@@ -8,7 +8,7 @@ pub mod screen;
 use makepad_widgets::Cx;
 use moly_widgets::{MolyApp, AppInfo};
 
-pub use screen::{McpApp, McpAppRef};
+pub use screen::{McpApp, McpAppAction, McpAppRef};
 
 /// Main app struct for MolyApp trait implementation
 pub struct MolyMcpApp;
@@ -26,6 +26,7 @@ live_design! {
         draw_bg: {
             instance dark_mode: 0.0
             instance hover: 0.0
+            instance disabled: 0.0
             instance radius: 4.0
             fn pixel(self) -> vec4 {
                 let sdf = Sdf2d::viewport(self.pos * self.rect_size);
@@ -38,8 +39,10 @@ live_design! {
 
                 let base = mix(light_base, dark_base, self.dark_mode);
                 let hovered = mix(light_hover, dark_hover, self.dark_mode);
-                let color = mix(base, hovered, self.hover);
-                sdf.fill(color);
+                let color = mix(base, hovered, self.hover * (1.0 - self.disabled));
+                // Greyed out while the editor holds invalid JSON
+                let muted = mix(#9ca3af, #475569, self.dark_mode);
+                sdf.fill(mix(color, muted, self.disabled));
                 return sdf.result;
             }
         }
@@ -59,6 +62,84 @@ live_design! {
         }
     }
 
+    ToolLabel = <Label> {
+        draw_text: {
+            instance dark_mode: 0.0
+            text_style: <THEME_FONT_REGULAR>{ font_size: 11.0 }
+        }
+    }
+
+    // Server of origin, shown as a muted tag
+    ToolServerLabel = <ToolLabel> {
+        draw_text: {
+            fn get_color(self) -> vec4 {
+                return mix(#6b7280, #94a3b8, self.dark_mode);
+            }
+            text_style: <THEME_FONT_REGULAR>{ font_size: 10.0 }
+        }
+    }
+
+    // Text field in the servers form
+    FormInput = <TextInput> {
+        width: Fill, height: Fit
+        padding: {left: 10, right: 10, top: 8, bottom: 8}
+
+        draw_bg: {
+            instance dark_mode: 0.0
+            instance radius: 4.0
+
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                let sz = self.rect_size - 2.0;
+                sdf.box(1.0, 1.0, sz.x, sz.y, self.radius);
+                sdf.fill(mix(#ffffff, #1e293b, self.dark_mode));
+                sdf.stroke(mix(#d1d5db, #475569, self.dark_mode), 1.0);
+                return sdf.result;
+            }
+        }
+
+        draw_text: {
+            instance dark_mode: 0.0
+            fn get_color(self) -> vec4 {
+                return mix(#1f2937, #f1f5f9, self.dark_mode);
+            }
+            text_style: <THEME_FONT_REGULAR>{ font_size: 11.0 }
+        }
+    }
+
+    // One server in the form editor
+    ServerFormItem = <View> {
+        width: Fill, height: Fit
+        flow: Down, spacing: 6
+        padding: 10
+        margin: {bottom: 8}
+        show_bg: true
+        draw_bg: {
+            instance dark_mode: 0.0
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                let sz = self.rect_size - 2.0;
+                sdf.box(1.0, 1.0, sz.x, sz.y, 6.0);
+                sdf.fill(mix(#ffffff, #1e293b, self.dark_mode));
+                sdf.stroke(mix(#e5e7eb, #374151, self.dark_mode), 1.0);
+                return sdf.result;
+            }
+        }
+
+        <View> {
+            width: Fill, height: Fit
+            flow: Right, spacing: 8
+            align: {y: 0.5}
+
+            server_enabled_switch = <McpSwitch> {}
+            server_name_input = <FormInput> { empty_text: "Name" }
+            remove_server_button = <Button> { text: "Remove" }
+        }
+        server_target_input = <FormInput> { empty_text: "Command, or URL for HTTP/SSE servers" }
+        server_args_input = <FormInput> { empty_text: "Arguments, separated by spaces (quote to keep spaces)" }
+        server_env_input = <FormInput> { empty_text: "Environment, e.g. API_KEY=${API_KEY} DEBUG=1" }
+    }
+
     ToggleRow = <View> {
         width: Fill, height: Fit
         flow: Right, spacing: 12
@@ -110,13 +191,21 @@ live_design! {
             width: Fill, height: Fill
             flow: Right, spacing: 20
 
-            // Left: Code editor
+            // Left: Code editor, or the same configuration as a form
             <View> {
                 width: 550, height: Fill
                 flow: Down, spacing: 10
 
+                <View> {
+                    width: Fill, height: Fit
+                    flow: Right, spacing: 8
+
+                    json_tab_button = <Button> { text: "JSON" }
+                    form_tab_button = <Button> { text: "Form" }
+                }
+
                 // Editor container
-                <RoundedView> {
+                json_editor = <RoundedView> {
                     width: Fill, height: Fill
                     draw_bg: {
                         color: #1d2330
@@ -125,10 +214,63 @@ live_design! {
                     mcp_code_view = <MolyCodeView> {}
                 }
 
+                servers_form = <View> {
+                    width: Fill, height: Fill
+                    flow: Down, spacing: 8
+                    visible: false
+
+                    servers_list = <PortalList> {
+                        width: Fill, height: Fill
+                        drag_scrolling: false
+
+                        ServerFormItem = <ServerFormItem> {}
+                    }
+                    add_server_button = <Button> { text: "Add server" }
+                }
+
+                // Shown when navigating away with unsaved edits
+                leave_confirm = <View> {
+                    width: Fill, height: Fit
+                    visible: false
+                    flow: Right, spacing: 8
+                    align: {y: 0.5}
+
+                    leave_confirm_label = <Label> {
+                        width: Fill
+                        text: "You have unsaved changes."
+                        draw_text: {
+                            wrap: Word
+                            instance dark_mode: 0.0
+                            fn get_color(self) -> vec4 {
+                                return mix(#1f2937, #f1f5f9, self.dark_mode);
+                            }
+                            text_style: <THEME_FONT_REGULAR>{ font_size: 11.0 }
+                        }
+                    }
+                    leave_save_button = <Button> { text: "Save" }
+                    leave_discard_button = <Button> { text: "Discard" }
+                    leave_cancel_button = <Button> { text: "Cancel" }
+                }
+
                 // Save button row
                 <View> {
                     width: Fill, height: Fit
-                    align: {x: 1.0}
+                    flow: Right, spacing: 12
+                    align: {x: 1.0, y: 0.5}
+
+                    unsaved_indicator = <Label> {
+                        visible: false
+                        text: "● Unsaved changes"
+                        draw_text: {
+                            instance dark_mode: 0.0
+                            fn get_color(self) -> vec4 {
+                                return mix(#d97706, #fbbf24, self.dark_mode);
+                            }
+                            text_style: <THEME_FONT_REGULAR>{ font_size: 11.0 }
+                        }
+                    }
+                    format_button = <Button> { text: "Format" }
+                    minify_button = <Button> { text: "Minify" }
                     save_button = <SaveButton> {}
                 }
             }
@@ -156,6 +298,23 @@ live_design! {
                     }
                 }
 
+                // Show tool calls in chat
+                <ToggleRow> {
+                    show_tool_calls_label = <Label> {
+                        text: "Show tool calls in chat"
+                        draw_text: {
+                            instance dark_mode: 0.0
+                            fn get_color(self) -> vec4 {
+                                return mix(#1f2937, #f1f5f9, self.dark_mode);
+                            }
+                            text_style: <THEME_FONT_BOLD>{ font_size: 12.0 }
+                        }
+                    }
+                    show_tool_calls_switch = <McpSwitch> {
+                        animator: { active = { default: off } }
+                    }
+                }
+
                 // Instructions
                 <View> {
                     width: Fill, height: Fit
@@ -163,7 +322,7 @@ live_design! {
 
                     instructions_label = <Label> {
                         width: Fill
-                        text: "Add new servers by editing the JSON. You can copy your configuration from Claude Desktop or VSCode.\n\nAdd \"enabled\": false to disable a specific server."
+                        text: "Add new servers in the form or by editing the JSON. You can copy your configuration from Claude Desktop or VSCode.\n\nAdd \"enabled\": false to disable a specific server.\n\nUse ${VAR} to reference environment variables (e.g. API keys); they are resolved when servers start and never saved."
                         draw_text: {
                             wrap: Word
                             instance dark_mode: 0.0
@@ -234,6 +393,94 @@ live_design! {
                         }
                     }
                 }
+
+                // Read-only catalog of tools offered by the enabled servers
+                <View> {
+                    width: Fill, height: Fill
+                    flow: Down, spacing: 8
+                    margin: {top: 10}
+
+                    <ToggleRow> {
+                        tools_title_label = <Label> {
+                            width: Fill
+                            text: "Available Tools"
+                            draw_text: {
+                                instance dark_mode: 0.0
+                                fn get_color(self) -> vec4 {
+                                    return mix(#1f2937, #f1f5f9, self.dark_mode);
+                                }
+                                text_style: <THEME_FONT_BOLD>{ font_size: 12.0 }
+                            }
+                        }
+                        refresh_tools_button = <Button> { text: "Refresh" }
+                    }
+
+                    tools_status = <Label> {
+                        width: Fill
+                        text: "Press Refresh to list tools from the saved configuration."
+                        draw_text: {
+                            wrap: Word
+                            instance dark_mode: 0.0
+                            fn get_color(self) -> vec4 {
+                                return mix(#6b7280, #94a3b8, self.dark_mode);
+                            }
+                            text_style: <THEME_FONT_REGULAR>{ font_size: 11.0 }
+                        }
+                    }
+
+                    tools_list = <PortalList> {
+                        width: Fill, height: Fill
+                        drag_scrolling: false
+
+                        ToolItem = <View> {
+                            width: Fill, height: Fit
+                            flow: Down, spacing: 2
+                            padding: {top: 6, bottom: 6}
+
+                            <View> {
+                                width: Fill, height: Fit
+                                flow: Right, spacing: 8
+                                align: {y: 0.5}
+
+                                tool_name = <ToolLabel> {
+                                    draw_text: {
+                                        fn get_color(self) -> vec4 {
+                                            return mix(#1f2937, #f1f5f9, self.dark_mode);
+                                        }
+                                        text_style: <THEME_FONT_BOLD>{ font_size: 11.0 }
+                                    }
+                                }
+                                tool_server = <ToolServerLabel> {}
+                            }
+                            tool_description = <ToolLabel> {
+                                width: Fill
+                                draw_text: {
+                                    wrap: Word
+                                    fn get_color(self) -> vec4 {
+                                        return mix(#4b5563, #9ca3af, self.dark_mode);
+                                    }
+                                }
+                            }
+                        }
+
+                        ToolServerError = <View> {
+                            width: Fill, height: Fit
+                            flow: Down, spacing: 2
+                            padding: {top: 6, bottom: 6}
+
+                            tool_server = <ToolServerLabel> {}
+                            error_message = <ToolLabel> {
+                                width: Fill
+                                draw_text: {
+                                    wrap: Word
+                                    fn get_color(self) -> vec4 {
+                                        return mix(#dc2626, #f87171, self.dark_mode);
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
             }
         }
     }
@@ -3,7 +3,146 @@
 pub mod design;
 
 use makepad_widgets::*;
-use moly_data::{McpServersConfig, Store};
+use moly_data::{McpServer, McpServersConfig, Store};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How long a single server may take to start and report its tools
+const TOOL_LIST_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Pause in typing, in seconds, before the editor JSON is validated
+const VALIDATION_DELAY: f64 = 0.4;
+
+/// Actions emitted by McpApp
+#[derive(Clone, Debug, DefaultNone)]
+pub enum McpAppAction {
+    None,
+    /// Unsaved edits were saved or discarded; the pending navigation can proceed
+    LeaveConfirmed,
+    /// The user chose to stay on the MCP screen
+    LeaveCancelled,
+}
+
+/// One row of the tools catalog
+#[derive(Clone, Debug)]
+enum ToolCatalogRow {
+    /// A tool offered by a server
+    Tool { server_id: String, name: String, description: String },
+    /// A server whose tools couldn't be listed
+    Error { server_id: String, message: String },
+}
+
+/// One server as edited in the form view
+#[derive(Clone, Debug)]
+struct ServerForm {
+    name: String,
+    /// Disabled servers stay configured but aren't started
+    enabled: bool,
+    /// Command for stdio servers, or URL for HTTP/SSE servers
+    target: String,
+    /// Arguments separated by spaces
+    args: String,
+    /// `KEY=VALUE` pairs separated by spaces
+    env: String,
+    /// The server as loaded, so fields the form doesn't show are kept
+    base: McpServer,
+}
+
+impl ServerForm {
+    fn from_server(name: &str, server: &McpServer) -> Self {
+        let env = server
+            .env
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<_>>();
+
+        Self {
+            name: name.to_string(),
+            enabled: server.enabled,
+            target: server.command.clone().or_else(|| server.url.clone()).unwrap_or_default(),
+            args: join_args(&server.args),
+            env: join_args(&env),
+            base: server.clone(),
+        }
+    }
+
+    fn empty() -> Self {
+        Self::from_server("", &McpServer::stdio(String::new(), Vec::new()))
+    }
+
+    fn to_server(&self) -> McpServer {
+        let mut server = self.base.clone();
+        server.enabled = self.enabled;
+        let target = self.target.trim().to_string();
+
+        if target.starts_with("http://") || target.starts_with("https://") {
+            server.command = None;
+            server.url = Some(target);
+            if server.transport_type.is_none() {
+                server.transport_type = Some("http".to_string());
+            }
+        } else {
+            server.command = Some(target);
+            server.url = None;
+            server.transport_type = None;
+        }
+
+        server.args = split_args(&self.args);
+        server.env = split_args(&self.env)
+            .into_iter()
+            .map(|pair| match pair.split_once('=') {
+                Some((key, value)) => (key.to_string(), value.to_string()),
+                None => (pair, String::new()),
+            })
+            .collect();
+        server
+    }
+}
+
+/// Split on whitespace, keeping double-quoted runs together
+fn split_args(text: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_token = false;
+
+    for c in text.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_token = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_token {
+                    args.push(std::mem::take(&mut current));
+                    has_token = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_token = true;
+            }
+        }
+    }
+    if has_token {
+        args.push(current);
+    }
+    args
+}
+
+/// Inverse of `split_args`: quote anything that contains whitespace
+fn join_args(args: &[String]) -> String {
+    args.iter()
+        .map(|arg| {
+            if arg.is_empty() || arg.contains(char::is_whitespace) {
+                format!("\"{}\"", arg)
+            } else {
+                arg.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
 
 /// Types of toggle switches in the MCP settings
 enum ToggleType {
@@ -23,6 +162,50 @@ pub struct McpApp {
     /// Whether the widget has been initialized with data from Store
     #[rust]
     initialized: bool,
+
+    /// Editor text of the last loaded or saved configuration
+    #[rust]
+    saved_json: String,
+
+    /// Whether the editor text differs from `saved_json`
+    #[rust]
+    dirty: bool,
+
+    /// Rows currently shown in the tools catalog
+    #[rust]
+    tool_rows: Vec<ToolCatalogRow>,
+
+    /// Whether a tools refresh is running in the background
+    #[rust]
+    tools_loading: bool,
+
+    /// Result of the background tools refresh (shared with the thread)
+    #[rust]
+    tools_result: Arc<Mutex<Option<Vec<ToolCatalogRow>>>>,
+
+    /// Whether servers are edited in the form instead of the JSON editor
+    #[rust]
+    form_mode: bool,
+
+    /// Servers shown in the form, in config order
+    #[rust]
+    form_servers: Vec<ServerForm>,
+
+    /// Config the form was loaded from; its servers are replaced on every edit
+    #[rust]
+    form_base_config: McpServersConfig,
+
+    /// Editor text as of the last event, to notice edits
+    #[rust]
+    last_editor_text: String,
+
+    /// Restarted on every edit; validation runs when it fires
+    #[rust]
+    validation_timer: Timer,
+
+    /// Parse error of the editor text, if invalid
+    #[rust]
+    json_error: Option<String>,
 }
 
 impl Widget for McpApp {
@@ -44,62 +227,133 @@ impl Widget for McpApp {
                     store.preferences.get_mcp_servers_dangerous_mode_enabled();
 
                 self.set_mcp_servers_config(cx, config);
+                self.check_box(ids!(show_tool_calls_switch))
+                    .set_active(cx, store.preferences.show_tool_calls);
             }
         }
+
+        self.update_dirty_state(cx);
+        self.check_tools_result(cx);
+
+        // Debounced validation of the editor text
+        let text = editor.text();
+        if text != self.last_editor_text {
+            self.last_editor_text = text;
+            self.validation_timer = cx.start_timeout(VALIDATION_DELAY);
+        }
+        if self.validation_timer.is_event(event).is_some() {
+            self.validate_editor(cx);
+        }
     }
 
     fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
         // Apply dark mode to all widgets that support it
-        if let Some(store) = scope.data.get::<Store>() {
-            let dark_mode_value = if store.is_dark_mode() { 1.0 } else { 0.0 };
-
-            // Main container background
-            self.view.apply_over(cx, live! {
-                draw_bg: { dark_mode: (dark_mode_value) }
-            });
-
-            // Header labels
-            self.view.label(ids!(title_label)).apply_over(cx, live! {
-                draw_text: { dark_mode: (dark_mode_value) }
-            });
-            self.view.label(ids!(subtitle_label)).apply_over(cx, live! {
+        let dark_mode_value = scope.data.get::<Store>()
+            .map_or(0.0, |store| if store.is_dark_mode() { 1.0 } else { 0.0 });
+
+        // Main container background
+        self.view.apply_over(cx, live! {
+            draw_bg: { dark_mode: (dark_mode_value) }
+        });
+
+        // Header labels
+        self.view.label(ids!(title_label)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode_value) }
+        });
+        self.view.label(ids!(subtitle_label)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode_value) }
+        });
+
+        // Settings panel labels
+        self.view.label(ids!(enable_label)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode_value) }
+        });
+        self.view.label(ids!(show_tool_calls_label)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode_value) }
+        });
+        self.view
+            .label(ids!(instructions_label))
+            .apply_over(cx, live! {
                 draw_text: { dark_mode: (dark_mode_value) }
             });
 
-            // Settings panel labels
-            self.view.label(ids!(enable_label)).apply_over(cx, live! {
-                draw_text: { dark_mode: (dark_mode_value) }
-            });
-            self.view
-                .label(ids!(instructions_label))
-                .apply_over(cx, live! {
-                    draw_text: { dark_mode: (dark_mode_value) }
-                });
+        // Danger mode labels
+        self.view.label(ids!(danger_label)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode_value) }
+        });
+        self.view.label(ids!(danger_warning)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode_value) }
+        });
+
+        // Status message
+        self.view.label(ids!(save_status)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode_value) }
+        });
+
+        // Unsaved changes
+        self.view.label(ids!(unsaved_indicator)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode_value) }
+        });
+        self.view.label(ids!(leave_confirm_label)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode_value) }
+        });
+
+        // Save button
+        self.view.view(ids!(save_button)).apply_over(cx, live! {
+            draw_bg: { dark_mode: (dark_mode_value) }
+        });
+
+        // Tools catalog
+        self.view.label(ids!(tools_title_label)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode_value) }
+        });
+        self.view.label(ids!(tools_status)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode_value) }
+        });
+
+        let tools_list_uid = self.view.portal_list(ids!(tools_list)).widget_uid();
+        let servers_list_uid = self.view.portal_list(ids!(servers_list)).widget_uid();
+
+        while let Some(widget) = self.view.draw_walk(cx, scope, walk).step() {
+            if widget.widget_uid() == tools_list_uid {
+                self.draw_tools_list(cx, scope, widget, dark_mode_value);
+            } else if widget.widget_uid() == servers_list_uid {
+                self.draw_servers_form(cx, scope, widget, dark_mode_value);
+            }
+        }
 
-            // Danger mode labels
-            self.view.label(ids!(danger_label)).apply_over(cx, live! {
-                draw_text: { dark_mode: (dark_mode_value) }
-            });
-            self.view.label(ids!(danger_warning)).apply_over(cx, live! {
-                draw_text: { dark_mode: (dark_mode_value) }
-            });
+        DrawStep::done()
+    }
+}
 
-            // Status message
-            self.view.label(ids!(save_status)).apply_over(cx, live! {
-                draw_text: { dark_mode: (dark_mode_value) }
-            });
+impl McpApp {
+    /// Whether the editor has edits that haven't been saved
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.dirty
+    }
 
-            // Save button
-            self.view.view(ids!(save_button)).apply_over(cx, live! {
-                draw_bg: { dark_mode: (dark_mode_value) }
-            });
+    /// Ask whether to save or discard unsaved edits before leaving.
+    /// Answers are reported as `McpAppAction`s.
+    pub fn request_leave(&mut self, cx: &mut Cx) {
+        self.view(ids!(leave_confirm)).set_visible(cx, true);
+        self.redraw(cx);
+    }
+
+    /// Recompute the dirty flag and its indicator
+    fn update_dirty_state(&mut self, cx: &mut Cx) {
+        let dirty = self.widget(ids!(mcp_code_view)).text() != self.saved_json;
+        if dirty != self.dirty {
+            self.dirty = dirty;
+            self.label(ids!(unsaved_indicator)).set_visible(cx, dirty);
+            self.redraw(cx);
         }
+    }
 
-        self.view.draw_walk(cx, scope, walk)
+    fn hide_leave_confirm(&mut self, cx: &mut Cx) {
+        self.view(ids!(leave_confirm)).set_visible(cx, false);
+        self.redraw(cx);
     }
-}
 
-impl McpApp {
     /// Update the MCP servers configuration and sync UI elements
     fn set_mcp_servers_config(&mut self, cx: &mut Cx, config: McpServersConfig) {
         self.mcp_servers_config = config;
@@ -120,6 +374,77 @@ impl McpApp {
             .to_json()
             .unwrap_or_else(|_| "{}".to_string());
         self.widget(ids!(mcp_code_view)).set_text(cx, &display_json);
+        self.saved_json = display_json;
+        if self.form_mode {
+            self.form_base_config = self.mcp_servers_config.clone();
+            self.load_form_servers();
+        }
+        self.update_dirty_state(cx);
+    }
+
+    /// Switch between the JSON editor and the servers form.
+    /// The form is loaded from the editor text, so invalid JSON keeps the editor open.
+    fn set_form_mode(&mut self, cx: &mut Cx, form_mode: bool) {
+        if form_mode == self.form_mode {
+            return;
+        }
+        if form_mode {
+            match McpServersConfig::from_json(&self.widget(ids!(mcp_code_view)).text()) {
+                Ok(config) => {
+                    self.form_base_config = config;
+                    self.load_form_servers();
+                    self.show_status(cx, "", false);
+                }
+                Err(e) => {
+                    self.show_status(cx, &format!("Invalid JSON: {}", e), true);
+                    self.redraw(cx);
+                    return;
+                }
+            }
+        }
+
+        self.form_mode = form_mode;
+        self.view(ids!(json_editor)).set_visible(cx, !form_mode);
+        self.view(ids!(servers_form)).set_visible(cx, form_mode);
+        self.redraw(cx);
+    }
+
+    fn load_form_servers(&mut self) {
+        self.form_servers = self
+            .form_base_config
+            .servers
+            .iter()
+            .map(|(name, server)| ServerForm::from_server(name, server))
+            .collect();
+    }
+
+    /// Write the form back into the editor, so the JSON view and dirty state follow every edit
+    fn sync_form_to_editor(&mut self, cx: &mut Cx) {
+        let mut config = self.form_base_config.clone();
+        config.servers.clear();
+        for server in &self.form_servers {
+            config.add_server(server.name.trim().to_string(), server.to_server());
+        }
+
+        if let Ok(json) = config.to_json() {
+            self.widget(ids!(mcp_code_view)).set_text(cx, &json);
+        }
+        self.update_dirty_state(cx);
+    }
+
+    /// Why the form can't be saved, if anything
+    fn form_error(&self) -> Option<String> {
+        let mut names = std::collections::HashSet::new();
+        for server in &self.form_servers {
+            let name = server.name.trim();
+            if name.is_empty() {
+                return Some("Every server needs a name".to_string());
+            }
+            if !names.insert(name) {
+                return Some(format!("Server name '{}' is used more than once", name));
+            }
+        }
+        None
     }
 
     /// Show a status message (success or error)
@@ -134,6 +459,321 @@ impl McpApp {
         );
     }
 
+    /// Show or clear the parse error of the editor text and enable Save accordingly
+    fn validate_editor(&mut self, cx: &mut Cx) {
+        let error = McpServersConfig::from_json(&self.widget(ids!(mcp_code_view)).text())
+            .err()
+            // serde_json errors already end with "at line L column C"
+            .map(|e| format!("Invalid JSON: {}", e));
+        if error == self.json_error {
+            return;
+        }
+
+        match &error {
+            Some(message) => self.show_status(cx, message, true),
+            // The previous error is no longer relevant
+            None => self.show_status(cx, "", false),
+        }
+        let disabled = if error.is_some() { 1.0 } else { 0.0 };
+        self.view(ids!(save_button)).apply_over(cx, live! {
+            draw_bg: { disabled: (disabled) }
+        });
+        self.json_error = error;
+        self.redraw(cx);
+    }
+
+    /// Pretty-print or minify the editor JSON. Invalid JSON is left untouched.
+    /// The result is not saved, so the editor shows as dirty until Save.
+    fn reformat_editor(&mut self, cx: &mut Cx, compact: bool) {
+        let editor = self.widget(ids!(mcp_code_view));
+        let formatted = McpServersConfig::from_json(&editor.text()).and_then(|config| {
+            if compact { config.to_json_compact() } else { config.to_json() }
+        });
+
+        match formatted {
+            Ok(json) => {
+                editor.set_text(cx, &json);
+                self.show_status(cx, "", false);
+                self.update_dirty_state(cx);
+            }
+            Err(e) => self.show_status(cx, &format!("Invalid JSON: {}", e), true),
+        }
+        self.redraw(cx);
+    }
+
+    /// Validate and save the editor contents. Returns true on success.
+    fn save_config(&mut self, cx: &mut Cx, scope: &mut Scope) -> bool {
+        if self.form_mode {
+            if let Some(error) = self.form_error() {
+                self.show_status(cx, &error, true);
+                self.redraw(cx);
+                return false;
+            }
+        }
+
+        let json_text = self.widget(ids!(mcp_code_view)).text();
+
+        match McpServersConfig::from_json(&json_text) {
+            Ok(config) if config.servers.keys().any(|name| name.trim().is_empty()) => {
+                self.show_status(cx, "Every server needs a name", true);
+                self.redraw(cx);
+            }
+            Ok(config) => {
+                if let Some(store) = scope.data.get_mut::<Store>() {
+                    match store.update_mcp_servers_from_json(&json_text) {
+                        Ok(()) => {
+                            // Also sync the enabled/dangerous mode flags
+                            store.set_mcp_servers_enabled(config.enabled);
+                            store.set_mcp_servers_dangerous_mode_enabled(
+                                config.dangerous_mode_enabled,
+                            );
+
+                            // Update local config
+                            self.set_mcp_servers_config(cx, config);
+
+                            // Show success message
+                            self.show_status(cx, "Configuration saved!", false);
+                            self.redraw(cx);
+                            return true;
+                        }
+                        Err(e) => {
+                            // Show error message
+                            self.show_status(cx, &format!("Error: {}", e), true);
+                            self.redraw(cx);
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                // Show JSON parse error
+                self.show_status(cx, &format!("Invalid JSON: {}", e), true);
+                self.redraw(cx);
+            }
+        }
+        false
+    }
+
+    /// Draw the aggregated tools catalog
+    fn draw_tools_list(&mut self, cx: &mut Cx2d, scope: &mut Scope, widget: WidgetRef, dark_mode: f64) {
+        let binding = widget.as_portal_list();
+        let Some(mut list) = binding.borrow_mut() else { return };
+
+        list.set_item_range(cx, 0, self.tool_rows.len());
+
+        while let Some(item_id) = list.next_visible_item(cx) {
+            if item_id >= self.tool_rows.len() {
+                continue;
+            }
+
+            let item_widget = match &self.tool_rows[item_id] {
+                ToolCatalogRow::Tool { server_id, name, description } => {
+                    let item_widget = list.item(cx, item_id, live_id!(ToolItem));
+                    item_widget.label(ids!(tool_name)).set_text(cx, name);
+                    item_widget.label(ids!(tool_server)).set_text(cx, server_id);
+                    let description_label = item_widget.label(ids!(tool_description));
+                    description_label.set_visible(cx, !description.is_empty());
+                    description_label.set_text(cx, description);
+                    item_widget
+                }
+                ToolCatalogRow::Error { server_id, message } => {
+                    let item_widget = list.item(cx, item_id, live_id!(ToolServerError));
+                    item_widget.label(ids!(tool_server)).set_text(cx, server_id);
+                    item_widget.label(ids!(error_message)).set_text(cx, message);
+                    item_widget
+                }
+            };
+
+            for label in [
+                item_widget.label(ids!(tool_name)),
+                item_widget.label(ids!(tool_server)),
+                item_widget.label(ids!(tool_description)),
+                item_widget.label(ids!(error_message)),
+            ] {
+                label.apply_over(cx, live! {
+                    draw_text: { dark_mode: (dark_mode) }
+                });
+            }
+
+            item_widget.draw_all(cx, scope);
+        }
+    }
+
+    /// Draw one editable card per server
+    fn draw_servers_form(&mut self, cx: &mut Cx2d, scope: &mut Scope, widget: WidgetRef, dark_mode: f64) {
+        let binding = widget.as_portal_list();
+        let Some(mut list) = binding.borrow_mut() else { return };
+
+        list.set_item_range(cx, 0, self.form_servers.len());
+
+        while let Some(item_id) = list.next_visible_item(cx) {
+            let Some(server) = self.form_servers.get(item_id) else { continue };
+            let item_widget = list.item(cx, item_id, live_id!(ServerFormItem));
+
+            let inputs = [
+                (item_widget.text_input(ids!(server_name_input)), &server.name),
+                (item_widget.text_input(ids!(server_target_input)), &server.target),
+                (item_widget.text_input(ids!(server_args_input)), &server.args),
+                (item_widget.text_input(ids!(server_env_input)), &server.env),
+            ];
+            for (input, value) in inputs {
+                // Only overwrite when it differs, so the cursor isn't reset while typing
+                if input.text() != *value {
+                    input.set_text(cx, value);
+                }
+                input.apply_over(cx, live! {
+                    draw_bg: { dark_mode: (dark_mode) }
+                    draw_text: { dark_mode: (dark_mode) }
+                });
+            }
+            item_widget.check_box(ids!(server_enabled_switch)).set_active(cx, server.enabled);
+            item_widget.apply_over(cx, live! {
+                draw_bg: { dark_mode: (dark_mode) }
+            });
+
+            item_widget.draw_all(cx, scope);
+        }
+    }
+
+    /// Handle edits, additions and removals in the servers form
+    fn handle_servers_form(&mut self, cx: &mut Cx, actions: &Actions) {
+        let mut changed = false;
+
+        if self.button(ids!(add_server_button)).clicked(actions) {
+            self.form_servers.push(ServerForm::empty());
+            changed = true;
+        }
+
+        let servers_list = self.portal_list(ids!(servers_list));
+        let mut removed = None;
+        for (item_id, item) in servers_list.items_with_actions(actions) {
+            let Some(server) = self.form_servers.get_mut(item_id) else { continue };
+            if let Some(enabled) = item.check_box(ids!(server_enabled_switch)).changed(actions) {
+                server.enabled = enabled;
+                changed = true;
+            }
+            if let Some(name) = item.text_input(ids!(server_name_input)).changed(actions) {
+                server.name = name;
+                changed = true;
+            }
+            if let Some(target) = item.text_input(ids!(server_target_input)).changed(actions) {
+                server.target = target;
+                changed = true;
+            }
+            if let Some(args) = item.text_input(ids!(server_args_input)).changed(actions) {
+                server.args = args;
+                changed = true;
+            }
+            if let Some(env) = item.text_input(ids!(server_env_input)).changed(actions) {
+                server.env = env;
+                changed = true;
+            }
+            if item.button(ids!(remove_server_button)).clicked(actions) {
+                removed = Some(item_id);
+            }
+        }
+
+        if let Some(item_id) = removed {
+            self.form_servers.remove(item_id);
+            changed = true;
+        }
+
+        if changed {
+            self.sync_form_to_editor(cx);
+            self.redraw(cx);
+        }
+    }
+
+    /// Query every enabled server for its tools in the background.
+    /// Uses the saved configuration, not unsaved editor text.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn refresh_tools(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        if self.tools_loading {
+            return;
+        }
+        let Some(store) = scope.data.get::<Store>() else { return };
+
+        if !store.preferences.get_mcp_servers_enabled() {
+            self.tool_rows.clear();
+            self.label(ids!(tools_status)).set_text(cx, "MCP servers are disabled.");
+            self.redraw(cx);
+            return;
+        }
+
+        // Resolve ${VAR} references the same way a launch would
+        let config = store.get_mcp_servers_config().resolve_env();
+        let servers: Vec<_> = config
+            .list_enabled_servers()
+            .map(|(id, server)| (id.clone(), server.clone()))
+            .collect();
+
+        if servers.is_empty() {
+            self.tool_rows.clear();
+            self.label(ids!(tools_status)).set_text(cx, "No enabled servers.");
+            self.redraw(cx);
+            return;
+        }
+
+        self.tools_loading = true;
+        self.label(ids!(tools_status)).set_text(cx, "Loading tools...");
+        self.redraw(cx);
+
+        let result_slot = self.tools_result.clone();
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+
+            let mut rows = Vec::new();
+            for (server_id, server) in &servers {
+                let listed = rt.block_on(async {
+                    tokio::time::timeout(TOOL_LIST_TIMEOUT, list_server_tools(server_id, server))
+                        .await
+                        .unwrap_or_else(|_| Err("Timed out waiting for the server".to_string()))
+                });
+
+                match listed {
+                    Ok(tools) => rows.extend(tools.into_iter().map(|(name, description)| {
+                        ToolCatalogRow::Tool { server_id: server_id.clone(), name, description }
+                    })),
+                    Err(message) => {
+                        ::log::warn!("Failed to list tools for MCP server '{}': {}", server_id, message);
+                        rows.push(ToolCatalogRow::Error { server_id: server_id.clone(), message });
+                    }
+                }
+            }
+
+            if let Ok(mut guard) = result_slot.lock() {
+                *guard = Some(rows);
+            }
+        });
+    }
+
+    /// Listing tools is not supported on wasm
+    #[cfg(target_arch = "wasm32")]
+    fn refresh_tools(&mut self, _cx: &mut Cx, _scope: &mut Scope) {}
+
+    /// Show the tools catalog once the background refresh finishes
+    fn check_tools_result(&mut self, cx: &mut Cx) {
+        if !self.tools_loading {
+            return;
+        }
+        let Some(rows) = self.tools_result.lock().ok().and_then(|mut guard| guard.take()) else {
+            return;
+        };
+
+        self.tools_loading = false;
+        let tool_count = rows.iter().filter(|row| matches!(row, ToolCatalogRow::Tool { .. })).count();
+        let error_count = rows.len() - tool_count;
+        let status = match error_count {
+            0 => format!("{} tools available", tool_count),
+            _ => format!("{} tools available, {} servers failed", tool_count, error_count),
+        };
+        self.label(ids!(tools_status)).set_text(cx, &status);
+        self.tool_rows = rows;
+        self.redraw(cx);
+    }
+
     /// Handle toggle switch changes (common logic for servers_enabled and dangerous_mode)
     fn handle_toggle_change(
         &mut self,
@@ -165,51 +805,97 @@ impl McpApp {
 impl WidgetMatchEvent for McpApp {
     fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, scope: &mut Scope) {
         // Handle save button click
-        if self.view(ids!(save_button)).finger_up(actions).is_some() {
-            let json_text = self.widget(ids!(mcp_code_view)).text();
+        if self.view(ids!(save_button)).finger_up(actions).is_some() && self.json_error.is_none() {
+            self.save_config(cx, scope);
+        }
 
-            match McpServersConfig::from_json(&json_text) {
-                Ok(config) => {
-                    if let Some(store) = scope.data.get_mut::<Store>() {
-                        match store.update_mcp_servers_from_json(&json_text) {
-                            Ok(()) => {
-                                // Also sync the enabled/dangerous mode flags
-                                store.set_mcp_servers_enabled(config.enabled);
-                                store.set_mcp_servers_dangerous_mode_enabled(
-                                    config.dangerous_mode_enabled,
-                                );
-
-                                // Update local config
-                                self.set_mcp_servers_config(cx, config);
-
-                                // Show success message
-                                self.show_status(cx, "Configuration saved!", false);
-                                self.redraw(cx);
-                            }
-                            Err(e) => {
-                                // Show error message
-                                self.show_status(cx, &format!("Error: {}", e), true);
-                                self.redraw(cx);
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    // Show JSON parse error
-                    self.show_status(cx, &format!("Invalid JSON: {}", e), true);
-                    self.redraw(cx);
-                }
+        // Re-serialize the editor contents
+        if self.button(ids!(format_button)).clicked(actions) {
+            self.reformat_editor(cx, false);
+        }
+        if self.button(ids!(minify_button)).clicked(actions) {
+            self.reformat_editor(cx, true);
+        }
+
+        if self.button(ids!(json_tab_button)).clicked(actions) {
+            self.set_form_mode(cx, false);
+        }
+        if self.button(ids!(form_tab_button)).clicked(actions) {
+            self.set_form_mode(cx, true);
+        }
+        if self.form_mode {
+            self.handle_servers_form(cx, actions);
+        }
+
+        if self.button(ids!(refresh_tools_button)).clicked(actions) {
+            self.refresh_tools(cx, scope);
+        }
+
+        // Unsaved changes prompt shown when navigating away
+        if self.button(ids!(leave_save_button)).clicked(actions) {
+            self.hide_leave_confirm(cx);
+            if self.save_config(cx, scope) {
+                cx.action(McpAppAction::LeaveConfirmed);
+            } else {
+                cx.action(McpAppAction::LeaveCancelled);
             }
         }
+        if self.button(ids!(leave_discard_button)).clicked(actions) {
+            self.hide_leave_confirm(cx);
+            self.sync_json_display(cx);
+            cx.action(McpAppAction::LeaveConfirmed);
+        }
+        if self.button(ids!(leave_cancel_button)).clicked(actions) {
+            self.hide_leave_confirm(cx);
+            cx.action(McpAppAction::LeaveCancelled);
+        }
 
         // Handle servers enabled switch toggle
         if let Some(enabled) = self.check_box(ids!(servers_enabled_switch)).changed(actions) {
             self.handle_toggle_change(cx, scope, ToggleType::ServersEnabled, enabled);
         }
 
+        // Tool calls display is a chat preference, not part of the servers JSON
+        if let Some(show) = self.check_box(ids!(show_tool_calls_switch)).changed(actions) {
+            if let Some(store) = scope.data.get_mut::<Store>() {
+                store.preferences.set_show_tool_calls(show);
+            }
+        }
+
         // Handle dangerous mode switch toggle
         if let Some(enabled) = self.check_box(ids!(dangerous_mode_switch)).changed(actions) {
             self.handle_toggle_change(cx, scope, ToggleType::DangerousMode, enabled);
         }
     }
 }
+
+/// Start a single server and return its `(name, description)` tool list
+#[cfg(not(target_arch = "wasm32"))]
+async fn list_server_tools(
+    server_id: &str,
+    server: &moly_data::McpServer,
+) -> Result<Vec<(String, String)>, String> {
+    use moly_kit::prelude::McpManagerClient;
+
+    let transport = server
+        .to_transport()
+        .ok_or_else(|| "No command or url configured".to_string())?;
+
+    // A dedicated manager, so every tool listed belongs to this server
+    let manager = McpManagerClient::new();
+    manager
+        .add_server(server_id, transport)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let prefix = format!("{}__", server_id);
+    Ok(manager
+        .get_all_namespaced_tools()
+        .into_iter()
+        .map(|tool| {
+            // Show the tool's own name; the server is shown separately
+            let name = tool.name.strip_prefix(&prefix).unwrap_or(&tool.name).to_string();
+            (name, tool.description.unwrap_or_default().to_string())
+        })
+        .collect())
+}
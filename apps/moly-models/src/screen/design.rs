@@ -2,7 +2,7 @@
 
 use makepad_widgets::*;
 
-use super::ModelsApp;
+use super::{ModelsApp, ModelFilesList};
 
 live_design! {
     use link::theme::*;
@@ -13,7 +13,7 @@ live_design! {
     // Search input style
     SearchInput = <TextInput> {
         width: Fill, height: 44
-        padding: {left: 40, right: 12, top: 10, bottom: 10}
+        padding: {left: 40, right: 40, top: 10, bottom: 10}
         empty_text: "Search models..."
 
         draw_bg: {
@@ -43,6 +43,276 @@ live_design! {
         }
     }
 
+    // Clear (x) button shown inside the search input
+    ClearSearchButton = <Button> {
+        width: 28, height: 28
+        margin: {right: 8}
+        padding: 0
+
+        draw_bg: {
+            instance hover: 0.0
+            instance pressed: 0.0
+            instance dark_mode: 0.0
+
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                let c = self.rect_size * 0.5;
+                sdf.circle(c.x, c.y, c.x - 2.0);
+                let bg = mix(#00000000, mix(#f3f4f6, #334155, self.dark_mode), self.hover);
+                sdf.fill(bg);
+                return sdf.result;
+            }
+        }
+
+        draw_text: {
+            instance dark_mode: 0.0
+            fn get_color(self) -> vec4 {
+                return mix(#6b7280, #94a3b8, self.dark_mode);
+            }
+            text_style: <THEME_FONT_BOLD>{ font_size: 12.0 }
+        }
+
+        text: "×"
+    }
+
+    // Call-to-action button in the empty state
+    EmptyStateButton = <Button> {
+        width: Fit, height: 36
+        padding: {left: 16, right: 16}
+
+        draw_bg: {
+            instance hover: 0.0
+            instance pressed: 0.0
+            instance dark_mode: 0.0
+
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                let sz = self.rect_size - 2.0;
+                let bg = mix(#ffffff, #1e293b, self.dark_mode);
+                let hover_bg = mix(#f3f4f6, #334155, self.dark_mode);
+                let border = mix(#d1d5db, #475569, self.dark_mode);
+                sdf.box(1.0, 1.0, sz.x, sz.y, 6.0);
+                sdf.fill(mix(bg, hover_bg, self.hover));
+                sdf.stroke(border, 1.0);
+                return sdf.result;
+            }
+        }
+
+        draw_text: {
+            instance dark_mode: 0.0
+            fn get_color(self) -> vec4 {
+                return mix(#1f2937, #f1f5f9, self.dark_mode);
+            }
+            text_style: <THEME_FONT_REGULAR>{ font_size: 12.0 }
+        }
+    }
+
+    // Toggleable filter chip
+    FilterChip = <Button> {
+        width: Fit, height: 28
+        padding: {left: 12, right: 12}
+        margin: {right: 8}
+
+        draw_bg: {
+            instance hover: 0.0
+            instance pressed: 0.0
+            instance selected: 0.0
+            instance dark_mode: 0.0
+
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                let sz = self.rect_size - 2.0;
+                sdf.box(1.0, 1.0, sz.x, sz.y, 13.0);
+                let bg = mix(#ffffff, #1e293b, self.dark_mode);
+                let hover_bg = mix(#f3f4f6, #334155, self.dark_mode);
+                let selected_bg = mix(#dbeafe, #1e3a8a, self.dark_mode);
+                let border = mix(#d1d5db, #475569, self.dark_mode);
+                sdf.fill(mix(mix(bg, hover_bg, self.hover), selected_bg, self.selected));
+                sdf.stroke(border, 1.0);
+                return sdf.result;
+            }
+        }
+
+        draw_text: {
+            instance dark_mode: 0.0
+            fn get_color(self) -> vec4 {
+                return mix(#374151, #e2e8f0, self.dark_mode);
+            }
+            text_style: <THEME_FONT_REGULAR>{ font_size: 11.0 }
+        }
+    }
+
+    // File item in model card
+    FileItem = <View> {
+        width: Fill, height: Fit
+        padding: {left: 8, right: 8, top: 6, bottom: 6}
+        flow: Right
+        align: {y: 0.5}
+        spacing: 8
+        show_bg: true
+
+        draw_bg: {
+            instance radius: 4.0
+            instance dark_mode: 0.0
+
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                let sz = self.rect_size - 2.0;
+                sdf.box(1.0, 1.0, sz.x, sz.y, self.radius);
+                let bg = mix(#f3f4f6, #0f172a, self.dark_mode);
+                sdf.fill(bg);
+                return sdf.result;
+            }
+        }
+
+        file_name = <Label> {
+            width: Fill
+            draw_text: {
+                instance dark_mode: 0.0
+                fn get_color(self) -> vec4 {
+                    return mix(#1f2937, #f1f5f9, self.dark_mode);
+                }
+                text_style: <THEME_FONT_REGULAR>{ font_size: 11.0 }
+            }
+        }
+
+        file_size = <Label> {
+            width: Fit
+            draw_text: {
+                instance dark_mode: 0.0
+                fn get_color(self) -> vec4 {
+                    return mix(#6b7280, #94a3b8, self.dark_mode);
+                }
+                text_style: <THEME_FONT_REGULAR>{ font_size: 10.0 }
+            }
+        }
+
+        file_quant = <Label> {
+            width: Fit
+            draw_text: {
+                instance dark_mode: 0.0
+                fn get_color(self) -> vec4 {
+                    return mix(#8b5cf6, #a78bfa, self.dark_mode);
+                }
+                text_style: <THEME_FONT_REGULAR>{ font_size: 10.0 }
+            }
+        }
+
+        // Shown when the file is already on disk
+        downloaded_badge = <View> {
+            visible: false
+            width: Fit, height: Fit
+            padding: {left: 8, right: 8, top: 3, bottom: 3}
+            show_bg: true
+
+            draw_bg: {
+                instance dark_mode: 0.0
+
+                fn pixel(self) -> vec4 {
+                    let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                    sdf.box(0.0, 0.0, self.rect_size.x, self.rect_size.y, 4.0);
+                    sdf.fill(mix(#dcfce7, #14532d, self.dark_mode));
+                    return sdf.result;
+                }
+            }
+
+            downloaded_label = <Label> {
+                text: "Downloaded"
+                draw_text: {
+                    instance dark_mode: 0.0
+                    fn get_color(self) -> vec4 {
+                        return mix(#15803d, #86efac, self.dark_mode);
+                    }
+                    text_style: <THEME_FONT_REGULAR>{ font_size: 10.0 }
+                }
+            }
+        }
+
+        download_btn = <Button> {
+            width: Fit, height: 24
+            padding: {left: 10, right: 10}
+
+            draw_bg: {
+                instance hover: 0.0
+                instance pressed: 0.0
+                instance radius: 4.0
+                instance dark_mode: 0.0
+
+                fn pixel(self) -> vec4 {
+                    let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                    let sz = self.rect_size - 2.0;
+                    // Blue colors: #3b82f6, #2563eb, #1d4ed8
+                    let light_base = vec4(0.231, 0.510, 0.965, 1.0);
+                    let dark_base = vec4(0.145, 0.388, 0.922, 1.0);
+                    let light_hover = vec4(0.145, 0.388, 0.922, 1.0);
+                    let dark_hover = vec4(0.114, 0.306, 0.847, 1.0);
+                    let base_color = mix(light_base, dark_base, self.dark_mode);
+                    let hover_color = mix(light_hover, dark_hover, self.dark_mode);
+                    let color = mix(base_color, hover_color, self.hover);
+                    sdf.box(1.0, 1.0, sz.x, sz.y, self.radius);
+                    sdf.fill(color);
+                    return sdf.result;
+                }
+            }
+
+            draw_text: {
+                color: #ffffff
+                text_style: <THEME_FONT_REGULAR>{ font_size: 10.0 }
+            }
+
+            text: "Download"
+        }
+    }
+
+    // One FileItem per file of a model
+    ModelFilesList = {{ModelFilesList}} {
+        width: Fill, height: Fit
+        flow: Down
+        spacing: 6
+
+        file_item: <FileItem> {}
+    }
+
+    // Star toggle on a model card, filled when the model is a favorite
+    FavoriteStar = <Button> {
+        width: 20, height: 20
+        padding: 0
+        text: ""
+
+        draw_bg: {
+            instance hover: 0.0
+            instance pressed: 0.0
+            instance favorite: 0.0
+            instance dark_mode: 0.0
+
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                let s = self.rect_size;
+                // Five-pointed star, outer and inner points alternating
+                sdf.move_to(0.500 * s.x, 0.060 * s.y);
+                sdf.line_to(0.612 * s.x, 0.386 * s.y);
+                sdf.line_to(0.956 * s.x, 0.392 * s.y);
+                sdf.line_to(0.681 * s.x, 0.599 * s.y);
+                sdf.line_to(0.782 * s.x, 0.928 * s.y);
+                sdf.line_to(0.500 * s.x, 0.730 * s.y);
+                sdf.line_to(0.218 * s.x, 0.928 * s.y);
+                sdf.line_to(0.319 * s.x, 0.599 * s.y);
+                sdf.line_to(0.044 * s.x, 0.392 * s.y);
+                sdf.line_to(0.388 * s.x, 0.386 * s.y);
+                sdf.close_path();
+
+                let outline = mix(mix(#9ca3af, #64748b, self.dark_mode), mix(#6b7280, #94a3b8, self.dark_mode), self.hover);
+                let filled = mix(#f59e0b, #fbbf24, self.dark_mode);
+                if self.favorite > 0.5 {
+                    sdf.fill(filled);
+                } else {
+                    sdf.stroke(outline, 1.2);
+                }
+                return sdf.result;
+            }
+        }
+    }
+
     // Model card component
     ModelCard = <View> {
         width: Fill, height: Fit
@@ -79,6 +349,8 @@ live_design! {
             align: {y: 0.5}
             spacing: 8
 
+            favorite_btn = <FavoriteStar> {}
+
             model_name = <Label> {
                 width: Fit
                 draw_text: {
@@ -170,153 +442,47 @@ live_design! {
             }
         }
 
-        // Files section with download button
+        // Files section, one row per file with its own download button
         files_section = <View> {
             width: Fill, height: Fit
-            flow: Right
-            spacing: 12
+            flow: Down
+            spacing: 8
             margin: {top: 8}
             padding: {top: 8}
-            align: {y: 0.5}
-
-            files_label = <Label> {
-                width: Fill
-                text: "1 file(s) available"
-                draw_text: {
-                    instance dark_mode: 0.0
-                    fn get_color(self) -> vec4 {
-                        return mix(#6b7280, #94a3b8, self.dark_mode);
-                    }
-                    text_style: <THEME_FONT_REGULAR>{ font_size: 11.0 }
-                }
-            }
-
-            download_btn = <Button> {
-                width: Fit, height: 32
-                padding: {left: 16, right: 16}
 
-                draw_bg: {
-                    instance hover: 0.0
-                    instance pressed: 0.0
-                    instance radius: 6.0
-                    instance dark_mode: 0.0
+            <View> {
+                width: Fill, height: Fit
+                flow: Right
+                align: {y: 0.5}
 
-                    fn pixel(self) -> vec4 {
-                        let sdf = Sdf2d::viewport(self.pos * self.rect_size);
-                        let sz = self.rect_size - 2.0;
-                        // Blue colors: #3b82f6, #2563eb, #1d4ed8
-                        let light_base = vec4(0.231, 0.510, 0.965, 1.0);
-                        let dark_base = vec4(0.145, 0.388, 0.922, 1.0);
-                        let light_hover = vec4(0.145, 0.388, 0.922, 1.0);
-                        let dark_hover = vec4(0.114, 0.306, 0.847, 1.0);
-                        let base_color = mix(light_base, dark_base, self.dark_mode);
-                        let hover_color = mix(light_hover, dark_hover, self.dark_mode);
-                        let color = mix(base_color, hover_color, self.hover);
-                        sdf.box(1.0, 1.0, sz.x, sz.y, self.radius);
-                        sdf.fill(color);
-                        return sdf.result;
+                files_label = <Label> {
+                    width: Fill
+                    text: "1 file(s) available"
+                    draw_text: {
+                        instance dark_mode: 0.0
+                        fn get_color(self) -> vec4 {
+                            return mix(#6b7280, #94a3b8, self.dark_mode);
+                        }
+                        text_style: <THEME_FONT_REGULAR>{ font_size: 11.0 }
                     }
                 }
 
-                draw_text: {
-                    color: #ffffff
-                    text_style: <THEME_FONT_REGULAR>{ font_size: 11.0 }
-                }
-
-                text: "Download"
-            }
-        }
-    }
-
-    // File item in model card
-    FileItem = <View> {
-        width: Fill, height: Fit
-        padding: {left: 8, right: 8, top: 6, bottom: 6}
-        flow: Right
-        align: {y: 0.5}
-        spacing: 8
-        show_bg: true
-
-        draw_bg: {
-            instance radius: 4.0
-            instance dark_mode: 0.0
-
-            fn pixel(self) -> vec4 {
-                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
-                let sz = self.rect_size - 2.0;
-                sdf.box(1.0, 1.0, sz.x, sz.y, self.radius);
-                let bg = mix(#f3f4f6, #0f172a, self.dark_mode);
-                sdf.fill(bg);
-                return sdf.result;
-            }
-        }
-
-        file_name = <Label> {
-            width: Fill
-            draw_text: {
-                instance dark_mode: 0.0
-                fn get_color(self) -> vec4 {
-                    return mix(#1f2937, #f1f5f9, self.dark_mode);
-                }
-                text_style: <THEME_FONT_REGULAR>{ font_size: 11.0 }
-            }
-        }
-
-        file_size = <Label> {
-            width: Fit
-            draw_text: {
-                instance dark_mode: 0.0
-                fn get_color(self) -> vec4 {
-                    return mix(#6b7280, #94a3b8, self.dark_mode);
-                }
-                text_style: <THEME_FONT_REGULAR>{ font_size: 10.0 }
-            }
-        }
-
-        file_quant = <Label> {
-            width: Fit
-            draw_text: {
-                instance dark_mode: 0.0
-                fn get_color(self) -> vec4 {
-                    return mix(#8b5cf6, #a78bfa, self.dark_mode);
+                // Shown for models with several files, while some still need downloading
+                download_all_btn = <EmptyStateButton> {
+                    visible: false
+                    height: 28
+                    text: "Download all files"
                 }
-                text_style: <THEME_FONT_REGULAR>{ font_size: 10.0 }
             }
-        }
-
-        download_btn = <Button> {
-            width: Fit, height: 24
-            padding: {left: 10, right: 10}
 
-            draw_bg: {
-                instance hover: 0.0
-                instance pressed: 0.0
-                instance radius: 4.0
-
-                fn pixel(self) -> vec4 {
-                    let sdf = Sdf2d::viewport(self.pos * self.rect_size);
-                    let sz = self.rect_size - 2.0;
-                    let base_color = vec4(0.231, 0.510, 0.965, 1.0);
-                    let hover_color = vec4(0.145, 0.388, 0.922, 1.0);
-                    let color = mix(base_color, hover_color, self.hover);
-                    sdf.box(1.0, 1.0, sz.x, sz.y, self.radius);
-                    sdf.fill(color);
-                    return sdf.result;
-                }
-            }
-
-            draw_text: {
-                color: #ffffff
-                text_style: <THEME_FONT_REGULAR>{ font_size: 10.0 }
-            }
-
-            text: "Download"
+            file_list = <ModelFilesList> {}
         }
     }
 
     // Download progress item
     DownloadItem = <View> {
-        width: Fill, height: Fit
+        width: Fill, height: 62
+        margin: {bottom: 8}
         padding: 12
         show_bg: true
         flow: Down
@@ -371,6 +537,72 @@ live_design! {
                     text_style: <THEME_FONT_REGULAR>{ font_size: 11.0 }
                 }
             }
+
+            pause_resume_btn = <Button> {
+                width: Fit, height: 22
+                margin: {left: 10}
+                padding: {left: 10, right: 10}
+                text: "Pause"
+
+                draw_bg: {
+                    instance hover: 0.0
+                    instance pressed: 0.0
+                    instance dark_mode: 0.0
+
+                    fn pixel(self) -> vec4 {
+                        let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                        let sz = self.rect_size - 2.0;
+                        sdf.box(1.0, 1.0, sz.x, sz.y, 4.0);
+                        let base = mix(#ffffff, #166534, self.dark_mode);
+                        let hover = mix(#dcfce7, #15803d, self.dark_mode);
+                        sdf.fill(mix(base, hover, self.hover));
+                        sdf.stroke(mix(#86efac, #22c55e, self.dark_mode), 1.0);
+                        return sdf.result;
+                    }
+                }
+
+                draw_text: {
+                    instance dark_mode: 0.0
+                    fn get_color(self) -> vec4 {
+                        return mix(#166534, #dcfce7, self.dark_mode);
+                    }
+                    text_style: <THEME_FONT_REGULAR>{ font_size: 10.0 }
+                }
+            }
+
+            // Shown on failed downloads that have retries left
+            retry_btn = <Button> {
+                width: Fit, height: 22
+                margin: {left: 10}
+                padding: {left: 10, right: 10}
+                visible: false
+                text: "Retry"
+
+                draw_bg: {
+                    instance hover: 0.0
+                    instance pressed: 0.0
+                    instance dark_mode: 0.0
+
+                    fn pixel(self) -> vec4 {
+                        let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                        let sz = self.rect_size - 2.0;
+                        sdf.box(1.0, 1.0, sz.x, sz.y, 4.0);
+                        let base = mix(#ffffff, #92400e, self.dark_mode);
+                        let hover = mix(#fef3c7, #b45309, self.dark_mode);
+                        sdf.fill(mix(base, hover, self.hover));
+                        sdf.stroke(mix(#fcd34d, #f59e0b, self.dark_mode), 1.0);
+                        return sdf.result;
+                    }
+                }
+
+                draw_text: {
+                    instance dark_mode: 0.0
+                    fn get_color(self) -> vec4 {
+                        return mix(#92400e, #fef3c7, self.dark_mode);
+                    }
+                    text_style: <THEME_FONT_REGULAR>{ font_size: 10.0 }
+                }
+            }
         }
 
         // Progress bar
@@ -393,7 +625,7 @@ live_design! {
             }
 
             progress_bar_fill = <View> {
-                width: 0, height: Fill
+                width: Fill, height: Fill
                 show_bg: true
 
                 draw_bg: {
@@ -403,7 +635,9 @@ live_design! {
                     fn pixel(self) -> vec4 {
                         let sdf = Sdf2d::viewport(self.pos * self.rect_size);
                         let sz = self.rect_size - 2.0;
-                        sdf.box(1.0, 1.0, sz.x, sz.y, self.radius);
+                        // Only the filled fraction is drawn so the bar can animate smoothly
+                        let fill_width = sz.x * clamp(self.progress, 0.0, 1.0);
+                        sdf.box(1.0, 1.0, fill_width, sz.y, min(self.radius, fill_width * 0.5));
                         // Green gradient for progress
                         let color = vec4(0.133, 0.545, 0.133, 1.0); // #22c55e
                         sdf.fill(color);
@@ -505,8 +739,11 @@ live_design! {
 
                 search_container = <View> {
                     width: Fill, height: Fit
+                    flow: Overlay
+                    align: {x: 1.0, y: 0.5}
 
                     search_input = <SearchInput> {}
+                    clear_search_btn = <ClearSearchButton> { visible: false }
                 }
 
                 refresh_btn = <Button> {
@@ -564,28 +801,116 @@ live_design! {
                 }
             }
 
-            downloads_list = <View> {
-                width: Fill, height: Fit
-                flow: Down
-                spacing: 8
+            // One line per model whose files are downloaded together
+            download_groups_label = <Label> {
+                width: Fill
+                margin: {bottom: 8}
+                visible: false
+                draw_text: {
+                    instance dark_mode: 0.0
+                    fn get_color(self) -> vec4 {
+                        return mix(#374151, #e2e8f0, self.dark_mode);
+                    }
+                    text_style: <THEME_FONT_REGULAR>{ font_size: 12.0 }
+                    wrap: Word
+                }
+            }
+
+            // Height is set from the number of downloads (see DOWNLOAD_ITEM_HEIGHT)
+            downloads_list = <PortalList> {
+                width: Fill, height: 70
+                drag_scrolling: false
+
+                DownloadListItem = <DownloadItem> {}
             }
         }
 
-        // Results info
-        results_info = <View> {
+        // Confirmation before deleting a downloaded file from disk
+        remove_confirm = <View> {
             width: Fill, height: Fit
+            visible: false
+            flow: Right
+            align: {y: 0.5}
+            spacing: 12
             padding: {left: 20, right: 20, bottom: 12}
 
-            results_label = <Label> {
-                text: "Featured Models"
+            remove_confirm_label = <Label> {
+                width: Fill
                 draw_text: {
+                    wrap: Word
                     instance dark_mode: 0.0
                     fn get_color(self) -> vec4 {
-                        return mix(#6b7280, #94a3b8, self.dark_mode);
+                        return mix(#dc2626, #f87171, self.dark_mode);
                     }
                     text_style: <THEME_FONT_REGULAR>{ font_size: 12.0 }
                 }
             }
+
+            confirm_remove_btn = <EmptyStateButton> { text: "Remove" }
+            cancel_remove_btn = <EmptyStateButton> { text: "Cancel" }
+        }
+
+        // Results info, sort order and favorites/architecture filters
+        results_info = <View> {
+            width: Fill, height: Fit
+            flow: Down
+            spacing: 8
+            padding: {left: 20, right: 20, bottom: 12}
+
+            <View> {
+                width: Fill, height: Fit
+                flow: Right
+                align: {y: 0.5}
+                spacing: 8
+
+                results_label = <Label> {
+                    width: Fill
+                    text: "Featured Models"
+                    draw_text: {
+                        instance dark_mode: 0.0
+                        fn get_color(self) -> vec4 {
+                            return mix(#6b7280, #94a3b8, self.dark_mode);
+                        }
+                        text_style: <THEME_FONT_REGULAR>{ font_size: 12.0 }
+                    }
+                }
+
+                sort_label = <Label> {
+                    text: "Sort by"
+                    draw_text: {
+                        instance dark_mode: 0.0
+                        fn get_color(self) -> vec4 {
+                            return mix(#6b7280, #94a3b8, self.dark_mode);
+                        }
+                        text_style: <THEME_FONT_REGULAR>{ font_size: 12.0 }
+                    }
+                }
+
+                // Order matches ModelSort::from_index
+                sort_dropdown = <DropDown> {
+                    width: Fit
+                    labels: ["Downloads", "Likes", "Size", "Name"]
+                    selected_item: 0
+                }
+            }
+
+            <View> {
+                width: Fill, height: Fit
+                flow: Right
+
+                // Narrows the list to starred models; combines with the architecture filter
+                favorites_chip = <FilterChip> { text: "Favorites" }
+
+                // First chip is "All"; hidden when the results share one architecture
+                architecture_list = <PortalList> {
+                    width: Fill, height: 28
+                    flow: Right
+                    drag_scrolling: false
+                    visible: false
+
+                    ArchitectureChip = <FilterChip> {}
+                }
+            }
         }
 
         // Model list
@@ -605,6 +930,8 @@ live_design! {
         // Empty state / loading / error
         empty_state = <View> {
             width: Fill, height: Fill
+            flow: Down
+            spacing: 12
             align: {x: 0.5, y: 0.5}
             visible: false
 
@@ -617,6 +944,35 @@ live_design! {
                     text_style: <THEME_FONT_REGULAR>{ font_size: 14.0 }
                 }
             }
+
+            // Shown while Moly Server is unreachable: local and hosted models are separate sources
+            server_actions = <View> {
+                visible: false
+                width: Fit, height: Fit
+                flow: Down
+                spacing: 12
+                align: {x: 0.5}
+
+                server_actions_hint = <Label> {
+                    text: "Local models are downloaded through Moly Server. Hosted models (OpenAI, Anthropic, ...) are set up in Settings."
+                    draw_text: {
+                        instance dark_mode: 0.0
+                        fn get_color(self) -> vec4 {
+                            return mix(#9ca3af, #64748b, self.dark_mode);
+                        }
+                        text_style: <THEME_FONT_REGULAR>{ font_size: 11.0 }
+                    }
+                }
+
+                <View> {
+                    width: Fit, height: Fit
+                    flow: Right
+                    spacing: 12
+
+                    retry_connection_btn = <EmptyStateButton> { text: "Reconnect" }
+                    configure_provider_btn = <EmptyStateButton> { text: "Configure a cloud provider" }
+                }
+            }
         }
     }
 }
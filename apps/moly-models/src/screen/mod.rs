@@ -3,9 +3,23 @@
 pub mod design;
 
 use makepad_widgets::*;
-use moly_data::{Store, Model, ModelFile, FileId, PendingDownload, PendingDownloadsStatus, ServerConnectionStatus};
+use moly_data::{Store, StoreAction, Model, ModelFile, FileId, DownloadedFile, PendingDownload, PendingDownloadsStatus, ServerConnectionStatus, DownloadGroup, DownloadState};
+use serde::{Deserialize, Serialize};
+use moly_widgets::toast::Toast;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
-use std::collections::HashMap;
+
+/// Fraction of the remaining distance the displayed progress covers each frame
+const PROGRESS_SMOOTHING: f64 = 0.15;
+
+/// Height of one row in the downloads list, including its margin
+const DOWNLOAD_ITEM_HEIGHT: f64 = 70.0;
+
+/// Downloads shown before the list starts scrolling
+const MAX_VISIBLE_DOWNLOADS: usize = 3;
+
+/// Delays before each automatic reconnect to Moly Server on launch, in seconds
+const LAUNCH_RETRY_DELAYS: [f64; 2] = [2.0, 5.0];
 
 /// State of the models list
 #[derive(Clone, Debug, Default)]
@@ -17,15 +31,45 @@ enum ModelsState {
     Error(String),
 }
 
-/// Download state for a file
-#[derive(Clone, Debug)]
-#[allow(dead_code)]
-struct DownloadState {
-    file_id: FileId,
-    model_name: String,
-    file_name: String,
-    progress: f64,
-    status: PendingDownloadsStatus,
+/// Order of the models list, as offered by the sort dropdown
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum ModelSort {
+    #[default]
+    Downloads,
+    Likes,
+    Size,
+    Name,
+}
+
+impl ModelSort {
+    /// Sort for a `sort_dropdown` item index
+    fn from_index(index: usize) -> Self {
+        match index {
+            1 => ModelSort::Likes,
+            2 => ModelSort::Size,
+            3 => ModelSort::Name,
+            _ => ModelSort::Downloads,
+        }
+    }
+
+    /// Compare two models, most relevant first
+    fn compare(self, a: &Model, b: &Model) -> std::cmp::Ordering {
+        match self {
+            ModelSort::Downloads => b.download_count.cmp(&a.download_count),
+            ModelSort::Likes => b.like_count.cmp(&a.like_count),
+            ModelSort::Size => parse_model_size(&b.size).total_cmp(&parse_model_size(&a.size)),
+            ModelSort::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+        }
+    }
+}
+
+/// Models screen state restored on the next launch
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ModelsSessionState {
+    /// Search query, empty when showing featured models
+    pub search_query: String,
+    /// Index of the topmost visible model
+    pub first_model: usize,
 }
 
 /// Result from async task
@@ -35,11 +79,135 @@ enum ModelsTaskResult {
     ModelsResult(Result<Vec<Model>, String>),
     DownloadStarted(Result<FileId, String>),
     DownloadsUpdate(Result<Vec<PendingDownload>, String>),
+    /// A retried download request finished
+    DownloadRetried(FileId, Result<(), String>),
+    /// A pause or resume request finished, with the status the download now has
+    DownloadStatusChanged(Result<(FileId, PendingDownloadsStatus), String>),
+    /// A downloaded file was deleted from disk
+    FileRemoved(Result<ModelFile, String>),
+    /// Downloads of a model's remaining files were requested, one result per file
+    GroupDownloadStarted(Vec<(FileId, Result<(), String>)>),
 }
 
 /// Shared state for async results
 type TaskResultState = Arc<Mutex<Option<ModelsTaskResult>>>;
 
+/// Shared state for the downloaded files list, kept apart from `TaskResultState`
+/// so it isn't overwritten by a models or downloads result arriving meanwhile
+type DownloadedFilesState = Arc<Mutex<Option<Result<Vec<DownloadedFile>, String>>>>;
+
+/// What one row of a model card's file list shows
+#[derive(Clone, Debug, Default)]
+pub struct FileRow {
+    pub name: String,
+    pub size: String,
+    pub quantization: String,
+    /// Text of the row's button: "Download", a progress percentage, "Paused" or "Remove"
+    pub action: String,
+    /// Whether the file is already on disk
+    pub downloaded: bool,
+}
+
+/// Lists the files of a model, one `FileItem` per file
+#[derive(Live, LiveHook, Widget)]
+pub struct ModelFilesList {
+    #[redraw]
+    #[rust]
+    area: Area,
+
+    #[walk]
+    walk: Walk,
+
+    #[layout]
+    layout: Layout,
+
+    /// Template for each row
+    #[live]
+    file_item: Option<LivePtr>,
+
+    #[rust]
+    items: ComponentMap<usize, WidgetRef>,
+
+    #[rust]
+    rows: Vec<FileRow>,
+
+    #[rust]
+    dark_mode: f64,
+}
+
+impl Widget for ModelFilesList {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for item in self.items.values() {
+            item.handle_event(cx, event, scope);
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        let dark_mode = self.dark_mode;
+        cx.begin_turtle(walk, self.layout);
+
+        for (index, row) in self.rows.iter().enumerate() {
+            let item = self.items.get_or_insert(cx, index, |cx| {
+                WidgetRef::new_from_ptr(cx, self.file_item)
+            });
+
+            item.apply_over(cx, live! {
+                draw_bg: { dark_mode: (dark_mode) }
+            });
+            item.label(ids!(file_name)).set_text(cx, &row.name);
+            item.label(ids!(file_size)).set_text(cx, &row.size);
+            item.label(ids!(file_quant)).set_text(cx, &row.quantization);
+            item.button(ids!(download_btn)).set_text(cx, &row.action);
+            item.view(ids!(downloaded_badge)).set_visible(cx, row.downloaded);
+            item.view(ids!(downloaded_badge)).apply_over(cx, live! {
+                draw_bg: { dark_mode: (dark_mode) }
+            });
+            for label in [ids!(file_name), ids!(file_size), ids!(file_quant), ids!(downloaded_label)] {
+                item.label(label).apply_over(cx, live! {
+                    draw_text: { dark_mode: (dark_mode) }
+                });
+            }
+            item.button(ids!(download_btn)).apply_over(cx, live! {
+                draw_bg: { dark_mode: (dark_mode) }
+            });
+
+            item.draw_all(cx, scope);
+        }
+
+        cx.end_turtle_with_area(&mut self.area);
+        // Drop rows left over from a model with more files
+        self.items.retain_visible();
+        DrawStep::done()
+    }
+}
+
+impl ModelFilesList {
+    /// Index of the file whose download button was clicked
+    pub fn clicked_file(&self, actions: &Actions) -> Option<usize> {
+        self.items.iter()
+            .find(|(_, item)| item.button(ids!(download_btn)).clicked(actions))
+            .map(|(index, _)| *index)
+    }
+}
+
+impl ModelFilesListRef {
+    pub fn set_rows(&self, cx: &mut Cx, rows: Vec<FileRow>, dark_mode: f64) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.rows = rows;
+            inner.dark_mode = dark_mode;
+            inner.redraw(cx);
+        }
+    }
+
+    pub fn clicked_file(&self, actions: &Actions) -> Option<usize> {
+        if let Some(inner) = self.borrow() {
+            inner.clicked_file(actions)
+        } else {
+            None
+        }
+    }
+}
+
 #[derive(Live, LiveHook, Widget)]
 pub struct ModelsApp {
     #[deref]
@@ -65,13 +233,18 @@ pub struct ModelsApp {
     #[rust]
     task_result: TaskResultState,
 
-    /// Whether we've initialized connection
+    /// Shared state for the downloaded files request
     #[rust]
-    initialized: bool,
+    downloaded_files_result: DownloadedFilesState,
+
+    /// Ids of files Moly Server has on disk, fetched once and refreshed when a
+    /// download finishes. Search results don't always flag these themselves
+    #[rust]
+    downloaded_file_ids: HashSet<FileId>,
 
-    /// Active downloads (file_id -> download state)
+    /// Whether we've initialized connection
     #[rust]
-    active_downloads: HashMap<FileId, DownloadState>,
+    initialized: bool,
 
     /// Index of expanded model (for showing files) - reserved for future use
     #[rust]
@@ -81,6 +254,68 @@ pub struct ModelsApp {
     /// Timer for polling download progress
     #[rust]
     download_poll_timer: Timer,
+
+    /// Progress currently drawn per download, eased toward the polled value
+    #[rust]
+    displayed_progress: HashMap<FileId, f64>,
+
+    /// Frame request used to animate download progress
+    #[rust]
+    progress_next_frame: NextFrame,
+
+    /// Downloads in the order they are listed
+    #[rust]
+    listed_downloads: Vec<DownloadState>,
+
+    /// Model index to scroll to once the restored search results arrive
+    #[rust]
+    restored_first_model: Option<usize>,
+
+    /// Downloaded file waiting for the user to confirm its removal
+    #[rust]
+    pending_removal: Option<ModelFile>,
+
+    /// Order `models` is kept in
+    #[rust]
+    sort: ModelSort,
+
+    /// Architecture the list is narrowed to, if any
+    #[rust]
+    architecture_filter: Option<String>,
+
+    /// Distinct architectures among `models`, for the filter chips
+    #[rust]
+    architectures: Vec<String>,
+
+    /// Only list starred models
+    #[rust]
+    favorites_only: bool,
+
+    /// Ids of starred models, mirrored from `Preferences` so drawing and
+    /// filtering don't need the store
+    #[rust]
+    favorite_models: HashSet<String>,
+
+    /// Indices into `models` that pass the filter, in display order
+    #[rust]
+    visible_models: Vec<usize>,
+
+    /// Moly Server status shown by the badge, kept current by `StoreAction::ServerStatusChanged`
+    #[rust]
+    server_status: ServerConnectionStatus,
+
+    /// Automatic reconnects made since launch, see `LAUNCH_RETRY_DELAYS`
+    #[rust]
+    launch_retries: usize,
+
+    /// Set once launch reconnects should stop: the server answered, the user
+    /// reconnected by hand, or the retries ran out
+    #[rust]
+    launch_retries_done: bool,
+
+    /// Fires the next automatic reconnect
+    #[rust]
+    launch_retry_timer: Timer,
 }
 
 impl Widget for ModelsApp {
@@ -89,16 +324,47 @@ impl Widget for ModelsApp {
         if Arc::strong_count(&self.task_result) == 0 {
             self.task_result = Arc::new(Mutex::new(None));
         }
+        if Arc::strong_count(&self.downloaded_files_result) == 0 {
+            self.downloaded_files_result = Arc::new(Mutex::new(None));
+        }
 
         // Initialize on first event
         if !self.initialized {
             self.initialized = true;
+            if let Some(store) = scope.data.get::<Store>() {
+                self.server_status = store.moly_client.connection_status();
+                self.favorite_models = store.preferences.favorite_models.iter().cloned().collect();
+            }
+            self.fetch_downloaded_files(scope);
+            if self.search_query.trim().is_empty() {
+                self.test_connection_and_load(cx, scope);
+            } else {
+                let query = self.search_query.clone();
+                self.handle_search(cx, scope, &query);
+            }
+        }
+
+        // Start polling for downloads restored by the Store on startup
+        let has_downloads = scope.data.get::<Store>().map_or(false, |store| !store.downloads.is_empty());
+        if has_downloads && self.download_poll_timer.is_empty() {
+            self.download_poll_timer = cx.start_interval(0.5);
+        }
+
+        // Keep animating download progress toward the polled values
+        if self.progress_next_frame.is_event(event).is_some() {
+            self.view.redraw(cx);
+        }
+
+        // Automatic reconnect on launch
+        if self.launch_retry_timer.is_event(event).is_some() {
+            self.launch_retry_timer = Timer::default();
+            ::log::info!("Reconnecting to Moly Server (attempt {} of {})", self.launch_retries, LAUNCH_RETRY_DELAYS.len());
             self.test_connection_and_load(cx, scope);
         }
 
         // Handle timer for download polling
         if self.download_poll_timer.is_event(event).is_some() {
-            if !self.active_downloads.is_empty() {
+            if has_downloads {
                 self.poll_downloads(cx, scope);
             }
         }
@@ -106,6 +372,16 @@ impl Widget for ModelsApp {
         // Check for async task results
         self.check_task_results(cx, scope);
 
+        // Moly Server status transitions, posted from the connection test thread
+        if let Event::Actions(actions) = event {
+            for action in actions.iter() {
+                if let StoreAction::ServerStatusChanged(status) = action.cast() {
+                    self.server_status = status;
+                    self.view.redraw(cx);
+                }
+            }
+        }
+
         // Handle events
         let actions = cx.capture_actions(|cx| {
             self.view.handle_event(cx, event, scope);
@@ -113,19 +389,71 @@ impl Widget for ModelsApp {
 
         // Handle refresh button click
         if self.view.button(ids!(refresh_btn)).clicked(&actions) {
+            self.stop_launch_retries(cx);
             self.test_connection_and_load(cx, scope);
         }
 
         // Handle search input changes
         if let Some(text) = self.view.text_input(ids!(search_input)).changed(&actions) {
+            self.view.button(ids!(clear_search_btn)).set_visible(cx, !text.is_empty());
             self.handle_search(cx, scope, &text);
         }
 
+        // Handle clear search button click
+        if self.view.button(ids!(clear_search_btn)).clicked(&actions) {
+            self.clear_search(cx, scope);
+        }
+
+        // Empty state shortcuts while Moly Server is unreachable
+        if self.view.button(ids!(retry_connection_btn)).clicked(&actions) {
+            self.stop_launch_retries(cx);
+            self.test_connection_and_load(cx, scope);
+        }
+        if self.view.button(ids!(configure_provider_btn)).clicked(&actions) {
+            cx.action(StoreAction::Navigate("Settings".to_string()));
+        }
+
+        // Re-sort the cached models; no new request is needed
+        if let Some(index) = self.view.drop_down(ids!(sort_dropdown)).selected(&actions) {
+            self.sort = ModelSort::from_index(index);
+            self.apply_sort_and_filter();
+            self.view.portal_list(ids!(models_list)).set_first_id_and_scroll(0, 0.0);
+            self.view.redraw(cx);
+        }
+
+        // Favorites and architecture filter chips
+        if self.view.button(ids!(favorites_chip)).clicked(&actions) {
+            self.favorites_only = !self.favorites_only;
+            self.apply_sort_and_filter();
+            self.view.portal_list(ids!(models_list)).set_first_id_and_scroll(0, 0.0);
+            self.view.redraw(cx);
+        }
+        self.handle_architecture_clicks(cx, &actions);
+
+        // Confirm or cancel removing a downloaded file
+        if self.view.button(ids!(confirm_remove_btn)).clicked(&actions) {
+            if let Some(file) = self.pending_removal.take() {
+                self.remove_downloaded_file(scope, file);
+            }
+            self.view.view(ids!(remove_confirm)).set_visible(cx, false);
+        }
+        if self.view.button(ids!(cancel_remove_btn)).clicked(&actions) {
+            self.pending_removal = None;
+            self.view.view(ids!(remove_confirm)).set_visible(cx, false);
+        }
+
         // Handle model card clicks (expand/collapse files)
         self.handle_model_card_clicks(cx, &actions);
 
+        // Handle star toggles on model cards
+        self.handle_favorite_clicks(cx, scope, &actions);
+
         // Handle download button clicks
         self.handle_download_clicks(cx, scope, &actions);
+        self.handle_download_all_clicks(cx, scope, &actions);
+
+        // Handle pause/resume clicks on active downloads
+        self.handle_pause_resume_clicks(cx, scope, &actions);
     }
 
     fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
@@ -145,16 +473,19 @@ impl Widget for ModelsApp {
         self.apply_dark_mode(cx, dark_mode);
 
         // Update connection status badge
-        self.update_status_badge(cx, scope);
+        self.update_status_badge(cx);
 
         // Update results label
         self.update_results_label(cx);
 
         // Show/hide downloads section
-        let has_downloads = !self.active_downloads.is_empty();
+        let (downloads, groups) = scope.data.get::<Store>()
+            .map(|store| (store.downloads.list(), store.downloads.groups()))
+            .unwrap_or_default();
+        let has_downloads = !downloads.is_empty();
         self.view.view(ids!(downloads_section)).set_visible(cx, has_downloads);
         if has_downloads {
-            self.update_downloads_section(cx, dark_mode);
+            self.update_downloads_section(cx, &downloads, &groups, dark_mode);
         }
 
         // Show/hide empty state vs model list
@@ -167,31 +498,56 @@ impl Widget for ModelsApp {
 
         // Update empty state message
         if !has_models || is_loading || is_error {
-            let message = match &self.models_state {
-                ModelsState::Loading => "Loading models...".to_string(),
-                ModelsState::Error(e) => format!("Error: {}", e),
-                ModelsState::Idle | ModelsState::Loaded => {
-                    if self.is_search_results && self.models.is_empty() {
-                        format!("No models found for '{}'", self.search_query)
-                    } else {
-                        "Start Moly Server to discover models".to_string()
-                    }
+            let mut message = match (&self.models_state, &self.server_status) {
+                (ModelsState::Loading, _) => "Loading models...".to_string(),
+                (ModelsState::Error(e), _) => format!("Error: {}", e),
+                (ModelsState::Idle | ModelsState::Loaded, _) if self.is_search_results && self.models.is_empty() => {
+                    format!("No models found for '{}'", self.search_query)
+                }
+                (ModelsState::Idle | ModelsState::Loaded, ServerConnectionStatus::Error(e)) => {
+                    format!("Moly Server is unavailable: {}", e)
                 }
+                (ModelsState::Idle | ModelsState::Loaded, _) => "Start Moly Server to discover models".to_string(),
             };
+            if !self.launch_retry_timer.is_empty() {
+                message.push_str(&format!("\nRetrying automatically ({} of {})...", self.launch_retries, LAUNCH_RETRY_DELAYS.len()));
+            }
             self.view.label(ids!(empty_label)).set_text(cx, &message);
             self.view.label(ids!(empty_label)).apply_over(cx, live! {
                 draw_text: { dark_mode: (dark_mode) }
             });
+
+            let server_unreachable = !is_loading && matches!(self.server_status,
+                ServerConnectionStatus::Disconnected | ServerConnectionStatus::Error(_));
+            self.view.view(ids!(server_actions)).set_visible(cx, server_unreachable);
+            if server_unreachable {
+                self.view.label(ids!(server_actions_hint)).apply_over(cx, live! {
+                    draw_text: { dark_mode: (dark_mode) }
+                });
+                for button in [self.view.button(ids!(retry_connection_btn)), self.view.button(ids!(configure_provider_btn))] {
+                    button.apply_over(cx, live! {
+                        draw_bg: { dark_mode: (dark_mode) }
+                        draw_text: { dark_mode: (dark_mode) }
+                    });
+                }
+            }
         }
 
-        // Get PortalList widget UID for step pattern
+        // Get PortalList widget UIDs for step pattern
         let models_list = self.view.portal_list(ids!(models_list));
         let models_list_uid = models_list.widget_uid();
+        let downloads_list_uid = self.view.portal_list(ids!(downloads_list)).widget_uid();
+        let architecture_list_uid = self.view.portal_list(ids!(architecture_list)).widget_uid();
+        self.view.portal_list(ids!(architecture_list)).set_visible(cx, self.architectures.len() > 1);
 
         // Draw with PortalList handling
         while let Some(widget) = self.view.draw_walk(cx, scope, walk).step() {
             if widget.widget_uid() == models_list_uid {
                 self.draw_models_list(cx, scope, widget, dark_mode);
+            } else if widget.widget_uid() == downloads_list_uid {
+                self.draw_downloads_list(cx, scope, widget, dark_mode);
+            } else if widget.widget_uid() == architecture_list_uid {
+                self.draw_architecture_list(cx, scope, widget, dark_mode);
             }
         }
 
@@ -200,6 +556,24 @@ impl Widget for ModelsApp {
 }
 
 impl ModelsApp {
+    /// Current view state, for restoring it on the next launch
+    pub fn session_state(&self) -> ModelsSessionState {
+        ModelsSessionState {
+            search_query: self.search_query.clone(),
+            first_model: self.view.portal_list(ids!(models_list)).first_id(),
+        }
+    }
+
+    /// Reopen with the saved search and scroll position. Must be called before
+    /// the first event; the position is dropped if the results have shrunk.
+    pub fn restore_session_state(&mut self, cx: &mut Cx, state: ModelsSessionState) {
+        let has_query = !state.search_query.trim().is_empty();
+        self.view.text_input(ids!(search_input)).set_text(cx, &state.search_query);
+        self.view.button(ids!(clear_search_btn)).set_visible(cx, has_query);
+        self.search_query = state.search_query;
+        self.restored_first_model = Some(state.first_model);
+    }
+
     /// Test connection and load featured models
     fn test_connection_and_load(&mut self, cx: &mut Cx, scope: &mut Scope) {
         self.models_state = ModelsState::Loading;
@@ -235,6 +609,24 @@ impl ModelsApp {
         });
     }
 
+    /// Reconnect again after the next launch delay, if any are left
+    fn schedule_launch_retry(&mut self, cx: &mut Cx) {
+        match LAUNCH_RETRY_DELAYS.get(self.launch_retries).filter(|_| !self.launch_retries_done) {
+            Some(&delay) => {
+                self.launch_retries += 1;
+                self.launch_retry_timer = cx.start_timeout(delay);
+            }
+            None => self.launch_retries_done = true,
+        }
+    }
+
+    /// Cancel any pending automatic reconnect
+    fn stop_launch_retries(&mut self, cx: &mut Cx) {
+        cx.stop_timer(self.launch_retry_timer);
+        self.launch_retry_timer = Timer::default();
+        self.launch_retries_done = true;
+    }
+
     /// Handle search input
     fn handle_search(&mut self, cx: &mut Cx, scope: &mut Scope, query: &str) {
         self.search_query = query.to_string();
@@ -272,8 +664,25 @@ impl ModelsApp {
         });
     }
 
+    /// Clear the search query and go back to featured models
+    fn clear_search(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        self.view.text_input(ids!(search_input)).set_text(cx, "");
+        self.view.button(ids!(clear_search_btn)).set_visible(cx, false);
+        self.handle_search(cx, scope, "");
+    }
+
     /// Check for async task results
-    fn check_task_results(&mut self, cx: &mut Cx, _scope: &mut Scope) {
+    fn check_task_results(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        let downloaded_files = self.downloaded_files_result.lock().ok().and_then(|mut guard| guard.take());
+        match downloaded_files {
+            Some(Ok(files)) => {
+                self.downloaded_file_ids = files.into_iter().map(|downloaded| downloaded.file.id).collect();
+                self.view.redraw(cx);
+            }
+            Some(Err(e)) => ::log::error!("Failed to get downloaded files: {}", e),
+            None => {}
+        }
+
         let result = {
             if let Ok(mut guard) = self.task_result.lock() {
                 guard.take()
@@ -285,20 +694,31 @@ impl ModelsApp {
         if let Some(task_result) = result {
             match task_result {
                 ModelsTaskResult::ConnectionResult(Err(e)) => {
+                    self.schedule_launch_retry(cx);
                     self.models_state = ModelsState::Error(e);
                     self.models.clear();
+                    self.apply_sort_and_filter();
                 }
                 ModelsTaskResult::ConnectionResult(Ok(())) => {
+                    self.launch_retries_done = true;
                     // Connection successful, will be followed by ModelsResult
                 }
                 ModelsTaskResult::ModelsResult(Ok(models)) => {
+                    self.launch_retries_done = true;
                     ::log::info!("Loaded {} models", models.len());
                     self.models = models;
                     self.models_state = ModelsState::Loaded;
+                    self.apply_sort_and_filter();
+                    if let Some(first_model) = self.restored_first_model.take() {
+                        if first_model < self.visible_models.len() {
+                            self.view.portal_list(ids!(models_list)).set_first_id_and_scroll(first_model, 0.0);
+                        }
+                    }
                 }
                 ModelsTaskResult::ModelsResult(Err(e)) => {
                     self.models_state = ModelsState::Error(e);
                     self.models.clear();
+                    self.apply_sort_and_filter();
                 }
                 ModelsTaskResult::DownloadStarted(Ok(file_id)) => {
                     ::log::info!("Download started for file: {}", file_id);
@@ -308,42 +728,85 @@ impl ModelsApp {
                 ModelsTaskResult::DownloadStarted(Err(e)) => {
                     ::log::error!("Failed to start download: {}", e);
                 }
+                ModelsTaskResult::DownloadRetried(file_id, Ok(())) => {
+                    ::log::info!("Download retried for file: {}", file_id);
+                    if self.download_poll_timer.is_empty() {
+                        self.download_poll_timer = cx.start_interval(0.5);
+                    }
+                }
+                ModelsTaskResult::DownloadRetried(file_id, Err(e)) => {
+                    ::log::error!("Failed to retry download {}: {}", file_id, e);
+                    if let Some(store) = scope.data.get::<Store>() {
+                        store.downloads.set_status(&file_id, PendingDownloadsStatus::Error);
+                    }
+                }
                 ModelsTaskResult::DownloadsUpdate(Ok(downloads)) => {
-                    self.update_downloads_state(downloads);
+                    // A download that drops out of the pending list has finished
+                    let finished = scope.data.get::<Store>().is_some_and(|store| {
+                        store.downloads.list().iter().any(|state| {
+                            !state.is_paused() && !downloads.iter().any(|d| d.file.id == state.file_id)
+                        })
+                    });
+                    self.update_downloads_state(cx, scope, downloads);
+                    if finished {
+                        self.fetch_downloaded_files(scope);
+                    }
                 }
                 ModelsTaskResult::DownloadsUpdate(Err(e)) => {
                     ::log::error!("Failed to get downloads: {}", e);
                 }
+                ModelsTaskResult::DownloadStatusChanged(Ok((file_id, status))) => {
+                    if let Some(store) = scope.data.get::<Store>() {
+                        store.downloads.set_status(&file_id, status);
+                    }
+                }
+                ModelsTaskResult::DownloadStatusChanged(Err(e)) => {
+                    ::log::error!("Failed to pause or resume download: {}", e);
+                }
+                ModelsTaskResult::FileRemoved(Ok(removed)) => {
+                    self.downloaded_file_ids.remove(&removed.id);
+                    // The same file can be listed under several models
+                    for file in self.models.iter_mut().flat_map(|model| model.files.iter_mut()) {
+                        if file.id == removed.id {
+                            file.downloaded = false;
+                        }
+                    }
+                    cx.action(Toast::success(format!("Removed {}", removed.name)));
+                }
+                ModelsTaskResult::GroupDownloadStarted(results) => {
+                    for (file_id, result) in results {
+                        if let Err(e) = result {
+                            ::log::error!("Failed to start download of {}: {}", file_id, e);
+                            if let Some(store) = scope.data.get::<Store>() {
+                                store.downloads.leave_group(&file_id);
+                            }
+                        }
+                    }
+                    if self.download_poll_timer.is_empty() {
+                        self.download_poll_timer = cx.start_interval(0.5);
+                    }
+                }
+                ModelsTaskResult::FileRemoved(Err(e)) => {
+                    ::log::error!("Failed to remove file: {}", e);
+                    cx.action(Toast::error(format!("Could not remove file: {}", e)));
+                }
             }
             self.view.redraw(cx);
         }
     }
 
     /// Update download state from pending downloads
-    fn update_downloads_state(&mut self, downloads: Vec<PendingDownload>) {
-        // Update or add downloads
-        for download in &downloads {
-            let file_id = download.file.id.clone();
-            if let Some(state) = self.active_downloads.get_mut(&file_id) {
-                state.progress = download.progress;
-                state.status = download.status.clone();
-            } else {
-                self.active_downloads.insert(file_id.clone(), DownloadState {
-                    file_id,
-                    model_name: download.model.name.clone(),
-                    file_name: download.file.name.clone(),
-                    progress: download.progress,
-                    status: download.status.clone(),
-                });
-            }
-        }
+    fn update_downloads_state(&mut self, cx: &mut Cx, scope: &mut Scope, downloads: Vec<PendingDownload>) {
+        let Some(store) = scope.data.get::<Store>() else { return };
 
-        // Remove completed downloads
-        let active_ids: Vec<_> = downloads.iter().map(|d| d.file.id.clone()).collect();
-        self.active_downloads.retain(|id, _| active_ids.contains(id));
+        // Update, add and remove completed downloads
+        for group in store.downloads.update_from_pending(&downloads) {
+            ::log::info!("All {} files of {} downloaded", group.file_ids.len(), group.model_id);
+            cx.action(Toast::success(format!("Downloaded all {} files of {}", group.file_ids.len(), group.model_name)));
+        }
 
         // Stop polling if no more downloads
-        if self.active_downloads.is_empty() {
+        if store.downloads.is_empty() {
             self.download_poll_timer = Timer::default();
         }
     }
@@ -361,6 +824,12 @@ impl ModelsApp {
             draw_text: { dark_mode: (dark_mode) }
         });
 
+        // Clear search button
+        self.view.button(ids!(clear_search_btn)).apply_over(cx, live! {
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+
         // Refresh button
         self.view.button(ids!(refresh_btn)).apply_over(cx, live! {
             draw_bg: { dark_mode: (dark_mode) }
@@ -371,19 +840,37 @@ impl ModelsApp {
         self.view.label(ids!(results_label)).apply_over(cx, live! {
             draw_text: { dark_mode: (dark_mode) }
         });
+
+        // Sort label
+        self.view.label(ids!(sort_label)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode) }
+        });
+
+        // Favorites filter chip
+        self.view.button(ids!(favorites_chip)).apply_over(cx, live! {
+            draw_bg: { dark_mode: (dark_mode), selected: (if self.favorites_only { 1.0 } else { 0.0 }) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+
+        // Remove confirmation
+        self.view.label(ids!(remove_confirm_label)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        for button in [self.view.button(ids!(confirm_remove_btn)), self.view.button(ids!(cancel_remove_btn))] {
+            button.apply_over(cx, live! {
+                draw_bg: { dark_mode: (dark_mode) }
+                draw_text: { dark_mode: (dark_mode) }
+            });
+        }
     }
 
     /// Update connection status badge
-    fn update_status_badge(&mut self, cx: &mut Cx2d, scope: &mut Scope) {
-        let (status_val, status_text) = if let Some(store) = scope.data.get::<Store>() {
-            match store.moly_client.connection_status() {
-                ServerConnectionStatus::Disconnected => (0.0, "Disconnected"),
-                ServerConnectionStatus::Connecting => (1.0, "Connecting..."),
-                ServerConnectionStatus::Connected => (2.0, "Connected"),
-                ServerConnectionStatus::Error(_) => (3.0, "Error"),
-            }
-        } else {
-            (0.0, "Disconnected")
+    fn update_status_badge(&mut self, cx: &mut Cx2d) {
+        let (status_val, status_text) = match self.server_status {
+            ServerConnectionStatus::Disconnected => (0.0, "Disconnected"),
+            ServerConnectionStatus::Connecting => (1.0, "Connecting..."),
+            ServerConnectionStatus::Connected => (2.0, "Connected"),
+            ServerConnectionStatus::Error(_) => (3.0, "Error"),
         };
 
         self.view.view(ids!(status_badge)).apply_over(cx, live! {
@@ -399,36 +886,151 @@ impl ModelsApp {
         } else {
             format!("Featured Models ({})", self.models.len())
         };
+        let label = match (&self.architecture_filter, self.favorites_only) {
+            (Some(architecture), true) => format!("{}, showing {} favorite {}", label, self.visible_models.len(), architecture),
+            (Some(architecture), false) => format!("{}, showing {} {}", label, self.visible_models.len(), architecture),
+            (None, true) => format!("{}, showing {} favorites", label, self.visible_models.len()),
+            (None, false) => label,
+        };
         self.view.label(ids!(results_label)).set_text(cx, &label);
     }
 
     /// Update downloads section with active download progress
-    fn update_downloads_section(&mut self, cx: &mut Cx2d, dark_mode: f64) {
+    fn update_downloads_section(&mut self, cx: &mut Cx2d, downloads: &[DownloadState], groups: &[DownloadGroup], dark_mode: f64) {
         // Update header
-        let download_count = self.active_downloads.len();
-        let header_text = if download_count == 1 {
-            "Downloading 1 file...".to_string()
+        let download_count = downloads.len();
+        let paused_count = downloads.iter().filter(|d| d.is_paused()).count();
+        let mut header_text = if download_count == 1 {
+            "Downloading 1 file".to_string()
         } else {
-            format!("Downloading {} files...", download_count)
+            format!("Downloading {} files", download_count)
         };
+        if paused_count > 0 {
+            header_text.push_str(&format!(" ({} paused)", paused_count));
+        }
         self.view.label(ids!(downloads_header)).set_text(cx, &header_text);
         self.view.label(ids!(downloads_header)).apply_over(cx, live! {
             draw_text: { dark_mode: (dark_mode) }
         });
 
-        // For simplicity, we just update labels with download info
-        // A more sophisticated implementation would dynamically create DownloadItem widgets
-        // For now, show summary of first download in the existing section
-        if let Some((_, state)) = self.active_downloads.iter().next() {
+        // Aggregate progress of models downloaded as a group, e.g. "Model X: 3/5 files, 62%"
+        let mut groups = groups.to_vec();
+        groups.sort_by(|a, b| a.model_name.cmp(&b.model_name));
+        let group_lines: Vec<String> = groups.iter().map(|group| {
+            format!("{}: {}/{} files, {}%",
+                group.model_name, group.finished.len(), group.file_ids.len(),
+                (group.progress(downloads) * 100.0) as u32)
+        }).collect();
+        let groups_label = self.view.label(ids!(download_groups_label));
+        groups_label.set_visible(cx, !group_lines.is_empty());
+        groups_label.set_text(cx, &group_lines.join("\n"));
+        groups_label.apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode) }
+        });
+
+        // Forget smoothing state for downloads that are gone
+        self.displayed_progress.retain(|id, _| downloads.iter().any(|d| &d.file_id == id));
+
+        // One row per download, in a stable order so rows don't jump between polls
+        // (the store keeps downloads in a map)
+        self.listed_downloads = downloads.to_vec();
+        self.listed_downloads.sort_by(|a, b| {
+            (&a.model_name, &a.file_name, &a.file_id).cmp(&(&b.model_name, &b.file_name, &b.file_id))
+        });
+
+        let height = download_count.min(MAX_VISIBLE_DOWNLOADS) as f64 * DOWNLOAD_ITEM_HEIGHT;
+        self.view.portal_list(ids!(downloads_list)).apply_over(cx, live! {
+            height: (height)
+        });
+    }
+
+    /// Draw the active downloads PortalList
+    fn draw_downloads_list(&mut self, cx: &mut Cx2d, scope: &mut Scope, widget: WidgetRef, dark_mode: f64) {
+        let binding = widget.as_portal_list();
+        let Some(mut list) = binding.borrow_mut() else { return };
+
+        list.set_item_range(cx, 0, self.listed_downloads.len());
+
+        while let Some(item_id) = list.next_visible_item(cx) {
+            let Some(state) = self.listed_downloads.get(item_id).cloned() else { continue };
+
             let status_text = match state.status {
                 PendingDownloadsStatus::Initializing => "Initializing...".to_string(),
-                PendingDownloadsStatus::Downloading => format!("{}% - {}", (state.progress * 100.0) as u32, state.file_name),
-                PendingDownloadsStatus::Paused => format!("Paused - {}", state.file_name),
-                PendingDownloadsStatus::Error => format!("Error - {}", state.file_name),
+                PendingDownloadsStatus::Downloading => {
+                    let mut text = format!("{}%", (state.progress * 100.0) as u32);
+                    if let Some(rate) = state.bytes_per_second() {
+                        text.push_str(&format!(" · {}/s", format_bytes(rate)));
+                    }
+                    if let Some(left) = state.time_left() {
+                        text.push_str(&format!(" · ~{} left", format_duration(left)));
+                    }
+                    text
+                }
+                PendingDownloadsStatus::Paused => format!("Paused at {}%", (state.progress * 100.0) as u32),
+                PendingDownloadsStatus::Error if state.can_retry() => "Error".to_string(),
+                PendingDownloadsStatus::Error => format!("Failed after {} retries", state.retries),
             };
-            // Update header with more detail
-            self.view.label(ids!(downloads_header)).set_text(cx, &status_text);
+            let progress = self.smoothed_progress(cx, &state);
+
+            let item = list.item(cx, item_id, live_id!(DownloadListItem));
+            item.apply_over(cx, live! {
+                draw_bg: { dark_mode: (dark_mode) }
+            });
+            item.label(ids!(download_name)).set_text(cx, &format!("{} - {}", state.model_name, state.file_name));
+            item.label(ids!(download_progress_text)).set_text(cx, &status_text);
+            item.label(ids!(download_name)).apply_over(cx, live! {
+                draw_text: { dark_mode: (dark_mode) }
+            });
+            item.label(ids!(download_progress_text)).apply_over(cx, live! {
+                draw_text: { dark_mode: (dark_mode) }
+            });
+            item.view(ids!(progress_bar_bg)).apply_over(cx, live! {
+                draw_bg: { dark_mode: (dark_mode) }
+            });
+            item.view(ids!(progress_bar_fill)).apply_over(cx, live! {
+                draw_bg: { progress: (progress) }
+            });
+
+            // Nothing to pause until the server has started the download
+            let pause_resume_btn = item.button(ids!(pause_resume_btn));
+            pause_resume_btn.set_visible(cx, matches!(state.status,
+                PendingDownloadsStatus::Downloading | PendingDownloadsStatus::Paused));
+            pause_resume_btn.set_text(cx, if state.is_paused() { "Resume" } else { "Pause" });
+            pause_resume_btn.apply_over(cx, live! {
+                draw_bg: { dark_mode: (dark_mode) }
+                draw_text: { dark_mode: (dark_mode) }
+            });
+
+            let retry_btn = item.button(ids!(retry_btn));
+            retry_btn.set_visible(cx, state.can_retry());
+            retry_btn.apply_over(cx, live! {
+                draw_bg: { dark_mode: (dark_mode) }
+                draw_text: { dark_mode: (dark_mode) }
+            });
+
+            item.draw_all(cx, scope);
+        }
+    }
+
+    /// Ease the drawn progress toward the latest polled value (EMA per frame).
+    /// Requests another frame until the bar has caught up.
+    fn smoothed_progress(&mut self, cx: &mut Cx2d, state: &DownloadState) -> f64 {
+        let target = state.progress.clamp(0.0, 1.0);
+        let displayed = self.displayed_progress.entry(state.file_id.clone()).or_insert(target);
+
+        if target >= 1.0 || target < *displayed {
+            // Snap when complete, or when the server reports a lower value (e.g. restarted)
+            *displayed = target;
+        } else {
+            *displayed += (target - *displayed) * PROGRESS_SMOOTHING;
+            if target - *displayed < 0.001 {
+                *displayed = target;
+            } else {
+                self.progress_next_frame = cx.new_next_frame();
+            }
         }
+
+        displayed.min(1.0)
     }
 
     /// Draw the models PortalList
@@ -436,14 +1038,14 @@ impl ModelsApp {
         let binding = widget.as_portal_list();
         let Some(mut list) = binding.borrow_mut() else { return };
 
-        list.set_item_range(cx, 0, self.models.len());
+        list.set_item_range(cx, 0, self.visible_models.len());
 
         while let Some(item_id) = list.next_visible_item(cx) {
-            if item_id >= self.models.len() {
+            let Some(&model_index) = self.visible_models.get(item_id) else {
                 continue;
-            }
+            };
 
-            let model = &self.models[item_id];
+            let model = &self.models[model_index];
             let item_widget = list.item(cx, item_id, live_id!(ModelCardItem));
 
             // Apply dark mode to card
@@ -451,6 +1053,12 @@ impl ModelsApp {
                 draw_bg: { dark_mode: (dark_mode) }
             });
 
+            // Star, filled for favorites
+            let favorite = self.favorite_models.contains(&model.id);
+            item_widget.button(ids!(favorite_btn)).apply_over(cx, live! {
+                draw_bg: { dark_mode: (dark_mode), favorite: (if favorite { 1.0 } else { 0.0 }) }
+            });
+
             // Set model name
             item_widget.label(ids!(model_name)).set_text(cx, &model.name);
             item_widget.label(ids!(model_name)).apply_over(cx, live! {
@@ -500,7 +1108,7 @@ impl ModelsApp {
                 draw_text: { dark_mode: (dark_mode) }
             });
 
-            // Show files count and download button for first file
+            // Show files count and one row per file
             let has_files = !model.files.is_empty();
             item_widget.view(ids!(files_section)).set_visible(cx, has_files);
 
@@ -512,26 +1120,112 @@ impl ModelsApp {
                     draw_text: { dark_mode: (dark_mode) }
                 });
 
-                // Check if first file is being downloaded
-                let first_file = &model.files[0];
-                let is_downloading = self.active_downloads.contains_key(&first_file.id);
-
-                if is_downloading {
-                    if let Some(download_state) = self.active_downloads.get(&first_file.id) {
-                        let progress_text = format!("{}%", (download_state.progress * 100.0) as u32);
-                        item_widget.button(ids!(download_btn)).set_text(cx, &progress_text);
+                let store = scope.data.get::<Store>();
+                let rows = model.files.iter().map(|file| {
+                    let download_state = store.and_then(|store| store.downloads.get(&file.id));
+                    let is_downloaded = file.downloaded || self.downloaded_file_ids.contains(&file.id);
+                    let action = if let Some(download_state) = download_state {
+                        if download_state.is_paused() {
+                            "Paused".to_string()
+                        } else {
+                            format!("{}%", (download_state.progress * 100.0) as u32)
+                        }
+                    } else if is_downloaded {
+                        "Remove".to_string()
+                    } else {
+                        "Download".to_string()
+                    };
+                    FileRow {
+                        name: file.name.clone(),
+                        size: file.size.parse::<u64>().map_or_else(|_| file.size.clone(), |bytes| format_bytes(bytes as f64)),
+                        quantization: file.quantization.clone(),
+                        action,
+                        downloaded: is_downloaded && download_state.is_none(),
                     }
-                } else if first_file.downloaded {
-                    item_widget.button(ids!(download_btn)).set_text(cx, "Downloaded");
-                } else {
-                    item_widget.button(ids!(download_btn)).set_text(cx, "Download");
-                }
+                }).collect();
+                item_widget.model_files_list(ids!(file_list)).set_rows(cx, rows, dark_mode);
+
+                let download_all_btn = item_widget.button(ids!(download_all_btn));
+                download_all_btn.set_visible(cx, !self.remaining_files(store, model).is_empty());
+                download_all_btn.apply_over(cx, live! {
+                    draw_bg: { dark_mode: (dark_mode) }
+                    draw_text: { dark_mode: (dark_mode) }
+                });
             }
 
             item_widget.draw_all(cx, scope);
         }
     }
 
+    /// Sort the cached models and recompute the architectures and visible rows
+    fn apply_sort_and_filter(&mut self) {
+        let sort = self.sort;
+        self.models.sort_by(|a, b| sort.compare(a, b));
+
+        let mut architectures: Vec<String> = self.models.iter()
+            .map(|model| model.architecture.clone())
+            .filter(|architecture| !architecture.is_empty())
+            .collect();
+        architectures.sort_by_key(|architecture| architecture.to_lowercase());
+        architectures.dedup();
+        self.architectures = architectures;
+
+        // A filter for an architecture no longer in the results would hide everything
+        if self.architecture_filter.as_ref().is_some_and(|filter| !self.architectures.contains(filter)) {
+            self.architecture_filter = None;
+        }
+
+        self.visible_models = self.models.iter().enumerate()
+            .filter(|(_, model)| self.architecture_filter.as_ref().map_or(true, |filter| &model.architecture == filter))
+            .filter(|(_, model)| !self.favorites_only || self.favorite_models.contains(&model.id))
+            .map(|(index, _)| index)
+            .collect();
+    }
+
+    /// Draw the architecture filter chips, "All" first
+    fn draw_architecture_list(&mut self, cx: &mut Cx2d, scope: &mut Scope, widget: WidgetRef, dark_mode: f64) {
+        let binding = widget.as_portal_list();
+        let Some(mut list) = binding.borrow_mut() else { return };
+
+        list.set_item_range(cx, 0, self.architectures.len() + 1);
+
+        while let Some(item_id) = list.next_visible_item(cx) {
+            if item_id > self.architectures.len() {
+                continue;
+            }
+            let (label, selected) = match item_id {
+                0 => ("All", self.architecture_filter.is_none()),
+                _ => {
+                    let architecture = &self.architectures[item_id - 1];
+                    (architecture.as_str(), self.architecture_filter.as_ref() == Some(architecture))
+                }
+            };
+
+            let chip = list.item(cx, item_id, live_id!(ArchitectureChip));
+            chip.set_text(cx, label);
+            chip.apply_over(cx, live! {
+                draw_bg: { dark_mode: (dark_mode), selected: (if selected { 1.0 } else { 0.0 }) }
+                draw_text: { dark_mode: (dark_mode) }
+            });
+            chip.draw_all(cx, scope);
+        }
+    }
+
+    /// Narrow the list to the clicked architecture, or show all again
+    fn handle_architecture_clicks(&mut self, cx: &mut Cx, actions: &Actions) {
+        let architecture_list = self.view.portal_list(ids!(architecture_list));
+
+        for (item_id, item) in architecture_list.items_with_actions(actions) {
+            if item.as_button().clicked(actions) {
+                self.architecture_filter = item_id.checked_sub(1)
+                    .and_then(|index| self.architectures.get(index).cloned());
+                self.apply_sort_and_filter();
+                self.view.portal_list(ids!(models_list)).set_first_id_and_scroll(0, 0.0);
+                self.view.redraw(cx);
+            }
+        }
+    }
+
     /// Handle model card clicks for expanding files section
     fn handle_model_card_clicks(&mut self, cx: &mut Cx, actions: &Actions) {
         // For now, model cards are always expanded to show files
@@ -539,25 +1233,207 @@ impl ModelsApp {
         let _ = (cx, actions);
     }
 
-    /// Handle download button clicks
+    /// Star or unstar the clicked model. Favorites are kept in `Preferences` by
+    /// model id, so they survive re-fetching the list
+    fn handle_favorite_clicks(&mut self, cx: &mut Cx, scope: &mut Scope, actions: &Actions) {
+        let models_list = self.view.portal_list(ids!(models_list));
+
+        for (item_id, item_widget) in models_list.items_with_actions(actions) {
+            if !item_widget.button(ids!(favorite_btn)).clicked(actions) {
+                continue;
+            }
+            let Some(model) = self.visible_models.get(item_id).and_then(|&index| self.models.get(index)) else {
+                continue;
+            };
+            let model_id = model.id.clone();
+            let Some(store) = scope.data.get_mut::<Store>() else { continue };
+
+            if store.preferences.toggle_favorite_model(&model_id) {
+                self.favorite_models.insert(model_id);
+            } else {
+                self.favorite_models.remove(&model_id);
+                if self.favorites_only {
+                    self.apply_sort_and_filter();
+                }
+            }
+            self.view.redraw(cx);
+        }
+    }
+
+    /// Handle download button clicks on a model's file rows
     fn handle_download_clicks(&mut self, cx: &mut Cx, scope: &mut Scope, actions: &Actions) {
         let models_list = self.view.portal_list(ids!(models_list));
 
         for (item_id, item_widget) in models_list.items_with_actions(actions) {
-            if item_widget.button(ids!(download_btn)).clicked(actions) {
-                if item_id < self.models.len() {
-                    let model = &self.models[item_id];
-                    if !model.files.is_empty() {
-                        let file = &model.files[0];
-                        if !file.downloaded && !self.active_downloads.contains_key(&file.id) {
-                            self.start_download(cx, scope, file.clone(), model.name.clone());
-                        }
-                    }
+            let Some(file_index) = item_widget.model_files_list(ids!(file_list)).clicked_file(actions) else {
+                continue;
+            };
+            let Some(model) = self.visible_models.get(item_id).and_then(|&index| self.models.get(index)) else {
+                continue;
+            };
+            let Some(file) = model.files.get(file_index) else { continue };
+
+            let is_downloading = scope.data.get::<Store>()
+                .map_or(false, |store| store.downloads.contains(&file.id));
+            if file.downloaded || self.downloaded_file_ids.contains(&file.id) {
+                self.confirm_remove(cx, file.clone());
+            } else if !is_downloading {
+                let (file, model_name) = (file.clone(), model.name.clone());
+                self.start_download(cx, scope, file, model_name);
+            }
+        }
+    }
+
+    /// Files of a multi-file model that aren't on disk yet, including ones
+    /// downloading now. Empty for single-file models.
+    fn remaining_files<'a>(&self, store: Option<&Store>, model: &'a Model) -> Vec<&'a ModelFile> {
+        if model.files.len() < 2 {
+            return Vec::new();
+        }
+        let remaining: Vec<&ModelFile> = model.files.iter()
+            .filter(|file| !file.downloaded && !self.downloaded_file_ids.contains(&file.id))
+            .collect();
+        // Nothing left to offer once every remaining file is already downloading
+        let all_downloading = store.is_some_and(|store| remaining.iter().all(|file| store.downloads.contains(&file.id)));
+        if all_downloading {
+            Vec::new()
+        } else {
+            remaining
+        }
+    }
+
+    /// Download every remaining file of the clicked model as one group
+    fn handle_download_all_clicks(&mut self, cx: &mut Cx, scope: &mut Scope, actions: &Actions) {
+        let models_list = self.view.portal_list(ids!(models_list));
+
+        for (item_id, item_widget) in models_list.items_with_actions(actions) {
+            if !item_widget.button(ids!(download_all_btn)).clicked(actions) {
+                continue;
+            }
+            let Some(model) = self.visible_models.get(item_id).and_then(|&index| self.models.get(index)) else {
+                continue;
+            };
+            let store = scope.data.get::<Store>();
+            let files: Vec<ModelFile> = self.remaining_files(store, model).into_iter().cloned().collect();
+            if files.is_empty() {
+                continue;
+            }
+            let model = model.clone();
+            self.start_group_download(cx, scope, model, files);
+        }
+    }
+
+    /// Ask before deleting a downloaded file from disk
+    fn confirm_remove(&mut self, cx: &mut Cx, file: ModelFile) {
+        let message = format!("Remove {} ({}) from disk? It will need to be downloaded again to be used.", file.name, file.size);
+        self.view.label(ids!(remove_confirm_label)).set_text(cx, &message);
+        self.view.view(ids!(remove_confirm)).set_visible(cx, true);
+        self.pending_removal = Some(file);
+        self.view.redraw(cx);
+    }
+
+    /// Delete a downloaded file through Moly Server
+    fn remove_downloaded_file(&mut self, scope: &mut Scope, file: ModelFile) {
+        let Some(store) = scope.data.get::<Store>() else { return };
+        let moly_client = store.moly_client.clone();
+        let task_result = self.task_result.clone();
+
+        ::log::info!("Removing downloaded file {}", file.id);
+
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+
+            rt.block_on(async {
+                let result = moly_client.delete_downloaded_file(&file.id).await;
+                if let Ok(mut guard) = task_result.lock() {
+                    *guard = Some(ModelsTaskResult::FileRemoved(result.map(|_| file)));
                 }
+            });
+        });
+    }
+
+    /// Handle pause/resume and retry clicks in the downloads list
+    fn handle_pause_resume_clicks(&mut self, cx: &mut Cx, scope: &mut Scope, actions: &Actions) {
+        let downloads_list = self.view.portal_list(ids!(downloads_list));
+
+        for (item_id, item_widget) in downloads_list.items_with_actions(actions) {
+            let Some(state) = self.listed_downloads.get(item_id).cloned() else { continue };
+            if item_widget.button(ids!(pause_resume_btn)).clicked(actions) {
+                self.toggle_download_paused(cx, scope, state);
+            } else if item_widget.button(ids!(retry_btn)).clicked(actions) {
+                self.retry_download(cx, scope, state);
             }
         }
     }
 
+    /// Request a failed download again
+    fn retry_download(&mut self, cx: &mut Cx, scope: &mut Scope, state: DownloadState) {
+        let Some(store) = scope.data.get::<Store>() else { return };
+        if !store.downloads.retry(&state.file_id) {
+            return;
+        }
+        let moly_client = store.moly_client.clone();
+        let task_result = self.task_result.clone();
+        let file_id = state.file_id;
+
+        ::log::info!("Retrying download {} (attempt {})", file_id, state.retries + 1);
+        self.view.redraw(cx);
+
+        // Moly Server has no endpoint to clear a failed download; requesting the file again restarts it
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+
+            rt.block_on(async {
+                let result = moly_client.download_file(&file_id).await;
+                if let Ok(mut guard) = task_result.lock() {
+                    *guard = Some(ModelsTaskResult::DownloadRetried(file_id, result));
+                }
+            });
+        });
+    }
+
+    /// Pause a running download or resume a paused one
+    fn toggle_download_paused(&mut self, cx: &mut Cx, scope: &mut Scope, state: DownloadState) {
+        let Some(store) = scope.data.get::<Store>() else { return };
+        let moly_client = store.moly_client.clone();
+        let task_result = self.task_result.clone();
+        let file_id = state.file_id;
+        let resume = state.is_paused();
+
+        ::log::info!("{} download {}", if resume { "Resuming" } else { "Pausing" }, file_id);
+
+        // Resumed downloads need polling again
+        if resume && self.download_poll_timer.is_empty() {
+            self.download_poll_timer = cx.start_interval(0.5);
+        }
+
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+
+            rt.block_on(async {
+                let (result, status) = if resume {
+                    (moly_client.resume_download(&file_id).await, PendingDownloadsStatus::Downloading)
+                } else {
+                    (moly_client.pause_download(&file_id).await, PendingDownloadsStatus::Paused)
+                };
+                if let Ok(mut guard) = task_result.lock() {
+                    *guard = Some(ModelsTaskResult::DownloadStatusChanged(
+                        result.map(|_| (file_id, status))
+                    ));
+                }
+            });
+        });
+    }
+
     /// Start downloading a file
     fn start_download(&mut self, cx: &mut Cx, scope: &mut Scope, file: ModelFile, model_name: String) {
         let Some(store) = scope.data.get::<Store>() else { return };
@@ -566,13 +1442,7 @@ impl ModelsApp {
         let file_id = file.id.clone();
 
         // Add to active downloads immediately with initializing status
-        self.active_downloads.insert(file_id.clone(), DownloadState {
-            file_id: file_id.clone(),
-            model_name,
-            file_name: file.name.clone(),
-            progress: 0.0,
-            status: PendingDownloadsStatus::Initializing,
-        });
+        store.downloads.insert(DownloadState::initializing(file_id.clone(), model_name, file.name.clone()));
 
         self.view.redraw(cx);
 
@@ -594,6 +1464,67 @@ impl ModelsApp {
         });
     }
 
+    /// Start downloading several files of a model, tracked as one group.
+    /// Files already downloading join the group without a new request.
+    fn start_group_download(&mut self, cx: &mut Cx, scope: &mut Scope, model: Model, files: Vec<ModelFile>) {
+        let Some(store) = scope.data.get::<Store>() else { return };
+        let moly_client = store.moly_client.clone();
+        let task_result = self.task_result.clone();
+
+        ::log::info!("Downloading {} files of {} as a group", files.len(), model.id);
+        store.downloads.start_group(model.id.clone(), model.name.clone(), files.iter().map(|file| file.id.clone()).collect());
+
+        let mut to_start = Vec::new();
+        for file in files {
+            if !store.downloads.contains(&file.id) {
+                store.downloads.insert(DownloadState::initializing(file.id.clone(), model.name.clone(), file.name.clone()));
+                to_start.push(file.id);
+            }
+        }
+
+        self.view.redraw(cx);
+
+        // One thread for the whole group, so the results arrive together
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+
+            rt.block_on(async {
+                let mut results = Vec::new();
+                for file_id in to_start {
+                    let result = moly_client.download_file(&file_id).await.map_err(|e| e.to_string());
+                    results.push((file_id, result));
+                }
+                if let Ok(mut guard) = task_result.lock() {
+                    *guard = Some(ModelsTaskResult::GroupDownloadStarted(results));
+                }
+            });
+        });
+    }
+
+    /// Fetch the ids of files already on disk, for badging them in the results
+    fn fetch_downloaded_files(&mut self, scope: &mut Scope) {
+        let Some(store) = scope.data.get::<Store>() else { return };
+        let moly_client = store.moly_client.clone();
+        let downloaded_files_result = self.downloaded_files_result.clone();
+
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+
+            rt.block_on(async {
+                let result = moly_client.get_downloaded_files().await;
+                if let Ok(mut guard) = downloaded_files_result.lock() {
+                    *guard = Some(result);
+                }
+            });
+        });
+    }
+
     /// Poll for download progress updates
     fn poll_downloads(&mut self, _cx: &mut Cx, scope: &mut Scope) {
         let Some(store) = scope.data.get::<Store>() else { return };
@@ -626,6 +1557,52 @@ impl ModelsApp {
     }
 }
 
+/// Size of a model for sorting, from labels like "7B", "1.5B" or "350M".
+/// Labels without a recognized number sort as the smallest.
+fn parse_model_size(size: &str) -> f64 {
+    let size = size.trim();
+    let number_end = size.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(size.len());
+    let Ok(number) = size[..number_end].parse::<f64>() else {
+        return 0.0;
+    };
+    let multiplier = match size[number_end..].trim_start().chars().next().map(|c| c.to_ascii_uppercase()) {
+        Some('K') => 1e3,
+        Some('M') => 1e6,
+        Some('B') | Some('G') => 1e9,
+        Some('T') => 1e12,
+        _ => 1.0,
+    };
+    number * multiplier
+}
+
+/// Byte count with a binary-scaled unit, e.g. "12.3 MB"
+fn format_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", value as u64, UNITS[unit])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Rough remaining time, e.g. "45s", "2m" or "1h 5m"
+fn format_duration(duration: std::time::Duration) -> String {
+    let secs = duration.as_secs();
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m", secs.div_ceil(60))
+    } else {
+        format!("{}h {}m", secs / 3600, (secs % 3600) / 60)
+    }
+}
+
 /// Format large numbers with K/M suffix
 fn format_count(count: u32) -> String {
     if count >= 1_000_000 {
@@ -161,6 +161,8 @@ live_design! {
         draw_bg: {
             // status: 0=not_connected (gray), 1=connecting (yellow), 2=connected (green), 3=error (red)
             instance status: 0.0
+            // 0..1, fades the dot in and out while connecting
+            instance pulse: 0.0
             instance dark_mode: 0.0
 
             fn pixel(self) -> vec4 {
@@ -182,7 +184,7 @@ live_design! {
                     step(1.5, self.status)
                 );
 
-                sdf.fill(color);
+                sdf.fill(vec4(color.rgb, color.a * mix(1.0, 0.35, self.pulse)));
                 return sdf.result;
             }
         }
@@ -235,6 +237,35 @@ live_design! {
         provider_enabled = <EnableToggle> {}
     }
 
+    // Small square icon button (e.g. "+" and "×")
+    SmallIconButton = <Button> {
+        width: 28, height: 28
+        padding: 0
+        draw_bg: {
+            instance hover: 0.0
+            instance pressed: 0.0
+            instance radius: 4.0
+            instance dark_mode: 0.0
+
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                let sz = self.rect_size - 2.0;
+                let hover_color = mix(#e5e7eb, #374151, self.dark_mode);
+                let color = mix(vec4(0.0), hover_color, self.hover);
+                sdf.box(1.0, 1.0, sz.x, sz.y, self.radius);
+                sdf.fill(color);
+                return sdf.result;
+            }
+        }
+        draw_text: {
+            instance dark_mode: 0.0
+            fn get_color(self) -> vec4 {
+                return mix(#374151, #e2e8f0, self.dark_mode);
+            }
+            text_style: <THEME_FONT_BOLD>{ font_size: 14.0 }
+        }
+    }
+
     // Save button
     SaveButton = <Button> {
         width: Fit, height: 40
@@ -362,6 +393,14 @@ live_design! {
 
                 <View> { width: Fill } // Spacer
 
+                // Test every enabled provider with its saved settings
+                test_all_button = <TestButton> {
+                    height: 28
+                    margin: {right: 8}
+                    padding: {left: 10, right: 10, top: 4, bottom: 4}
+                    text: "Test All"
+                }
+
                 add_provider_button = <Button> {
                     width: 28, height: 28
                     padding: 0
@@ -399,6 +438,362 @@ live_design! {
 
                 ProviderListItem = <ProviderItem> {}
             }
+
+            // Favorite prompts pinned to the chat quick-access bar
+            favorites_section = <View> {
+                width: Fill, height: Fit
+                flow: Down
+                spacing: 8
+                padding: {left: 16, right: 16, top: 12, bottom: 16}
+
+                favorites_header = <Label> {
+                    text: "Favorite Prompts"
+                    draw_text: {
+                        instance dark_mode: 0.0
+                        fn get_color(self) -> vec4 {
+                            return mix(#374151, #e2e8f0, self.dark_mode);
+                        }
+                        text_style: <THEME_FONT_BOLD>{ font_size: 13.0 }
+                    }
+                }
+
+                favorites_list = <PortalList> {
+                    width: Fill, height: 120
+                    drag_scrolling: false
+
+                    FavoritePromptItem = <View> {
+                        width: Fill, height: Fit
+                        padding: {top: 4, bottom: 4}
+                        flow: Right
+                        align: {y: 0.5}
+                        spacing: 8
+
+                        prompt_text = <Label> {
+                            width: Fill
+                            draw_text: {
+                                instance dark_mode: 0.0
+                                fn get_color(self) -> vec4 {
+                                    return mix(#374151, #e2e8f0, self.dark_mode);
+                                }
+                                text_style: <THEME_FONT_REGULAR>{ font_size: 11.0 }
+                                wrap: Ellipsis
+                            }
+                        }
+
+                        remove_favorite_button = <SmallIconButton> {
+                            width: 24, height: 24
+                            text: "×"
+                        }
+                    }
+                }
+
+                <View> {
+                    width: Fill, height: Fit
+                    flow: Right
+                    align: {y: 0.5}
+                    spacing: 8
+
+                    new_favorite_input = <SettingsTextInput> {
+                        height: 36
+                        padding: {left: 10, right: 10, top: 8, bottom: 8}
+                        empty_text: "Add a favorite prompt..."
+                    }
+
+                    add_favorite_button = <SmallIconButton> {
+                        text: "+"
+                    }
+                }
+            }
+
+            // App-wide connection options
+            general_section = <View> {
+                width: Fill, height: Fit
+                flow: Down
+                spacing: 8
+                padding: {left: 16, right: 16, bottom: 16}
+
+                general_header = <Label> {
+                    text: "General"
+                    draw_text: {
+                        instance dark_mode: 0.0
+                        fn get_color(self) -> vec4 {
+                            return mix(#374151, #e2e8f0, self.dark_mode);
+                        }
+                        text_style: <THEME_FONT_BOLD>{ font_size: 13.0 }
+                    }
+                }
+
+                <View> {
+                    width: Fill, height: Fit
+                    flow: Right
+                    align: {y: 0.5}
+                    spacing: 8
+
+                    retest_on_focus_toggle = <EnableToggle> {}
+                    retest_on_focus_label = <SettingsHint> {
+                        width: Fill
+                        text: "Re-test active provider when Moly regains focus"
+                        draw_text: { wrap: Word }
+                    }
+                }
+
+                // Guard against accidentally sending very large contexts
+                <View> {
+                    width: Fill, height: Fit
+                    flow: Right
+                    align: {y: 0.5}
+                    spacing: 8
+
+                    confirm_large_sends_toggle = <EnableToggle> {}
+                    confirm_large_sends_label = <SettingsHint> {
+                        width: Fit
+                        text: "Ask before sending more than"
+                    }
+                    send_confirmation_tokens_input = <SettingsTextInput> {
+                        width: 90, height: 36
+                        padding: {left: 10, right: 10, top: 8, bottom: 8}
+                        empty_text: "8000"
+                    }
+                    send_confirmation_tokens_label = <SettingsHint> {
+                        width: Fill
+                        text: "estimated tokens"
+                    }
+                }
+
+                // Persona applied to new chats
+                system_prompt_label = <SettingsLabel> { text: "System prompt for new chats" }
+                system_prompt_input = <SettingsTextInput> {
+                    height: Fit
+                    empty_text: "e.g. You are a concise assistant. Answer in plain English."
+                    draw_text: { wrap: Word }
+                }
+                system_prompt_hint = <SettingsHint> {
+                    width: Fill
+                    text: "Added as the first message of each new chat. Existing chats keep the prompt they started with."
+                    draw_text: { wrap: Word }
+                }
+
+                // Shown in place of the messages while a chat is empty
+                empty_chat_greeting_label = <SettingsLabel> { text: "Empty chat greeting" }
+                empty_chat_greeting_input = <SettingsTextInput> {
+                    empty_text: "How can I help you today?"
+                }
+                suggested_prompts_label = <SettingsLabel> { text: "Suggested prompts" }
+                suggested_prompts_input = <SettingsTextInput> {
+                    height: Fit
+                    empty_text: "One prompt per line"
+                    draw_text: { wrap: Word }
+                }
+                suggested_prompts_hint = <SettingsHint> {
+                    width: Fill
+                    text: "Offered under the greeting in empty chats, one per line. Clicking one sends it."
+                    draw_text: { wrap: Word }
+                }
+
+                // Used for the "Context nearly full" warning when a provider doesn't report a limit
+                <View> {
+                    width: Fill, height: Fit
+                    flow: Right
+                    align: {y: 0.5}
+                    spacing: 8
+
+                    default_context_window_input = <SettingsTextInput> {
+                        width: 90, height: 36
+                        padding: {left: 10, right: 10, top: 8, bottom: 8}
+                        empty_text: "8192"
+                    }
+                    default_context_window_label = <SettingsHint> {
+                        width: Fill
+                        text: "tokens of context for models whose provider doesn't report a limit"
+                    }
+                }
+
+                export_env_button = <TestButton> {
+                    text: "Export providers as .env"
+                }
+
+                // Confirmation before exposing secrets
+                export_env_confirm = <View> {
+                    width: Fill, height: Fit
+                    visible: false
+                    flow: Down
+                    spacing: 8
+
+                    export_env_warning = <Label> {
+                        width: Fill
+                        text: "The export contains your API keys in plain text. Only share or store it somewhere private."
+                        draw_text: {
+                            wrap: Word
+                            instance dark_mode: 0.0
+                            fn get_color(self) -> vec4 {
+                                return mix(#dc2626, #f87171, self.dark_mode);
+                            }
+                            text_style: <THEME_FONT_REGULAR>{ font_size: 10.0 }
+                        }
+                    }
+
+                    <View> {
+                        width: Fill, height: Fit
+                        flow: Right
+                        spacing: 8
+
+                        copy_env_button = <TestButton> { text: "Copy" }
+                        save_env_button = <TestButton> { text: "Save to file" }
+                        cancel_env_button = <TestButton> { text: "Cancel" }
+                    }
+                }
+
+                export_env_status = <SettingsHint> {
+                    width: Fill
+                    text: ""
+                    draw_text: { wrap: Word }
+                }
+
+                // Backup or migration of every saved chat as one JSON file
+                <View> {
+                    width: Fill, height: Fit
+                    flow: Right
+                    align: {y: 0.5}
+                    spacing: 8
+
+                    chats_file_input = <SettingsTextInput> {
+                        height: 36
+                        padding: {left: 10, right: 10, top: 8, bottom: 8}
+                        empty_text: "File path (default: chats-export.json in the data folder)"
+                    }
+                    export_chats_button = <TestButton> { text: "Export all chats" }
+                    import_chats_button = <TestButton> { text: "Import chats" }
+                }
+
+                chats_transfer_status = <SettingsHint> {
+                    width: Fill
+                    text: ""
+                    draw_text: { wrap: Word }
+                }
+
+                // Retention applied at startup; blank fields are off
+                <View> {
+                    width: Fill, height: Fit
+                    flow: Right
+                    align: {y: 0.5}
+                    spacing: 8
+
+                    retention_days_input = <SettingsTextInput> {
+                        width: 70, height: 36
+                        padding: {left: 10, right: 10, top: 8, bottom: 8}
+                        empty_text: "off"
+                    }
+                    retention_days_label = <SettingsHint> {
+                        text: "days without opening before a chat is deleted at startup"
+                    }
+                }
+                <View> {
+                    width: Fill, height: Fit
+                    flow: Right
+                    align: {y: 0.5}
+                    spacing: 8
+
+                    retention_count_input = <SettingsTextInput> {
+                        width: 70, height: 36
+                        padding: {left: 10, right: 10, top: 8, bottom: 8}
+                        empty_text: "off"
+                    }
+                    retention_count_label = <SettingsHint> {
+                        width: Fill
+                        text: "chats kept at most, the most recently opened. Chats pinned with the dot in the history list are never deleted"
+                    }
+                }
+
+                // Deleting every saved chat needs the confirmation word typed out
+                clear_history_button = <TestButton> {
+                    text: "Clear all history"
+                }
+
+                clear_history_confirm = <View> {
+                    width: Fill, height: Fit
+                    visible: false
+                    flow: Down
+                    spacing: 8
+
+                    clear_history_warning = <Label> {
+                        width: Fill
+                        text: "This permanently deletes every saved chat. Type DELETE to confirm."
+                        draw_text: {
+                            wrap: Word
+                            instance dark_mode: 0.0
+                            fn get_color(self) -> vec4 {
+                                return mix(#dc2626, #f87171, self.dark_mode);
+                            }
+                            text_style: <THEME_FONT_REGULAR>{ font_size: 10.0 }
+                        }
+                    }
+
+                    <View> {
+                        width: Fill, height: Fit
+                        flow: Right
+                        align: {y: 0.5}
+                        spacing: 8
+
+                        clear_history_input = <SettingsTextInput> {
+                            width: 160, height: 36
+                            padding: {left: 10, right: 10, top: 8, bottom: 8}
+                            empty_text: "DELETE"
+                        }
+                        confirm_clear_history_button = <TestButton> { text: "Delete all chats" }
+                        cancel_clear_history_button = <TestButton> { text: "Cancel" }
+                    }
+                }
+
+                clear_history_status = <SettingsHint> {
+                    width: Fill
+                    text: ""
+                    draw_text: { wrap: Word }
+                }
+
+                // Proxy for all outbound requests
+                <View> {
+                    width: Fill, height: Fit
+                    flow: Right
+                    align: {y: 0.5}
+                    spacing: 8
+
+                    proxy_enabled_toggle = <EnableToggle> {}
+                    proxy_enabled_label = <SettingsHint> {
+                        width: Fill
+                        text: "Route chats, model listing, connection tests and downloads through an HTTP/HTTPS or SOCKS5 proxy"
+                        draw_text: { wrap: Word }
+                    }
+                }
+
+                proxy_url_input = <SettingsTextInput> {
+                    empty_text: "http://proxy.example.com:8080"
+                }
+                proxy_username_input = <SettingsTextInput> {
+                    empty_text: "Username (optional)"
+                }
+                proxy_password_input = <SettingsTextInput> {
+                    is_password: true
+                    empty_text: "Password (optional)"
+                }
+                no_proxy_input = <SettingsTextInput> {
+                    empty_text: "localhost,127.0.0.1,::1"
+                }
+                no_proxy_hint = <SettingsHint> {
+                    width: Fill
+                    text: "Comma-separated hosts that bypass the proxy (e.g. local Ollama)"
+                    draw_text: { wrap: Word }
+                }
+
+                save_proxy_button = <TestButton> {
+                    text: "Save proxy"
+                }
+
+                proxy_status = <SettingsHint> {
+                    width: Fill
+                    text: ""
+                    draw_text: { wrap: Word }
+                }
+            }
         }
 
         // Divider
@@ -483,23 +878,140 @@ live_design! {
                 spacing: 6
 
                 <SettingsLabel> { text: "API Key" }
-                api_key_input = <SettingsTextInput> {
-                    is_password: true
-                    empty_text: "sk-..."
+                <View> {
+                    width: Fill, height: Fit
+                    flow: Right
+                    align: {y: 0.5}
+                    spacing: 8
+
+                    api_key_input = <SettingsTextInput> {
+                        is_password: true
+                        empty_text: "sk-..."
+                    }
+                    // Show/hide the key (display only, storage is unaffected)
+                    reveal_key_button = <SmallIconButton> {
+                        width: Fit
+                        padding: {left: 8, right: 8}
+                        text: "Show"
+                        draw_text: {
+                            text_style: <THEME_FONT_REGULAR>{ font_size: 10.0 }
+                        }
+                    }
                 }
                 <SettingsHint> { text: "Your API key (stored locally)" }
             }
 
+            // Advanced section
+            advanced_section = <View> {
+                width: Fill, height: Fit
+                flow: Down
+                spacing: 6
+
+                <SettingsLabel> { text: "Advanced" }
+
+                <View> {
+                    width: Fill, height: Fit
+                    flow: Right
+                    align: {y: 0.5}
+                    spacing: 12
+
+                    allow_invalid_certs_toggle = <EnableToggle> {}
+                    allow_invalid_certs_label = <SettingsHint> {
+                        text: "Allow invalid TLS certificates (insecure, for self-signed local servers)"
+                    }
+                }
+
+                invalid_certs_warning = <Label> {
+                    width: Fill
+                    visible: false
+                    text: "Warning: certificate verification is disabled for this provider. Only use this with servers you control."
+                    draw_text: {
+                        wrap: Word
+                        instance dark_mode: 0.0
+                        fn get_color(self) -> vec4 {
+                            return mix(#dc2626, #f87171, self.dark_mode);
+                        }
+                        text_style: <THEME_FONT_REGULAR>{ font_size: 10.0 }
+                    }
+                }
+
+                chat_endpoint_input = <SettingsTextInput> {
+                    empty_text: "/chat/completions"
+                }
+                chat_endpoint_hint = <SettingsHint> {
+                    width: Fill
+                    text: "Chat completions URL, or a path relative to the API host. Only chat requests use it; models are still listed from the API host. Leave empty for the standard path."
+                    draw_text: { wrap: Word }
+                }
+
+                models_path_input = <SettingsTextInput> {
+                    empty_text: "/models"
+                }
+                models_path_hint = <SettingsHint> {
+                    width: Fill
+                    text: "Models path used by the connection test, relative to the API host or a full URL. Leave empty to try the standard paths."
+                    draw_text: { wrap: Word }
+                }
+
+                timeout_input = <SettingsTextInput> {
+                    width: 120
+                    empty_text: "10"
+                }
+                timeout_hint = <SettingsHint> {
+                    width: Fill
+                    text: "Connection test timeout in seconds. Leave empty for the default."
+                    draw_text: { wrap: Word }
+                }
+
+                // Extra headers sent with every request (e.g. HTTP-Referer, X-Title)
+                custom_headers_label = <SettingsHint> { text: "Custom headers" }
+
+                headers_list = <PortalList> {
+                    width: Fill, height: 100
+                    drag_scrolling: false
+
+                    HeaderItem = <View> {
+                        width: Fill, height: Fit
+                        padding: {top: 2, bottom: 2}
+                        flow: Right
+                        align: {y: 0.5}
+                        spacing: 8
+
+                        header_name_input = <SettingsTextInput> {
+                            width: 180, height: 36
+                            empty_text: "Header"
+                        }
+                        header_value_input = <SettingsTextInput> {
+                            height: 36
+                            empty_text: "Value"
+                        }
+                        remove_header_button = <SmallIconButton> {
+                            width: 24, height: 24
+                            text: "×"
+                        }
+                    }
+                }
+
+                add_header_button = <TestButton> {
+                    text: "Add header"
+                }
+            }
+
             // Actions
             actions = <View> {
                 width: Fill, height: Fit
                 flow: Right
                 spacing: 12
                 margin: {top: 12}
+                align: {y: 0.5}
 
                 save_button = <SaveButton> {}
                 test_button = <TestButton> {}
 
+                // Opt-in: also send a sample chat completion (consumes a token)
+                deep_test_toggle = <EnableToggle> {}
+                deep_test_label = <SettingsHint> { text: "Also test chat (one token from the cheapest model)" }
+
                 <View> { width: Fill } // Spacer
 
                 delete_provider_button = <Button> {
@@ -539,15 +1051,32 @@ live_design! {
                 }
             }
 
-            // Status message
-            status_message = <Label> {
-                text: ""
-                draw_text: {
-                    instance dark_mode: 0.0
-                    fn get_color(self) -> vec4 {
-                        return mix(#059669, #10b981, self.dark_mode);
+            // Status message, with a note and refresh button when showing a cached test result
+            <View> {
+                width: Fill, height: Fit
+                flow: Right
+                align: {y: 0.5}
+                spacing: 8
+
+                status_message = <Label> {
+                    text: ""
+                    draw_text: {
+                        instance dark_mode: 0.0
+                        fn get_color(self) -> vec4 {
+                            return mix(#059669, #10b981, self.dark_mode);
+                        }
+                        text_style: <THEME_FONT_REGULAR>{ font_size: 11.0 }
                     }
-                    text_style: <THEME_FONT_REGULAR>{ font_size: 11.0 }
+                }
+
+                cached_note = <SettingsHint> {
+                    visible: false
+                    text: "(cached)"
+                }
+
+                refresh_models_button = <SmallIconButton> {
+                    visible: false
+                    text: "↻"
                 }
             }
 
@@ -593,6 +1122,33 @@ live_design! {
                     select_all_toggle = <EnableToggle> {}
                 }
 
+                // Narrows the list by name, e.g. a quantization or family; the
+                // buttons apply to the models that match
+                model_filter_row = <View> {
+                    width: Fill, height: Fit
+                    flow: Right
+                    align: {y: 0.5}
+                    spacing: 8
+
+                    model_filter_input = <SettingsTextInput> {
+                        height: 36
+                        padding: {left: 12, right: 12, top: 8, bottom: 8}
+                        empty_text: "Filter models, e.g. q4 or llama"
+                    }
+                    enable_matching_button = <TestButton> {
+                        visible: false
+                        height: 36
+                        padding: {left: 12, right: 12, top: 8, bottom: 8}
+                        text: "Enable matching"
+                    }
+                    disable_matching_button = <TestButton> {
+                        visible: false
+                        height: 36
+                        padding: {left: 12, right: 12, top: 8, bottom: 8}
+                        text: "Disable matching"
+                    }
+                }
+
                 models_scroll = <View> {
                     width: Fill, height: 200
                     flow: Down
@@ -635,9 +1191,50 @@ live_design! {
                                     text_style: <THEME_FONT_REGULAR>{ font_size: 11.0 }
                                 }
                             }
+
+                            // Opens the alias row below the list for this model
+                            alias_model_button = <SmallIconButton> {
+                                width: Fit, height: 24
+                                padding: {left: 6, right: 6}
+                                text: "Alias"
+                                draw_text: {
+                                    text_style: <THEME_FONT_REGULAR>{ font_size: 10.0 }
+                                }
+                            }
+
+                            // Marks the model picked when this provider is activated
+                            default_model_button = <SmallIconButton> {
+                                width: Fit, height: 24
+                                padding: {left: 6, right: 6}
+                                text: "Set as default"
+                                draw_text: {
+                                    text_style: <THEME_FONT_REGULAR>{ font_size: 10.0 }
+                                }
+                            }
                         }
                     }
                 }
+
+                // Name shown for a model in the chat; blank to use its id
+                model_alias_row = <View> {
+                    visible: false
+                    width: Fill, height: Fit
+                    flow: Right
+                    align: {y: 0.5}
+                    spacing: 8
+
+                    model_alias_label = <SettingsHint> { text: "" }
+                    model_alias_input = <SettingsTextInput> {
+                        height: 36
+                        padding: {left: 12, right: 12, top: 8, bottom: 8}
+                        empty_text: "Friendly name, blank to use the model id"
+                    }
+                    save_model_alias_button = <TestButton> {
+                        height: 36
+                        padding: {left: 12, right: 12, top: 8, bottom: 8}
+                        text: "Save alias"
+                    }
+                }
             }
 
             // Spacer
@@ -771,6 +1368,20 @@ live_design! {
                         }
                     }
 
+                    // Why the provider couldn't be added
+                    modal_error = <Label> {
+                        width: Fill
+                        visible: false
+                        draw_text: {
+                            wrap: Word
+                            instance dark_mode: 0.0
+                            fn get_color(self) -> vec4 {
+                                return mix(#dc2626, #f87171, self.dark_mode);
+                            }
+                            text_style: <THEME_FONT_REGULAR>{ font_size: 11.0 }
+                        }
+                    }
+
                     // Modal actions
                     modal_actions = <View> {
                         width: Fill, height: Fit
@@ -3,23 +3,62 @@
 pub mod design;
 
 use makepad_widgets::*;
-use moly_data::{Store, ProviderId, ProviderConnectionStatus};
-use std::collections::HashMap;
+use moly_data::{cheapest_test_model, ChatRetention, Chats, Store, ProviderId, ProviderConnectionStatus, ProviderPreferences, ProviderType, ProvidersManager, ProxySettings, normalize_provider_url, ollama_tags_url};
+use moly_data::shortcuts::SHORTCUT_CLOSE_DIALOG;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
-use std::path::Path;
-use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+
+/// How long a connection test result is reused when re-selecting a provider
+const TEST_CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Word to type before all chat history is deleted
+const CLEAR_HISTORY_CONFIRMATION: &str = "DELETE";
 
 /// Result from connection test stored in shared state
 #[derive(Clone, Debug)]
 struct ConnectionTestResult {
     provider_id: String,
+    /// URL and key the test was run with, used to invalidate the cache
+    url: String,
+    api_key: String,
     status: ProviderConnectionStatus,
     model_count: Option<usize>,
     models: Vec<String>,
+    /// Context windows reported by the models listing, by model name
+    context_windows: HashMap<String, usize>,
+    /// Result of the optional chat completion check (None if not requested)
+    chat_check: Option<Result<(), String>>,
+}
+
+/// Settings screen state restored on the next launch
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SettingsSessionState {
+    /// Provider that was selected
+    pub selected_provider_id: Option<ProviderId>,
+}
+
+/// Last successful connection test for a provider
+#[derive(Clone, Debug)]
+struct CachedTestResult {
+    url: String,
+    api_key: String,
+    model_count: Option<usize>,
+    models: Vec<String>,
+    tested_at: Instant,
+}
+
+impl CachedTestResult {
+    /// Whether the cache still applies to the provider's current URL and key
+    fn is_valid_for(&self, url: &str, api_key: &str) -> bool {
+        self.url == url && self.api_key == api_key && self.tested_at.elapsed() < TEST_CACHE_TTL
+    }
 }
 
-/// Shared state for async connection testing
-type ConnectionTestState = Arc<Mutex<Option<ConnectionTestResult>>>;
+/// Shared queue of finished connection tests (several may run at once)
+type ConnectionTestState = Arc<Mutex<Vec<ConnectionTestResult>>>;
 
 /// Response from OpenAI-compatible /models endpoint
 #[derive(Deserialize)]
@@ -27,10 +66,24 @@ struct ModelsResponse {
     data: Vec<ModelInfo>,
 }
 
+/// Response from Ollama's native /api/tags endpoint
+#[derive(Deserialize)]
+struct OllamaTagsResponse {
+    models: Vec<OllamaModel>,
+}
+
+#[derive(Deserialize)]
+struct OllamaModel {
+    name: String,
+}
+
 #[derive(Deserialize)]
 #[allow(dead_code)]
 struct ModelInfo {
     id: String,
+    /// Context window, reported by some providers (e.g. OpenRouter, Groq)
+    #[serde(default, alias = "context_window")]
+    context_length: Option<usize>,
 }
 
 #[derive(Live, LiveHook, Widget)]
@@ -49,9 +102,9 @@ pub struct SettingsApp {
     #[rust]
     connection_test_state: ConnectionTestState,
 
-    /// Whether a connection test is currently in progress
+    /// Whether the connection test should also send a sample chat completion
     #[rust]
-    connection_test_in_progress: bool,
+    deep_test_enabled: bool,
 
     /// Current connection status for selected provider
     #[rust]
@@ -65,6 +118,14 @@ pub struct SettingsApp {
     #[rust]
     fetched_models: Vec<(String, bool)>,
 
+    /// Text the models list is narrowed by; hidden models keep their state
+    #[rust]
+    model_filter: String,
+
+    /// Model whose alias is being edited
+    #[rust]
+    alias_model: Option<String>,
+
     /// Whether the Add Provider modal is visible
     #[rust]
     modal_visible: bool,
@@ -73,23 +134,72 @@ pub struct SettingsApp {
     #[rust]
     provider_ids: Vec<String>,
 
-    /// Connection status per provider (persists after testing)
+    /// Provider being dragged to a new position, as (index, drop index)
+    #[rust]
+    provider_drag: Option<(usize, usize)>,
+
+    /// Providers with a connection test in flight. Settled statuses are
+    /// read from each provider's persisted `last_connection_status`.
+    #[rust]
+    testing_providers: HashSet<String>,
+
+    /// Drives the pulsing status dot while a test is in flight
+    #[rust]
+    status_pulse_frame: NextFrame,
+
+    /// Last successful connection test per provider, so re-selecting it skips the request
+    #[rust]
+    test_cache: HashMap<String, CachedTestResult>,
+
+    /// Custom headers being edited for the selected provider (saved with the provider)
+    #[rust]
+    custom_headers: Vec<(String, String)>,
+
+    /// Whether the API key is shown in plain text (reset when switching providers)
     #[rust]
-    provider_statuses: HashMap<String, ProviderConnectionStatus>,
+    api_key_revealed: bool,
+
+    /// Provider to select on first use instead of the default
+    #[rust]
+    restored_provider_id: Option<ProviderId>,
 }
 
 impl Widget for SettingsApp {
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
         // Initialize shared state if needed
         if Arc::strong_count(&self.connection_test_state) == 0 {
-            self.connection_test_state = Arc::new(Mutex::new(None));
+            self.connection_test_state = Arc::new(Mutex::new(Vec::new()));
         }
 
         // Initialize with first provider selected (before handling events)
         if self.selected_provider_id.is_none() {
-            self.selected_provider_id = Some("openai".to_string());
-            self.connection_test_state = Arc::new(Mutex::new(None));
+            // A restored provider may have been deleted since
+            let restored_provider_id = self.restored_provider_id.take().filter(|id| {
+                scope.data.get::<Store>().is_some_and(|store| store.preferences.get_provider(id).is_some())
+            });
+            self.selected_provider_id = Some(restored_provider_id.unwrap_or_else(|| "openai".to_string()));
+            self.connection_test_state = Arc::new(Mutex::new(Vec::new()));
             self.load_provider_data(cx, scope);
+            if let Some(store) = scope.data.get::<Store>() {
+                self.view.check_box(ids!(retest_on_focus_toggle)).set_active(cx, store.preferences.retest_provider_on_focus);
+                self.view.check_box(ids!(confirm_large_sends_toggle)).set_active(cx, store.preferences.confirm_large_sends);
+                self.view.text_input(ids!(send_confirmation_tokens_input))
+                    .set_text(cx, &store.preferences.send_confirmation_tokens.to_string());
+                self.view.text_input(ids!(system_prompt_input))
+                    .set_text(cx, &store.preferences.system_prompt);
+                self.view.text_input(ids!(empty_chat_greeting_input))
+                    .set_text(cx, &store.preferences.empty_chat_greeting);
+                self.view.text_input(ids!(suggested_prompts_input))
+                    .set_text(cx, &store.preferences.suggested_prompts.join("\n"));
+                self.view.text_input(ids!(default_context_window_input))
+                    .set_text(cx, &store.preferences.default_context_window_tokens.to_string());
+                let retention = store.preferences.chat_retention;
+                self.view.text_input(ids!(retention_days_input))
+                    .set_text(cx, &retention.max_age_days.map_or(String::new(), |days| days.to_string()));
+                self.view.text_input(ids!(retention_count_input))
+                    .set_text(cx, &retention.max_chats.map_or(String::new(), |count| count.to_string()));
+            }
+            self.load_proxy_settings(cx, scope);
             self.view.redraw(cx);
 
             // Log icon paths at startup for debugging (debug level)
@@ -99,6 +209,20 @@ impl Widget for SettingsApp {
         // Check for connection test results
         self.check_connection_test_result(cx, scope);
 
+        // Keep the connecting status dot pulsing
+        if self.status_pulse_frame.is_event(event).is_some() {
+            self.view.portal_list(ids!(providers_list)).redraw(cx);
+        }
+
+        // Close the Add Provider dialog (Escape unless rebound)
+        if let Event::KeyDown(key_event) = event {
+            let pressed = scope.data.get::<Store>()
+                .map_or(false, |store| store.preferences.shortcut_pressed(SHORTCUT_CLOSE_DIALOG, key_event));
+            if self.modal_visible && pressed {
+                self.close_add_provider_modal(cx);
+            }
+        }
+
         // Handle events
         let actions = cx.capture_actions(|cx| {
             self.view.handle_event(cx, event, scope);
@@ -112,11 +236,126 @@ impl Widget for SettingsApp {
             self.save_provider(cx, scope);
         }
 
-        // Test Connection button click
-        if self.view.button(ids!(test_button)).clicked(&actions) {
+        // Test Connection button click (refresh re-runs the test, bypassing the cache)
+        if self.view.button(ids!(test_button)).clicked(&actions)
+            || self.view.button(ids!(refresh_models_button)).clicked(&actions)
+        {
             self.test_connection(cx, scope);
         }
 
+        if self.view.button(ids!(test_all_button)).clicked(&actions) {
+            self.test_all_providers(cx, scope);
+        }
+
+        // Show/hide API key
+        if self.view.button(ids!(reveal_key_button)).clicked(&actions) {
+            self.set_api_key_revealed(cx, !self.api_key_revealed);
+        }
+
+        // Deep test toggle
+        if let Some(enabled) = self.view.check_box(ids!(deep_test_toggle)).changed(&actions) {
+            self.deep_test_enabled = enabled;
+        }
+
+        // Allow invalid certificates toggle (saved immediately, scoped to the selected provider)
+        if let Some(allow) = self.view.check_box(ids!(allow_invalid_certs_toggle)).changed(&actions) {
+            if let Some(provider_id) = self.selected_provider_id.clone() {
+                if let Some(store) = scope.data.get_mut::<Store>() {
+                    store.preferences.set_provider_allow_invalid_certs(&provider_id, allow);
+                    // Rebuild the clients so chats use the new setting too
+                    store.reconfigure_providers();
+                }
+            }
+            self.view.label(ids!(invalid_certs_warning)).set_visible(cx, allow);
+            self.view.redraw(cx);
+        }
+
+        // Re-test on focus toggle
+        if let Some(enabled) = self.view.check_box(ids!(retest_on_focus_toggle)).changed(&actions) {
+            if let Some(store) = scope.data.get_mut::<Store>() {
+                store.preferences.set_retest_provider_on_focus(enabled);
+            }
+        }
+
+        // Confirmation before large sends
+        if let Some(enabled) = self.view.check_box(ids!(confirm_large_sends_toggle)).changed(&actions) {
+            if let Some(store) = scope.data.get_mut::<Store>() {
+                store.preferences.set_confirm_large_sends(enabled);
+            }
+        }
+        if let Some(text) = self.view.text_input(ids!(send_confirmation_tokens_input)).changed(&actions) {
+            // Partial or invalid input keeps the last valid threshold
+            if let Ok(tokens) = text.trim().parse::<usize>() {
+                if tokens > 0 {
+                    if let Some(store) = scope.data.get_mut::<Store>() {
+                        store.preferences.set_send_confirmation_tokens(tokens);
+                    }
+                }
+            }
+        }
+
+        if let Some(text) = self.view.text_input(ids!(system_prompt_input)).changed(&actions) {
+            if let Some(store) = scope.data.get_mut::<Store>() {
+                store.preferences.set_system_prompt(text);
+            }
+        }
+        if let Some(text) = self.view.text_input(ids!(empty_chat_greeting_input)).changed(&actions) {
+            if let Some(store) = scope.data.get_mut::<Store>() {
+                store.preferences.set_empty_chat_greeting(text);
+            }
+        }
+        if let Some(text) = self.view.text_input(ids!(suggested_prompts_input)).changed(&actions) {
+            // Blank lines are dropped rather than offered as empty prompts
+            let prompts = text.lines().map(str::trim).filter(|line| !line.is_empty()).map(str::to_string).collect();
+            if let Some(store) = scope.data.get_mut::<Store>() {
+                store.preferences.set_suggested_prompts(prompts);
+            }
+        }
+        // Blank turns a retention limit off; anything but a positive number is ignored.
+        // Numbers too large for the field are clamped rather than wrapped
+        let retention_limit = |text: String| match text.trim() {
+            "" => Some(None),
+            text => text.parse::<u64>().ok().filter(|&n| n > 0).map(Some),
+        };
+        if let Some(days) = self.view.text_input(ids!(retention_days_input)).changed(&actions).and_then(retention_limit) {
+            if let Some(store) = scope.data.get_mut::<Store>() {
+                let max_age_days = days.map(|days| u32::try_from(days).unwrap_or(u32::MAX));
+                let retention = ChatRetention { max_age_days, ..store.preferences.chat_retention };
+                store.preferences.set_chat_retention(retention);
+            }
+        }
+        if let Some(count) = self.view.text_input(ids!(retention_count_input)).changed(&actions).and_then(retention_limit) {
+            if let Some(store) = scope.data.get_mut::<Store>() {
+                let max_chats = count.map(|count| usize::try_from(count).unwrap_or(usize::MAX));
+                let retention = ChatRetention { max_chats, ..store.preferences.chat_retention };
+                store.preferences.set_chat_retention(retention);
+            }
+        }
+        if let Some(text) = self.view.text_input(ids!(default_context_window_input)).changed(&actions) {
+            if let Ok(tokens) = text.trim().parse::<usize>() {
+                if tokens > 0 {
+                    if let Some(store) = scope.data.get_mut::<Store>() {
+                        store.preferences.set_default_context_window_tokens(tokens);
+                    }
+                }
+            }
+        }
+
+        // Custom headers table for the selected provider
+        self.handle_custom_headers(cx, &actions);
+
+        // Export providers as .env (requires confirmation since it contains secrets)
+        self.handle_env_export(cx, scope, &actions);
+
+        // Clear all chat history (requires typing the confirmation word)
+        self.handle_clear_history(cx, scope, &actions);
+        self.handle_chats_transfer(cx, scope, &actions);
+
+        // Proxy settings
+        if self.view.button(ids!(save_proxy_button)).clicked(&actions) {
+            self.save_proxy_settings(cx, scope);
+        }
+
         // Add Provider button click
         if self.view.button(ids!(add_provider_button)).clicked(&actions) {
             self.open_add_provider_modal(cx);
@@ -143,6 +382,15 @@ impl Widget for SettingsApp {
 
         // Handle Select All toggle
         self.handle_select_all_toggle(cx, scope, &actions);
+
+        // Filter the models list and bulk-toggle the matches
+        self.handle_model_filter(cx, scope, &actions);
+
+        // Save the alias of the model picked from the list
+        self.handle_model_alias(cx, scope, &actions);
+
+        // Handle favorite prompts add/remove
+        self.handle_favorite_prompts(cx, scope, &actions);
     }
 
     fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
@@ -165,8 +413,7 @@ impl Widget for SettingsApp {
 
         // Update select_all_toggle state: ON if all models enabled, OFF otherwise
         if has_models {
-            let all_enabled = self.fetched_models.iter().all(|(_, enabled)| *enabled);
-            self.view.check_box(ids!(select_all_toggle)).set_active(cx, all_enabled);
+            self.sync_select_all_toggle(cx);
         }
 
         // Show/hide add provider modal
@@ -185,6 +432,9 @@ impl Widget for SettingsApp {
         let providers_list_uid = providers_list.widget_uid();
         let models_list = self.view.portal_list(ids!(models_list));
         let models_list_uid = models_list.widget_uid();
+        let favorites_list = self.view.portal_list(ids!(favorites_list));
+        let favorites_list_uid = favorites_list.widget_uid();
+        let headers_list_uid = self.view.portal_list(ids!(headers_list)).widget_uid();
 
         // Draw with PortalList handling
         while let Some(widget) = self.view.draw_walk(cx, scope, walk).step() {
@@ -192,18 +442,35 @@ impl Widget for SettingsApp {
             if widget.widget_uid() == providers_list_uid {
                 self.draw_providers_list(cx, scope, widget, dark_mode_value);
             }
+            // Draw favorite prompts list
+            else if widget.widget_uid() == favorites_list_uid {
+                self.draw_favorites_list(cx, scope, widget, dark_mode_value);
+            }
+            // Draw custom headers table
+            else if widget.widget_uid() == headers_list_uid {
+                self.draw_headers_list(cx, scope, widget, dark_mode_value);
+            }
             // Draw models list
             else if widget.widget_uid() == models_list_uid {
+                let default_model = scope.data.get::<Store>()
+                    .zip(self.selected_provider_id.as_ref())
+                    .and_then(|(store, id)| store.preferences.get_provider(id))
+                    .and_then(|provider| provider.default_model.clone());
+                let aliases = self.model_aliases(scope);
+                let visible_models = self.filtered_model_indices();
                 if let Some(mut list) = widget.as_portal_list().borrow_mut() {
-                    list.set_item_range(cx, 0, self.fetched_models.len());
+                    list.set_item_range(cx, 0, visible_models.len());
 
                     while let Some(item_id) = list.next_visible_item(cx) {
-                        if item_id < self.fetched_models.len() {
-                            let (model_name, enabled) = &self.fetched_models[item_id];
+                        if let Some(&index) = visible_models.get(item_id) {
+                            let (model_name, enabled) = &self.fetched_models[index];
                             let item_widget = list.item(cx, item_id, live_id!(ModelItem));
 
-                            // Set model name
-                            item_widget.label(ids!(model_name)).set_text(cx, model_name);
+                            // Set model name, after its alias if it has one
+                            match aliases.get(model_name) {
+                                Some(alias) => item_widget.label(ids!(model_name)).set_text(cx, &format!("{} ({})", alias, model_name)),
+                                None => item_widget.label(ids!(model_name)).set_text(cx, model_name),
+                            }
                             item_widget.label(ids!(model_name)).apply_over(cx, live!{
                                 draw_text: { dark_mode: (dark_mode_value) }
                             });
@@ -211,6 +478,20 @@ impl Widget for SettingsApp {
                             // Set checkbox state
                             item_widget.check_box(ids!(model_enabled)).set_active(cx, *enabled);
 
+                            let is_default = default_model.as_deref() == Some(model_name.as_str());
+                            let default_text = if is_default { "★ Default" } else { "Set as default" };
+                            item_widget.button(ids!(alias_model_button)).apply_over(cx, live!{
+                                draw_bg: { dark_mode: (dark_mode_value) }
+                                draw_text: { dark_mode: (dark_mode_value) }
+                            });
+
+                            let default_button = item_widget.button(ids!(default_model_button));
+                            default_button.set_text(cx, default_text);
+                            default_button.apply_over(cx, live!{
+                                draw_bg: { dark_mode: (dark_mode_value) }
+                                draw_text: { dark_mode: (dark_mode_value) }
+                            });
+
                             item_widget.draw_all(cx, scope);
                         }
                     }
@@ -239,17 +520,125 @@ impl SettingsApp {
         index.and_then(|i| self.provider_icons.get(i))
     }
 
+    /// Current view state, for restoring it on the next launch
+    pub fn session_state(&self) -> SettingsSessionState {
+        SettingsSessionState {
+            selected_provider_id: self.selected_provider_id.clone(),
+        }
+    }
+
+    /// Reopen with the saved provider selected. Must be called before the first event.
+    pub fn restore_session_state(&mut self, state: SettingsSessionState) {
+        self.restored_provider_id = state.selected_provider_id;
+    }
+
     fn select_provider(&mut self, cx: &mut Cx, scope: &mut Scope, id: &str) {
         self.selected_provider_id = Some(id.to_string());
         // Reset connection status when changing providers
         self.connection_status = ProviderConnectionStatus::NotConnected;
         self.model_count = None;
         self.fetched_models.clear();
-        self.connection_test_in_progress = false;
+        self.model_filter.clear();
+        self.view.text_input(ids!(model_filter_input)).set_text(cx, "");
+        self.alias_model = None;
+        self.view.view(ids!(model_alias_row)).set_visible(cx, false);
+        self.view.button(ids!(enable_matching_button)).set_visible(cx, false);
+        self.view.button(ids!(disable_matching_button)).set_visible(cx, false);
+        self.set_api_key_revealed(cx, false);
         self.load_provider_data(cx, scope);
+        self.apply_cached_test_result(cx, scope);
+        if self.fetched_models.is_empty() {
+            self.apply_saved_models(cx, scope);
+        }
+        // A test started by "Test All" may still be running for this provider
+        if self.testing_providers.contains(id) {
+            self.connection_status = ProviderConnectionStatus::Connecting;
+            self.view.label(ids!(status_message)).set_text(cx, "Testing connection...");
+        }
+        self.view.redraw(cx);
+    }
+
+    /// Switch the API key input between masked and plain text display
+    fn set_api_key_revealed(&mut self, cx: &mut Cx, revealed: bool) {
+        self.api_key_revealed = revealed;
+        self.view.text_input(ids!(api_key_input)).apply_over(cx, live!{
+            is_password: (!revealed)
+        });
+        self.view.button(ids!(reveal_key_button)).set_text(cx, if revealed { "Hide" } else { "Show" });
         self.view.redraw(cx);
     }
 
+    /// Show the cached test result for the selected provider, if still valid
+    fn apply_cached_test_result(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        let Some(provider_id) = self.selected_provider_id.clone() else { return };
+        let Some(cached) = self.test_cache.get(&provider_id).cloned() else { return };
+
+        let still_valid = scope.data.get::<Store>()
+            .and_then(|store| store.preferences.get_provider(&provider_id))
+            .map_or(false, |provider| {
+                cached.is_valid_for(&provider.url, provider.api_key.as_deref().unwrap_or(""))
+            });
+        if !still_valid {
+            self.test_cache.remove(&provider_id);
+            return;
+        }
+
+        self.connection_status = ProviderConnectionStatus::Connected;
+        self.model_count = cached.model_count;
+        self.set_fetched_models(scope, &provider_id, cached.models);
+
+        let minutes = cached.tested_at.elapsed().as_secs() / 60;
+        let status_text = match cached.model_count {
+            Some(count) => format!("Connected! Found {} models", count),
+            None => "Connected!".to_string(),
+        };
+        let note = if minutes == 0 {
+            "(cached, just now)".to_string()
+        } else {
+            format!("(cached, {}m ago)", minutes)
+        };
+        self.view.label(ids!(status_message)).set_text(cx, &status_text);
+        self.set_cached_note(cx, Some(&note));
+    }
+
+    /// Show the models listed by the provider's last successful test, with
+    /// their enabled states, until it's tested again
+    fn apply_saved_models(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        let Some(provider_id) = self.selected_provider_id.clone() else { return };
+        let models = scope.data.get::<Store>()
+            .and_then(|store| store.preferences.get_provider(&provider_id))
+            .map(|provider| provider.fetched_models.clone())
+            .unwrap_or_default();
+        if models.is_empty() {
+            return;
+        }
+
+        self.model_count = Some(models.len());
+        self.set_fetched_models(scope, &provider_id, models);
+        self.set_cached_note(cx, Some("(models from last test)"));
+    }
+
+    /// Show or hide the cached note and refresh button
+    fn set_cached_note(&mut self, cx: &mut Cx, note: Option<&str>) {
+        self.view.label(ids!(cached_note)).set_text(cx, note.unwrap_or(""));
+        self.view.label(ids!(cached_note)).set_visible(cx, note.is_some());
+        self.view.button(ids!(refresh_models_button)).set_visible(cx, note.is_some());
+    }
+
+    /// Merge fetched model names with the stored enabled state for a provider
+    fn set_fetched_models(&mut self, scope: &mut Scope, provider_id: &str, models: Vec<String>) {
+        let stored_models: HashMap<String, bool> = scope.data.get::<Store>()
+            .and_then(|store| store.preferences.get_provider(provider_id))
+            .map(|provider| provider.models.iter().cloned().collect())
+            .unwrap_or_default();
+
+        // Use stored preference, default to enabled if not found
+        self.fetched_models = models.into_iter().map(|name| {
+            let enabled = stored_models.get(&name).copied().unwrap_or(true);
+            (name, enabled)
+        }).collect();
+    }
+
     fn load_provider_data(&mut self, cx: &mut Cx, scope: &mut Scope) {
         let Some(provider_id) = self.selected_provider_id.clone() else { return };
 
@@ -278,8 +667,18 @@ impl SettingsApp {
                 // Show/hide delete button based on whether provider was custom added
                 self.view.button(ids!(delete_provider_button)).set_visible(cx, provider.was_customly_added);
 
+                // Advanced options
+                self.view.check_box(ids!(allow_invalid_certs_toggle)).set_active(cx, provider.allow_invalid_certs);
+                self.view.label(ids!(invalid_certs_warning)).set_visible(cx, provider.allow_invalid_certs);
+                self.view.text_input(ids!(chat_endpoint_input)).set_text(cx, provider.chat_endpoint.as_deref().unwrap_or(""));
+                self.view.text_input(ids!(models_path_input)).set_text(cx, provider.models_path.as_deref().unwrap_or(""));
+                let timeout_text = provider.timeout_secs.map(|secs| secs.to_string()).unwrap_or_default();
+                self.view.text_input(ids!(timeout_input)).set_text(cx, &timeout_text);
+                self.custom_headers = provider.custom_headers.clone();
+
                 // Clear status message
                 self.view.label(ids!(status_message)).set_text(cx, "");
+                self.set_cached_note(cx, None);
             } else {
                 ::log::warn!("Provider {} not found in preferences", provider_id);
             }
@@ -298,8 +697,37 @@ impl SettingsApp {
         ::log::info!("save_provider: provider={}, url={}, api_key_len={}",
             provider_id, url, api_key_text.len());
 
+        // Catch a malformed URL now rather than as a confusing connection failure later
+        let url = match normalize_provider_url(&url) {
+            Ok(url) => url,
+            Err(e) => {
+                self.view.label(ids!(status_message)).set_text(cx, &format!("Error: {}", e));
+                self.view.redraw(cx);
+                return;
+            }
+        };
+        self.view.text_input(ids!(api_host_input)).set_text(cx, &url);
+
+        let timeout_secs = match self.timeout_secs_input() {
+            Ok(secs) => secs,
+            Err(e) => {
+                self.view.label(ids!(status_message)).set_text(cx, &format!("Error: {}", e));
+                self.view.redraw(cx);
+                return;
+            }
+        };
+
         // Save to Store
         if let Some(store) = scope.data.get_mut::<Store>() {
+            // A changed URL or key makes the last test result meaningless
+            let changed = store.preferences.get_provider(provider_id).map_or(true, |provider| {
+                provider.url != url
+                    || (!api_key_text.is_empty() && provider.api_key.as_deref() != Some(api_key_text.as_str()))
+            });
+            if changed {
+                self.test_cache.remove(provider_id);
+            }
+
             store.preferences.set_provider_url(provider_id, url);
 
             // Only update API key if user entered something, or if explicitly clearing
@@ -318,6 +746,18 @@ impl SettingsApp {
                 }
             }
 
+            store.preferences.set_provider_custom_headers(provider_id, self.custom_headers.clone());
+            let models_path = self.view.text_input(ids!(models_path_input)).text();
+            store.preferences.set_provider_models_path(provider_id, Some(models_path));
+            store.preferences.set_provider_timeout_secs(provider_id, timeout_secs);
+
+            let chat_endpoint = self.view.text_input(ids!(chat_endpoint_input)).text();
+            if let Err(e) = store.preferences.set_provider_chat_endpoint(provider_id, Some(chat_endpoint)) {
+                self.view.label(ids!(status_message)).set_text(cx, &format!("Error: {}", e));
+                self.view.redraw(cx);
+                return;
+            }
+
             // Show success message
             self.view.label(ids!(status_message)).set_text(cx, "Settings saved!");
 
@@ -327,6 +767,19 @@ impl SettingsApp {
         self.view.redraw(cx);
     }
 
+    /// Parse the timeout field; empty means use the default
+    fn timeout_secs_input(&self) -> Result<Option<u64>, String> {
+        let text = self.view.text_input(ids!(timeout_input)).text();
+        let text = text.trim();
+        if text.is_empty() {
+            return Ok(None);
+        }
+        match text.parse::<u64>() {
+            Ok(secs) if secs > 0 => Ok(Some(secs)),
+            _ => Err("Timeout must be a whole number of seconds".to_string()),
+        }
+    }
+
     fn update_selection(&mut self, _cx: &mut Cx2d) {
         // Selection highlighting is now handled in draw_providers_list
     }
@@ -347,31 +800,46 @@ impl SettingsApp {
             let item_widget = list.item(cx, item_id, live_id!(ProviderListItem));
 
             // Get provider info from store
-            let (name, enabled) = if let Some(store) = scope.data.get::<Store>() {
+            let (name, enabled, last_status) = if let Some(store) = scope.data.get::<Store>() {
                 if let Some(provider) = store.preferences.get_provider(provider_id) {
-                    (provider.name.clone(), provider.enabled)
+                    (provider.name.clone(), provider.enabled, provider.last_connection_status.clone())
                 } else {
-                    (provider_id.clone(), false)
+                    (provider_id.clone(), false, ProviderConnectionStatus::NotConnected)
                 }
             } else {
-                (provider_id.clone(), false)
+                (provider_id.clone(), false, ProviderConnectionStatus::NotConnected)
             };
 
-            // Set selection state
+            // Set selection state; a drag marks where the provider would land
             let is_selected = self.selected_provider_id.as_deref() == Some(provider_id.as_str());
             let selected_val = if is_selected { 1.0 } else { 0.0 };
+            let drop_val = match self.provider_drag {
+                Some((from, to)) if from != to && to == item_id => 1.0,
+                _ => 0.0,
+            };
 
-            // Get status for this provider
-            let status_val = match self.provider_statuses.get(provider_id) {
-                Some(ProviderConnectionStatus::NotConnected) | None => 0.0,
-                Some(ProviderConnectionStatus::Connecting) => 1.0,
-                Some(ProviderConnectionStatus::Connected) => 2.0,
-                Some(ProviderConnectionStatus::Error(_)) => 3.0,
+            // Get status for this provider, pulsing while a test is in flight
+            let status = if self.testing_providers.contains(provider_id) {
+                ProviderConnectionStatus::Connecting
+            } else {
+                last_status
+            };
+            let status_val = match status {
+                ProviderConnectionStatus::NotConnected => 0.0,
+                ProviderConnectionStatus::Connecting => 1.0,
+                ProviderConnectionStatus::Connected => 2.0,
+                ProviderConnectionStatus::Error(_) => 3.0,
+            };
+            let pulse_val = if status == ProviderConnectionStatus::Connecting {
+                self.status_pulse_frame = cx.new_next_frame();
+                0.5 - 0.5 * (cx.seconds_since_app_start() * std::f64::consts::TAU).cos()
+            } else {
+                0.0
             };
 
             // Apply styling
             item_widget.apply_over(cx, live!{
-                draw_bg: { dark_mode: (dark_mode), selected: (selected_val) }
+                draw_bg: { dark_mode: (dark_mode), selected: (selected_val), hover: (drop_val) }
             });
             item_widget.label(ids!(provider_name)).set_text(cx, &name);
             item_widget.label(ids!(provider_name)).apply_over(cx, live!{
@@ -380,7 +848,7 @@ impl SettingsApp {
 
             // Set status dot
             item_widget.view(ids!(status_dot)).apply_over(cx, live!{
-                draw_bg: { status: (status_val), dark_mode: (dark_mode) }
+                draw_bg: { status: (status_val), pulse: (pulse_val), dark_mode: (dark_mode) }
             });
 
             // Set icon if available - use file path loading
@@ -404,115 +872,528 @@ impl SettingsApp {
         }
     }
 
-    /// Handle clicks on provider list items
-    fn handle_provider_list_clicks(&mut self, cx: &mut Cx, scope: &mut Scope, actions: &Actions) {
-        let providers_list = self.view.portal_list(ids!(providers_list));
+    /// Draw the custom headers PortalList
+    fn draw_headers_list(&mut self, cx: &mut Cx2d, scope: &mut Scope, widget: WidgetRef, dark_mode: f64) {
+        let binding = widget.as_portal_list();
+        let Some(mut list) = binding.borrow_mut() else { return };
 
-        for (item_id, item) in providers_list.items_with_actions(actions) {
-            // Handle enabled checkbox toggle
-            let checkbox = item.check_box(ids!(provider_enabled));
-            if let Some(new_state) = checkbox.changed(actions) {
-                if item_id < self.provider_ids.len() {
-                    let provider_id = self.provider_ids[item_id].clone();
-                    // Save enabled state to preferences
-                    if let Some(store) = scope.data.get_mut::<Store>() {
-                        store.preferences.set_provider_enabled(&provider_id, new_state);
-                        ::log::info!("Provider '{}' enabled: {}", provider_id, new_state);
-                    }
-                    self.view.redraw(cx);
-                }
-                continue; // Don't select provider when toggling checkbox
-            }
+        list.set_item_range(cx, 0, self.custom_headers.len());
 
-            // Check for finger down on the item (for selection)
-            if let Some(fd) = item.as_view().finger_down(actions) {
-                if fd.tap_count == 1 && item_id < self.provider_ids.len() {
-                    let provider_id = self.provider_ids[item_id].clone();
-                    self.select_provider(cx, scope, &provider_id);
-                }
+        while let Some(item_id) = list.next_visible_item(cx) {
+            if item_id >= self.custom_headers.len() {
+                continue;
             }
-        }
-    }
-
-    /// Handle model checkbox toggle events
-    fn handle_model_checkbox_clicks(&mut self, cx: &mut Cx, scope: &mut Scope, actions: &Actions) {
-        let models_list = self.view.portal_list(ids!(models_list));
-
-        for (item_id, item) in models_list.items_with_actions(actions) {
-            let checkbox = item.check_box(ids!(model_enabled));
-            if let Some(new_state) = checkbox.changed(actions) {
-                if item_id < self.fetched_models.len() {
-                    let model_name = self.fetched_models[item_id].0.clone();
 
-                    // Update local state
-                    self.fetched_models[item_id].1 = new_state;
+            let (name, value) = &self.custom_headers[item_id];
+            let item_widget = list.item(cx, item_id, live_id!(HeaderItem));
 
-                    // Save to preferences
-                    self.save_model_enabled_state(scope, &model_name, new_state);
+            // Only push text when it differs, so typing keeps the cursor position
+            let name_input = item_widget.text_input(ids!(header_name_input));
+            if name_input.text() != *name {
+                name_input.set_text(cx, name);
+            }
+            let value_input = item_widget.text_input(ids!(header_value_input));
+            if value_input.text() != *value {
+                value_input.set_text(cx, value);
+            }
 
-                    ::log::info!("Model '{}' enabled: {}", model_name, new_state);
-                    self.view.redraw(cx);
-                }
+            for input in [&name_input, &value_input] {
+                input.apply_over(cx, live!{
+                    draw_bg: { dark_mode: (dark_mode) }
+                    draw_text: { dark_mode: (dark_mode) }
+                });
             }
+            item_widget.button(ids!(remove_header_button)).apply_over(cx, live!{
+                draw_bg: { dark_mode: (dark_mode) }
+                draw_text: { dark_mode: (dark_mode) }
+            });
+
+            item_widget.draw_all(cx, scope);
         }
     }
 
-    /// Handle the Select All toggle for models
-    fn handle_select_all_toggle(&mut self, cx: &mut Cx, scope: &mut Scope, actions: &Actions) {
-        let select_all_toggle = self.view.check_box(ids!(select_all_toggle));
-        if let Some(new_state) = select_all_toggle.changed(actions) {
-            // Set all models to the new state
-            for (_, enabled) in &mut self.fetched_models {
-                *enabled = new_state;
-            }
+    /// Handle edits to the custom headers table
+    fn handle_custom_headers(&mut self, cx: &mut Cx, actions: &Actions) {
+        if self.view.button(ids!(add_header_button)).clicked(actions) {
+            self.custom_headers.push((String::new(), String::new()));
+            self.view.redraw(cx);
+        }
 
-            // Save all model states to preferences
-            if let Some(provider_id) = &self.selected_provider_id {
-                if let Some(store) = scope.data.get_mut::<Store>() {
-                    if let Some(provider) = store.preferences.get_provider_mut(provider_id) {
-                        // Update all models in preferences
-                        for (model_name, enabled) in &self.fetched_models {
-                            if let Some(model_entry) = provider.models.iter_mut().find(|(name, _)| name == model_name) {
-                                model_entry.1 = *enabled;
-                            } else {
-                                provider.models.push((model_name.clone(), *enabled));
-                            }
-                        }
-                        store.preferences.save();
-                    }
-                }
+        let headers_list = self.view.portal_list(ids!(headers_list));
+        let mut removed = None;
+        for (item_id, item) in headers_list.items_with_actions(actions) {
+            let Some(header) = self.custom_headers.get_mut(item_id) else { continue };
+            if let Some(name) = item.text_input(ids!(header_name_input)).changed(actions) {
+                header.0 = name;
+            }
+            if let Some(value) = item.text_input(ids!(header_value_input)).changed(actions) {
+                header.1 = value;
+            }
+            if item.button(ids!(remove_header_button)).clicked(actions) {
+                removed = Some(item_id);
             }
+        }
 
-            ::log::info!("Select All toggled: all models set to {}", new_state);
+        if let Some(item_id) = removed {
+            self.custom_headers.remove(item_id);
             self.view.redraw(cx);
         }
     }
 
-    /// Save model enabled state to preferences
-    fn save_model_enabled_state(&mut self, scope: &mut Scope, model_name: &str, enabled: bool) {
-        let Some(provider_id) = &self.selected_provider_id else { return };
+    /// Draw the favorite prompts PortalList
+    fn draw_favorites_list(&mut self, cx: &mut Cx2d, scope: &mut Scope, widget: WidgetRef, dark_mode: f64) {
+        let prompts = scope.data.get::<Store>()
+            .map(|store| store.preferences.favorite_prompts.clone())
+            .unwrap_or_default();
 
-        if let Some(store) = scope.data.get_mut::<Store>() {
-            if let Some(provider) = store.preferences.get_provider_mut(provider_id) {
-                // Find and update or add the model entry
-                if let Some(model_entry) = provider.models.iter_mut().find(|(name, _)| name == model_name) {
-                    model_entry.1 = enabled;
-                } else {
-                    provider.models.push((model_name.to_string(), enabled));
-                }
-                store.preferences.save();
+        let binding = widget.as_portal_list();
+        let Some(mut list) = binding.borrow_mut() else { return };
+
+        list.set_item_range(cx, 0, prompts.len());
+
+        while let Some(item_id) = list.next_visible_item(cx) {
+            if item_id >= prompts.len() {
+                continue;
             }
+
+            let item_widget = list.item(cx, item_id, live_id!(FavoritePromptItem));
+            item_widget.label(ids!(prompt_text)).set_text(cx, &prompts[item_id]);
+            item_widget.label(ids!(prompt_text)).apply_over(cx, live!{
+                draw_text: { dark_mode: (dark_mode) }
+            });
+            item_widget.button(ids!(remove_favorite_button)).apply_over(cx, live!{
+                draw_bg: { dark_mode: (dark_mode) }
+                draw_text: { dark_mode: (dark_mode) }
+            });
+
+            item_widget.draw_all(cx, scope);
         }
     }
 
-    fn apply_dark_mode(&mut self, cx: &mut Cx2d, dark_mode: f64) {
-        self.view.apply_over(cx, live! {
-            draw_bg: { dark_mode: (dark_mode) }
-        });
+    /// Handle exporting the enabled providers as environment variables
+    fn handle_env_export(&mut self, cx: &mut Cx, scope: &mut Scope, actions: &Actions) {
+        if self.view.button(ids!(export_env_button)).clicked(actions) {
+            self.view.view(ids!(export_env_confirm)).set_visible(cx, true);
+            self.view.label(ids!(export_env_status)).set_text(cx, "");
+            self.view.redraw(cx);
+        }
 
-        // Apply to panels
-        self.view.view(ids!(providers_panel)).apply_over(cx, live!{
-            draw_bg: { dark_mode: (dark_mode) }
+        if self.view.button(ids!(cancel_env_button)).clicked(actions) {
+            self.view.view(ids!(export_env_confirm)).set_visible(cx, false);
+            self.view.redraw(cx);
+        }
+
+        if self.view.button(ids!(copy_env_button)).clicked(actions) {
+            if let Some(store) = scope.data.get::<Store>() {
+                cx.copy_to_clipboard(&store.preferences.enabled_providers_env());
+                self.view.label(ids!(export_env_status)).set_text(cx, "Copied to clipboard");
+            }
+            self.view.view(ids!(export_env_confirm)).set_visible(cx, false);
+            self.view.redraw(cx);
+        }
+
+        if self.view.button(ids!(save_env_button)).clicked(actions) {
+            if let Some(store) = scope.data.get::<Store>() {
+                let status = match store.preferences.save_enabled_providers_env() {
+                    Ok(path) => format!("Saved to {}", path.display()),
+                    Err(e) => format!("Error: {}", e),
+                };
+                self.view.label(ids!(export_env_status)).set_text(cx, &status);
+            }
+            self.view.view(ids!(export_env_confirm)).set_visible(cx, false);
+            self.view.redraw(cx);
+        }
+    }
+
+    /// Delete every saved chat once the confirmation word is typed
+    fn handle_clear_history(&mut self, cx: &mut Cx, scope: &mut Scope, actions: &Actions) {
+        if self.view.button(ids!(clear_history_button)).clicked(actions) {
+            self.view.text_input(ids!(clear_history_input)).set_text(cx, "");
+            self.view.view(ids!(clear_history_confirm)).set_visible(cx, true);
+            self.view.label(ids!(clear_history_status)).set_text(cx, "");
+            self.view.redraw(cx);
+        }
+
+        if self.view.button(ids!(cancel_clear_history_button)).clicked(actions) {
+            self.view.view(ids!(clear_history_confirm)).set_visible(cx, false);
+            self.view.redraw(cx);
+        }
+
+        if self.view.button(ids!(confirm_clear_history_button)).clicked(actions) {
+            let typed = self.view.text_input(ids!(clear_history_input)).text();
+            if typed.trim() != CLEAR_HISTORY_CONFIRMATION {
+                self.view.label(ids!(clear_history_status)).set_text(cx,
+                    &format!("Type {} to confirm", CLEAR_HISTORY_CONFIRMATION));
+            } else if let Some(store) = scope.data.get_mut::<Store>() {
+                let count = store.chats.get_sorted_chats().len();
+                store.chats.delete_all();
+                self.view.label(ids!(clear_history_status)).set_text(cx, &format!("Deleted {} chats", count));
+                self.view.view(ids!(clear_history_confirm)).set_visible(cx, false);
+            }
+            self.view.redraw(cx);
+        }
+    }
+
+    /// Export every saved chat to a file, or import the chats of one
+    fn handle_chats_transfer(&mut self, cx: &mut Cx, scope: &mut Scope, actions: &Actions) {
+        let export = self.view.button(ids!(export_chats_button)).clicked(actions);
+        let import = self.view.button(ids!(import_chats_button)).clicked(actions);
+        if !export && !import {
+            return;
+        }
+        let Some(store) = scope.data.get_mut::<Store>() else { return };
+
+        let typed = self.view.text_input(ids!(chats_file_input)).text();
+        let path = match typed.trim() {
+            "" => Chats::default_export_path(),
+            typed => PathBuf::from(typed),
+        };
+
+        let status = if export {
+            match store.chats.export_all_to(&path) {
+                Ok(count) => format!("Exported {} chats to {}", count, path.display()),
+                Err(e) => format!("Error: {}", e),
+            }
+        } else {
+            match store.chats.import_all_from(&path) {
+                Ok(result) => {
+                    let mut status = format!("Imported {} chats", result.imported);
+                    if result.duplicates > 0 {
+                        status.push_str(&format!(", skipped {} already present", result.duplicates));
+                    }
+                    if result.invalid > 0 {
+                        status.push_str(&format!(", skipped {} invalid", result.invalid));
+                    }
+                    status
+                }
+                Err(e) => format!("Error: {}", e),
+            }
+        };
+        self.view.label(ids!(chats_transfer_status)).set_text(cx, &status);
+        self.view.redraw(cx);
+    }
+
+    /// Fill the proxy inputs from preferences
+    fn load_proxy_settings(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        let Some(store) = scope.data.get::<Store>() else { return };
+        let proxy = &store.preferences.proxy;
+
+        self.view.check_box(ids!(proxy_enabled_toggle)).set_active(cx, proxy.enabled);
+        self.view.text_input(ids!(proxy_url_input)).set_text(cx, &proxy.url);
+        self.view.text_input(ids!(proxy_username_input)).set_text(cx, proxy.username.as_deref().unwrap_or(""));
+        self.view.text_input(ids!(proxy_password_input)).set_text(cx, proxy.password.as_deref().unwrap_or(""));
+        self.view.text_input(ids!(no_proxy_input)).set_text(cx, &proxy.no_proxy);
+
+        // Without a proxy here, requests still honor the environment's proxy variables
+        let status = match (moly_data::http::environment_proxy(), proxy.enabled) {
+            (Some(url), false) => format!("Using the proxy from the environment: {}", url),
+            _ => String::new(),
+        };
+        self.view.label(ids!(proxy_status)).set_text(cx, &status);
+    }
+
+    /// Validate and save the proxy, then rebuild clients so it takes effect
+    fn save_proxy_settings(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        let optional = |text: String| {
+            let text = text.trim().to_string();
+            (!text.is_empty()).then_some(text)
+        };
+
+        let proxy = ProxySettings {
+            enabled: self.view.check_box(ids!(proxy_enabled_toggle)).active(cx),
+            url: self.view.text_input(ids!(proxy_url_input)).text().trim().to_string(),
+            username: optional(self.view.text_input(ids!(proxy_username_input)).text()),
+            password: optional(self.view.text_input(ids!(proxy_password_input)).text()),
+            no_proxy: self.view.text_input(ids!(no_proxy_input)).text().trim().to_string(),
+        };
+
+        if let Some(store) = scope.data.get_mut::<Store>() {
+            let status = match store.preferences.set_proxy(proxy) {
+                Ok(()) => {
+                    store.reconfigure_providers();
+                    "Proxy settings saved".to_string()
+                }
+                Err(e) => format!("Error: {}", e),
+            };
+            self.view.label(ids!(proxy_status)).set_text(cx, &status);
+        }
+        self.view.redraw(cx);
+    }
+
+    /// Handle adding and removing favorite prompts
+    fn handle_favorite_prompts(&mut self, cx: &mut Cx, scope: &mut Scope, actions: &Actions) {
+        if self.view.button(ids!(add_favorite_button)).clicked(actions) {
+            let prompt = self.view.text_input(ids!(new_favorite_input)).text();
+            if !prompt.trim().is_empty() {
+                if let Some(store) = scope.data.get_mut::<Store>() {
+                    store.preferences.add_favorite_prompt(prompt.trim().to_string());
+                }
+                self.view.text_input(ids!(new_favorite_input)).set_text(cx, "");
+                self.view.redraw(cx);
+            }
+        }
+
+        let favorites_list = self.view.portal_list(ids!(favorites_list));
+        for (item_id, item) in favorites_list.items_with_actions(actions) {
+            if item.button(ids!(remove_favorite_button)).clicked(actions) {
+                if let Some(store) = scope.data.get_mut::<Store>() {
+                    store.preferences.remove_favorite_prompt(item_id);
+                }
+                self.view.redraw(cx);
+            }
+        }
+    }
+
+    /// Handle clicks on provider list items
+    fn handle_provider_list_clicks(&mut self, cx: &mut Cx, scope: &mut Scope, actions: &Actions) {
+        let providers_list = self.view.portal_list(ids!(providers_list));
+
+        for (item_id, item) in providers_list.items_with_actions(actions) {
+            // Handle enabled checkbox toggle
+            let checkbox = item.check_box(ids!(provider_enabled));
+            if let Some(new_state) = checkbox.changed(actions) {
+                if item_id < self.provider_ids.len() {
+                    let provider_id = self.provider_ids[item_id].clone();
+                    // Save enabled state to preferences
+                    if let Some(store) = scope.data.get_mut::<Store>() {
+                        store.preferences.set_provider_enabled(&provider_id, new_state);
+                        ::log::info!("Provider '{}' enabled: {}", provider_id, new_state);
+                    }
+                    self.view.redraw(cx);
+                }
+                continue; // Don't select provider when toggling checkbox
+            }
+
+            // Check for finger down on the item (for selection)
+            if let Some(fd) = item.as_view().finger_down(actions) {
+                if fd.tap_count == 1 && item_id < self.provider_ids.len() {
+                    let provider_id = self.provider_ids[item_id].clone();
+                    self.select_provider(cx, scope, &provider_id);
+                }
+            }
+
+            // Dragging an item vertically moves it by whole rows
+            let row_height = item.area().rect(cx).size.y;
+            let drop_index = |dy: f64| {
+                let rows = if row_height > 0.0 { (dy / row_height).round() as isize } else { 0 };
+                (item_id as isize + rows).clamp(0, self.provider_ids.len().saturating_sub(1) as isize) as usize
+            };
+            if let Some(fm) = item.as_view().finger_move(actions) {
+                if item_id < self.provider_ids.len() {
+                    let drag = Some((item_id, drop_index(fm.abs.y - fm.abs_start.y)));
+                    if drag != self.provider_drag {
+                        self.provider_drag = drag;
+                        self.view.portal_list(ids!(providers_list)).redraw(cx);
+                    }
+                }
+            }
+            if let Some(fu) = item.as_view().finger_up(actions) {
+                self.provider_drag = None;
+                if item_id < self.provider_ids.len() {
+                    let to = drop_index(fu.abs.y - fu.abs_start.y);
+                    self.move_provider(scope, item_id, to);
+                }
+                self.view.portal_list(ids!(providers_list)).redraw(cx);
+            }
+        }
+    }
+
+    /// Move a provider in the list, keeping the cached ids in step with preferences
+    fn move_provider(&mut self, scope: &mut Scope, from: usize, to: usize) {
+        if from == to || from >= self.provider_ids.len() || to >= self.provider_ids.len() {
+            return;
+        }
+        let Some(store) = scope.data.get_mut::<Store>() else { return };
+        store.preferences.move_provider(from, to);
+        let provider_id = self.provider_ids.remove(from);
+        self.provider_ids.insert(to, provider_id);
+    }
+
+    /// Handle model checkbox toggle events
+    fn handle_model_checkbox_clicks(&mut self, cx: &mut Cx, scope: &mut Scope, actions: &Actions) {
+        let models_list = self.view.portal_list(ids!(models_list));
+        let visible_models = self.filtered_model_indices();
+
+        for (item_id, item) in models_list.items_with_actions(actions) {
+            // Rows show the filtered models; map back to `fetched_models`
+            let Some(&index) = visible_models.get(item_id) else { continue };
+            let checkbox = item.check_box(ids!(model_enabled));
+            if let Some(new_state) = checkbox.changed(actions) {
+                let model_name = self.fetched_models[index].0.clone();
+
+                // Update local state
+                self.fetched_models[index].1 = new_state;
+
+                // Save to preferences
+                self.save_model_enabled_state(scope, &model_name, new_state);
+
+                ::log::info!("Model '{}' enabled: {}", model_name, new_state);
+                self.sync_select_all_toggle(cx);
+                self.view.redraw(cx);
+            }
+
+            if item.button(ids!(alias_model_button)).clicked(actions) {
+                let model_name = self.fetched_models[index].0.clone();
+                let alias = self.model_aliases(scope).remove(&model_name).unwrap_or_default();
+                self.view.label(ids!(model_alias_label)).set_text(cx, &format!("Alias for {}", model_name));
+                let alias_input = self.view.text_input(ids!(model_alias_input));
+                alias_input.set_text(cx, &alias);
+                alias_input.set_key_focus(cx);
+                self.view.view(ids!(model_alias_row)).set_visible(cx, true);
+                self.alias_model = Some(model_name);
+                self.view.redraw(cx);
+            }
+
+            // Clicking the current default clears it
+            if item.button(ids!(default_model_button)).clicked(actions) {
+                let Some(provider_id) = self.selected_provider_id.clone() else { continue };
+                let model_name = self.fetched_models[index].0.clone();
+                if let Some(store) = scope.data.get_mut::<Store>() {
+                    let is_default = store.preferences.get_provider(&provider_id)
+                        .map_or(false, |provider| provider.default_model.as_deref() == Some(model_name.as_str()));
+                    let default_model = if is_default { None } else { Some(model_name) };
+                    store.preferences.set_provider_default_model(&provider_id, default_model);
+                }
+                self.view.redraw(cx);
+            }
+        }
+    }
+
+    /// Handle the Select All toggle for models
+    fn handle_select_all_toggle(&mut self, cx: &mut Cx, scope: &mut Scope, actions: &Actions) {
+        let select_all_toggle = self.view.check_box(ids!(select_all_toggle));
+        if let Some(new_state) = select_all_toggle.changed(actions) {
+            // Set all models to the new state
+            for (_, enabled) in &mut self.fetched_models {
+                *enabled = new_state;
+            }
+
+            let states = self.fetched_models.clone();
+            self.save_models_enabled_state(scope, &states);
+
+            ::log::info!("Select All toggled: all models set to {}", new_state);
+            self.view.redraw(cx);
+        }
+    }
+
+    /// Aliases of the selected provider's models, by model name. Only models
+    /// loaded by the chat have a bot id to key an alias by.
+    fn model_aliases(&self, scope: &mut Scope) -> HashMap<String, String> {
+        let (Some(store), Some(provider_id)) = (scope.data.get::<Store>(), self.selected_provider_id.as_ref()) else {
+            return HashMap::new();
+        };
+        self.fetched_models.iter()
+            .filter_map(|(model_name, _)| {
+                let bot_id = store.providers_manager.bot_id_for_model(provider_id, model_name)?;
+                let alias = store.preferences.model_alias(bot_id.as_str())?;
+                Some((model_name.clone(), alias.to_string()))
+            })
+            .collect()
+    }
+
+    /// Save the alias typed for the model picked with its Alias button
+    fn handle_model_alias(&mut self, cx: &mut Cx, scope: &mut Scope, actions: &Actions) {
+        let alias_input = self.view.text_input(ids!(model_alias_input));
+        let submitted = alias_input.returned(actions).is_some()
+            || self.view.button(ids!(save_model_alias_button)).clicked(actions);
+        if !submitted {
+            return;
+        }
+        let (Some(model_name), Some(provider_id)) = (self.alias_model.clone(), self.selected_provider_id.clone()) else { return };
+        let Some(store) = scope.data.get_mut::<Store>() else { return };
+
+        let Some(bot_id) = store.providers_manager.bot_id_for_model(&provider_id, &model_name).cloned() else {
+            self.view.label(ids!(model_alias_label))
+                .set_text(cx, "Open the chat once so this provider's models load, then set the alias");
+            return;
+        };
+        store.preferences.set_model_alias(bot_id.as_str(), &alias_input.text());
+
+        self.alias_model = None;
+        self.view.view(ids!(model_alias_row)).set_visible(cx, false);
+        self.view.redraw(cx);
+    }
+
+    /// Indices into `fetched_models` of the models matching the filter, in order
+    fn filtered_model_indices(&self) -> Vec<usize> {
+        let filter = self.model_filter.trim().to_lowercase();
+        self.fetched_models.iter().enumerate()
+            .filter(|(_, (name, _))| filter.is_empty() || name.to_lowercase().contains(&filter))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Narrow the models list as the filter is typed, and enable or disable
+    /// every matching model at once
+    fn handle_model_filter(&mut self, cx: &mut Cx, scope: &mut Scope, actions: &Actions) {
+        if let Some(text) = self.view.text_input(ids!(model_filter_input)).changed(actions) {
+            self.model_filter = text;
+            let filtering = !self.model_filter.trim().is_empty();
+            self.view.button(ids!(enable_matching_button)).set_visible(cx, filtering);
+            self.view.button(ids!(disable_matching_button)).set_visible(cx, filtering);
+            self.view.portal_list(ids!(models_list)).set_first_id_and_scroll(0, 0.0);
+            self.view.redraw(cx);
+        }
+
+        let enable = if self.view.button(ids!(enable_matching_button)).clicked(actions) {
+            true
+        } else if self.view.button(ids!(disable_matching_button)).clicked(actions) {
+            false
+        } else {
+            return;
+        };
+
+        let matching = self.filtered_model_indices();
+        for &index in &matching {
+            self.fetched_models[index].1 = enable;
+        }
+        let states: Vec<(String, bool)> = matching.iter().map(|&index| self.fetched_models[index].clone()).collect();
+        self.save_models_enabled_state(scope, &states);
+
+        ::log::info!("{} {} models matching '{}'", if enable { "Enabled" } else { "Disabled" }, states.len(), self.model_filter);
+        self.sync_select_all_toggle(cx);
+        self.view.redraw(cx);
+    }
+
+    /// Reflect in the Select All toggle whether every fetched model is enabled
+    fn sync_select_all_toggle(&mut self, cx: &mut Cx) {
+        let all_enabled = !self.fetched_models.is_empty()
+            && self.fetched_models.iter().all(|(_, enabled)| *enabled);
+        self.view.check_box(ids!(select_all_toggle)).set_active(cx, all_enabled);
+    }
+
+    /// Save model enabled state to preferences
+    fn save_model_enabled_state(&mut self, scope: &mut Scope, model_name: &str, enabled: bool) {
+        self.save_models_enabled_state(scope, &[(model_name.to_string(), enabled)]);
+    }
+
+    /// Save the enabled state of several models, writing preferences once
+    fn save_models_enabled_state(&mut self, scope: &mut Scope, states: &[(String, bool)]) {
+        let Some(provider_id) = &self.selected_provider_id else { return };
+
+        if let Some(store) = scope.data.get_mut::<Store>() {
+            if let Some(provider) = store.preferences.get_provider_mut(provider_id) {
+                // Find and update or add each model entry
+                for (model_name, enabled) in states {
+                    if let Some(model_entry) = provider.models.iter_mut().find(|(name, _)| name == model_name) {
+                        model_entry.1 = *enabled;
+                    } else {
+                        provider.models.push((model_name.clone(), *enabled));
+                    }
+                }
+                store.preferences.save();
+            }
+        }
+    }
+
+    fn apply_dark_mode(&mut self, cx: &mut Cx2d, dark_mode: f64) {
+        self.view.apply_over(cx, live! {
+            draw_bg: { dark_mode: (dark_mode) }
+        });
+
+        // Apply to panels
+        self.view.view(ids!(providers_panel)).apply_over(cx, live!{
+            draw_bg: { dark_mode: (dark_mode) }
         });
 
         // Apply to all labels and inputs that have dark_mode
@@ -537,12 +1418,66 @@ impl SettingsApp {
             draw_bg: { dark_mode: (dark_mode) }
             draw_text: { dark_mode: (dark_mode) }
         });
+        self.view.button(ids!(reveal_key_button)).apply_over(cx, live!{
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
 
-        // Apply to test button
+        // Apply to test buttons
         self.view.button(ids!(test_button)).apply_over(cx, live!{
             draw_bg: { dark_mode: (dark_mode) }
             draw_text: { dark_mode: (dark_mode) }
         });
+        self.view.button(ids!(test_all_button)).apply_over(cx, live!{
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.label(ids!(deep_test_label)).apply_over(cx, live!{
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.label(ids!(cached_note)).apply_over(cx, live!{
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.button(ids!(refresh_models_button)).apply_over(cx, live!{
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+
+        // Apply to advanced section
+        self.view.label(ids!(allow_invalid_certs_label)).apply_over(cx, live!{
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.label(ids!(invalid_certs_warning)).apply_over(cx, live!{
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.text_input(ids!(chat_endpoint_input)).apply_over(cx, live!{
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.label(ids!(chat_endpoint_hint)).apply_over(cx, live!{
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.text_input(ids!(models_path_input)).apply_over(cx, live!{
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.label(ids!(models_path_hint)).apply_over(cx, live!{
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.text_input(ids!(timeout_input)).apply_over(cx, live!{
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.label(ids!(timeout_hint)).apply_over(cx, live!{
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.label(ids!(custom_headers_label)).apply_over(cx, live!{
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.button(ids!(add_header_button)).apply_over(cx, live!{
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
 
         // Apply to models section
         self.view.label(ids!(models_header)).apply_over(cx, live!{
@@ -551,6 +1486,23 @@ impl SettingsApp {
         self.view.label(ids!(select_all_label)).apply_over(cx, live!{
             draw_text: { dark_mode: (dark_mode) }
         });
+        self.view.text_input(ids!(model_filter_input)).apply_over(cx, live!{
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.label(ids!(model_alias_label)).apply_over(cx, live!{
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.text_input(ids!(model_alias_input)).apply_over(cx, live!{
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        for button in [self.view.button(ids!(enable_matching_button)), self.view.button(ids!(disable_matching_button)), self.view.button(ids!(save_model_alias_button))] {
+            button.apply_over(cx, live!{
+                draw_bg: { dark_mode: (dark_mode) }
+                draw_text: { dark_mode: (dark_mode) }
+            });
+        }
         self.view.view(ids!(models_scroll)).apply_over(cx, live!{
             draw_bg: { dark_mode: (dark_mode) }
         });
@@ -561,6 +1513,154 @@ impl SettingsApp {
             draw_text: { dark_mode: (dark_mode) }
         });
 
+        // Apply to favorite prompts section
+        self.view.label(ids!(general_header)).apply_over(cx, live!{
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.label(ids!(retest_on_focus_label)).apply_over(cx, live!{
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.label(ids!(confirm_large_sends_label)).apply_over(cx, live!{
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.label(ids!(send_confirmation_tokens_label)).apply_over(cx, live!{
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.text_input(ids!(send_confirmation_tokens_input)).apply_over(cx, live!{
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.label(ids!(system_prompt_label)).apply_over(cx, live!{
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.label(ids!(system_prompt_hint)).apply_over(cx, live!{
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.text_input(ids!(system_prompt_input)).apply_over(cx, live!{
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.label(ids!(empty_chat_greeting_label)).apply_over(cx, live!{
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.text_input(ids!(empty_chat_greeting_input)).apply_over(cx, live!{
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.label(ids!(suggested_prompts_label)).apply_over(cx, live!{
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.label(ids!(suggested_prompts_hint)).apply_over(cx, live!{
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.text_input(ids!(suggested_prompts_input)).apply_over(cx, live!{
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.label(ids!(default_context_window_label)).apply_over(cx, live!{
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.text_input(ids!(default_context_window_input)).apply_over(cx, live!{
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.button(ids!(export_env_button)).apply_over(cx, live!{
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.button(ids!(copy_env_button)).apply_over(cx, live!{
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.button(ids!(save_env_button)).apply_over(cx, live!{
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.button(ids!(cancel_env_button)).apply_over(cx, live!{
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.label(ids!(export_env_warning)).apply_over(cx, live!{
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.label(ids!(export_env_status)).apply_over(cx, live!{
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        for input in [ids!(retention_days_input), ids!(retention_count_input)] {
+            self.view.text_input(input).apply_over(cx, live!{
+                draw_bg: { dark_mode: (dark_mode) }
+                draw_text: { dark_mode: (dark_mode) }
+            });
+        }
+        for label in [ids!(retention_days_label), ids!(retention_count_label)] {
+            self.view.label(label).apply_over(cx, live!{
+                draw_text: { dark_mode: (dark_mode) }
+            });
+        }
+        self.view.text_input(ids!(chats_file_input)).apply_over(cx, live!{
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.label(ids!(chats_transfer_status)).apply_over(cx, live!{
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        for button in [ids!(export_chats_button), ids!(import_chats_button), ids!(clear_history_button), ids!(confirm_clear_history_button), ids!(cancel_clear_history_button)] {
+            self.view.button(button).apply_over(cx, live!{
+                draw_bg: { dark_mode: (dark_mode) }
+                draw_text: { dark_mode: (dark_mode) }
+            });
+        }
+        self.view.label(ids!(clear_history_warning)).apply_over(cx, live!{
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.text_input(ids!(clear_history_input)).apply_over(cx, live!{
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.label(ids!(clear_history_status)).apply_over(cx, live!{
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.label(ids!(proxy_enabled_label)).apply_over(cx, live!{
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.text_input(ids!(proxy_url_input)).apply_over(cx, live!{
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.text_input(ids!(proxy_username_input)).apply_over(cx, live!{
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.text_input(ids!(proxy_password_input)).apply_over(cx, live!{
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.text_input(ids!(no_proxy_input)).apply_over(cx, live!{
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.label(ids!(no_proxy_hint)).apply_over(cx, live!{
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.button(ids!(save_proxy_button)).apply_over(cx, live!{
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.label(ids!(proxy_status)).apply_over(cx, live!{
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.label(ids!(favorites_header)).apply_over(cx, live!{
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.text_input(ids!(new_favorite_input)).apply_over(cx, live!{
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+        self.view.button(ids!(add_favorite_button)).apply_over(cx, live!{
+            draw_bg: { dark_mode: (dark_mode) }
+            draw_text: { dark_mode: (dark_mode) }
+        });
+
         // Apply to modal
         self.view.view(ids!(modal_content)).apply_over(cx, live!{
             draw_bg: { dark_mode: (dark_mode) }
@@ -568,6 +1668,9 @@ impl SettingsApp {
         self.view.label(ids!(modal_title)).apply_over(cx, live!{
             draw_text: { dark_mode: (dark_mode) }
         });
+        self.view.label(ids!(modal_error)).apply_over(cx, live!{
+            draw_text: { dark_mode: (dark_mode) }
+        });
         self.view.button(ids!(close_modal_button)).apply_over(cx, live!{
             draw_bg: { dark_mode: (dark_mode) }
             draw_text: { dark_mode: (dark_mode) }
@@ -591,7 +1694,7 @@ impl SettingsApp {
     }
 
     /// Start a connection test for the currently selected provider
-    fn test_connection(&mut self, cx: &mut Cx, _scope: &mut Scope) {
+    fn test_connection(&mut self, cx: &mut Cx, scope: &mut Scope) {
         let Some(provider_id) = self.selected_provider_id.clone() else { return };
 
         // Get provider URL and API key from the current input values
@@ -607,96 +1710,182 @@ impl SettingsApp {
 
         // Update status to connecting
         self.connection_status = ProviderConnectionStatus::Connecting;
-        self.provider_statuses.insert(provider_id.clone(), ProviderConnectionStatus::Connecting);
-        self.connection_test_in_progress = true;
         self.view.label(ids!(status_message)).set_text(cx, "Testing connection...");
+        self.set_cached_note(cx, None);
+        self.view.redraw(cx);
+
+        let Some(mut provider) = scope.data.get::<Store>()
+            .and_then(|store| store.preferences.get_provider(&provider_id).cloned())
+        else {
+            return;
+        };
+
+        // Test the values being edited, so they can be checked before saving
+        provider.url = url;
+        provider.api_key = Some(api_key);
+        provider.custom_headers = self.custom_headers.clone();
+        provider.models_path = Some(self.view.text_input(ids!(models_path_input)).text());
+        // An invalid timeout is reported on save; test with the default meanwhile
+        provider.timeout_secs = self.timeout_secs_input().unwrap_or(None);
+        self.spawn_connection_test(scope, &provider);
+    }
+
+    /// Test every enabled provider concurrently, using their saved settings.
+    /// Results are routed by provider id as they arrive.
+    fn test_all_providers(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        let Some(store) = scope.data.get::<Store>() else { return };
+        // Enabled providers always have an API key
+        let providers: Vec<ProviderPreferences> = store.preferences.get_enabled_providers().into_iter()
+            .filter(|provider| !self.testing_providers.contains(&provider.id))
+            .cloned()
+            .collect();
+
+        for provider in &providers {
+            if self.selected_provider_id.as_ref() == Some(&provider.id) {
+                self.connection_status = ProviderConnectionStatus::Connecting;
+                self.view.label(ids!(status_message)).set_text(cx, "Testing connection...");
+                self.set_cached_note(cx, None);
+            }
+            self.spawn_connection_test(scope, provider);
+        }
         self.view.redraw(cx);
+    }
+
+    /// Run a connection test for `provider` on a background thread.
+    /// The result is queued in `connection_test_state`.
+    fn spawn_connection_test(&mut self, scope: &mut Scope, provider: &ProviderPreferences) {
+        let provider_id = provider.id.clone();
+        let url = provider.url.clone();
+        let api_key = provider.api_key.clone().unwrap_or_default();
+        let provider_type = provider.provider_type.clone();
+        let custom_headers = provider.custom_headers.clone();
+        let allow_invalid_certs = provider.allow_invalid_certs;
+        let models_url = provider.models_url();
+        let timeout = provider.connection_timeout();
+        // Stored model preferences, used to pick an enabled model for the deep test
+        let stored_models: HashMap<String, bool> = provider.models.iter().cloned().collect();
+        // The deep test goes through the chat endpoint, honoring any override
+        let chat_url = provider.chat_request_url();
+        let deep_test = self.deep_test_enabled;
+        let proxy = scope.data.get::<Store>()
+            .map(|store| store.preferences.proxy.clone())
+            .unwrap_or_default();
+
+        self.testing_providers.insert(provider_id.clone());
 
         // Clone shared state for the thread
         let state = self.connection_test_state.clone();
-        let provider_id_clone = provider_id.clone();
-        let url_clone = url.clone();
-        let api_key_clone = api_key.clone();
 
         // Spawn a thread to test the connection
         std::thread::spawn(move || {
-            let result = test_provider_connection(&url_clone, &api_key_clone);
+            let result = test_provider_connection(&url, models_url.as_deref(), &api_key, &provider_type, &custom_headers, allow_invalid_certs, &proxy, timeout);
 
             let test_result = match result {
-                Ok((model_count, models)) => ConnectionTestResult {
-                    provider_id: provider_id_clone,
-                    status: ProviderConnectionStatus::Connected,
-                    model_count: Some(model_count),
-                    models,
-                },
+                Ok((model_count, models, context_windows)) => {
+                    // Optionally verify that the provider also accepts chat completions
+                    let chat_check = deep_test.then(|| {
+                        let enabled: Vec<&str> = models.iter()
+                            .filter(|name| stored_models.get(*name).copied().unwrap_or(true))
+                            .map(String::as_str)
+                            .collect();
+                        match (cheapest_test_model(&enabled), &chat_url) {
+                            (Some(model), Ok(chat_url)) => {
+                                test_chat_completion(chat_url, &api_key, &provider_type, &custom_headers, model, allow_invalid_certs, &proxy, timeout)
+                            }
+                            (Some(_), Err(e)) => Err(e.clone()),
+                            (None, _) => Err("No enabled chat model to test".to_string()),
+                        }
+                    });
+
+                    ConnectionTestResult {
+                        provider_id,
+                        url,
+                        api_key,
+                        status: ProviderConnectionStatus::Connected,
+                        model_count: Some(model_count),
+                        models,
+                        context_windows,
+                        chat_check,
+                    }
+                }
                 Err(e) => ConnectionTestResult {
-                    provider_id: provider_id_clone,
+                    provider_id,
+                    url,
+                    api_key,
                     status: ProviderConnectionStatus::Error(e),
                     model_count: None,
                     models: vec![],
+                    context_windows: HashMap::new(),
+                    chat_check: None,
                 },
             };
 
-            // Store result in shared state
+            // Queue the result in shared state
             if let Ok(mut guard) = state.lock() {
-                *guard = Some(test_result);
+                guard.push(test_result);
             }
         });
     }
 
     /// Check for connection test results and update UI
     fn check_connection_test_result(&mut self, cx: &mut Cx, scope: &mut Scope) {
-        if !self.connection_test_in_progress {
+        if self.testing_providers.is_empty() {
             return;
         }
 
-        // Try to get the result from shared state
-        let result = {
+        // Take every result that has arrived from shared state
+        let results = {
             if let Ok(mut guard) = self.connection_test_state.lock() {
-                guard.take()
+                std::mem::take(&mut *guard)
             } else {
-                None
+                Vec::new()
             }
         };
 
-        if let Some(test_result) = result {
-            // Store the status for this provider (for the list indicator)
-            self.provider_statuses.insert(
-                test_result.provider_id.clone(),
-                test_result.status.clone()
-            );
+        for test_result in results {
+            // Persist as the provider's last connection status (shown by the list indicator)
+            self.testing_providers.remove(&test_result.provider_id);
+            if let Some(store) = scope.data.get_mut::<Store>() {
+                store.preferences.set_provider_connection_status(&test_result.provider_id, test_result.status.clone());
+                if test_result.status == ProviderConnectionStatus::Connected && !test_result.models.is_empty() {
+                    store.preferences.set_provider_fetched_models(&test_result.provider_id, test_result.models.clone());
+                }
+                if !test_result.context_windows.is_empty() {
+                    store.providers_manager.set_context_windows(&test_result.provider_id, test_result.context_windows.clone());
+                }
+            }
+
+            // Cache successful results; a failed test drops any stale cache
+            if test_result.status == ProviderConnectionStatus::Connected {
+                self.test_cache.insert(test_result.provider_id.clone(), CachedTestResult {
+                    url: test_result.url.clone(),
+                    api_key: test_result.api_key.clone(),
+                    model_count: test_result.model_count,
+                    models: test_result.models.clone(),
+                    tested_at: Instant::now(),
+                });
+            } else {
+                self.test_cache.remove(&test_result.provider_id);
+            }
 
             // Only apply detailed results if this is for the currently selected provider
             if self.selected_provider_id.as_ref() == Some(&test_result.provider_id) {
                 self.connection_status = test_result.status.clone();
                 self.model_count = test_result.model_count;
-                self.connection_test_in_progress = false;
-
-                // Get stored model preferences for this provider
-                let stored_models: HashMap<String, bool> = if let Some(store) = scope.data.get::<Store>() {
-                    if let Some(provider) = store.preferences.get_provider(&test_result.provider_id) {
-                        provider.models.iter().cloned().collect()
-                    } else {
-                        HashMap::new()
-                    }
-                } else {
-                    HashMap::new()
-                };
 
-                // Merge fetched models with stored enabled state
-                self.fetched_models = test_result.models.into_iter().map(|name| {
-                    // Use stored preference, default to enabled if not found
-                    let enabled = stored_models.get(&name).copied().unwrap_or(true);
-                    (name, enabled)
-                }).collect();
+                self.set_fetched_models(scope, &test_result.provider_id, test_result.models.clone());
 
                 // Update status message
                 let status_text = match &test_result.status {
                     ProviderConnectionStatus::Connected => {
-                        if let Some(count) = test_result.model_count {
-                            format!("Connected! Found {} models", count)
-                        } else {
-                            "Connected!".to_string()
+                        let count = test_result.model_count.unwrap_or(0);
+                        match &test_result.chat_check {
+                            Some(Ok(())) => format!("Models OK ({} found), chat OK", count),
+                            Some(Err(e)) => format!("Models OK ({} found), chat failed: {}", count, e),
+                            None if test_result.model_count.is_some() => {
+                                format!("Connected! Found {} models", count)
+                            }
+                            None => "Connected!".to_string(),
                         }
                     }
                     ProviderConnectionStatus::Error(e) => format!("Error: {}", e),
@@ -715,9 +1904,16 @@ impl SettingsApp {
         self.view.text_input(ids!(new_provider_name)).set_text(cx, "");
         self.view.text_input(ids!(new_provider_url)).set_text(cx, "https://api.example.com/v1");
         self.view.text_input(ids!(new_provider_key)).set_text(cx, "");
+        self.set_modal_error(cx, None);
         self.view.redraw(cx);
     }
 
+    /// Show or hide the error in the Add Provider modal
+    fn set_modal_error(&mut self, cx: &mut Cx, error: Option<&str>) {
+        self.view.label(ids!(modal_error)).set_text(cx, error.unwrap_or(""));
+        self.view.label(ids!(modal_error)).set_visible(cx, error.is_some());
+    }
+
     /// Close the Add Provider modal
     fn close_add_provider_modal(&mut self, cx: &mut Cx) {
         self.modal_visible = false;
@@ -730,42 +1926,17 @@ impl SettingsApp {
         let url = self.view.text_input(ids!(new_provider_url)).text();
         let api_key = self.view.text_input(ids!(new_provider_key)).text();
 
-        // Validate inputs
-        if name.trim().is_empty() {
-            ::log::warn!("Provider name is required");
-            return;
-        }
-        if url.trim().is_empty() {
-            ::log::warn!("Provider URL is required");
-            return;
-        }
-
-        // Generate a unique ID from the name
-        let id = name.trim().to_lowercase().replace(' ', "_");
-
-        ::log::info!("Adding new provider: id={}, name={}, url={}", id, name, url);
-
         // Add to preferences
         if let Some(store) = scope.data.get_mut::<Store>() {
-            // Check if provider already exists
-            if store.preferences.get_provider(&id).is_some() {
-                ::log::warn!("Provider with id '{}' already exists", id);
-                return;
-            }
-
-            // Create new provider
-            let mut new_provider = moly_data::ProviderPreferences::new(&id, name.trim(), url.trim());
-            new_provider.was_customly_added = true;
-            new_provider.enabled = true;
-            if !api_key.is_empty() {
-                new_provider.api_key = Some(api_key);
+            match store.preferences.add_custom_provider(&name, &url, Some(api_key)) {
+                Ok(id) => ::log::info!("New provider '{}' added successfully", id),
+                Err(e) => {
+                    ::log::warn!("{}", e);
+                    self.set_modal_error(cx, Some(&e));
+                    self.view.redraw(cx);
+                    return;
+                }
             }
-
-            // Add to preferences and save
-            store.preferences.providers_preferences.push(new_provider);
-            store.preferences.save();
-
-            ::log::info!("New provider '{}' added successfully", id);
         }
 
         // Close modal and refresh
@@ -803,24 +1974,81 @@ impl SettingsApp {
     }
 }
 
-/// Test connection to a provider by fetching models
-/// Returns (model_count, model_names) on success, or an error message on failure
-fn test_provider_connection(base_url: &str, api_key: &str) -> Result<(usize, Vec<String>), String> {
-    use reqwest::blocking::Client;
-    use std::time::Duration;
+/// Send a one-token chat request to a provider (blocking, for use from the test thread)
+///
+/// Goes to the same chat endpoint as the chat screen, in the provider's API format.
+#[allow(clippy::too_many_arguments)]
+fn test_chat_completion(
+    completions_url: &str,
+    api_key: &str,
+    provider_type: &ProviderType,
+    custom_headers: &[(String, String)],
+    model_id: &str,
+    allow_invalid_certs: bool,
+    proxy: &ProxySettings,
+    timeout: Duration,
+) -> Result<(), String> {
+    ::log::info!("Testing chat completion with model {} at {}", model_id, completions_url);
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| format!("Failed to create runtime: {}", e))?;
+
+    rt.block_on(ProvidersManager::test_chat_completion(completions_url, api_key, provider_type, custom_headers, model_id, allow_invalid_certs, proxy, timeout))
+}
 
+/// Check whether a request error was caused by TLS certificate verification
+fn is_certificate_error(error: &reqwest::Error) -> bool {
+    let mut source: Option<&dyn std::error::Error> = Some(error);
+    while let Some(err) = source {
+        let text = err.to_string().to_lowercase();
+        if text.contains("certificate") || text.contains("unknownissuer") {
+            return true;
+        }
+        source = err.source();
+    }
+    false
+}
+
+/// Test connection to a provider by fetching models
+/// Returns (model_count, model_names, context_windows) on success, or an error message on failure
+///
+/// `models_url` replaces the standard endpoint guesses when the provider sets a models path.
+/// `allow_invalid_certs` disables TLS certificate verification for this request only.
+#[allow(clippy::too_many_arguments)]
+fn test_provider_connection(
+    base_url: &str,
+    models_url: Option<&str>,
+    api_key: &str,
+    provider_type: &ProviderType,
+    custom_headers: &[(String, String)],
+    allow_invalid_certs: bool,
+    proxy: &ProxySettings,
+    timeout: Duration,
+) -> Result<(usize, Vec<String>, HashMap<String, usize>), String> {
     let base = base_url.trim_end_matches('/');
 
-    // Try multiple endpoint patterns (different providers use different paths)
-    let endpoints_to_try = [
-        format!("{}/models", base),           // OpenAI standard: /v1/models
-        format!("{}/v1/models", base),        // Some need explicit /v1
-        format!("{}", base),                  // Base URL might already include /models
-    ];
+    // Try multiple endpoint patterns (different providers use different paths).
+    // Anthropic only serves /v1/models, whose `data[].id` shape matches OpenAI's.
+    let mut endpoints_to_try = match (models_url, provider_type) {
+        (Some(models_url), _) => vec![models_url.to_string()],
+        (None, ProviderType::Anthropic) => vec![format!("{}/models", base)],
+        (None, _) => vec![
+            format!("{}/models", base),           // OpenAI standard: /v1/models
+            format!("{}/v1/models", base),        // Some need explicit /v1
+            format!("{}", base),                  // Base URL might already include /models
+        ],
+    };
+    // Ollama lists its installed models natively at /api/tags
+    if let (None, Some(tags_url)) = (models_url, ollama_tags_url(base)) {
+        endpoints_to_try.insert(0, tags_url);
+    }
 
     // Create blocking client with timeout
-    let client = Client::builder()
-        .timeout(Duration::from_secs(10))
+    let client = moly_data::http::blocking_client_builder(proxy)
+        .timeout(timeout)
+        .danger_accept_invalid_certs(allow_invalid_certs)
         .build()
         .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
 
@@ -830,16 +2058,23 @@ fn test_provider_connection(base_url: &str, api_key: &str) -> Result<(usize, Vec
         ::log::info!("Testing connection to: {}", models_url);
 
         // Make request to models endpoint
-        let response = match client
+        let mut request = client
             .get(models_url)
-            .header("Authorization", format!("Bearer {}", api_key))
-            .header("Content-Type", "application/json")
-            .send()
-        {
+            .header("Content-Type", "application/json");
+        for (name, value) in provider_type.auth_headers(api_key) {
+            request = request.header(name, value);
+        }
+        for (name, value) in custom_headers.iter().filter(|(name, _)| !name.trim().is_empty()) {
+            request = request.header(name.trim(), value.as_str());
+        }
+        let response = match moly_data::http::blocking_send_with_rate_limit_retries(request) {
             Ok(resp) => resp,
             Err(e) => {
                 last_error = if e.is_timeout() {
-                    "Connection timed out".to_string()
+                    format!("Connection timed out after {}s", timeout.as_secs())
+                } else if is_certificate_error(&e) {
+                    "TLS certificate error (self-signed or invalid certificate). \
+                     Enable \"Allow invalid TLS certificates\" under Advanced if you trust this server".to_string()
                 } else if e.is_connect() {
                     "Failed to connect to server".to_string()
                 } else {
@@ -863,7 +2098,7 @@ fn test_provider_connection(base_url: &str, api_key: &str) -> Result<(usize, Vec
             return Err(match status.as_u16() {
                 401 => "Invalid API key".to_string(),
                 403 => "Access denied".to_string(),
-                429 => "Rate limited".to_string(),
+                429 => format!("Rate limited (gave up after {} retries)", moly_data::http::MAX_RATE_LIMIT_RETRIES),
                 _ => format!("HTTP {}: {}", status.as_u16(), error_text),
             });
         }
@@ -878,18 +2113,25 @@ fn test_provider_connection(base_url: &str, api_key: &str) -> Result<(usize, Vec
         };
 
         // Try to parse as OpenAI-compatible models response
-        match serde_json::from_str::<ModelsResponse>(&body) {
-            Ok(models) => {
-                let model_names: Vec<String> = models.data.into_iter().map(|m| m.id).collect();
-                ::log::info!("Found {} models at {}", model_names.len(), models_url);
-                return Ok((model_names.len(), model_names));
-            }
-            Err(_) => {
-                // If we got a 200 but can't parse models, still consider it connected
-                ::log::warn!("Connected to {} but could not parse models response", models_url);
-                return Ok((0, vec![]));
-            }
+        if let Ok(models) = serde_json::from_str::<ModelsResponse>(&body) {
+            let context_windows: HashMap<String, usize> = models.data.iter()
+                .filter_map(|m| Some((m.id.clone(), m.context_length?)))
+                .collect();
+            let model_names: Vec<String> = models.data.into_iter().map(|m| m.id).collect();
+            ::log::info!("Found {} models at {}", model_names.len(), models_url);
+            return Ok((model_names.len(), model_names, context_windows));
         }
+
+        // Ollama's /api/tags lists `models[].name` instead
+        if let Ok(tags) = serde_json::from_str::<OllamaTagsResponse>(&body) {
+            let model_names: Vec<String> = tags.models.into_iter().map(|m| m.name).collect();
+            ::log::info!("Found {} Ollama models at {}", model_names.len(), models_url);
+            return Ok((model_names.len(), model_names, HashMap::new()));
+        }
+
+        // If we got a 200 but can't parse models, still consider it connected
+        ::log::warn!("Connected to {} but could not parse models response", models_url);
+        return Ok((0, vec![], HashMap::new()));
     }
 
     // All endpoints failed
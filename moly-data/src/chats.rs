@@ -1,33 +1,148 @@
 use chrono::{DateTime, Utc};
 use moly_kit::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::generation::GenerationParams;
 
 pub type ChatId = u128;
 
 const CHATS_DIR: &str = "chats";
 
+/// Format of `ChatData` written by this build; older files are upgraded on load
+pub const CHAT_DATA_VERSION: u32 = 1;
+
+/// File name of the all-chats export in the data directory
+const EXPORT_FILENAME: &str = "chats-export.json";
+
+const ANCHOR_PREFIX: &str = "moly://chat/";
+
+/// Positional reference to a message within a chat, e.g. `moly://chat/1712/message/12`
+///
+/// Message numbers are 1-based and derived from the message position,
+/// so anchors stay valid as long as earlier messages are not removed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MessageAnchor {
+    pub chat_id: ChatId,
+    /// 0-based index into `ChatData::messages`
+    pub message_index: usize,
+}
+
+impl MessageAnchor {
+    pub fn new(chat_id: ChatId, message_index: usize) -> Self {
+        Self { chat_id, message_index }
+    }
+
+    /// Parse an anchor link, returns None if it is malformed
+    pub fn parse(link: &str) -> Option<Self> {
+        let rest = link.trim().strip_prefix(ANCHOR_PREFIX)?;
+        let (chat_id, number) = rest.split_once("/message/")?;
+        let number: usize = number.parse().ok()?;
+        if number == 0 {
+            return None;
+        }
+        Some(Self {
+            chat_id: chat_id.parse().ok()?,
+            message_index: number - 1,
+        })
+    }
+}
+
+impl std::fmt::Display for MessageAnchor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}/message/{}", ANCHOR_PREFIX, self.chat_id, self.message_index + 1)
+    }
+}
+
+/// An MCP tool call made by an assistant message, kept with the chat so its
+/// calls can be shown again after a restart
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ToolCallRecord {
+    /// Index of the assistant message that made the call
+    pub message_index: usize,
+    pub name: String,
+    /// Arguments as JSON
+    pub arguments: String,
+    /// What the tool returned; None while the call awaits approval or execution
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<String>,
+    #[serde(default)]
+    pub is_error: bool,
+}
+
+impl ToolCallRecord {
+    /// Calls made in `messages`, each with its result if one arrived in a later message
+    pub fn from_messages(messages: &[Message]) -> Vec<Self> {
+        let mut records = Vec::new();
+        for (index, message) in messages.iter().enumerate() {
+            for call in &message.content.tool_calls {
+                let result = messages[index + 1..].iter()
+                    .flat_map(|m| m.content.tool_results.iter())
+                    .find(|result| result.tool_call_id == call.id);
+                records.push(ToolCallRecord {
+                    message_index: index,
+                    name: call.name.clone(),
+                    arguments: serde_json::to_string(&call.arguments).unwrap_or_default(),
+                    result: result.map(|result| result.content.clone()),
+                    is_error: result.is_some_and(|result| result.is_error),
+                });
+            }
+        }
+        records
+    }
+}
+
 /// Serializable chat data for persistence
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ChatData {
+    /// Format the chat was written in (0 for files from before versioning)
+    #[serde(default)]
+    pub version: u32,
     pub id: ChatId,
     pub title: String,
+    /// Whether the title was set manually (disables auto-titling)
+    #[serde(default)]
+    pub title_is_custom: bool,
     pub bot_id: Option<BotId>,
     pub messages: Vec<Message>,
     pub created_at: DateTime<Utc>,
     pub accessed_at: DateTime<Utc>,
+    /// Show messages as raw text instead of rendered markdown
+    #[serde(default)]
+    pub raw_text: bool,
+    /// Per-chat overrides of the default generation parameters
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation_params: GenerationParams,
+    /// Listed first in the history and kept regardless of the retention policy
+    #[serde(default)]
+    pub pinned: bool,
+    /// MCP tool calls made in this chat
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCallRecord>,
+    /// Throwaway chat: never written to disk, hidden from history and
+    /// discarded once another chat becomes current
+    #[serde(skip)]
+    pub ephemeral: bool,
 }
 
 impl ChatData {
     pub fn new() -> Self {
         let now = Utc::now();
         Self {
+            version: CHAT_DATA_VERSION,
             id: now.timestamp_millis() as u128,
             title: "New Chat".to_string(),
+            title_is_custom: false,
             bot_id: None,
             messages: Vec::new(),
             created_at: now,
             accessed_at: now,
+            raw_text: false,
+            generation_params: GenerationParams::default(),
+            pinned: false,
+            tool_calls: Vec::new(),
+            ephemeral: false,
         }
     }
 
@@ -36,13 +151,16 @@ impl ChatData {
         format!("{}.chat.json", self.id)
     }
 
-    /// Save this chat to disk
+    /// Save this chat to disk (a no-op for ephemeral chats)
     pub fn save(&self, chats_dir: &PathBuf) {
+        if self.ephemeral {
+            return;
+        }
         let path = chats_dir.join(self.file_name());
 
         match serde_json::to_string_pretty(self) {
             Ok(json) => {
-                if let Err(e) = std::fs::write(&path, &json) {
+                if let Err(e) = crate::files::write_atomic(&path, &json) {
                     log::error!("Failed to save chat {}: {:?}", self.id, e);
                 } else {
                     log::debug!("Saved chat {} to {:?}", self.id, path);
@@ -54,11 +172,14 @@ impl ChatData {
         }
     }
 
-    /// Load a chat from disk
+    /// Load a chat from disk, upgrading older formats
     pub fn load(path: &PathBuf) -> Option<Self> {
         match std::fs::read_to_string(path) {
             Ok(contents) => {
-                match serde_json::from_str::<ChatData>(&contents) {
+                let parsed = serde_json::from_str::<serde_json::Value>(&contents)
+                    .map_err(|e| e.to_string())
+                    .and_then(Self::from_json_value);
+                match parsed {
                     Ok(chat) => {
                         log::debug!("Loaded chat {} from {:?}", chat.id, path);
                         Some(chat)
@@ -76,6 +197,33 @@ impl ChatData {
         }
     }
 
+    /// Parse a chat of any known format, upgrading it to `CHAT_DATA_VERSION`
+    pub fn from_json_value(mut value: serde_json::Value) -> Result<Self, String> {
+        Self::migrate(&mut value)?;
+        serde_json::from_value(value).map_err(|e| e.to_string())
+    }
+
+    /// Upgrade a serialized chat in place, one format version at a time
+    fn migrate(value: &mut serde_json::Value) -> Result<(), String> {
+        let object = value.as_object_mut().ok_or("chat is not a JSON object")?;
+        let version = object.get("version").and_then(|v| v.as_u64()).unwrap_or(0);
+        if version > CHAT_DATA_VERSION as u64 {
+            log::warn!("Chat was written by a newer version (format {}), unknown fields are ignored", version);
+            return Ok(());
+        }
+
+        // 0 -> 1: fields added before versioning all have serde defaults, except
+        // that a chat needs `accessed_at`, which hand-edited files may lack
+        if version < 1 && !object.contains_key("accessed_at") {
+            if let Some(created_at) = object.get("created_at").cloned() {
+                object.insert("accessed_at".to_string(), created_at);
+            }
+        }
+
+        object.insert("version".to_string(), CHAT_DATA_VERSION.into());
+        Ok(())
+    }
+
     /// Delete the chat file from disk
     pub fn delete_file(&self, chats_dir: &PathBuf) {
         let path = chats_dir.join(self.file_name());
@@ -91,10 +239,28 @@ impl ChatData {
         self.accessed_at = Utc::now();
     }
 
+    /// Model that produced `message`, one of this chat's messages
+    ///
+    /// Assistant messages record their bot at send time in `Message::from`.
+    /// Older chats without a per-message bot fall back to the chat-level `bot_id`.
+    pub fn message_bot_id<'a>(&'a self, message: &'a Message) -> Option<&'a BotId> {
+        use moly_kit::aitk::protocol::EntityId;
+
+        match &message.from {
+            EntityId::Bot(bot_id) if !bot_id.as_str().is_empty() => Some(bot_id),
+            EntityId::Bot(_) => self.bot_id.as_ref(),
+            _ => None,
+        }
+    }
+
     /// Generate a title from the first message if title is default
     pub fn maybe_update_title_from_messages(&mut self) {
         use moly_kit::aitk::protocol::EntityId;
 
+        if self.title_is_custom {
+            return;
+        }
+
         if self.title == "New Chat" && !self.messages.is_empty() {
             // Find the first user message
             if let Some(msg) = self.messages.iter().find(|m| matches!(m.from, EntityId::User)) {
@@ -119,11 +285,42 @@ impl Default for ChatData {
     }
 }
 
+/// Which saved chats are deleted automatically when chats are loaded.
+/// Both limits are off by default; pinned chats are always kept.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChatRetention {
+    /// Delete chats not opened for this many days
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age_days: Option<u32>,
+    /// Keep at most this many unpinned chats, the most recently opened ones
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_chats: Option<usize>,
+}
+
+impl ChatRetention {
+    /// Whether any limit is set
+    pub fn is_enabled(&self) -> bool {
+        self.max_age_days.is_some() || self.max_chats.is_some()
+    }
+}
+
+/// Outcome of importing a chats export
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChatsImport {
+    pub imported: usize,
+    /// Entries whose id matches a chat that already exists
+    pub duplicates: usize,
+    /// Entries that aren't valid chats
+    pub invalid: usize,
+}
+
 /// Manages chat sessions with persistence
 pub struct Chats {
     pub saved_chats: Vec<ChatData>,
     pub current_chat_id: Option<ChatId>,
     chats_dir: PathBuf,
+    /// Bumped whenever a chat is added, removed or may have changed
+    revision: u64,
 }
 
 impl Chats {
@@ -133,20 +330,17 @@ impl Chats {
             saved_chats: Vec::new(),
             current_chat_id: None,
             chats_dir: Self::get_chats_dir(),
+            revision: 0,
         }
     }
 
-    /// Get the chats directory path (~/.moly/chats/)
+    /// Get the chats directory path (`chats/` in the data directory)
     fn get_chats_dir() -> PathBuf {
-        if let Some(home) = dirs::home_dir() {
-            home.join(".moly").join(CHATS_DIR)
-        } else {
-            PathBuf::from(CHATS_DIR)
-        }
+        crate::files::data_dir().join(CHATS_DIR)
     }
 
-    /// Load all chats from disk
-    pub fn load() -> Self {
+    /// Load all chats from disk, deleting those the retention policy expires
+    pub fn load(retention: &ChatRetention) -> Self {
         let chats_dir = Self::get_chats_dir();
         log::info!("Loading chats from {:?}", chats_dir);
 
@@ -154,6 +348,7 @@ impl Chats {
             saved_chats: Vec::new(),
             current_chat_id: None,
             chats_dir: chats_dir.clone(),
+            revision: 0,
         };
 
         // Ensure directory exists
@@ -178,6 +373,8 @@ impl Chats {
                 // Sort by accessed_at descending (most recent first)
                 chats.saved_chats.sort_by(|a, b| b.accessed_at.cmp(&a.accessed_at));
 
+                chats.prune(retention);
+
                 // Set current chat to most recently accessed
                 if let Some(first) = chats.saved_chats.first() {
                     chats.current_chat_id = Some(first.id);
@@ -197,12 +394,20 @@ impl Chats {
     }
 
     pub fn get_current_chat_mut(&mut self) -> Option<&mut ChatData> {
+        self.revision += 1;
         self.current_chat_id
             .and_then(|id| self.saved_chats.iter_mut().find(|c| c.id == id))
     }
 
+    /// Changes whenever the list of chats or any chat in it may have changed,
+    /// for views that cache what they derive from the chats
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
     /// Set the current chat and save the access time
     pub fn set_current_chat(&mut self, chat_id: Option<ChatId>) {
+        self.discard_ephemeral_chats(chat_id);
         self.current_chat_id = chat_id;
         let chats_dir = self.chats_dir.clone();
         if let Some(chat) = self.get_current_chat_mut() {
@@ -213,7 +418,19 @@ impl Chats {
 
     /// Create a new chat and save it to disk
     pub fn create_chat(&mut self, bot_id: Option<BotId>) -> ChatId {
+        self.insert_new_chat(bot_id, false)
+    }
+
+    /// Create a new ephemeral chat, kept in memory only
+    pub fn create_ephemeral_chat(&mut self, bot_id: Option<BotId>) -> ChatId {
+        self.insert_new_chat(bot_id, true)
+    }
+
+    fn insert_new_chat(&mut self, bot_id: Option<BotId>, ephemeral: bool) -> ChatId {
+        self.discard_ephemeral_chats(None);
+
         let mut chat = ChatData::new();
+        chat.ephemeral = ephemeral;
 
         // Use provided bot_id or inherit from last chat
         if let Some(bid) = bot_id {
@@ -225,16 +442,44 @@ impl Chats {
         let id = chat.id;
         chat.save(&self.chats_dir);
         self.saved_chats.insert(0, chat); // Insert at front (most recent)
+        self.revision += 1;
         self.current_chat_id = Some(id);
-        log::info!("Created new chat {}", id);
+        log::info!("Created new {}chat {}", if ephemeral { "ephemeral " } else { "" }, id);
         id
     }
 
+    /// Drop ephemeral chats other than `keep`
+    fn discard_ephemeral_chats(&mut self, keep: Option<ChatId>) {
+        self.revision += 1;
+        self.saved_chats.retain(|chat| {
+            let discard = chat.ephemeral && Some(chat.id) != keep;
+            if discard {
+                log::info!("Discarded ephemeral chat {}", chat.id);
+            }
+            !discard
+        });
+    }
+
+    /// Turn an ephemeral chat into a saved one, storing `messages` with it
+    pub fn persist_chat(&mut self, chat_id: ChatId, messages: Vec<Message>) {
+        if let Some(chat) = self.get_chat_by_id_mut(chat_id) {
+            chat.ephemeral = false;
+            log::info!("Saved ephemeral chat {}", chat_id);
+        }
+        self.update_chat_messages(chat_id, messages);
+    }
+
+    /// Whether the chat exists and is ephemeral
+    pub fn is_ephemeral(&self, chat_id: ChatId) -> bool {
+        self.get_chat_by_id(chat_id).map_or(false, |chat| chat.ephemeral)
+    }
+
     pub fn get_chat_by_id(&self, chat_id: ChatId) -> Option<&ChatData> {
         self.saved_chats.iter().find(|c| c.id == chat_id)
     }
 
     pub fn get_chat_by_id_mut(&mut self, chat_id: ChatId) -> Option<&mut ChatData> {
+        self.revision += 1;
         self.saved_chats.iter_mut().find(|c| c.id == chat_id)
     }
 
@@ -243,7 +488,10 @@ impl Chats {
         // Find and remove the chat, get it for file deletion
         if let Some(pos) = self.saved_chats.iter().position(|c| c.id == chat_id) {
             let chat = self.saved_chats.remove(pos);
-            chat.delete_file(&self.chats_dir);
+            self.revision += 1;
+            if !chat.ephemeral {
+                chat.delete_file(&self.chats_dir);
+            }
             log::info!("Deleted chat {}", chat_id);
         }
 
@@ -253,6 +501,65 @@ impl Chats {
         }
     }
 
+    /// Delete every chat from memory and disk, then start a fresh empty chat
+    /// (keeping the most recent chat's bot) so there always is a current chat
+    pub fn delete_all(&mut self) -> ChatId {
+        let bot_id = self.saved_chats.first().and_then(|chat| chat.bot_id.clone());
+        for chat in self.saved_chats.drain(..) {
+            if !chat.ephemeral {
+                chat.delete_file(&self.chats_dir);
+            }
+        }
+        self.current_chat_id = None;
+        log::info!("Deleted all chats");
+        self.create_chat(bot_id)
+    }
+
+    /// Delete saved chats that are older than the policy allows or beyond its
+    /// chat limit, from memory and disk. Returns the number of chats deleted.
+    pub fn prune(&mut self, retention: &ChatRetention) -> usize {
+        if !retention.is_enabled() {
+            return 0;
+        }
+
+        // An age beyond chrono's date range means nothing is old enough to delete
+        let cutoff = retention.max_age_days
+            .and_then(|days| Utc::now().checked_sub_signed(chrono::Duration::days(days as i64)));
+        let mut kept_unpinned = 0;
+        let mut expired = Vec::new();
+
+        // Most recently opened first, so the chat limit keeps the newest ones
+        for chat in self.get_sorted_chats() {
+            if chat.pinned {
+                continue;
+            }
+            let too_old = cutoff.is_some_and(|cutoff| chat.accessed_at < cutoff);
+            let over_limit = retention.max_chats.is_some_and(|max| kept_unpinned >= max);
+            if too_old || over_limit {
+                expired.push(chat.id);
+            } else {
+                kept_unpinned += 1;
+            }
+        }
+
+        for chat_id in &expired {
+            self.delete_chat(*chat_id);
+        }
+        if !expired.is_empty() {
+            log::info!("Pruned {} chats by the retention policy {:?}", expired.len(), retention);
+        }
+        expired.len()
+    }
+
+    /// Pin or unpin a chat, exempting it from pruning, and save
+    pub fn set_chat_pinned(&mut self, chat_id: ChatId, pinned: bool) {
+        let chats_dir = self.chats_dir.clone();
+        if let Some(chat) = self.get_chat_by_id_mut(chat_id) {
+            chat.pinned = pinned;
+            chat.save(&chats_dir);
+        }
+    }
+
     /// Save the current chat to disk
     pub fn save_current_chat(&self) {
         if let Some(chat) = self.get_current_chat() {
@@ -267,9 +574,9 @@ impl Chats {
         }
     }
 
-    /// Get chats sorted by most recently accessed
+    /// Get saved (non-ephemeral) chats sorted by most recently accessed
     pub fn get_sorted_chats(&self) -> Vec<&ChatData> {
-        let mut chats: Vec<_> = self.saved_chats.iter().collect();
+        let mut chats: Vec<_> = self.saved_chats.iter().filter(|c| !c.ephemeral).collect();
         chats.sort_by(|a, b| b.accessed_at.cmp(&a.accessed_at));
         chats
     }
@@ -283,12 +590,29 @@ impl Chats {
             for msg in &mut messages {
                 msg.metadata.is_writing = false;
             }
+            // Messages restored without their tool calls keep the recorded ones
+            let tool_calls = ToolCallRecord::from_messages(&messages);
+            if !tool_calls.is_empty() {
+                chat.tool_calls = tool_calls;
+            }
+            chat.tool_calls.retain(|call| call.message_index < messages.len());
             chat.messages = messages;
             chat.maybe_update_title_from_messages();
             chat.save(&chats_dir);
         }
     }
 
+    /// Rename a chat and save. Manually set titles are never replaced by auto-titling.
+    pub fn rename_chat(&mut self, chat_id: ChatId, title: String) {
+        let chats_dir = self.chats_dir.clone();
+        if let Some(chat) = self.get_chat_by_id_mut(chat_id) {
+            chat.title = title;
+            chat.title_is_custom = true;
+            chat.save(&chats_dir);
+            log::info!("Renamed chat {}", chat_id);
+        }
+    }
+
     /// Update a chat's bot and save
     pub fn update_chat_bot(&mut self, chat_id: ChatId, bot_id: Option<BotId>) {
         let chats_dir = self.chats_dir.clone();
@@ -298,10 +622,98 @@ impl Chats {
         }
     }
 
+    /// Switch a chat between raw and rendered messages and save
+    pub fn set_chat_raw_text(&mut self, chat_id: ChatId, raw_text: bool) {
+        let chats_dir = self.chats_dir.clone();
+        if let Some(chat) = self.get_chat_by_id_mut(chat_id) {
+            chat.raw_text = raw_text;
+            chat.save(&chats_dir);
+        }
+    }
+
+    /// Update a chat's generation parameter overrides and save
+    pub fn update_chat_generation_params(&mut self, chat_id: ChatId, params: GenerationParams) {
+        let chats_dir = self.chats_dir.clone();
+        if let Some(chat) = self.get_chat_by_id_mut(chat_id) {
+            chat.generation_params = params;
+            chat.save(&chats_dir);
+        }
+    }
+
     /// Get the chats directory path
     pub fn chats_dir(&self) -> &PathBuf {
         &self.chats_dir
     }
+
+    /// Default location of the all-chats export
+    pub fn default_export_path() -> PathBuf {
+        crate::files::data_dir().join(EXPORT_FILENAME)
+    }
+
+    /// Every saved chat as one JSON array, most recent first.
+    ///
+    /// Entries use the same format as the `.chat.json` files, so messages
+    /// keep their attachments and the export can be imported back as is.
+    pub fn export_all(&self) -> String {
+        serde_json::to_string_pretty(&self.get_sorted_chats()).unwrap_or_else(|e| {
+            log::error!("Failed to serialize chats export: {:?}", e);
+            "[]".to_string()
+        })
+    }
+
+    /// Write `export_all` to `path`, returning the number of chats exported
+    pub fn export_all_to(&self, path: &Path) -> Result<usize, String> {
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+        }
+        crate::files::write_atomic(path, self.export_all())
+            .map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+
+        let count = self.get_sorted_chats().len();
+        log::info!("Exported {} chats to {:?}", count, path);
+        Ok(count)
+    }
+
+    /// Add the chats of an export, saving each to disk.
+    ///
+    /// Every entry is checked on its own: invalid entries and chats whose id
+    /// already exists are skipped without affecting the rest.
+    pub fn import_all(&mut self, json: &str) -> Result<ChatsImport, String> {
+        let entries: Vec<serde_json::Value> = serde_json::from_str(json)
+            .map_err(|e| format!("Not a chats export: {}", e))?;
+
+        let mut known_ids: HashSet<ChatId> = self.saved_chats.iter().map(|chat| chat.id).collect();
+        let mut result = ChatsImport::default();
+        for entry in entries {
+            let chat = match ChatData::from_json_value(entry) {
+                Ok(chat) => chat,
+                Err(e) => {
+                    log::warn!("Skipping invalid chat in import: {}", e);
+                    result.invalid += 1;
+                    continue;
+                }
+            };
+            if !known_ids.insert(chat.id) {
+                result.duplicates += 1;
+                continue;
+            }
+
+            chat.save(&self.chats_dir);
+            self.saved_chats.push(chat);
+            result.imported += 1;
+        }
+
+        self.saved_chats.sort_by(|a, b| b.accessed_at.cmp(&a.accessed_at));
+        self.revision += 1;
+        log::info!("Imported chats: {:?}", result);
+        Ok(result)
+    }
+
+    /// Read an export from `path` and import it
+    pub fn import_all_from(&mut self, path: &Path) -> Result<ChatsImport, String> {
+        let json = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        self.import_all(&json)
+    }
 }
 
 impl Default for Chats {
@@ -309,3 +721,59 @@ impl Default for Chats {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A chat as written before format versioning
+    fn v0_chat() -> serde_json::Value {
+        json!({
+            "id": 1700000000000u64,
+            "title": "Trip planning",
+            "bot_id": null,
+            "messages": [],
+            "created_at": "2024-03-01T10:00:00Z",
+            "accessed_at": "2024-03-02T12:30:00Z"
+        })
+    }
+
+    #[test]
+    fn migrates_v0_chat() {
+        let chat = ChatData::from_json_value(v0_chat()).unwrap();
+
+        assert_eq!(chat.version, CHAT_DATA_VERSION);
+        assert_eq!(chat.id, 1700000000000);
+        assert_eq!(chat.title, "Trip planning");
+        assert_eq!(chat.accessed_at.to_rfc3339(), "2024-03-02T12:30:00+00:00");
+        assert!(!chat.title_is_custom);
+        assert!(!chat.raw_text);
+        assert!(!chat.pinned);
+        assert!(chat.generation_params.is_empty());
+    }
+
+    #[test]
+    fn migrates_v0_chat_without_accessed_at() {
+        let mut value = v0_chat();
+        value.as_object_mut().unwrap().remove("accessed_at");
+
+        let chat = ChatData::from_json_value(value).unwrap();
+
+        assert_eq!(chat.version, CHAT_DATA_VERSION);
+        assert_eq!(chat.accessed_at, chat.created_at);
+    }
+
+    #[test]
+    fn keeps_chats_from_newer_formats() {
+        let mut value = v0_chat();
+        let object = value.as_object_mut().unwrap();
+        object.insert("version".to_string(), json!(CHAT_DATA_VERSION + 1));
+        object.insert("field_from_the_future".to_string(), json!(true));
+
+        let chat = ChatData::from_json_value(value).unwrap();
+
+        assert_eq!(chat.version, CHAT_DATA_VERSION + 1);
+        assert_eq!(chat.title, "Trip planning");
+    }
+}
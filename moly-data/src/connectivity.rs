@@ -0,0 +1,146 @@
+//! Network reachability
+//!
+//! A quick TCP connect to a few well-known public hosts tells whether the
+//! machine is online, so requests to remote providers can be skipped instead
+//! of failing one by one. With a proxy enabled, the proxy is probed instead,
+//! since direct connections are often blocked behind one. Providers on this
+//! machine or the local network (see `is_local_url`), and Moly Server, are
+//! used regardless.
+
+use makepad_widgets::Cx;
+use std::net::{IpAddr, SocketAddr, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::http::ProxySettings;
+use crate::store::StoreAction;
+
+/// Time between reachability checks
+pub const RECHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long each probe waits for a connection
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Public DNS resolvers, reachable by address so the probe works without DNS
+const PROBE_ADDRS: &[&str] = &["1.1.1.1:443", "8.8.8.8:53", "9.9.9.9:443"];
+
+/// Whether any probe address (or the proxy, if one is enabled) accepts a connection
+pub fn probe_online(proxy: &ProxySettings) -> bool {
+    let addrs: Vec<SocketAddr> = if proxy.enabled && !proxy.url.trim().is_empty() {
+        proxy_addrs(&proxy.url)
+    } else {
+        PROBE_ADDRS.iter().filter_map(|addr| addr.parse().ok()).collect()
+    };
+    addrs.iter().any(|addr| TcpStream::connect_timeout(addr, PROBE_TIMEOUT).is_ok())
+}
+
+/// Whether a URL points at this machine or the local network, e.g. Ollama or
+/// LM Studio, which stay reachable without internet access
+pub fn is_local_url(url: &str) -> bool {
+    let Ok(url) = reqwest::Url::parse(url.trim()) else { return false };
+    let Some(host) = url.host_str() else { return false };
+    // IPv6 hosts keep their brackets in URLs
+    if let Ok(ip) = host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        return is_local_ip(ip);
+    }
+    // Single-label names (e.g. a LAN hostname) resolve locally too
+    let host = host.to_lowercase();
+    host == "localhost" || host.ends_with(".localhost") || host.ends_with(".local") || !host.contains('.')
+}
+
+fn is_local_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => ip.is_loopback() || ip.is_private() || ip.is_link_local(),
+        // Loopback, unique local (fc00::/7) and link-local (fe80::/10)
+        IpAddr::V6(ip) => ip.is_loopback()
+            || (ip.segments()[0] & 0xfe00) == 0xfc00
+            || (ip.segments()[0] & 0xffc0) == 0xfe80,
+    }
+}
+
+/// Socket addresses of a proxy URL; empty if it can't be parsed or resolved
+fn proxy_addrs(url: &str) -> Vec<SocketAddr> {
+    let Ok(url) = reqwest::Url::parse(url.trim()) else { return Vec::new() };
+    let Some(host) = url.host_str() else { return Vec::new() };
+    let port = url.port_or_known_default().unwrap_or(1080);
+    (host, port).to_socket_addrs().map(Iterator::collect).unwrap_or_default()
+}
+
+/// Shared state of the connectivity monitor
+struct ConnectivityInner {
+    online: bool,
+    proxy: ProxySettings,
+    monitoring: bool,
+}
+
+/// Tracks whether the network is reachable, rechecked in the background
+#[derive(Clone)]
+pub struct Connectivity {
+    inner: Arc<Mutex<ConnectivityInner>>,
+}
+
+impl Default for Connectivity {
+    fn default() -> Self {
+        Self::new(&ProxySettings::default())
+    }
+}
+
+impl Connectivity {
+    /// Assumes online until the first check says otherwise
+    pub fn new(proxy: &ProxySettings) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(ConnectivityInner {
+                online: true,
+                proxy: proxy.clone(),
+                monitoring: false,
+            })),
+        }
+    }
+
+    /// Whether the last check found the network reachable
+    pub fn is_online(&self) -> bool {
+        self.inner.lock().unwrap().online
+    }
+
+    /// Probe through a new proxy from the next check on
+    pub fn set_proxy(&self, proxy: &ProxySettings) {
+        self.inner.lock().unwrap().proxy = proxy.clone();
+    }
+
+    /// Set the state, posting `StoreAction::ConnectivityChanged` if it changed.
+    /// Callable from background threads.
+    fn set_online(&self, online: bool) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.online == online {
+            return;
+        }
+        inner.online = online;
+        log::info!("Network is {}", if online { "reachable again" } else { "unreachable" });
+        // Posted under the lock so concurrent transitions arrive in order
+        Cx::post_action(StoreAction::ConnectivityChanged(online));
+    }
+
+    /// Check now and then every `RECHECK_INTERVAL` on a background thread.
+    /// Calling it again while monitoring does nothing.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn start_monitoring(&self) {
+        {
+            let mut inner = self.inner.lock().unwrap();
+            if inner.monitoring {
+                return;
+            }
+            inner.monitoring = true;
+        }
+
+        let connectivity = self.clone();
+        std::thread::spawn(move || loop {
+            let proxy = connectivity.inner.lock().unwrap().proxy.clone();
+            connectivity.set_online(probe_online(&proxy));
+            std::thread::sleep(RECHECK_INTERVAL);
+        });
+    }
+
+    /// Connectivity monitoring is not supported on wasm; always online
+    #[cfg(target_arch = "wasm32")]
+    pub fn start_monitoring(&self) {}
+}
@@ -0,0 +1,366 @@
+//! Active download tracking
+//!
+//! Downloads are owned by the Store so they survive view changes and can be
+//! restored from Moly Server on startup.
+
+use moly_protocol::data::{FileId, PendingDownload, PendingDownloadsStatus};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Progress samples kept per download; the rate is averaged over this window
+const RATE_SAMPLES: usize = 8;
+
+/// Retries offered for a failed download before it's reported as failed for good
+pub const MAX_DOWNLOAD_RETRIES: u32 = 3;
+
+/// Download state for a file
+#[derive(Clone, Debug)]
+pub struct DownloadState {
+    pub file_id: FileId,
+    pub model_name: String,
+    pub file_name: String,
+    pub progress: f64,
+    pub status: PendingDownloadsStatus,
+    /// Size of the file, when Moly Server reports it
+    pub total_bytes: Option<u64>,
+    /// Times the download was retried after failing
+    pub retries: u32,
+    /// Recent (time, progress) samples, oldest first
+    samples: VecDeque<(Instant, f64)>,
+}
+
+impl DownloadState {
+    /// Create a download state for a download that was just requested
+    pub fn initializing(file_id: FileId, model_name: String, file_name: String) -> Self {
+        Self {
+            file_id,
+            model_name,
+            file_name,
+            progress: 0.0,
+            status: PendingDownloadsStatus::Initializing,
+            total_bytes: None,
+            retries: 0,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Map a pending download reported by Moly Server
+    pub fn from_pending(download: &PendingDownload) -> Self {
+        Self {
+            file_id: download.file.id.clone(),
+            model_name: download.model.name.clone(),
+            file_name: download.file.name.clone(),
+            progress: download.progress,
+            status: download.status.clone(),
+            total_bytes: file_size_bytes(download),
+            retries: 0,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Whether the download is paused
+    pub fn is_paused(&self) -> bool {
+        matches!(self.status, PendingDownloadsStatus::Paused)
+    }
+
+    /// Record a polled progress value for the rate estimate
+    fn record_progress(&mut self, progress: f64, at: Instant) {
+        self.progress = progress;
+        self.samples.push_back((at, progress));
+        while self.samples.len() > RATE_SAMPLES {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Transfer rate averaged over the recent samples, if it can be measured
+    pub fn bytes_per_second(&self) -> Option<f64> {
+        let total_bytes = self.total_bytes? as f64;
+        let (first_at, first_progress) = self.samples.front()?;
+        let (last_at, last_progress) = self.samples.back()?;
+        let elapsed = last_at.duration_since(*first_at).as_secs_f64();
+        if elapsed <= 0.0 {
+            return None;
+        }
+        Some(((last_progress - first_progress) * total_bytes / elapsed).max(0.0))
+    }
+
+    /// Estimated time left at the current rate
+    pub fn time_left(&self) -> Option<Duration> {
+        let rate = self.bytes_per_second().filter(|rate| *rate > 0.0)?;
+        let remaining = (1.0 - self.progress).max(0.0) * self.total_bytes? as f64;
+        Some(Duration::from_secs_f64(remaining / rate))
+    }
+
+    /// Whether the download failed and may be retried
+    pub fn can_retry(&self) -> bool {
+        matches!(self.status, PendingDownloadsStatus::Error) && self.retries < MAX_DOWNLOAD_RETRIES
+    }
+
+    /// Whether this download should be restored after a restart
+    pub fn is_resumable(&self) -> bool {
+        !matches!(self.status, PendingDownloadsStatus::Error)
+    }
+}
+
+/// Files of one model downloaded together, e.g. the shards of a large model.
+/// The group completes once every file in it has finished.
+#[derive(Clone, Debug)]
+pub struct DownloadGroup {
+    pub model_id: String,
+    pub model_name: String,
+    /// Every file in the group, in the model's order
+    pub file_ids: Vec<FileId>,
+    /// Files that finished downloading
+    pub finished: HashSet<FileId>,
+    /// Files Moly Server has listed as pending at least once. Only these can
+    /// finish: a file that drops out before it was ever listed may not have started.
+    reported: HashSet<FileId>,
+}
+
+impl DownloadGroup {
+    /// Whether every file in the group has finished
+    pub fn is_complete(&self) -> bool {
+        self.file_ids.iter().all(|id| self.finished.contains(id))
+    }
+
+    /// Overall progress from 0 to 1, each file counting equally. Files that
+    /// haven't started count as 0.
+    pub fn progress(&self, active: &[DownloadState]) -> f64 {
+        if self.file_ids.is_empty() {
+            return 0.0;
+        }
+        let total: f64 = self.file_ids.iter().map(|id| {
+            if self.finished.contains(id) {
+                1.0
+            } else {
+                active.iter().find(|state| &state.file_id == id).map_or(0.0, |state| state.progress.clamp(0.0, 1.0))
+            }
+        }).sum();
+        total / self.file_ids.len() as f64
+    }
+}
+
+/// Shared map of active downloads (file_id -> download state)
+///
+/// Cloning shares the same underlying map, so background tasks can update it.
+#[derive(Clone, Default)]
+pub struct Downloads {
+    inner: Arc<Mutex<HashMap<FileId, DownloadState>>>,
+    /// Download groups by model id. Locked after `inner` when both are needed
+    groups: Arc<Mutex<HashMap<String, DownloadGroup>>>,
+}
+
+impl Downloads {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether there are no active downloads
+    pub fn is_empty(&self) -> bool {
+        self.inner.lock().unwrap().is_empty()
+    }
+
+    /// Number of active downloads
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().len()
+    }
+
+    /// Whether a file is currently being downloaded
+    pub fn contains(&self, file_id: &FileId) -> bool {
+        self.inner.lock().unwrap().contains_key(file_id)
+    }
+
+    /// Get the state of a single download
+    pub fn get(&self, file_id: &FileId) -> Option<DownloadState> {
+        self.inner.lock().unwrap().get(file_id).cloned()
+    }
+
+    /// Snapshot of all active downloads
+    pub fn list(&self) -> Vec<DownloadState> {
+        self.inner.lock().unwrap().values().cloned().collect()
+    }
+
+    /// Track a new download
+    pub fn insert(&self, state: DownloadState) {
+        self.inner.lock().unwrap().insert(state.file_id.clone(), state);
+    }
+
+    /// Set the status of a tracked download
+    pub fn set_status(&self, file_id: &FileId, status: PendingDownloadsStatus) {
+        if let Some(state) = self.inner.lock().unwrap().get_mut(file_id) {
+            state.status = status;
+            // Time spent paused must not count toward the rate
+            state.samples.clear();
+        }
+    }
+
+    /// Mark a failed download as starting again. Returns false if it isn't
+    /// tracked or has used up its retries.
+    pub fn retry(&self, file_id: &FileId) -> bool {
+        let mut active = self.inner.lock().unwrap();
+        let Some(state) = active.get_mut(file_id).filter(|state| state.can_retry()) else {
+            return false;
+        };
+        state.retries += 1;
+        state.status = PendingDownloadsStatus::Initializing;
+        state.samples.clear();
+        true
+    }
+
+    /// Track files of a model as one group. Files already in a group for the
+    /// model are kept, so starting the rest of a model's files extends it.
+    pub fn start_group(&self, model_id: String, model_name: String, file_ids: Vec<FileId>) {
+        let mut groups = self.groups.lock().unwrap();
+        let group = groups.entry(model_id.clone()).or_insert_with(|| DownloadGroup {
+            model_id,
+            model_name,
+            file_ids: Vec::new(),
+            finished: HashSet::new(),
+            reported: HashSet::new(),
+        });
+        for file_id in file_ids {
+            if !group.file_ids.contains(&file_id) {
+                group.file_ids.push(file_id);
+            }
+        }
+    }
+
+    /// Snapshot of the groups still downloading
+    pub fn groups(&self) -> Vec<DownloadGroup> {
+        self.groups.lock().unwrap().values().cloned().collect()
+    }
+
+    /// Take a file that failed to start out of its group, so the group can
+    /// still complete with the remaining files
+    pub fn leave_group(&self, file_id: &FileId) {
+        let mut groups = self.groups.lock().unwrap();
+        for group in groups.values_mut() {
+            group.file_ids.retain(|id| id != file_id);
+        }
+        groups.retain(|_, group| !group.file_ids.is_empty());
+    }
+
+    /// Sync with the pending downloads reported by Moly Server.
+    /// Downloads no longer reported are considered finished and removed,
+    /// except paused ones, which keep their last progress until resumed.
+    /// Returns the groups whose last file just finished.
+    pub fn update_from_pending(&self, downloads: &[PendingDownload]) -> Vec<DownloadGroup> {
+        let mut active = self.inner.lock().unwrap();
+        let now = Instant::now();
+
+        for download in downloads {
+            let file_id = download.file.id.clone();
+            if let Some(state) = active.get_mut(&file_id) {
+                if state.is_paused() {
+                    continue;
+                }
+                state.record_progress(download.progress, now);
+                state.status = download.status.clone();
+                if state.total_bytes.is_none() {
+                    state.total_bytes = file_size_bytes(download);
+                }
+            } else {
+                let mut state = DownloadState::from_pending(download);
+                state.record_progress(download.progress, now);
+                active.insert(file_id, state);
+            }
+        }
+
+        let active_ids: Vec<_> = downloads.iter().map(|d| d.file.id.clone()).collect();
+        active.retain(|id, state| state.is_paused() || active_ids.contains(id));
+
+        // Map the reported files back to their groups
+        let mut groups = self.groups.lock().unwrap();
+        for group in groups.values_mut() {
+            for file_id in &group.file_ids {
+                if active_ids.contains(file_id) {
+                    group.reported.insert(file_id.clone());
+                } else if group.reported.contains(file_id) && !active.contains_key(file_id) {
+                    group.finished.insert(file_id.clone());
+                }
+            }
+        }
+
+        let completed: Vec<DownloadGroup> = groups.values().filter(|group| group.is_complete()).cloned().collect();
+        groups.retain(|_, group| !group.is_complete());
+        completed
+    }
+
+    /// Restore in-progress and paused downloads after a restart.
+    /// Returns the number of restored downloads.
+    pub fn restore_from_pending(&self, downloads: &[PendingDownload]) -> usize {
+        self.restore(downloads.iter().map(DownloadState::from_pending))
+    }
+
+    /// Track the resumable downloads among `states` that aren't tracked yet
+    fn restore(&self, states: impl IntoIterator<Item = DownloadState>) -> usize {
+        let mut active = self.inner.lock().unwrap();
+        let mut restored = 0;
+
+        for state in states {
+            if state.is_resumable() && !active.contains_key(&state.file_id) {
+                active.insert(state.file_id.clone(), state);
+                restored += 1;
+            }
+        }
+
+        restored
+    }
+}
+
+/// Size of a pending download's file in bytes. Moly Server reports it as a decimal string.
+fn file_size_bytes(download: &PendingDownload) -> Option<u64> {
+    download.file.size.trim().parse().ok().filter(|bytes| *bytes > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(file_id: &str, status: PendingDownloadsStatus, progress: f64) -> DownloadState {
+        let mut state = DownloadState::initializing(FileId::from(file_id), "Model".to_string(), format!("{}.gguf", file_id));
+        state.status = status;
+        state.progress = progress;
+        state
+    }
+
+    #[test]
+    fn restores_paused_and_in_progress_downloads() {
+        let downloads = Downloads::new();
+        let restored = downloads.restore(vec![
+            state("paused", PendingDownloadsStatus::Paused, 0.4),
+            state("downloading", PendingDownloadsStatus::Downloading, 0.7),
+            state("initializing", PendingDownloadsStatus::Initializing, 0.0),
+        ]);
+
+        assert_eq!(restored, 3);
+        let paused = downloads.get(&FileId::from("paused")).unwrap();
+        assert!(paused.is_paused());
+        assert_eq!(paused.progress, 0.4);
+        let downloading = downloads.get(&FileId::from("downloading")).unwrap();
+        assert!(matches!(downloading.status, PendingDownloadsStatus::Downloading));
+        assert_eq!(downloading.progress, 0.7);
+        assert!(downloads.contains(&FileId::from("initializing")));
+    }
+
+    #[test]
+    fn skips_errored_downloads() {
+        let downloads = Downloads::new();
+        let restored = downloads.restore(vec![state("failed", PendingDownloadsStatus::Error, 0.2)]);
+
+        assert_eq!(restored, 0);
+        assert!(downloads.is_empty());
+    }
+
+    #[test]
+    fn keeps_downloads_already_tracked() {
+        let downloads = Downloads::new();
+        downloads.insert(state("file", PendingDownloadsStatus::Downloading, 0.9));
+
+        let restored = downloads.restore(vec![state("file", PendingDownloadsStatus::Paused, 0.1)]);
+
+        assert_eq!(restored, 0);
+        assert_eq!(downloads.get(&FileId::from("file")).unwrap().progress, 0.9);
+    }
+}
@@ -0,0 +1,117 @@
+//! File helpers shared by the stores that persist to disk
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Environment variable that overrides the data directory
+pub const DATA_DIR_ENV: &str = "MOLY_DATA_DIR";
+
+/// Directory holding preferences (including the MCP config), chats and the model cache.
+///
+/// Defaults to `~/.moly`. Set `MOLY_DATA_DIR` to use another directory, e.g. for
+/// testing or when the home directory isn't where data should live. Falls back to
+/// `.moly` in the working directory if no home directory is known.
+pub fn data_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os(DATA_DIR_ENV).filter(|dir| !dir.is_empty()) {
+        return PathBuf::from(dir);
+    }
+    match dirs::home_dir() {
+        Some(home) => home.join(".moly"),
+        None => PathBuf::from(".moly"),
+    }
+}
+
+/// Replace `path` with `contents` without ever leaving it half-written.
+///
+/// The data goes to a temporary file next to the target, is flushed to disk,
+/// then renamed over the target. The rename is atomic on the same filesystem,
+/// so a crash mid-write leaves the previous copy intact.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+    write_atomic_with(path, contents.as_ref(), |from, to| std::fs::rename(from, to))
+}
+
+/// `write_atomic` with the final rename step supplied by the caller
+fn write_atomic_with(
+    path: &Path,
+    contents: &[u8],
+    rename: impl FnOnce(&Path, &Path) -> std::io::Result<()>,
+) -> std::io::Result<()> {
+    let temp_path = temp_path_for(path);
+
+    let result = (|| {
+        let mut file = std::fs::File::create(&temp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        rename(&temp_path, path)
+    })();
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Hidden sibling of `path`, e.g. `chats/.123.chat.json.tmp`
+fn temp_path_for(path: &Path) -> PathBuf {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.tmp", file_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Empty scratch directory for one test
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("moly-files-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn replaces_the_file() {
+        let dir = scratch_dir("replace");
+        let path = dir.join("data.json");
+        std::fs::write(&path, "old").unwrap();
+
+        write_atomic(&path, "new").unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+        assert!(!temp_path_for(&path).exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failed_temp_write_keeps_the_original() {
+        let dir = scratch_dir("temp-write");
+        let path = dir.join("data.json");
+        std::fs::write(&path, "old").unwrap();
+        // A directory where the temporary file goes makes creating it fail
+        std::fs::create_dir(temp_path_for(&path)).unwrap();
+
+        assert!(write_atomic(&path, "new").is_err());
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "old");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failed_rename_keeps_the_original() {
+        let dir = scratch_dir("rename");
+        let path = dir.join("data.json");
+        std::fs::write(&path, "old").unwrap();
+
+        let result = write_atomic_with(&path, b"new", |_, _| {
+            Err(std::io::Error::new(std::io::ErrorKind::Other, "rename failed"))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "old");
+        assert!(!temp_path_for(&path).exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
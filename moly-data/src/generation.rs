@@ -0,0 +1,63 @@
+//! Generation parameters
+//!
+//! Sampling settings sent along with chat requests. Every field is optional:
+//! an unset field is left out of the request so the provider's own default
+//! applies. Defaults live in `Preferences`, and each chat can override them.
+//!
+//! The chat screen hands the parameters of the current chat to the
+//! `ProvidersManager`, whose clients merge `request_fields` into every chat
+//! request body.
+
+use serde::{Deserialize, Serialize};
+
+use crate::providers::ProviderType;
+
+/// Sampling settings for chat requests
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct GenerationParams {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+}
+
+impl GenerationParams {
+    /// Whether no parameter is set
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// These parameters with any field set in `overrides` replaced
+    pub fn overridden_by(&self, overrides: &GenerationParams) -> GenerationParams {
+        GenerationParams {
+            temperature: overrides.temperature.or(self.temperature),
+            top_p: overrides.top_p.or(self.top_p),
+            max_tokens: overrides.max_tokens.or(self.max_tokens),
+        }
+    }
+
+    /// Request body fields for a provider
+    ///
+    /// Values the provider doesn't accept are left out rather than sent and
+    /// rejected, e.g. Anthropic only takes temperatures up to 1.0.
+    pub fn request_fields(&self, provider_type: &ProviderType) -> serde_json::Map<String, serde_json::Value> {
+        let max_temperature = match provider_type {
+            ProviderType::Anthropic => 1.0,
+            _ => 2.0,
+        };
+
+        let mut fields = serde_json::Map::new();
+        if let Some(temperature) = self.temperature.filter(|t| (0.0..=max_temperature).contains(t)) {
+            fields.insert("temperature".to_string(), temperature.into());
+        }
+        if let Some(top_p) = self.top_p.filter(|p| (0.0..=1.0).contains(p)) {
+            fields.insert("top_p".to_string(), top_p.into());
+        }
+        if let Some(max_tokens) = self.max_tokens.filter(|&n| n > 0) {
+            fields.insert("max_tokens".to_string(), max_tokens.into());
+        }
+        fields
+    }
+}
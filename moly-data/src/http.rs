@@ -0,0 +1,354 @@
+//! Shared HTTP client construction
+//!
+//! All `reqwest` clients created by Moly go through these builders so that
+//! network settings such as the proxy are applied consistently. Without a
+//! configured proxy, reqwest falls back to the standard `HTTP_PROXY`,
+//! `HTTPS_PROXY` and `NO_PROXY` environment variables.
+//!
+//! Provider clients (`ProviderClient`, used for listing models and chatting)
+//! are built on `client_builder` too. The configured proxy is never exported to
+//! the environment: changing the environment of a running process is unsound
+//! with other threads reading it, and it would hand the proxy credentials to
+//! every child process, such as MCP servers.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Retries of a request answered with HTTP 429 before the error is returned
+pub const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+
+/// Longest wait before retrying a rate-limited request
+const MAX_RATE_LIMIT_DELAY: Duration = Duration::from_secs(60);
+
+/// HTTP/HTTPS or SOCKS5 proxy used for all outbound requests
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProxySettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Proxy URL, e.g. `http://proxy.corp:8080` or `socks5://proxy.corp:1080`
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Comma-separated hosts that bypass the proxy (`NO_PROXY` format)
+    #[serde(default = "default_no_proxy")]
+    pub no_proxy: String,
+}
+
+fn default_no_proxy() -> String {
+    "localhost,127.0.0.1,::1".to_string()
+}
+
+impl Default for ProxySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: String::new(),
+            username: None,
+            password: None,
+            no_proxy: default_no_proxy(),
+        }
+    }
+}
+
+impl ProxySettings {
+    /// Check the proxy URL is usable. Disabled settings are always valid.
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let url = reqwest::Url::parse(self.url.trim())
+            .map_err(|e| format!("Invalid proxy URL: {}", e))?;
+        if !matches!(url.scheme(), "http" | "https" | "socks5" | "socks5h") {
+            return Err(format!("Unsupported proxy scheme: {}", url.scheme()));
+        }
+        if url.host_str().map_or(true, |h| h.is_empty()) {
+            return Err("Proxy URL is missing a host".to_string());
+        }
+        Ok(())
+    }
+
+    fn credentials(&self) -> Option<(&str, &str)> {
+        let username = self.username.as_deref().filter(|u| !u.is_empty())?;
+        Some((username, self.password.as_deref().unwrap_or("")))
+    }
+
+    /// Build the reqwest proxy, or None when disabled
+    fn to_proxy(&self) -> Option<reqwest::Proxy> {
+        if !self.enabled || self.validate().is_err() {
+            return None;
+        }
+
+        let mut proxy = match reqwest::Proxy::all(self.url.trim()) {
+            Ok(proxy) => proxy,
+            Err(e) => {
+                log::error!("Failed to configure proxy: {}", e);
+                return None;
+            }
+        };
+        if let Some((username, password)) = self.credentials() {
+            proxy = proxy.basic_auth(username, password);
+        }
+        Some(proxy.no_proxy(reqwest::NoProxy::from_string(&self.no_proxy)))
+    }
+}
+
+/// Async client builder with the proxy applied
+pub fn client_builder(proxy: &ProxySettings) -> reqwest::ClientBuilder {
+    let builder = reqwest::Client::builder();
+    match proxy.to_proxy() {
+        Some(proxy) => builder.proxy(proxy),
+        None => builder,
+    }
+}
+
+/// Blocking client builder with the proxy applied
+#[cfg(not(target_arch = "wasm32"))]
+pub fn blocking_client_builder(proxy: &ProxySettings) -> reqwest::blocking::ClientBuilder {
+    let builder = reqwest::blocking::Client::builder();
+    match proxy.to_proxy() {
+        Some(proxy) => builder.proxy(proxy),
+        None => builder,
+    }
+}
+
+/// Wait before retry `attempt` (0-based) of a rate-limited request.
+///
+/// Uses the server's `Retry-After` value (seconds or an HTTP date) when given,
+/// otherwise backs off exponentially from one second. Capped at a minute.
+pub fn rate_limit_delay(retry_after: Option<&str>, attempt: u32) -> Duration {
+    let requested = retry_after.and_then(|value| {
+        let value = value.trim();
+        value.parse::<u64>().ok().map(Duration::from_secs).or_else(|| {
+            let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+            (date.with_timezone(&chrono::Utc) - chrono::Utc::now()).to_std().ok()
+        })
+    });
+    requested
+        .unwrap_or_else(|| Duration::from_secs(1 << attempt.min(6)))
+        .min(MAX_RATE_LIMIT_DELAY)
+}
+
+/// Whether an error reported by a client (e.g. aitk's chat clients, which only
+/// give us the message) means the provider rate-limited the request. A 429
+/// only counts as a reported status, not as any number in the message
+pub fn is_rate_limit_error(message: &str) -> bool {
+    let lower = message.to_lowercase();
+    lower.contains("too many requests") || lower.contains("rate limit")
+        || reported_statuses(message).contains(&429)
+}
+
+/// Whether a client error means the provider can't be used right now: the
+/// request never reached it, or it rejected the API key with a reported 401
+/// or 403 (e.g. an expired key). Other mentions of keys or numbers don't count.
+pub fn is_connection_error(message: &str) -> bool {
+    transport_error_kind(message).is_some()
+        || reported_statuses(message).iter().any(|status| matches!(status, 401 | 403))
+}
+
+/// Whether a client error is likely to pass on its own: the request failed
+/// in transport, or the server answered with a reported 5xx status
+pub fn is_transient_error(message: &str) -> bool {
+    transport_error_kind(message).is_some()
+        || reported_statuses(message).iter().any(|status| (500..600).contains(status))
+}
+
+/// How a request failed before any response arrived
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TransportErrorKind {
+    /// The host name couldn't be resolved
+    Dns,
+    /// The connection couldn't be established
+    Connect,
+    /// The connection was dropped mid-request
+    Reset,
+    /// No answer arrived in time
+    Timeout,
+}
+
+/// The transport failure a client error message reports, recognized by the
+/// wording reqwest, hyper and the OS use for it ("error trying to connect",
+/// "Connection refused (os error 111)", "operation timed out"), so a word
+/// such as "connect" or "timeout" elsewhere in a message doesn't count
+fn transport_error_kind(message: &str) -> Option<TransportErrorKind> {
+    const KINDS: &[(&str, TransportErrorKind)] = &[
+        ("dns error", TransportErrorKind::Dns),
+        ("failed to lookup address", TransportErrorKind::Dns),
+        ("name or service not known", TransportErrorKind::Dns),
+        ("no such host is known", TransportErrorKind::Dns),
+        ("error trying to connect", TransportErrorKind::Connect),
+        ("tcp connect error", TransportErrorKind::Connect),
+        ("could not connect to", TransportErrorKind::Connect),
+        ("connection refused", TransportErrorKind::Connect),
+        ("network is unreachable", TransportErrorKind::Connect),
+        ("host is unreachable", TransportErrorKind::Connect),
+        ("no route to host", TransportErrorKind::Connect),
+        ("connection reset", TransportErrorKind::Reset),
+        ("connection aborted", TransportErrorKind::Reset),
+        ("connection closed before message completed", TransportErrorKind::Reset),
+        ("broken pipe", TransportErrorKind::Reset),
+        ("operation timed out", TransportErrorKind::Timeout),
+        ("connection timed out", TransportErrorKind::Timeout),
+        ("request timed out", TransportErrorKind::Timeout),
+        ("deadline has elapsed", TransportErrorKind::Timeout),
+    ];
+    let message = message.to_lowercase();
+    KINDS.iter().find(|(wording, _)| message.contains(wording)).map(|&(_, kind)| kind)
+}
+
+/// HTTP status codes a client error message reports: three-digit numbers after
+/// "status" ("status: 503", "status code 502") or before their reason phrase
+/// ("503 Service Unavailable"). Other numbers, such as token counts, are ignored.
+fn reported_statuses(message: &str) -> Vec<u16> {
+    let message = message.to_lowercase();
+    let bytes = message.as_bytes();
+    let mut statuses = Vec::new();
+    let mut start = 0;
+    while start < bytes.len() {
+        if !bytes[start].is_ascii_digit() {
+            start += 1;
+            continue;
+        }
+        let end = start + bytes[start..].iter().take_while(|b| b.is_ascii_digit()).count();
+        if let (3, Ok(status)) = (end - start, message[start..end].parse::<u16>()) {
+            let before = message[..start].trim_end_matches([' ', ':', '=', '(']);
+            let after = message[end..].trim_start();
+            let reason = reqwest::StatusCode::from_u16(status).ok()
+                .and_then(|status| status.canonical_reason())
+                .map(str::to_lowercase);
+            if before.ends_with("status") || before.ends_with("status code")
+                || reason.is_some_and(|reason| after.starts_with(&reason))
+            {
+                statuses.push(status);
+            }
+        }
+        start = end;
+    }
+    statuses
+}
+
+fn retry_after_header(headers: &reqwest::header::HeaderMap) -> Option<&str> {
+    headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()
+}
+
+/// Send a request, waiting and retrying up to `MAX_RATE_LIMIT_RETRIES` times
+/// while the server answers 429. Requests whose body can't be cloned are sent once.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn send_with_rate_limit_retries(mut request: reqwest::RequestBuilder) -> reqwest::Result<reqwest::Response> {
+    let mut attempt = 0;
+    loop {
+        let retry = request.try_clone();
+        let response = request.send().await?;
+        match retry {
+            Some(retry) if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS && attempt < MAX_RATE_LIMIT_RETRIES => {
+                let delay = rate_limit_delay(retry_after_header(response.headers()), attempt);
+                log::warn!("Rate limited by {}, retrying in {}s", response.url(), delay.as_secs());
+                tokio::time::sleep(delay).await;
+                request = retry;
+                attempt += 1;
+            }
+            _ => return Ok(response),
+        }
+    }
+}
+
+/// Blocking counterpart of `send_with_rate_limit_retries`
+#[cfg(not(target_arch = "wasm32"))]
+pub fn blocking_send_with_rate_limit_retries(
+    mut request: reqwest::blocking::RequestBuilder,
+) -> reqwest::Result<reqwest::blocking::Response> {
+    let mut attempt = 0;
+    loop {
+        let retry = request.try_clone();
+        let response = request.send()?;
+        match retry {
+            Some(retry) if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS && attempt < MAX_RATE_LIMIT_RETRIES => {
+                let delay = rate_limit_delay(retry_after_header(response.headers()), attempt);
+                log::warn!("Rate limited by {}, retrying in {}s", response.url(), delay.as_secs());
+                std::thread::sleep(delay);
+                request = retry;
+                attempt += 1;
+            }
+            _ => return Ok(response),
+        }
+    }
+}
+
+/// Proxy taken from the environment by clients when none is configured in Moly,
+/// with any credentials removed so it can be shown
+pub fn environment_proxy() -> Option<String> {
+    let value = ["HTTPS_PROXY", "https_proxy", "HTTP_PROXY", "http_proxy", "ALL_PROXY", "all_proxy"]
+        .iter()
+        .find_map(|key| std::env::var(key).ok().filter(|value| !value.trim().is_empty()))?;
+    match reqwest::Url::parse(value.trim()) {
+        Ok(mut url) => {
+            let _ = url.set_username("");
+            let _ = url.set_password(None);
+            Some(url.to_string())
+        }
+        Err(_) => Some(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_statuses_only_where_a_status_is_reported() {
+        assert_eq!(reported_statuses("Server returned status: 503"), vec![503]);
+        assert_eq!(reported_statuses("HTTP status code 502 from upstream"), vec![502]);
+        assert_eq!(reported_statuses("HTTP status server error (500 Internal Server Error) for url"), vec![500]);
+        assert_eq!(reported_statuses("Prompt is 500 tokens over the 128000 token limit"), Vec::<u16>::new());
+    }
+
+    #[test]
+    fn recognizes_rate_limits_by_status_or_wording() {
+        assert!(is_rate_limit_error("Server returned status 429"));
+        assert!(is_rate_limit_error("429 Too Many Requests"));
+        assert!(is_rate_limit_error("Rate limit reached for gpt-4o"));
+        assert!(!is_rate_limit_error("Context is 4290 tokens, model gpt-4-0429 allows 4096"));
+        assert!(!is_rate_limit_error("Request 429 of 1000 failed: invalid JSON"));
+    }
+
+    #[test]
+    fn retries_connection_failures_and_server_errors() {
+        assert!(is_transient_error("Could not connect to server"));
+        assert!(is_transient_error("Connection timed out"));
+        assert!(is_transient_error(
+            "error sending request for url (https://api.example.com/v1/models): error trying to connect: tcp connect error: Connection refused (os error 111)"
+        ));
+        assert!(is_transient_error("Connection reset by peer (os error 104)"));
+        assert!(is_transient_error("Server returned status: 503 Service Unavailable"));
+        assert!(!is_transient_error("Server returned status: 404 Not Found"));
+        assert!(!is_transient_error("Invalid API key"));
+    }
+
+    #[test]
+    fn ignores_transport_words_outside_transport_failures() {
+        assert!(!is_transient_error("Tool reconnect_device failed: device not found"));
+        assert!(!is_transient_error("Unknown parameter: 'timeout'"));
+        assert!(!is_transient_error("Reset the conversation to continue"));
+    }
+
+    #[test]
+    fn recognizes_connection_failures_and_rejected_keys() {
+        assert!(is_connection_error("dns error: failed to lookup address information: Name or service not known"));
+        assert!(is_connection_error("operation timed out"));
+        assert!(is_connection_error("Server returned status: 401 Unauthorized"));
+        assert!(is_connection_error("403 Forbidden"));
+    }
+
+    #[test]
+    fn ignores_connection_words_outside_connection_failures() {
+        assert!(!is_connection_error("Prompt is 401 tokens over the limit"));
+        assert!(!is_connection_error("Request 403 of 1000 failed: invalid JSON"));
+        assert!(!is_connection_error("Connector tool returned no results"));
+        assert!(!is_connection_error("The timeout parameter must be positive"));
+        assert!(!is_connection_error("Please don't paste your API key into the chat"));
+        assert!(!is_connection_error("Server returned status: 400 Bad Request"));
+    }
+}
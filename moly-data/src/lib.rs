@@ -1,18 +1,37 @@
 pub mod chats;
+pub mod connectivity;
+pub mod downloads;
+pub mod files;
+pub mod generation;
+pub mod http;
 pub mod mcp_servers;
 pub mod moly_client;
 pub mod preferences;
+pub mod pricing;
+pub mod provider_client;
 pub mod providers;
 pub mod providers_manager;
+pub mod recommendations;
+pub mod shortcuts;
 pub mod store;
+pub mod tokens;
+pub mod vision;
 
-pub use chats::{ChatData, ChatId, Chats};
+pub use chats::{ChatData, ChatId, ChatRetention, Chats, ChatsImport, MessageAnchor, ToolCallRecord, CHAT_DATA_VERSION};
+pub use connectivity::{is_local_url, Connectivity};
+pub use downloads::{DownloadGroup, DownloadState, Downloads};
+pub use files::data_dir;
+pub use generation::GenerationParams;
+pub use http::ProxySettings;
 pub use mcp_servers::{InputConfig, McpServer, McpServersConfig};
 pub use moly_client::{MolyClient, ServerConnectionStatus};
-pub use preferences::Preferences;
-pub use providers::{ProviderPreferences, ProviderId, ProviderType, ProviderConnectionStatus, get_supported_providers};
+pub use preferences::{Preferences, WindowGeometry, PREFERENCES_VERSION};
+pub use pricing::Pricing;
+pub use provider_client::ProviderClient;
+pub use providers::{ProviderPreferences, ProviderId, ProviderType, ProviderConnectionStatus, get_supported_providers, env_var_prefix, normalize_provider_url, ollama_tags_url, providers_to_env, ANTHROPIC_VERSION, DEFAULT_CHAT_ENDPOINT};
 pub use providers_manager::ProvidersManager;
-pub use store::{Store, StoreAction};
+pub use recommendations::{cheapest_test_model, Recommendations};
+pub use store::{ActiveModel, Store, StoreAction};
 
 // Re-export moly_protocol types used by the models UI
 pub use moly_protocol::data::{Model, File as ModelFile, FileId, DownloadedFile, PendingDownload, PendingDownloadsStatus, Author};
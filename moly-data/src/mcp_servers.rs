@@ -151,6 +151,40 @@ impl McpServer {
     }
 }
 
+/// Replace `${VAR}` references in `value`. Names that `lookup` can't resolve
+/// are kept verbatim and collected into `unresolved`.
+fn substitute_env_vars(
+    value: &str,
+    lookup: &impl Fn(&str) -> Option<String>,
+    unresolved: &mut Vec<String>,
+) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find("${") {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            // No closing brace, keep the remainder as-is
+            result.push_str(&rest[start..]);
+            return result;
+        };
+
+        let name = &after[..end];
+        match lookup(name) {
+            Some(resolved) => result.push_str(&resolved),
+            None => {
+                unresolved.push(name.to_string());
+                result.push_str(&rest[start..start + 2 + end + 1]);
+            }
+        }
+        rest = &after[end + 1..];
+    }
+
+    result.push_str(rest);
+    result
+}
+
 fn default_mcp_servers_enabled() -> bool {
     true
 }
@@ -207,10 +241,56 @@ impl McpServersConfig {
         serde_json::to_string_pretty(self)
     }
 
+    /// Single-line JSON, for sharing or pasting into other tools
+    pub fn to_json_compact(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
     pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
         serde_json::from_str(json)
     }
 
+    /// Copy of the config with `${VAR}` references resolved from the process environment.
+    ///
+    /// Used when launching servers; the stored config keeps the raw `${VAR}` form
+    /// so secrets aren't persisted. Unresolved variables are left as-is and logged.
+    pub fn resolve_env(&self) -> McpServersConfig {
+        self.resolve_env_with(&IndexMap::new())
+    }
+
+    /// Like `resolve_env`, but values in `secrets` take precedence over the environment
+    pub fn resolve_env_with(&self, secrets: &IndexMap<String, String>) -> McpServersConfig {
+        let lookup = |name: &str| secrets.get(name).cloned().or_else(|| std::env::var(name).ok());
+        let mut resolved = self.clone();
+
+        for (server_id, server) in resolved.servers.iter_mut() {
+            let mut unresolved = Vec::new();
+            let mut resolve = |value: &mut String| {
+                *value = substitute_env_vars(value, &lookup, &mut unresolved);
+            };
+
+            if let Some(command) = server.command.as_mut() {
+                resolve(command);
+            }
+            server.args.iter_mut().for_each(&mut resolve);
+            server.env.values_mut().for_each(&mut resolve);
+            if let Some(url) = server.url.as_mut() {
+                resolve(url);
+            }
+            server.headers.values_mut().for_each(&mut resolve);
+            if let Some(working_directory) = server.working_directory.as_mut() {
+                resolve(working_directory);
+            }
+
+            if !unresolved.is_empty() {
+                unresolved.dedup();
+                log::warn!("MCP server '{}' references unset variables: {}", server_id, unresolved.join(", "));
+            }
+        }
+
+        resolved
+    }
+
     /// Create a sample configuration with example servers
     pub fn create_sample() -> Self {
         let mut config = Self::new();
@@ -2,14 +2,21 @@
 //!
 //! Handles model discovery, search, and download management.
 
+use makepad_widgets::Cx;
 use moly_protocol::data::{Model, DownloadedFile, PendingDownload};
 use reqwest::Client;
+
+use crate::http::{client_builder, ProxySettings};
+use crate::store::StoreAction;
 use serde::Serialize;
 use std::sync::{Arc, Mutex};
 
 /// Default port for Moly Server
 const DEFAULT_SERVER_PORT: u16 = 8765;
 
+/// Request timeout for Moly Server calls
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
 /// Connection status for the Moly Server
 #[derive(Clone, Debug, Default, PartialEq)]
 pub enum ServerConnectionStatus {
@@ -55,10 +62,7 @@ impl MolyClient {
         let base_url = format!("http://localhost:{}", port);
 
         Self {
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(30))
-                .build()
-                .expect("Failed to create HTTP client"),
+            client: Self::build_client(&ProxySettings::default()),
             inner: Arc::new(Mutex::new(MolyClientInner {
                 base_url,
                 connection_status: ServerConnectionStatus::Disconnected,
@@ -66,14 +70,34 @@ impl MolyClient {
         }
     }
 
+    fn build_client(proxy: &ProxySettings) -> Client {
+        client_builder(proxy)
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .expect("Failed to create HTTP client")
+    }
+
+    /// Rebuild the HTTP client with new proxy settings.
+    /// Existing clones keep using the previous client.
+    pub fn set_proxy(&mut self, proxy: &ProxySettings) {
+        self.client = Self::build_client(proxy);
+    }
+
     /// Get the current connection status
     pub fn connection_status(&self) -> ServerConnectionStatus {
         self.inner.lock().unwrap().connection_status.clone()
     }
 
-    /// Set the connection status
+    /// Set the connection status, posting `StoreAction::ServerStatusChanged` if it changed.
+    /// Callable from background threads.
     fn set_connection_status(&self, status: ServerConnectionStatus) {
-        self.inner.lock().unwrap().connection_status = status;
+        let mut inner = self.inner.lock().unwrap();
+        if inner.connection_status == status {
+            return;
+        }
+        inner.connection_status = status.clone();
+        // Posted under the lock so concurrent transitions arrive in order
+        Cx::post_action(StoreAction::ServerStatusChanged(status));
     }
 
     /// Get the base URL
@@ -234,6 +258,11 @@ impl MolyClient {
         Ok(())
     }
 
+    /// Resume a paused download. Moly Server resumes by requesting the file again.
+    pub async fn resume_download(&self, file_id: &str) -> Result<(), String> {
+        self.download_file(file_id).await
+    }
+
     /// Cancel a download
     pub async fn cancel_download(&self, file_id: &str) -> Result<(), String> {
         let url = format!("{}/downloads/{}", self.base_url(), file_id);
@@ -251,8 +280,8 @@ impl MolyClient {
         Ok(())
     }
 
-    /// Delete a downloaded file
-    pub async fn delete_file(&self, file_id: &str) -> Result<(), String> {
+    /// Delete a downloaded file from disk
+    pub async fn delete_downloaded_file(&self, file_id: &str) -> Result<(), String> {
         let url = format!("{}/files/{}", self.base_url(), file_id);
 
         let response = self.client
@@ -1,14 +1,64 @@
+use makepad_widgets::KeyEvent;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
+use crate::chats::ChatRetention;
+use crate::generation::GenerationParams;
+use crate::http::ProxySettings;
 use crate::mcp_servers::McpServersConfig;
-use crate::providers::{get_supported_providers, ProviderId, ProviderPreferences};
+use crate::providers::{get_supported_providers, normalize_provider_url, providers_to_env, ProviderConnectionStatus, ProviderId, ProviderPreferences};
+use crate::shortcuts::{binding_matches, is_valid_binding, shortcut_def};
 
 const PREFERENCES_FILENAME: &str = "preferences.json";
 
+/// Format of `Preferences` written by this build; older files are upgraded on load
+pub const PREFERENCES_VERSION: u32 = 1;
+const PROVIDERS_ENV_FILENAME: &str = "providers.env";
+
+/// Smallest window size restored, so a bad value can't leave an unusable window
+const MIN_WINDOW_SIZE: (f64, f64) = (800.0, 600.0);
+
+/// Largest plausible desktop coordinate; positions beyond it are treated as off-screen
+const MAX_SCREEN_EXTENT: f64 = 16384.0;
+
+/// Window size and position when it was last resized or moved, in logical pixels
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct WindowGeometry {
+    pub width: f64,
+    pub height: f64,
+    pub x: f64,
+    pub y: f64,
+}
+
+impl WindowGeometry {
+    /// Size clamped to a usable minimum, and the position if it is likely visible.
+    ///
+    /// Makepad doesn't expose display bounds, so a window is assumed lost (e.g. its
+    /// monitor was unplugged) when its title bar would lie outside the plausible
+    /// desktop area; the position is then dropped and the OS places the window.
+    pub fn clamped(&self) -> ((f64, f64), Option<(f64, f64)>) {
+        let width = self.width.clamp(MIN_WINDOW_SIZE.0, MAX_SCREEN_EXTENT);
+        let height = self.height.clamp(MIN_WINDOW_SIZE.1, MAX_SCREEN_EXTENT);
+
+        // Keep at least part of the title bar reachable
+        let visible = self.x > -(width - 100.0)
+            && self.x < MAX_SCREEN_EXTENT - 100.0
+            && self.y >= 0.0
+            && self.y < MAX_SCREEN_EXTENT - 100.0;
+        let position = visible.then_some((self.x, self.y));
+        ((width, height), position)
+    }
+}
+
 /// User preferences that persist across sessions
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Preferences {
+    /// Format the preferences were written in (0 for files from before versioning)
+    #[serde(default)]
+    pub version: u32,
+
     /// Whether dark mode is enabled
     #[serde(default)]
     pub dark_mode: bool,
@@ -32,21 +82,150 @@ pub struct Preferences {
     /// MCP servers configuration
     #[serde(default)]
     pub mcp_servers_config: McpServersConfig,
+
+    /// Prompts pinned to the quick-access bar above the chat
+    #[serde(default)]
+    pub favorite_prompts: Vec<String>,
+
+    /// Ids of models starred in the Models screen
+    #[serde(default)]
+    pub favorite_models: Vec<String>,
+
+    /// Re-test the active provider when the app regains focus
+    #[serde(default = "default_retest_provider_on_focus")]
+    pub retest_provider_on_focus: bool,
+
+    /// Greeting shown in an empty chat
+    #[serde(default = "default_empty_chat_greeting")]
+    pub empty_chat_greeting: String,
+
+    /// Suggested prompts shown as chips in an empty chat
+    #[serde(default = "default_suggested_prompts")]
+    pub suggested_prompts: Vec<String>,
+
+    /// Seconds to wait for a provider's model list before retrying or skipping it
+    #[serde(default = "default_model_fetch_timeout_secs")]
+    pub model_fetch_timeout_secs: u64,
+
+    /// Hours a provider's cached model list is used at startup
+    #[serde(default = "default_model_cache_ttl_hours")]
+    pub model_cache_ttl_hours: u64,
+
+    /// Proxy used for all outbound HTTP requests
+    #[serde(default)]
+    pub proxy: ProxySettings,
+
+    /// Show MCP tool calls made during a chat turn
+    #[serde(default)]
+    pub show_tool_calls: bool,
+
+    /// User overrides of keyboard shortcuts, by shortcut id (see `shortcuts::SHORTCUTS`)
+    #[serde(default)]
+    pub shortcut_bindings: HashMap<String, String>,
+
+    /// Ask before a send whose estimated size exceeds `send_confirmation_tokens`
+    #[serde(default)]
+    pub confirm_large_sends: bool,
+
+    /// Estimated tokens (conversation plus draft) above which a send is confirmed first
+    #[serde(default = "default_send_confirmation_tokens")]
+    pub send_confirmation_tokens: usize,
+
+    /// System prompt added to every new chat (empty for none)
+    #[serde(default)]
+    pub system_prompt: String,
+
+    /// Context window assumed for models whose provider doesn't report one
+    #[serde(default = "default_context_window_tokens")]
+    pub default_context_window_tokens: usize,
+
+    /// Generation parameters used by chats that don't override them
+    #[serde(default)]
+    pub generation_params: GenerationParams,
+
+    /// Window geometry restored at startup (None until the window is first resized or moved)
+    #[serde(default)]
+    pub window_geometry: Option<WindowGeometry>,
+
+    /// View state of each app when it was last left, by app id (see `MolyApp::info`)
+    #[serde(default)]
+    pub session_state: HashMap<String, serde_json::Value>,
+
+    /// Saved chats deleted automatically at startup (off by default)
+    #[serde(default)]
+    pub chat_retention: ChatRetention,
+
+    /// Names shown instead of model ids, by bot id string. Requests still use the id
+    #[serde(default)]
+    pub model_aliases: HashMap<String, String>,
 }
 
 fn default_sidebar_expanded() -> bool {
     true
 }
 
+fn default_retest_provider_on_focus() -> bool {
+    true
+}
+
+fn default_model_fetch_timeout_secs() -> u64 {
+    15
+}
+
+fn default_model_cache_ttl_hours() -> u64 {
+    24
+}
+
+fn default_send_confirmation_tokens() -> usize {
+    8000
+}
+
+fn default_context_window_tokens() -> usize {
+    8192
+}
+
+fn default_empty_chat_greeting() -> String {
+    "How can I help you today?".to_string()
+}
+
+fn default_suggested_prompts() -> Vec<String> {
+    vec![
+        "Explain a concept in simple terms".to_string(),
+        "Help me debug some code".to_string(),
+        "Summarize a long text".to_string(),
+        "Brainstorm ideas for a project".to_string(),
+    ]
+}
+
 impl Default for Preferences {
     fn default() -> Self {
         Self {
+            version: PREFERENCES_VERSION,
             dark_mode: false,
             sidebar_expanded: true,
             current_view: "Chat".to_string(),
             providers_preferences: get_supported_providers(),
             current_chat_model: None,
             mcp_servers_config: McpServersConfig::new(),
+            favorite_prompts: Vec::new(),
+            favorite_models: Vec::new(),
+            retest_provider_on_focus: true,
+            empty_chat_greeting: default_empty_chat_greeting(),
+            suggested_prompts: default_suggested_prompts(),
+            model_fetch_timeout_secs: default_model_fetch_timeout_secs(),
+            model_cache_ttl_hours: default_model_cache_ttl_hours(),
+            proxy: ProxySettings::default(),
+            show_tool_calls: false,
+            shortcut_bindings: HashMap::new(),
+            confirm_large_sends: false,
+            send_confirmation_tokens: default_send_confirmation_tokens(),
+            system_prompt: String::new(),
+            default_context_window_tokens: default_context_window_tokens(),
+            generation_params: GenerationParams::default(),
+            window_geometry: None,
+            session_state: HashMap::new(),
+            chat_retention: ChatRetention::default(),
+            model_aliases: HashMap::new(),
         }
     }
 }
@@ -58,7 +237,10 @@ impl Preferences {
         log::debug!("Loading preferences from {:?}", path);
 
         if let Ok(contents) = std::fs::read_to_string(&path) {
-            match serde_json::from_str::<Preferences>(&contents) {
+            let parsed = serde_json::from_str::<serde_json::Value>(&contents)
+                .map_err(|e| e.to_string())
+                .and_then(Self::from_json_value);
+            match parsed {
                 Ok(mut prefs) => {
                     log::debug!("Parsed preferences successfully");
                     // Ensure all supported providers exist
@@ -76,6 +258,30 @@ impl Preferences {
         Preferences::default()
     }
 
+    /// Parse preferences of any known format, upgrading them to `PREFERENCES_VERSION`
+    pub fn from_json_value(mut value: serde_json::Value) -> Result<Self, String> {
+        Self::migrate(&mut value)?;
+        let mut prefs: Self = serde_json::from_value(value).map_err(|e| e.to_string())?;
+        // A hand-edited zero would time every fetch out immediately
+        prefs.model_fetch_timeout_secs = prefs.model_fetch_timeout_secs.max(1);
+        Ok(prefs)
+    }
+
+    /// Upgrade serialized preferences in place, one format version at a time
+    fn migrate(value: &mut serde_json::Value) -> Result<(), String> {
+        let object = value.as_object_mut().ok_or("preferences are not a JSON object")?;
+        let version = object.get("version").and_then(|v| v.as_u64()).unwrap_or(0);
+        if version > PREFERENCES_VERSION as u64 {
+            log::warn!("Preferences were written by a newer version (format {}), unknown fields are ignored", version);
+            return Ok(());
+        }
+
+        // 0 -> 1: fields added before versioning all have serde defaults, so
+        // unversioned files only need the version stamped
+        object.insert("version".to_string(), PREFERENCES_VERSION.into());
+        Ok(())
+    }
+
     /// Save preferences to disk
     pub fn save(&self) {
         let path = Self::preferences_path();
@@ -90,7 +296,7 @@ impl Preferences {
 
         match serde_json::to_string_pretty(self) {
             Ok(json) => {
-                if let Err(e) = std::fs::write(&path, &json) {
+                if let Err(e) = crate::files::write_atomic(&path, &json) {
                     log::error!("Failed to write preferences: {:?}", e);
                 } else {
                     log::info!("Saved preferences to {:?} ({} bytes)", path, json.len());
@@ -102,17 +308,37 @@ impl Preferences {
         }
     }
 
+    /// Enabled providers as a `.env` snippet (contains API keys in plain text)
+    pub fn enabled_providers_env(&self) -> String {
+        providers_to_env(&self.get_enabled_providers())
+    }
+
+    /// Write the enabled providers `.env` snippet next to the preferences file.
+    /// The file is only readable by the current user on unix.
+    pub fn save_enabled_providers_env(&self) -> Result<PathBuf, String> {
+        let path = Self::preferences_path().with_file_name(PROVIDERS_ENV_FILENAME);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+        }
+
+        crate::files::write_atomic(&path, self.enabled_providers_env())
+            .map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600));
+        }
+
+        log::info!("Exported providers env to {:?}", path);
+        Ok(path)
+    }
+
     /// Get the path to the preferences file
     fn preferences_path() -> PathBuf {
-        // Use home directory for reliable persistence
-        if let Some(home) = dirs::home_dir() {
-            let path = home.join(".moly").join(PREFERENCES_FILENAME);
-            log::debug!("Preferences path: {:?}", path);
-            path
-        } else {
-            // Fallback to current directory
-            PathBuf::from(".moly").join(PREFERENCES_FILENAME)
-        }
+        let path = crate::files::data_dir().join(PREFERENCES_FILENAME);
+        log::debug!("Preferences path: {:?}", path);
+        path
     }
 
     /// Set dark mode and save
@@ -167,6 +393,49 @@ impl Preferences {
         }
     }
 
+    /// Add a custom provider and save. Its id is derived from the name, with a
+    /// numeric suffix if another custom provider has it already; names of
+    /// built-in providers are refused. An API key, if given, enables it right
+    /// away. Returns the new provider's id.
+    pub fn add_custom_provider(&mut self, name: &str, url: &str, api_key: Option<String>) -> Result<ProviderId, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Provider name is required".to_string());
+        }
+        let url = normalize_provider_url(url)?;
+
+        let base_id = name.to_lowercase().replace(' ', "_");
+        if let Some(builtin) = get_supported_providers().into_iter().find(|provider| provider.id == base_id) {
+            return Err(format!("\"{}\" is the built-in {} provider. Choose another name", name, builtin.name));
+        }
+        let id = (1..)
+            .map(|n| if n == 1 { base_id.clone() } else { format!("{}_{}", base_id, n) })
+            .find(|id| self.get_provider(id).is_none())
+            .expect("an unused provider id");
+
+        log::info!("add_custom_provider: id={}, name={}, url={}", id, name, url);
+        let mut provider = ProviderPreferences::new(&id, name, &url);
+        provider.was_customly_added = true;
+        provider.enabled = true;
+        provider.api_key = api_key.filter(|key| !key.is_empty());
+        self.providers_preferences.push(provider);
+        self.save();
+        Ok(id)
+    }
+
+    /// Move a provider to another position in the list and save. The order is
+    /// the one providers are listed in and their models are fetched in.
+    pub fn move_provider(&mut self, from: usize, to: usize) {
+        let len = self.providers_preferences.len();
+        if from >= len || to >= len || from == to {
+            return;
+        }
+        let provider = self.providers_preferences.remove(from);
+        log::info!("move_provider: {} from {} to {}", provider.id, from, to);
+        self.providers_preferences.insert(to, provider);
+        self.save();
+    }
+
     /// Update a provider's enabled state and save
     pub fn set_provider_enabled(&mut self, id: &ProviderId, enabled: bool) {
         if let Some(provider) = self.get_provider_mut(id) {
@@ -175,6 +444,139 @@ impl Preferences {
         }
     }
 
+    /// Update whether a provider accepts invalid TLS certificates and save
+    pub fn set_provider_allow_invalid_certs(&mut self, id: &ProviderId, allow: bool) {
+        log::info!("set_provider_allow_invalid_certs: provider={}, allow={}", id, allow);
+        if let Some(provider) = self.get_provider_mut(id) {
+            provider.allow_invalid_certs = allow;
+            self.save();
+        }
+    }
+
+    /// Set the custom request headers and save. Rows without a name are dropped.
+    pub fn set_provider_custom_headers(&mut self, id: &ProviderId, headers: Vec<(String, String)>) {
+        log::info!("set_provider_custom_headers: provider={}, count={}", id, headers.len());
+        let headers: Vec<_> = headers
+            .into_iter()
+            .map(|(name, value)| (name.trim().to_string(), value))
+            .filter(|(name, _)| !name.is_empty())
+            .collect();
+        if let Some(provider) = self.get_provider_mut(id) {
+            provider.custom_headers = headers;
+            self.save();
+        }
+    }
+
+    /// Set (or clear) the provider's default model and save
+    pub fn set_provider_default_model(&mut self, id: &ProviderId, model: Option<String>) {
+        log::info!("set_provider_default_model: provider={}, model={:?}", id, model);
+        if let Some(provider) = self.get_provider_mut(id) {
+            provider.default_model = model;
+            self.save();
+        }
+    }
+
+    /// Set (or clear, with None/empty) the chat endpoint override and save.
+    /// Returns an error without saving if the endpoint can't be used.
+    pub fn set_provider_chat_endpoint(&mut self, id: &ProviderId, endpoint: Option<String>) -> Result<(), String> {
+        log::info!("set_provider_chat_endpoint: provider={}, endpoint={:?}", id, endpoint);
+        let endpoint = endpoint.map(|e| e.trim().to_string()).filter(|e| !e.is_empty());
+        if let Some(provider) = self.get_provider_mut(id) {
+            let previous = std::mem::replace(&mut provider.chat_endpoint, endpoint);
+            if let Err(e) = provider.chat_completions_url() {
+                provider.chat_endpoint = previous;
+                return Err(e);
+            }
+            self.save();
+        }
+        Ok(())
+    }
+
+    /// Set (or clear, with None/empty) the models listing path override and save
+    pub fn set_provider_models_path(&mut self, id: &ProviderId, path: Option<String>) {
+        log::info!("set_provider_models_path: provider={}, path={:?}", id, path);
+        let path = path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+        if let Some(provider) = self.get_provider_mut(id) {
+            provider.models_path = path;
+            self.save();
+        }
+    }
+
+    /// Set (or clear) the connection test timeout override and save
+    pub fn set_provider_timeout_secs(&mut self, id: &ProviderId, secs: Option<u64>) {
+        log::info!("set_provider_timeout_secs: provider={}, secs={:?}", id, secs);
+        if let Some(provider) = self.get_provider_mut(id) {
+            provider.timeout_secs = secs.map(|s| s.max(1));
+            self.save();
+        }
+    }
+
+    /// Remember the models a connection test listed for a provider and save, if they changed
+    pub fn set_provider_fetched_models(&mut self, id: &ProviderId, models: Vec<String>) {
+        if let Some(provider) = self.get_provider_mut(id) {
+            if provider.fetched_models != models {
+                log::info!("set_provider_fetched_models: provider={}, count={}", id, models.len());
+                provider.fetched_models = models;
+                self.save();
+            }
+        }
+    }
+
+    /// Record the result of a provider connection check and save
+    pub fn set_provider_connection_status(&mut self, id: &ProviderId, status: ProviderConnectionStatus) {
+        log::info!("set_provider_connection_status: provider={}, status={:?}", id, status);
+        if let Some(provider) = self.get_provider_mut(id) {
+            if provider.last_connection_status != status {
+                provider.last_connection_status = status;
+                self.save();
+            }
+        }
+    }
+
+    /// Set whether the active provider is re-tested on app focus and save
+    pub fn set_retest_provider_on_focus(&mut self, enabled: bool) {
+        log::info!("set_retest_provider_on_focus: {}", enabled);
+        self.retest_provider_on_focus = enabled;
+        self.save();
+    }
+
+    /// Set whether MCP tool calls are shown in chat and save
+    pub fn set_show_tool_calls(&mut self, show: bool) {
+        log::info!("set_show_tool_calls: {}", show);
+        self.show_tool_calls = show;
+        self.save();
+    }
+
+    /// Set the model fetch timeout and save
+    pub fn set_model_fetch_timeout_secs(&mut self, secs: u64) {
+        log::info!("set_model_fetch_timeout_secs: {}", secs);
+        self.model_fetch_timeout_secs = secs.max(1);
+        self.save();
+    }
+
+    /// Validate and save the proxy settings
+    pub fn set_proxy(&mut self, proxy: ProxySettings) -> Result<(), String> {
+        proxy.validate()?;
+        log::info!("set_proxy: enabled={}", proxy.enabled);
+        self.proxy = proxy;
+        self.save();
+        Ok(())
+    }
+
+    /// Set the empty chat greeting and save
+    pub fn set_empty_chat_greeting(&mut self, greeting: String) {
+        log::info!("set_empty_chat_greeting: len={}", greeting.len());
+        self.empty_chat_greeting = greeting;
+        self.save();
+    }
+
+    /// Set the suggested prompts for empty chats and save
+    pub fn set_suggested_prompts(&mut self, prompts: Vec<String>) {
+        log::info!("set_suggested_prompts: count={}", prompts.len());
+        self.suggested_prompts = prompts;
+        self.save();
+    }
+
     /// Set the current chat model and save
     pub fn set_current_chat_model(&mut self, model: Option<String>) {
         log::info!("set_current_chat_model: {:?}", model);
@@ -187,6 +589,158 @@ impl Preferences {
         self.current_chat_model.as_deref()
     }
 
+    /// Set whether large sends need confirmation and save
+    pub fn set_confirm_large_sends(&mut self, enabled: bool) {
+        log::info!("set_confirm_large_sends: {}", enabled);
+        self.confirm_large_sends = enabled;
+        self.save();
+    }
+
+    /// Set the token threshold for confirming a send and save
+    pub fn set_send_confirmation_tokens(&mut self, tokens: usize) {
+        log::info!("set_send_confirmation_tokens: {}", tokens);
+        self.send_confirmation_tokens = tokens;
+        self.save();
+    }
+
+    /// Set the system prompt for new chats and save
+    pub fn set_system_prompt(&mut self, prompt: String) {
+        log::info!("set_system_prompt: len={}", prompt.len());
+        self.system_prompt = prompt;
+        self.save();
+    }
+
+    /// Set the context window assumed when a provider reports none and save
+    pub fn set_default_context_window_tokens(&mut self, tokens: usize) {
+        log::info!("set_default_context_window_tokens: {}", tokens);
+        self.default_context_window_tokens = tokens;
+        self.save();
+    }
+
+    /// Set the chat retention policy and save; it applies from the next launch
+    pub fn set_chat_retention(&mut self, retention: ChatRetention) {
+        log::info!("set_chat_retention: {:?}", retention);
+        self.chat_retention = retention;
+        self.save();
+    }
+
+    /// Set the default generation parameters and save
+    pub fn set_generation_params(&mut self, params: GenerationParams) {
+        log::info!("set_generation_params: {:?}", params);
+        self.generation_params = params;
+        self.save();
+    }
+
+    /// Set the window geometry restored at startup and save
+    pub fn set_window_geometry(&mut self, geometry: WindowGeometry) {
+        log::info!("set_window_geometry: {:?}", geometry);
+        self.window_geometry = Some(geometry);
+        self.save();
+    }
+
+    /// Token threshold for confirming a send, if confirmation is on
+    pub fn send_confirmation_threshold(&self) -> Option<usize> {
+        self.confirm_large_sends.then_some(self.send_confirmation_tokens)
+    }
+
+    /// Binding of a shortcut, honoring a valid user override
+    pub fn shortcut_binding(&self, id: &str) -> String {
+        if let Some(binding) = self.shortcut_bindings.get(id) {
+            if is_valid_binding(binding) {
+                return binding.clone();
+            }
+            log::warn!("Ignoring invalid binding '{}' for shortcut {}", binding, id);
+        }
+        shortcut_def(id).map(|def| def.default_binding.to_string()).unwrap_or_default()
+    }
+
+    /// Whether a key press triggers the shortcut with this id
+    pub fn shortcut_pressed(&self, id: &str, event: &KeyEvent) -> bool {
+        binding_matches(&self.shortcut_binding(id), event)
+    }
+
+    /// Saved view state of an app, or None when missing or no longer readable
+    pub fn app_session_state<T: DeserializeOwned>(&self, app_id: &str) -> Option<T> {
+        let value = self.session_state.get(app_id)?;
+        match serde_json::from_value(value.clone()) {
+            Ok(state) => Some(state),
+            Err(e) => {
+                log::warn!("Ignoring stale session state for {}: {:?}", app_id, e);
+                None
+            }
+        }
+    }
+
+    /// Set the view state of an app and save, if it changed
+    pub fn set_app_session_state<T: Serialize>(&mut self, app_id: &str, state: &T) {
+        let value = match serde_json::to_value(state) {
+            Ok(value) => value,
+            Err(e) => {
+                log::error!("Failed to serialize session state for {}: {:?}", app_id, e);
+                return;
+            }
+        };
+        if self.session_state.get(app_id) == Some(&value) {
+            return;
+        }
+        log::info!("set_app_session_state: {}", app_id);
+        self.session_state.insert(app_id.to_string(), value);
+        self.save();
+    }
+
+    /// Pin a prompt to the quick-access bar and save
+    pub fn add_favorite_prompt(&mut self, prompt: String) {
+        if prompt.trim().is_empty() || self.favorite_prompts.contains(&prompt) {
+            return;
+        }
+        log::info!("add_favorite_prompt: len={}", prompt.len());
+        self.favorite_prompts.push(prompt);
+        self.save();
+    }
+
+    /// Unpin a favorite prompt by index and save
+    pub fn remove_favorite_prompt(&mut self, index: usize) {
+        if index < self.favorite_prompts.len() {
+            self.favorite_prompts.remove(index);
+            self.save();
+        }
+    }
+
+    /// Whether a model is starred
+    pub fn is_favorite_model(&self, model_id: &str) -> bool {
+        self.favorite_models.iter().any(|id| id == model_id)
+    }
+
+    /// Star or unstar a model and save. Returns whether it is now starred
+    pub fn toggle_favorite_model(&mut self, model_id: &str) -> bool {
+        let favorite = !self.is_favorite_model(model_id);
+        log::info!("toggle_favorite_model: {} -> {}", model_id, favorite);
+        if favorite {
+            self.favorite_models.push(model_id.to_string());
+        } else {
+            self.favorite_models.retain(|id| id != model_id);
+        }
+        self.save();
+        favorite
+    }
+
+    /// Name shown for a model instead of its id, if the user set one
+    pub fn model_alias(&self, bot_id: &str) -> Option<&str> {
+        self.model_aliases.get(bot_id).map(String::as_str)
+    }
+
+    /// Set the name shown for a model and save. A blank alias removes it
+    pub fn set_model_alias(&mut self, bot_id: &str, alias: &str) {
+        let alias = alias.trim();
+        log::info!("set_model_alias: {} -> {:?}", bot_id, alias);
+        if alias.is_empty() {
+            self.model_aliases.remove(bot_id);
+        } else {
+            self.model_aliases.insert(bot_id.to_string(), alias.to_string());
+        }
+        self.save();
+    }
+
     /// Get all enabled providers with API keys
     pub fn get_enabled_providers(&self) -> Vec<&ProviderPreferences> {
         self.providers_preferences
@@ -206,7 +760,12 @@ impl Preferences {
     pub fn merge_with_supported_providers(&mut self) {
         let supported = get_supported_providers();
         for sp in supported {
-            if !self.providers_preferences.iter().any(|p| p.id == sp.id) {
+            if let Some(existing) = self.providers_preferences.iter_mut().find(|p| p.id == sp.id) {
+                // Built-in providers follow the current API format (e.g. Anthropic used to be OpenAi)
+                if !existing.was_customly_added {
+                    existing.provider_type = sp.provider_type;
+                }
+            } else {
                 self.providers_preferences.push(sp);
             }
         }
@@ -249,3 +808,68 @@ impl Preferences {
         self.mcp_servers_config.dangerous_mode_enabled
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::ProviderType;
+    use serde_json::json;
+
+    /// Preferences as written before format versioning
+    fn v0_preferences() -> serde_json::Value {
+        json!({
+            "dark_mode": true,
+            "current_view": "Settings",
+            "current_chat_model": "6;gpt-4o@https://api.openai.com/v1",
+            "favorite_prompts": ["Summarize this"],
+            "providers_preferences": [{
+                "id": "openai",
+                "name": "OpenAI",
+                "url": "https://api.openai.com/v1",
+                "api_key": "sk-test",
+                "enabled": true,
+                "provider_type": "OpenAI",
+                "models": [["gpt-4o", true], ["gpt-3.5-turbo", false]]
+            }]
+        })
+    }
+
+    #[test]
+    fn migrates_v0_preferences() {
+        let prefs = Preferences::from_json_value(v0_preferences()).unwrap();
+
+        assert_eq!(prefs.version, PREFERENCES_VERSION);
+        assert!(prefs.dark_mode);
+        assert_eq!(prefs.current_view, "Settings");
+        assert_eq!(prefs.current_chat_model.as_deref(), Some("6;gpt-4o@https://api.openai.com/v1"));
+        assert_eq!(prefs.favorite_prompts, vec!["Summarize this".to_string()]);
+
+        // Fields added later take their defaults
+        assert!(prefs.sidebar_expanded);
+        assert_eq!(prefs.model_fetch_timeout_secs, default_model_fetch_timeout_secs());
+        assert_eq!(prefs.model_cache_ttl_hours, default_model_cache_ttl_hours());
+        assert!(!prefs.proxy.enabled);
+        assert!(prefs.model_aliases.is_empty());
+
+        let provider = &prefs.providers_preferences[0];
+        assert_eq!(provider.id, "openai");
+        assert_eq!(provider.api_key.as_deref(), Some("sk-test"));
+        assert_eq!(provider.provider_type, ProviderType::OpenAi);
+        assert_eq!(provider.models, vec![("gpt-4o".to_string(), true), ("gpt-3.5-turbo".to_string(), false)]);
+        assert!(provider.tools_enabled);
+        assert_eq!(provider.chat_endpoint, None);
+        assert!(provider.custom_headers.is_empty());
+    }
+
+    #[test]
+    fn clamps_a_zero_model_fetch_timeout() {
+        let mut value = v0_preferences();
+        value["model_fetch_timeout_secs"] = json!(0);
+        assert_eq!(Preferences::from_json_value(value).unwrap().model_fetch_timeout_secs, 1);
+    }
+
+    #[test]
+    fn rejects_preferences_that_are_not_an_object() {
+        assert!(Preferences::from_json_value(json!([])).is_err());
+    }
+}
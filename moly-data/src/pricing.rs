@@ -0,0 +1,110 @@
+//! Model pricing
+//!
+//! Input token prices of well-known cloud models, used to show what a large
+//! send would cost before it goes out. Patterns match model names as in
+//! [`crate::recommendations`] and the first match wins; a provider prefix such
+//! as `openai/` (OpenRouter) is ignored. Prices are US dollars per million
+//! input tokens.
+//!
+//! Prices change, so the built-in table can be extended or corrected with
+//! `model_pricing.json` in the data directory, a map of pattern to price:
+//!
+//! ```json
+//! { "gpt-4o": 2.5, "my-finetune*": 0.3 }
+//! ```
+//!
+//! Patterns from the file are tried before the built-in ones, longest first.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::recommendations::glob_matches;
+
+const PRICING_FILENAME: &str = "model_pricing.json";
+
+/// Built-in prices, more specific patterns first
+const BUILTIN_PRICES: &[(&str, f64)] = &[
+    ("gpt-4o-mini*", 0.15),
+    ("gpt-4o*", 2.5),
+    ("gpt-4.1-nano*", 0.1),
+    ("gpt-4.1-mini*", 0.4),
+    ("gpt-4.1*", 2.0),
+    ("claude-3-haiku*", 0.25),
+    ("claude-3-5-haiku*", 0.8),
+    ("claude-3-5-sonnet*", 3.0),
+    ("claude-3-7-sonnet*", 3.0),
+    ("claude-sonnet-4*", 3.0),
+    ("claude-opus-4*", 15.0),
+    ("gemini-2.0-flash*", 0.1),
+    ("deepseek-chat", 0.27),
+];
+
+/// Input token prices by model name pattern
+#[derive(Clone, Debug, Default)]
+pub struct Pricing {
+    prices: Vec<(String, f64)>,
+}
+
+impl Pricing {
+    /// The built-in table only
+    pub fn builtin() -> Self {
+        let prices = BUILTIN_PRICES.iter().map(|(pattern, price)| (pattern.to_string(), *price)).collect();
+        Self { prices }
+    }
+
+    /// The built-in table with the prices from the data directory tried first
+    pub fn load() -> Self {
+        let mut pricing = Self::builtin();
+        let path = Self::file_path();
+
+        let Ok(contents) = std::fs::read_to_string(&path) else {
+            return pricing;
+        };
+        match serde_json::from_str::<HashMap<String, f64>>(&contents) {
+            Ok(overrides) => {
+                log::info!("Loaded prices for {} models from {:?}", overrides.len(), path);
+                let mut overrides: Vec<(String, f64)> = overrides.into_iter()
+                    .filter(|(_, price)| price.is_finite() && *price >= 0.0)
+                    .collect();
+                overrides.sort_by(|a, b| b.0.len().cmp(&a.0.len()));
+                overrides.append(&mut pricing.prices);
+                pricing.prices = overrides;
+            }
+            Err(e) => {
+                log::warn!("Ignoring invalid model pricing file {:?}: {:?}", path, e);
+            }
+        }
+        pricing
+    }
+
+    fn file_path() -> PathBuf {
+        crate::files::data_dir().join(PRICING_FILENAME)
+    }
+
+    /// US dollars per million input tokens for `model`, if known
+    pub fn input_price(&self, model: &str) -> Option<f64> {
+        let name = model.rsplit('/').next().unwrap_or(model);
+        self.prices.iter()
+            .find(|(pattern, _)| glob_matches(pattern, name))
+            .map(|(_, price)| *price)
+    }
+
+    /// Estimated US dollar cost of sending `tokens` input tokens to `model`
+    pub fn estimate_cost(&self, model: &str, tokens: usize) -> Option<f64> {
+        self.input_price(model).map(|price| price * tokens as f64 / 1_000_000.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prices_the_most_specific_pattern() {
+        let pricing = Pricing::builtin();
+        assert_eq!(pricing.input_price("gpt-4o-mini-2024-07-18"), Some(0.15));
+        assert_eq!(pricing.input_price("openai/gpt-4o"), Some(2.5));
+        assert_eq!(pricing.input_price("llama3.3"), None);
+        assert_eq!(pricing.estimate_cost("gpt-4o", 200_000), Some(0.5));
+    }
+}
@@ -0,0 +1,760 @@
+//! Provider client
+//!
+//! Implements aitk's `BotClient` on a reqwest client from `http::client_builder`,
+//! so listing models and chatting go through the configured proxy like every
+//! other request Moly makes. Models are listed from `<url>/models`, and replies
+//! are streamed from the chat endpoint of the provider type, or exactly the URL
+//! of the provider's chat endpoint override. Anthropic is spoken to through its
+//! Messages API, every other provider through the OpenAI-compatible chat
+//! completions API. Chat requests carry the generation parameters shared by all clients
+//! of a `ProvidersManager`, as they are when the request is made.
+
+use futures::StreamExt;
+use moly_kit::aitk::protocol::{
+    Bot, BotClient, BotId, ClientError, ClientErrorKind, ClientResult, EntityAvatar, EntityId, Message,
+    MessageContent, Tool, ToolCall,
+};
+use moly_kit::aitk::utils::asynchronous::{BoxPlatformSendFuture, BoxPlatformSendStream};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::generation::GenerationParams;
+use crate::http::{client_builder, ProxySettings};
+use crate::providers::{ProviderPreferences, ProviderType};
+
+/// Longest error body quoted in an error message
+const MAX_ERROR_DETAIL_CHARS: usize = 300;
+
+/// Reply length asked of Anthropic when no `max_tokens` is set, since its
+/// Messages API requires one
+const ANTHROPIC_DEFAULT_MAX_TOKENS: u32 = 4096;
+
+/// Client listing the models of one provider and streaming its chat replies
+#[derive(Clone)]
+pub struct ProviderClient {
+    /// URL models are listed from, which bot ids refer to
+    url: String,
+    /// URL chat requests are posted to
+    chat_url: String,
+    /// Authentication and custom headers sent with every request
+    headers: HeaderMap,
+    /// Decides which generation parameters the provider accepts
+    provider_type: ProviderType,
+    /// Generation parameters sent with chat requests
+    generation_params: Arc<Mutex<GenerationParams>>,
+    http: reqwest::Client,
+}
+
+impl ProviderClient {
+    /// Client for `provider`, authenticated with `api_key`, sending its requests
+    /// through `proxy`. Certificate verification is skipped only if the provider
+    /// allows invalid certificates. Chats are sent with `generation_params`.
+    pub fn new(
+        provider: &ProviderPreferences,
+        api_key: &str,
+        proxy: &ProxySettings,
+        generation_params: Arc<Mutex<GenerationParams>>,
+    ) -> Result<Self, String> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in provider.provider_type.auth_headers(api_key) {
+            let value = HeaderValue::from_str(&value).map_err(|_| "Invalid API key".to_string())?;
+            headers.insert(name, value);
+        }
+        // Applied last so they can override the defaults
+        for (name, value) in provider.request_headers() {
+            match (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(value)) {
+                (Ok(name), Ok(value)) => {
+                    headers.insert(name, value);
+                }
+                _ => log::warn!("Invalid custom header {} for provider {}", name, provider.id),
+            }
+        }
+
+        let http = client_builder(proxy)
+            .danger_accept_invalid_certs(provider.allow_invalid_certs)
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+        // An invalid override can only come from a hand-edited preferences file
+        let chat_url = provider.chat_request_url().unwrap_or_else(|e| {
+            log::warn!("Ignoring chat endpoint of provider {}: {}", provider.id, e);
+            provider.default_chat_url()
+        });
+
+        Ok(Self {
+            url: provider.url.clone(),
+            chat_url,
+            headers,
+            provider_type: provider.provider_type.clone(),
+            generation_params,
+            http,
+        })
+    }
+
+    /// Chat request body in the provider's API format, with the generation
+    /// parameters the provider accepts
+    fn chat_body(
+        &self,
+        model: &str,
+        messages: &[Message],
+        images: &[Vec<InlineImage>],
+        tools: &[Tool],
+        params: &GenerationParams,
+    ) -> Value {
+        match self.provider_type {
+            ProviderType::Anthropic => self.anthropic_body(model, messages, images, tools, params),
+            _ => self.openai_body(model, messages, images, tools, params),
+        }
+    }
+
+    /// Chat completions request body
+    fn openai_body(
+        &self,
+        model: &str,
+        messages: &[Message],
+        images: &[Vec<InlineImage>],
+        tools: &[Tool],
+        params: &GenerationParams,
+    ) -> Value {
+        let messages: Vec<Value> = messages.iter()
+            .enumerate()
+            .flat_map(|(i, message)| openai_messages(message, images.get(i).map_or(&[], Vec::as_slice)))
+            .collect();
+
+        let mut body = params.request_fields(&self.provider_type);
+        body.insert("model".to_string(), model.into());
+        body.insert("messages".to_string(), messages.into());
+        body.insert("stream".to_string(), true.into());
+        if !tools.is_empty() {
+            let tools = tools.iter().map(|tool| json!({
+                "type": "function",
+                "function": {
+                    "name": tool.name.to_string(),
+                    "description": tool.description.as_deref().unwrap_or_default(),
+                    "parameters": Value::Object((*tool.input_schema).clone()),
+                },
+            })).collect();
+            body.insert("tools".to_string(), tools);
+        }
+        Value::Object(body)
+    }
+
+    /// Messages API request body. System messages go in the separate `system`
+    /// field, and `max_tokens` is always set since Anthropic requires it.
+    fn anthropic_body(
+        &self,
+        model: &str,
+        messages: &[Message],
+        images: &[Vec<InlineImage>],
+        tools: &[Tool],
+        params: &GenerationParams,
+    ) -> Value {
+        let system: Vec<&str> = messages.iter()
+            .filter(|message| matches!(message.from, EntityId::System))
+            .map(|message| message.content.text.as_str())
+            .filter(|text| !text.trim().is_empty())
+            .collect();
+        let messages: Vec<Value> = messages.iter()
+            .enumerate()
+            .filter_map(|(i, message)| anthropic_message(message, images.get(i).map_or(&[], Vec::as_slice)))
+            .collect();
+
+        let mut body = params.request_fields(&self.provider_type);
+        body.entry("max_tokens").or_insert_with(|| ANTHROPIC_DEFAULT_MAX_TOKENS.into());
+        body.insert("model".to_string(), model.into());
+        body.insert("messages".to_string(), messages.into());
+        body.insert("stream".to_string(), true.into());
+        if !system.is_empty() {
+            body.insert("system".to_string(), system.join("\n\n").into());
+        }
+        if !tools.is_empty() {
+            let tools = tools.iter().map(|tool| json!({
+                "name": tool.name.to_string(),
+                "description": tool.description.as_deref().unwrap_or_default(),
+                "input_schema": Value::Object((*tool.input_schema).clone()),
+            })).collect();
+            body.insert("tools".to_string(), tools);
+        }
+        Value::Object(body)
+    }
+}
+
+impl BotClient for ProviderClient {
+    fn bots(&self) -> BoxPlatformSendFuture<'static, ClientResult<Vec<Bot>>> {
+        let request = self.http
+            .get(format!("{}/models", self.url.trim_end_matches('/')))
+            .headers(self.headers.clone());
+        let url = self.url.clone();
+
+        Box::pin(async move {
+            let listing = match request.send().await {
+                Ok(response) => response_json(response).await,
+                Err(e) => Err(transport_error(&e)),
+            };
+            match listing.and_then(|listing| model_ids(&listing)) {
+                Ok(ids) => ClientResult::new_ok(ids.into_iter().map(|id| model_bot(&url, id)).collect()),
+                Err(error) => ClientResult::new_err(vec![error]),
+            }
+        })
+    }
+
+    fn send(
+        &mut self,
+        bot_id: &BotId,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> BoxPlatformSendStream<'static, ClientResult<MessageContent>> {
+        let client = self.clone();
+        let model = bot_id.id().to_string();
+        let messages = messages.to_vec();
+        let tools = tools.to_vec();
+        // The parameters as they are when the chat is sent
+        let params = self.generation_params.lock().unwrap().clone();
+
+        let response = futures::stream::once(async move {
+            let images = inline_images(&messages).await;
+            let body = client.chat_body(&model, &messages, &images, &tools, &params);
+            let request = client.http.post(&client.chat_url).headers(client.headers.clone()).json(&body);
+            match request.send().await {
+                Ok(response) => checked_response(response).await,
+                Err(e) => Err(transport_error(&e)),
+            }
+        });
+        let provider_type = self.provider_type.clone();
+        Box::pin(response.flat_map(move |response| match response {
+            Ok(response) => reply_stream(response, ReplyDraft::new(provider_type.clone())).boxed(),
+            Err(error) => futures::stream::iter([ClientResult::new_err(vec![error])]).boxed(),
+        }))
+    }
+
+    fn clone_box(&self) -> Box<dyn BotClient> {
+        Box::new(self.clone())
+    }
+}
+
+/// Bot for a model listed at `url`
+fn model_bot(url: &str, id: String) -> Bot {
+    let initial = id.chars().next().map_or_else(|| "?".to_string(), |c| c.to_uppercase().to_string());
+    Bot {
+        id: BotId::new(&id, url),
+        name: id,
+        avatar: EntityAvatar::Text(initial),
+        capabilities: Default::default(),
+    }
+}
+
+/// Model ids of a models listing (`{"data": [{"id": ...}, ...]}`)
+fn model_ids(listing: &Value) -> Result<Vec<String>, ClientError> {
+    let models = listing["data"].as_array().ok_or_else(|| {
+        ClientError::new(ClientErrorKind::Format, "Unexpected models listing from the provider".to_string())
+    })?;
+    Ok(models.iter().filter_map(|model| model["id"].as_str()).map(str::to_string).collect())
+}
+
+/// An image attached to a message, as sent inline
+struct InlineImage {
+    mime_type: String,
+    base64: String,
+}
+
+impl InlineImage {
+    fn data_url(&self) -> String {
+        format!("data:{};base64,{}", self.mime_type, self.base64)
+    }
+}
+
+/// Images attached to each message. Other attachments aren't sent.
+async fn inline_images(messages: &[Message]) -> Vec<Vec<InlineImage>> {
+    let mut images = Vec::with_capacity(messages.len());
+    for message in messages {
+        let mut message_images = Vec::new();
+        for attachment in message.content.attachments.iter().filter(|a| a.is_image()) {
+            match attachment.read_base64().await {
+                Ok(base64) => message_images.push(InlineImage {
+                    mime_type: attachment.content_type.clone().unwrap_or_else(|| "image/png".to_string()),
+                    base64,
+                }),
+                Err(e) => log::warn!("Could not read attachment {}: {}", attachment.name, e),
+            }
+        }
+        images.push(message_images);
+    }
+    images
+}
+
+/// A message in the chat completions format. Tool results become one message
+/// per result; messages from the app itself (e.g. errors) aren't sent.
+fn openai_messages(message: &Message, images: &[InlineImage]) -> Vec<Value> {
+    let content = &message.content;
+    match &message.from {
+        EntityId::System => vec![json!({ "role": "system", "content": content.text })],
+        EntityId::User if images.is_empty() => vec![json!({ "role": "user", "content": content.text })],
+        EntityId::User => {
+            let mut parts = vec![json!({ "type": "text", "text": content.text })];
+            parts.extend(images.iter().map(|image| json!({
+                "type": "image_url",
+                "image_url": { "url": image.data_url() },
+            })));
+            vec![json!({ "role": "user", "content": parts })]
+        }
+        EntityId::Bot(_) => {
+            let mut turn = json!({ "role": "assistant", "content": content.text });
+            if !content.tool_calls.is_empty() {
+                turn["tool_calls"] = content.tool_calls.iter().map(|call| json!({
+                    "id": call.id,
+                    "type": "function",
+                    "function": {
+                        "name": call.name,
+                        "arguments": Value::Object(call.arguments.clone()).to_string(),
+                    },
+                })).collect();
+            }
+            vec![turn]
+        }
+        EntityId::Tool => content.tool_results.iter().map(|result| json!({
+            "role": "tool",
+            "tool_call_id": result.tool_call_id,
+            "content": result.content,
+        })).collect(),
+        EntityId::App => Vec::new(),
+    }
+}
+
+/// A message in the Messages API format, with its text and images, tool uses
+/// or tool results as content blocks. System messages (sent separately),
+/// messages from the app itself and messages left without content aren't sent.
+fn anthropic_message(message: &Message, images: &[InlineImage]) -> Option<Value> {
+    let content = &message.content;
+    let text_block = (!content.text.trim().is_empty()).then(|| json!({ "type": "text", "text": content.text }));
+    let (role, blocks): (&str, Vec<Value>) = match &message.from {
+        EntityId::User => {
+            let mut blocks: Vec<Value> = text_block.into_iter().collect();
+            blocks.extend(images.iter().map(|image| json!({
+                "type": "image",
+                "source": { "type": "base64", "media_type": image.mime_type, "data": image.base64 },
+            })));
+            ("user", blocks)
+        }
+        EntityId::Bot(_) => {
+            let mut blocks: Vec<Value> = text_block.into_iter().collect();
+            blocks.extend(content.tool_calls.iter().map(|call| json!({
+                "type": "tool_use",
+                "id": call.id,
+                "name": call.name,
+                "input": Value::Object(call.arguments.clone()),
+            })));
+            ("assistant", blocks)
+        }
+        // Tool results answer the assistant from the user's side
+        EntityId::Tool => ("user", content.tool_results.iter().map(|result| json!({
+            "type": "tool_result",
+            "tool_use_id": result.tool_call_id,
+            "content": result.content,
+            "is_error": result.is_error,
+        })).collect()),
+        EntityId::System | EntityId::App => return None,
+    };
+    (!blocks.is_empty()).then(|| json!({ "role": role, "content": blocks }))
+}
+
+/// The response if it's a success, otherwise an error quoting its status and body
+async fn checked_response(response: reqwest::Response) -> Result<reqwest::Response, ClientError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    let detail = error_detail(&body);
+    // Worded so that `http::is_rate_limit_error` and friends find the status
+    let message = if detail.is_empty() {
+        format!("Server returned status: {}", status)
+    } else {
+        format!("Server returned status: {}. {}", status, detail)
+    };
+    Err(ClientError::new(ClientErrorKind::Response, message))
+}
+
+async fn response_json(response: reqwest::Response) -> Result<Value, ClientError> {
+    checked_response(response).await?
+        .json()
+        .await
+        .map_err(|e| ClientError::new(ClientErrorKind::Format, format!("Unreadable response: {}", e)))
+}
+
+/// Readable part of an error body: its error message if it's JSON, otherwise
+/// the start of the text
+fn error_detail(body: &str) -> String {
+    match serde_json::from_str::<Value>(body) {
+        Ok(value) => error_message(value.get("error").unwrap_or(&value)),
+        Err(_) => body.trim().chars().take(MAX_ERROR_DETAIL_CHARS).collect(),
+    }
+}
+
+/// Message of an error object (`{"message": ...}`) or string
+fn error_message(error: &Value) -> String {
+    error["message"].as_str()
+        .or_else(|| error.as_str())
+        .map_or_else(|| error.to_string(), str::to_string)
+}
+
+/// Error for a request that got no response, including the causes reqwest
+/// reports (e.g. "tcp connect error: Connection refused") so it can be told
+/// apart from errors of the provider itself
+fn transport_error(error: &reqwest::Error) -> ClientError {
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(error);
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    ClientError::new(ClientErrorKind::Network, message)
+}
+
+/// What a line of the reply stream did to the reply
+#[derive(Debug, PartialEq)]
+enum StreamStep {
+    /// Nothing changed (e.g. a comment or keep-alive line)
+    Skipped,
+    Updated,
+    /// The reply is complete
+    Finished,
+}
+
+/// Tool call being streamed, whose arguments arrive in pieces
+#[derive(Default)]
+struct DraftToolCall {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+/// Reply assembled from the streamed events
+#[derive(Default)]
+struct ReplyDraft {
+    /// Decides the format of the events
+    provider_type: ProviderType,
+    text: String,
+    reasoning: String,
+    tool_calls: Vec<DraftToolCall>,
+    /// Anthropic content block index -> position of its tool call in `tool_calls`
+    tool_blocks: HashMap<u64, usize>,
+}
+
+impl ReplyDraft {
+    fn new(provider_type: ProviderType) -> Self {
+        Self { provider_type, ..Default::default() }
+    }
+
+    /// Apply one line of the server-sent event stream
+    fn apply_line(&mut self, line: &str) -> Result<StreamStep, String> {
+        let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+            return Ok(StreamStep::Skipped);
+        };
+        if data == "[DONE]" {
+            return Ok(StreamStep::Finished);
+        }
+        let event: Value = serde_json::from_str(data).map_err(|e| format!("Unreadable reply from the provider: {}", e))?;
+        self.apply_event(&event)
+    }
+
+    /// Apply an event in the provider's format
+    fn apply_event(&mut self, event: &Value) -> Result<StreamStep, String> {
+        if let Some(error) = event.get("error") {
+            return Err(error_message(error));
+        }
+        match self.provider_type {
+            ProviderType::Anthropic => Ok(self.apply_anthropic_event(event)),
+            _ => Ok(self.apply_openai_event(event)),
+        }
+    }
+
+    /// Apply a chat completions chunk
+    fn apply_openai_event(&mut self, event: &Value) -> StreamStep {
+        let Some(delta) = event["choices"][0].get("delta") else {
+            return StreamStep::Skipped;
+        };
+
+        if let Some(text) = delta["content"].as_str() {
+            self.text.push_str(text);
+        }
+        // DeepSeek and OpenRouter name the reasoning differently
+        if let Some(reasoning) = delta["reasoning_content"].as_str().or_else(|| delta["reasoning"].as_str()) {
+            self.reasoning.push_str(reasoning);
+        }
+        for call in delta["tool_calls"].as_array().into_iter().flatten() {
+            let index = call["index"].as_u64().unwrap_or(0) as usize;
+            if self.tool_calls.len() <= index {
+                self.tool_calls.resize_with(index + 1, Default::default);
+            }
+            let draft = &mut self.tool_calls[index];
+            if let Some(id) = call["id"].as_str() {
+                draft.id = id.to_string();
+            }
+            if let Some(name) = call["function"]["name"].as_str() {
+                draft.name.push_str(name);
+            }
+            if let Some(arguments) = call["function"]["arguments"].as_str() {
+                draft.arguments.push_str(arguments);
+            }
+        }
+        StreamStep::Updated
+    }
+
+    /// Apply a Messages API event. Text, thinking and tool use arrive as
+    /// content blocks; tool use input is streamed as pieces of JSON.
+    fn apply_anthropic_event(&mut self, event: &Value) -> StreamStep {
+        let index = event["index"].as_u64().unwrap_or(0);
+        match event["type"].as_str() {
+            Some("content_block_start") => {
+                let block = &event["content_block"];
+                if block["type"] != "tool_use" {
+                    return StreamStep::Skipped;
+                }
+                self.tool_blocks.insert(index, self.tool_calls.len());
+                self.tool_calls.push(DraftToolCall {
+                    id: block["id"].as_str().unwrap_or_default().to_string(),
+                    name: block["name"].as_str().unwrap_or_default().to_string(),
+                    arguments: String::new(),
+                });
+                StreamStep::Updated
+            }
+            Some("content_block_delta") => {
+                let delta = &event["delta"];
+                match delta["type"].as_str() {
+                    Some("text_delta") => self.text.push_str(delta["text"].as_str().unwrap_or_default()),
+                    Some("thinking_delta") => self.reasoning.push_str(delta["thinking"].as_str().unwrap_or_default()),
+                    Some("input_json_delta") => {
+                        let Some(&position) = self.tool_blocks.get(&index) else {
+                            return StreamStep::Skipped;
+                        };
+                        self.tool_calls[position].arguments.push_str(delta["partial_json"].as_str().unwrap_or_default());
+                    }
+                    _ => return StreamStep::Skipped,
+                }
+                StreamStep::Updated
+            }
+            Some("message_stop") => StreamStep::Finished,
+            // message_start, content_block_stop, message_delta and ping
+            _ => StreamStep::Skipped,
+        }
+    }
+
+    /// Content of the reply so far. Tool calls are only added once it's
+    /// finished, since their arguments aren't valid JSON before that.
+    fn content(&self, finished: bool) -> MessageContent {
+        let tool_calls = if finished {
+            self.tool_calls.iter().map(|call| ToolCall {
+                id: call.id.clone(),
+                name: call.name.clone(),
+                arguments: parse_arguments(&call.arguments),
+                ..Default::default()
+            }).collect()
+        } else {
+            Vec::new()
+        };
+        MessageContent {
+            text: self.text.clone(),
+            reasoning: self.reasoning.clone(),
+            tool_calls,
+            ..Default::default()
+        }
+    }
+}
+
+/// Tool call arguments as streamed, or none when they aren't a JSON object
+fn parse_arguments(arguments: &str) -> Map<String, Value> {
+    if arguments.trim().is_empty() {
+        return Map::new();
+    }
+    serde_json::from_str(arguments).unwrap_or_else(|e| {
+        log::warn!("Ignoring unreadable tool call arguments: {}", e);
+        Map::new()
+    })
+}
+
+/// Reply stream state: bytes received but not yet split into lines
+struct ReplyStream {
+    chunks: futures::stream::BoxStream<'static, reqwest::Result<Vec<u8>>>,
+    buffer: Vec<u8>,
+    draft: ReplyDraft,
+    finished: bool,
+}
+
+/// The reply assembled into `draft` so far, after each streamed event of `response`
+fn reply_stream(
+    response: reqwest::Response,
+    draft: ReplyDraft,
+) -> impl futures::Stream<Item = ClientResult<MessageContent>> + Send {
+    let state = ReplyStream {
+        chunks: response.bytes_stream().map(|chunk| chunk.map(|bytes| bytes.to_vec())).boxed(),
+        buffer: Vec::new(),
+        draft,
+        finished: false,
+    };
+
+    futures::stream::unfold(state, |mut state| async move {
+        while !state.finished {
+            if let Some(end) = state.buffer.iter().position(|&byte| byte == b'\n') {
+                let line: Vec<u8> = state.buffer.drain(..=end).collect();
+                let step = state.draft.apply_line(String::from_utf8_lossy(&line).trim());
+                let item = match step {
+                    Ok(StreamStep::Skipped) => continue,
+                    Ok(StreamStep::Updated) => ClientResult::new_ok(state.draft.content(false)),
+                    Ok(StreamStep::Finished) => {
+                        state.finished = true;
+                        ClientResult::new_ok(state.draft.content(true))
+                    }
+                    Err(message) => {
+                        state.finished = true;
+                        ClientResult::new_err(vec![ClientError::new(ClientErrorKind::Response, message)])
+                    }
+                };
+                return Some((item, state));
+            }
+
+            match state.chunks.next().await {
+                Some(Ok(chunk)) => state.buffer.extend_from_slice(&chunk),
+                Some(Err(e)) => {
+                    state.finished = true;
+                    return Some((ClientResult::new_err(vec![transport_error(&e)]), state));
+                }
+                // A last line without a newline still counts
+                None if !state.buffer.is_empty() => state.buffer.push(b'\n'),
+                // Some servers close the stream without a final marker
+                None => {
+                    state.finished = true;
+                    return Some((ClientResult::new_ok(state.draft.content(true)), state));
+                }
+            }
+        }
+        None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply_lines(provider_type: ProviderType, lines: &[&str]) -> (ReplyDraft, Vec<StreamStep>) {
+        let mut draft = ReplyDraft::new(provider_type);
+        let steps = lines.iter().map(|line| draft.apply_line(line).unwrap()).collect();
+        (draft, steps)
+    }
+
+    #[test]
+    fn assembles_streamed_text_and_tool_calls() {
+        let (draft, steps) = apply_lines(ProviderType::OpenAi, &[
+            r#"data: {"choices":[{"delta":{"role":"assistant","content":"Let me "}}]}"#,
+            ": keep-alive",
+            r#"data: {"choices":[{"delta":{"content":"check."}}]}"#,
+            r#"data: {"choices":[{"delta":{"tool_calls":[{"index":0,"id":"call_1","function":{"name":"get_weather","arguments":"{\"city\":"}}]}}]}"#,
+            r#"data: {"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"\"Lisbon\"}"}}]}}]}"#,
+            "data: [DONE]",
+        ]);
+
+        use StreamStep::*;
+        assert_eq!(steps, [Updated, Skipped, Updated, Updated, Updated, Finished]);
+        assert!(draft.content(false).tool_calls.is_empty());
+
+        let content = draft.content(true);
+        assert_eq!(content.text, "Let me check.");
+        assert_eq!(content.tool_calls.len(), 1);
+        assert_eq!(content.tool_calls[0].id, "call_1");
+        assert_eq!(content.tool_calls[0].name, "get_weather");
+        assert_eq!(content.tool_calls[0].arguments["city"], "Lisbon");
+    }
+
+    #[test]
+    fn reports_errors_sent_in_the_stream() {
+        let mut draft = ReplyDraft::default();
+        let error = draft.apply_line(r#"data: {"error":{"message":"Model overloaded"}}"#);
+        assert_eq!(error, Err("Model overloaded".to_string()));
+    }
+
+    #[test]
+    fn assembles_anthropic_text_thinking_and_tool_use() {
+        let (draft, steps) = apply_lines(ProviderType::Anthropic, &[
+            "event: message_start",
+            r#"data: {"type":"message_start","message":{"id":"msg_1","role":"assistant","content":[]}}"#,
+            r#"data: {"type":"content_block_start","index":0,"content_block":{"type":"thinking","thinking":""}}"#,
+            r#"data: {"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"Needs the weather."}}"#,
+            r#"data: {"type":"content_block_start","index":1,"content_block":{"type":"text","text":""}}"#,
+            r#"data: {"type":"content_block_delta","index":1,"delta":{"type":"text_delta","text":"Let me check."}}"#,
+            r#"data: {"type":"content_block_stop","index":1}"#,
+            r#"data: {"type":"content_block_start","index":2,"content_block":{"type":"tool_use","id":"toolu_1","name":"get_weather","input":{}}}"#,
+            r#"data: {"type":"content_block_delta","index":2,"delta":{"type":"input_json_delta","partial_json":"{\"city\":"}}"#,
+            r#"data: {"type":"content_block_delta","index":2,"delta":{"type":"input_json_delta","partial_json":"\"Lisbon\"}"}}"#,
+            r#"data: {"type":"message_delta","delta":{"stop_reason":"tool_use"}}"#,
+            r#"data: {"type":"message_stop"}"#,
+        ]);
+
+        use StreamStep::*;
+        assert_eq!(steps, [
+            Skipped, Skipped, Skipped, Updated, Skipped, Updated, Skipped, Updated, Updated, Updated, Skipped, Finished,
+        ]);
+
+        let content = draft.content(true);
+        assert_eq!(content.reasoning, "Needs the weather.");
+        assert_eq!(content.text, "Let me check.");
+        assert_eq!(content.tool_calls.len(), 1);
+        assert_eq!(content.tool_calls[0].id, "toolu_1");
+        assert_eq!(content.tool_calls[0].name, "get_weather");
+        assert_eq!(content.tool_calls[0].arguments["city"], "Lisbon");
+    }
+
+    #[test]
+    fn reports_anthropic_errors_sent_in_the_stream() {
+        let mut draft = ReplyDraft::new(ProviderType::Anthropic);
+        let error = draft.apply_line(r#"data: {"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#);
+        assert_eq!(error, Err("Overloaded".to_string()));
+    }
+
+    #[test]
+    fn anthropic_requests_use_the_messages_api_format() {
+        let provider = ProviderPreferences {
+            provider_type: ProviderType::Anthropic,
+            ..ProviderPreferences::new("anthropic", "Anthropic", "https://api.anthropic.com/v1")
+        };
+        let params = Arc::new(Mutex::new(GenerationParams::default()));
+        let client = ProviderClient::new(&provider, "sk-ant-test", &ProxySettings::default(), params).unwrap();
+
+        assert_eq!(client.chat_url, "https://api.anthropic.com/v1/messages");
+        assert_eq!(client.headers["x-api-key"], "sk-ant-test");
+        assert!(client.headers.contains_key("anthropic-version"));
+        assert!(!client.headers.contains_key("authorization"));
+
+        let message = |from, text: &str| Message {
+            from,
+            content: MessageContent { text: text.to_string(), ..Default::default() },
+            ..Default::default()
+        };
+        let mut tool_use = message(EntityId::Bot(BotId::new("claude-sonnet-4", &provider.url)), "");
+        tool_use.content.tool_calls.push(ToolCall {
+            id: "toolu_1".to_string(),
+            name: "get_weather".to_string(),
+            arguments: json!({ "city": "Lisbon" }).as_object().unwrap().clone(),
+            ..Default::default()
+        });
+        let messages = [
+            message(EntityId::System, "Be brief."),
+            message(EntityId::User, "Weather in Lisbon?"),
+            tool_use,
+            message(EntityId::App, "Request failed"),
+        ];
+
+        let body = client.chat_body("claude-sonnet-4", &messages, &[], &[], &GenerationParams::default());
+        assert_eq!(body["system"], "Be brief.");
+        assert_eq!(body["max_tokens"], ANTHROPIC_DEFAULT_MAX_TOKENS);
+        assert_eq!(body["stream"], true);
+        assert_eq!(body["messages"], json!([
+            { "role": "user", "content": [{ "type": "text", "text": "Weather in Lisbon?" }] },
+            {
+                "role": "assistant",
+                "content": [{ "type": "tool_use", "id": "toolu_1", "name": "get_weather", "input": { "city": "Lisbon" } }],
+            },
+        ]));
+    }
+}
@@ -3,6 +3,15 @@ use serde::{Deserialize, Serialize};
 /// Unique identifier for a provider
 pub type ProviderId = String;
 
+/// Path OpenAI-compatible clients append to the base URL for chat requests
+pub const DEFAULT_CHAT_ENDPOINT: &str = "/chat/completions";
+
+/// Path of Anthropic's Messages API, relative to the base URL
+pub const ANTHROPIC_CHAT_ENDPOINT: &str = "/messages";
+
+/// Connection test timeout used when a provider doesn't set `timeout_secs`
+pub const DEFAULT_CONNECTION_TIMEOUT_SECS: u64 = 10;
+
 /// Determines the API format used by the provider
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub enum ProviderType {
@@ -13,6 +22,36 @@ pub enum ProviderType {
     OpenAiRealtime,
     MoFa,
     MolyServer,
+    /// Anthropic's native API: `x-api-key`/`anthropic-version` auth, and chats
+    /// through the Messages API (`/messages`)
+    Anthropic,
+}
+
+/// Port Ollama listens on unless configured otherwise
+pub const OLLAMA_DEFAULT_PORT: u16 = 11434;
+
+/// Value sent in the `anthropic-version` header
+pub const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+impl ProviderType {
+    /// Authentication headers expected by this provider type
+    pub fn auth_headers(&self, api_key: &str) -> Vec<(&'static str, String)> {
+        match self {
+            ProviderType::Anthropic => vec![
+                ("x-api-key", api_key.to_string()),
+                ("anthropic-version", ANTHROPIC_VERSION.to_string()),
+            ],
+            _ => vec![("Authorization", format!("Bearer {}", api_key))],
+        }
+    }
+
+    /// Path appended to the base URL for chat requests
+    pub fn chat_endpoint(&self) -> &'static str {
+        match self {
+            ProviderType::Anthropic => ANTHROPIC_CHAT_ENDPOINT,
+            _ => DEFAULT_CHAT_ENDPOINT,
+        }
+    }
 }
 
 /// Connection status of a provider
@@ -42,6 +81,10 @@ pub struct ProviderPreferences {
     /// (model_name, enabled) pairs
     #[serde(default)]
     pub models: Vec<(String, bool)>,
+    /// Models listed by the last successful connection test, so Settings can
+    /// show them without testing again
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fetched_models: Vec<String>,
     #[serde(default)]
     pub was_customly_added: bool,
     /// Custom system prompt (for Realtime providers)
@@ -50,6 +93,27 @@ pub struct ProviderPreferences {
     /// Whether MCP tools are enabled
     #[serde(default = "default_true")]
     pub tools_enabled: bool,
+    /// Accept self-signed/invalid TLS certificates for this provider only (insecure)
+    #[serde(default)]
+    pub allow_invalid_certs: bool,
+    /// Result of the most recent connection check
+    #[serde(default)]
+    pub last_connection_status: ProviderConnectionStatus,
+    /// Chat completions URL override, relative to `url` or absolute (see `chat_completions_url`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chat_endpoint: Option<String>,
+    /// Extra headers sent with every request (e.g. `HTTP-Referer`, `X-Title` for OpenRouter)
+    #[serde(default)]
+    pub custom_headers: Vec<(String, String)>,
+    /// Model picked when this provider is activated for a chat with no model of its own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_model: Option<String>,
+    /// Models listing path override, relative to `url` or absolute (see `models_url`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub models_path: Option<String>,
+    /// Connection test timeout override, in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
 }
 
 fn default_true() -> bool {
@@ -66,9 +130,17 @@ impl Default for ProviderPreferences {
             enabled: true,
             provider_type: ProviderType::OpenAi,
             models: Vec::new(),
+            fetched_models: Vec::new(),
             was_customly_added: false,
             system_prompt: None,
             tools_enabled: true,
+            allow_invalid_certs: false,
+            last_connection_status: ProviderConnectionStatus::NotConnected,
+            chat_endpoint: None,
+            custom_headers: Vec::new(),
+            default_model: None,
+            models_path: None,
+            timeout_secs: None,
         }
     }
 }
@@ -86,6 +158,155 @@ impl ProviderPreferences {
     pub fn has_api_key(&self) -> bool {
         self.api_key.as_ref().map_or(false, |k| !k.is_empty())
     }
+
+    /// Custom headers with a non-empty name
+    pub fn request_headers(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.custom_headers
+            .iter()
+            .map(|(name, value)| (name.trim(), value.as_str()))
+            .filter(|(name, _)| !name.is_empty())
+    }
+
+    /// Full models listing URL from `models_path`, or None to use the standard paths.
+    /// An absolute path (`http://` or `https://`) is used as is; a relative one is joined to `url`.
+    pub fn models_url(&self) -> Option<String> {
+        let path = self.models_path.as_deref().map(str::trim).filter(|p| !p.is_empty())?;
+        if path.starts_with("http://") || path.starts_with("https://") {
+            Some(path.to_string())
+        } else {
+            Some(format!("{}/{}", self.url.trim_end_matches('/'), path.trim_start_matches('/')))
+        }
+    }
+
+    /// Timeout for connection tests, falling back to the default when unset
+    pub fn connection_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.timeout_secs.unwrap_or(DEFAULT_CONNECTION_TIMEOUT_SECS))
+    }
+
+    /// Full chat completions URL from `chat_endpoint`, or None to use `url` with the
+    /// standard chat path of the provider type. An absolute endpoint (`http://` or `https://`) is used
+    /// as is; a relative one is joined to `url`. Only chat requests go there: models are
+    /// still listed from `url`, and bot ids keep referring to it.
+    pub fn chat_completions_url(&self) -> Result<Option<String>, String> {
+        let Some(endpoint) = self.chat_endpoint.as_deref().map(str::trim).filter(|e| !e.is_empty()) else {
+            return Ok(None);
+        };
+
+        let full = if endpoint.starts_with("http://") || endpoint.starts_with("https://") {
+            endpoint.to_string()
+        } else {
+            format!("{}/{}", self.url.trim_end_matches('/'), endpoint.trim_start_matches('/'))
+        };
+
+        let parsed = reqwest::Url::parse(&full).map_err(|e| format!("Invalid chat endpoint: {}", e))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(format!("Unsupported chat endpoint scheme: {}", parsed.scheme()));
+        }
+        Ok(Some(full))
+    }
+
+    /// URL chat requests are sent to: the endpoint override, or `url` with the
+    /// standard chat path of the provider type
+    pub fn chat_request_url(&self) -> Result<String, String> {
+        Ok(self.chat_completions_url()?.unwrap_or_else(|| self.default_chat_url()))
+    }
+
+    /// `url` with the standard chat path of the provider type
+    pub fn default_chat_url(&self) -> String {
+        format!("{}{}", self.url.trim_end_matches('/'), self.provider_type.chat_endpoint())
+    }
+}
+
+/// Check that a provider URL has an http(s) scheme and a host, and return it
+/// trimmed and without trailing slashes
+pub fn normalize_provider_url(url: &str) -> Result<String, String> {
+    let url = url.trim();
+    if url.is_empty() {
+        return Err("Provider URL is required".to_string());
+    }
+    // Without a scheme, "api.openai.com/v1" would otherwise parse as a relative path
+    if !url.contains("://") {
+        return Err(format!("URL must start with http:// or https:// (e.g. https://{})", url));
+    }
+
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!("Unsupported URL scheme: {}", parsed.scheme()));
+    }
+    if parsed.host_str().map_or(true, |host| host.is_empty()) {
+        return Err("URL is missing a host".to_string());
+    }
+    Ok(url.trim_end_matches('/').to_string())
+}
+
+/// Ollama's native model listing (`/api/tags`) for a provider URL that looks
+/// like an Ollama server, i.e. uses its default port or has "ollama" in the
+/// host. Ollama's OpenAI-compatible `/v1/models` may list nothing on older
+/// versions, while `/api/tags` always lists the installed models.
+pub fn ollama_tags_url(url: &str) -> Option<String> {
+    let parsed = reqwest::Url::parse(url.trim()).ok()?;
+    let host = parsed.host_str()?;
+    if parsed.port() != Some(OLLAMA_DEFAULT_PORT) && !host.contains("ollama") {
+        return None;
+    }
+    let port = parsed.port().map(|port| format!(":{}", port)).unwrap_or_default();
+    Some(format!("{}://{}{}/api/tags", parsed.scheme(), host, port))
+}
+
+/// Environment variable prefix for a provider, e.g. `OPENAI` for `OPENAI_API_KEY`
+///
+/// Known providers use their conventional names; custom providers get their
+/// id uppercased with anything outside `[A-Z0-9]` replaced by `_`.
+pub fn env_var_prefix(provider: &ProviderPreferences) -> String {
+    match provider.id.as_str() {
+        "openai" => "OPENAI".to_string(),
+        "anthropic" => "ANTHROPIC".to_string(),
+        "gemini" => "GEMINI".to_string(),
+        "ollama" => "OLLAMA".to_string(),
+        "groq" => "GROQ".to_string(),
+        "deepseek" => "DEEPSEEK".to_string(),
+        id => {
+            let source = if id.is_empty() { provider.name.as_str() } else { id };
+            let mut prefix: String = source
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+                .collect();
+            prefix = prefix.trim_matches('_').to_string();
+            if prefix.is_empty() {
+                prefix = "PROVIDER".to_string();
+            }
+            // Variable names can't start with a digit
+            if prefix.starts_with(|c: char| c.is_ascii_digit()) {
+                prefix.insert(0, '_');
+            }
+            prefix
+        }
+    }
+}
+
+/// Quote a `.env` value when it contains characters a shell would interpret
+fn env_value(value: &str) -> String {
+    if value.chars().any(|c| c.is_whitespace() || matches!(c, '#' | '"' | '\'' | '$' | '\\')) {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Render providers as a `.env` snippet with `<PREFIX>_API_KEY` and `<PREFIX>_BASE_URL`
+///
+/// API keys are written in plain text; callers must confirm with the user first.
+pub fn providers_to_env(providers: &[&ProviderPreferences]) -> String {
+    let mut lines = vec!["# Exported from Moly - contains API keys, keep private".to_string()];
+    for provider in providers {
+        let prefix = env_var_prefix(provider);
+        lines.push(format!("# {}", provider.name));
+        if let Some(api_key) = provider.api_key.as_deref().filter(|k| !k.trim().is_empty()) {
+            lines.push(format!("{}_API_KEY={}", prefix, env_value(api_key.trim())));
+        }
+        lines.push(format!("{}_BASE_URL={}", prefix, env_value(&provider.url)));
+    }
+    lines.join("\n") + "\n"
 }
 
 /// Get list of supported providers with default URLs
@@ -102,7 +323,7 @@ pub fn get_supported_providers() -> Vec<ProviderPreferences> {
             id: "anthropic".to_string(),
             name: "Anthropic".to_string(),
             url: "https://api.anthropic.com/v1".to_string(),
-            provider_type: ProviderType::OpenAi,
+            provider_type: ProviderType::Anthropic,
             ..Default::default()
         },
         ProviderPreferences {
@@ -1,17 +1,60 @@
 use std::collections::HashMap;
-use moly_kit::aitk::clients::openai::OpenAiClient;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Utc};
 use moly_kit::aitk::protocol::{Bot, BotId};
+use serde::{Deserialize, Serialize};
 
-use crate::providers::ProviderPreferences;
+use crate::generation::GenerationParams;
+use crate::http::{client_builder, rate_limit_delay, send_with_rate_limit_retries, ProxySettings, MAX_RATE_LIMIT_RETRIES};
+use crate::provider_client::ProviderClient;
+use crate::providers::{ProviderPreferences, ProviderType};
+
+const MODEL_CACHE_FILENAME: &str = "model_cache.json";
+
+/// Bots last fetched from a provider, as persisted in the model cache
+#[derive(Serialize, Deserialize)]
+struct CachedProviderBots {
+    /// Provider URL the bots were fetched from (BotIds embed it)
+    url: String,
+    fetched_at: DateTime<Utc>,
+    bots: Vec<Bot>,
+}
+
+/// Backoff of a provider that rate-limited our requests
+struct RateLimitBackoff {
+    /// Consecutive rate-limited requests
+    attempts: u32,
+    /// When requests may resume
+    until: Instant,
+}
 
 /// Manages multiple AI provider clients and their models
 pub struct ProvidersManager {
-    /// Map of provider_id -> OpenAiClient
-    clients: HashMap<String, OpenAiClient>,
+    /// Map of provider_id -> client listing its models and sending its chats
+    clients: HashMap<String, ProviderClient>,
+    /// Generation parameters all clients send chats with
+    generation_params: Arc<Mutex<GenerationParams>>,
+    /// Map of provider_id -> URL of its configured client
+    provider_urls: HashMap<String, String>,
     /// Map of provider_id -> list of bots from that provider
     provider_bots: HashMap<String, Vec<Bot>>,
+    /// Map of provider_id -> when its bots were fetched
+    fetched_at: HashMap<String, DateTime<Utc>>,
     /// Combined list of all bots from all providers
     all_bots: Vec<Bot>,
+    /// Map of bot id -> provider_id, so lookups stay fast with hundreds of bots
+    bot_providers: HashMap<BotId, String>,
+    /// Map of provider_id -> model name -> context window in tokens, as reported by
+    /// the provider's models listing. Kept across reconfiguration.
+    context_windows: HashMap<String, HashMap<String, usize>>,
+    /// Map of provider_id -> rate-limit backoff after it answered 429
+    rate_limits: HashMap<String, RateLimitBackoff>,
+    /// Bumped whenever the combined bot list changes
+    bots_revision: u64,
+    /// Bumped whenever the clients are rebuilt, e.g. for a new proxy
+    clients_revision: u64,
     /// Currently active provider ID
     active_provider_id: Option<String>,
 }
@@ -26,17 +69,30 @@ impl ProvidersManager {
     pub fn new() -> Self {
         Self {
             clients: HashMap::new(),
+            generation_params: Arc::new(Mutex::new(GenerationParams::default())),
+            provider_urls: HashMap::new(),
             provider_bots: HashMap::new(),
+            fetched_at: HashMap::new(),
             all_bots: Vec::new(),
+            bot_providers: HashMap::new(),
+            context_windows: HashMap::new(),
+            rate_limits: HashMap::new(),
+            bots_revision: 0,
+            clients_revision: 0,
             active_provider_id: None,
         }
     }
 
-    /// Configure clients for all enabled providers
-    pub fn configure_providers(&mut self, providers: &[&ProviderPreferences]) {
+    /// Configure clients for all enabled providers, sending their requests through `proxy`
+    pub fn configure_providers(&mut self, providers: &[&ProviderPreferences], proxy: &ProxySettings) {
         self.clients.clear();
+        self.provider_urls.clear();
         self.provider_bots.clear();
+        self.fetched_at.clear();
         self.all_bots.clear();
+        self.bot_providers.clear();
+        self.bots_revision += 1;
+        self.clients_revision += 1;
 
         for provider in providers {
             if let Some(api_key) = &provider.api_key {
@@ -45,27 +101,38 @@ impl ProvidersManager {
                     continue;
                 }
 
-                let mut client = OpenAiClient::new(provider.url.clone());
-                if client.set_key(api_key).is_ok() {
-                    log::info!("Configured client for provider: {} ({})", provider.id, provider.url);
-                    self.clients.insert(provider.id.clone(), client);
-
-                    // Set first provider as active if none set
-                    if self.active_provider_id.is_none() {
-                        self.active_provider_id = Some(provider.id.clone());
+                let client = match ProviderClient::new(provider, api_key, proxy, self.generation_params.clone()) {
+                    Ok(client) => client,
+                    Err(e) => {
+                        log::warn!("Could not configure provider {}: {}", provider.id, e);
+                        continue;
                     }
+                };
+                log::info!("Configured client for provider: {} ({})", provider.id, provider.url);
+                self.clients.insert(provider.id.clone(), client);
+                self.provider_urls.insert(provider.id.clone(), provider.url.clone());
+
+                // Set first provider as active if none set
+                if self.active_provider_id.is_none() {
+                    self.active_provider_id = Some(provider.id.clone());
                 }
             }
         }
     }
 
+    /// Set the generation parameters chats are sent with, from the next chat
+    /// request on. Clients keep them across reconfiguration.
+    pub fn set_generation_params(&self, params: GenerationParams) {
+        *self.generation_params.lock().unwrap() = params;
+    }
+
     /// Get the currently active client
-    pub fn get_active_client(&self) -> Option<&OpenAiClient> {
+    pub fn get_active_client(&self) -> Option<&ProviderClient> {
         self.active_provider_id.as_ref().and_then(|id| self.clients.get(id))
     }
 
     /// Get a mutable reference to the active client
-    pub fn get_active_client_mut(&mut self) -> Option<&mut OpenAiClient> {
+    pub fn get_active_client_mut(&mut self) -> Option<&mut ProviderClient> {
         if let Some(id) = &self.active_provider_id {
             self.clients.get_mut(id)
         } else {
@@ -74,12 +141,12 @@ impl ProvidersManager {
     }
 
     /// Get client for a specific provider
-    pub fn get_client(&self, provider_id: &str) -> Option<&OpenAiClient> {
+    pub fn get_client(&self, provider_id: &str) -> Option<&ProviderClient> {
         self.clients.get(provider_id)
     }
 
     /// Clone client for a specific provider (needed for ChatController)
-    pub fn clone_client(&self, provider_id: &str) -> Option<OpenAiClient> {
+    pub fn clone_client(&self, provider_id: &str) -> Option<ProviderClient> {
         self.clients.get(provider_id).cloned()
     }
 
@@ -104,24 +171,40 @@ impl ProvidersManager {
     pub fn set_provider_bots(&mut self, provider_id: &str, bots: Vec<Bot>) {
         log::info!("Setting {} bots for provider {}", bots.len(), provider_id);
         self.provider_bots.insert(provider_id.to_string(), bots);
+        self.fetched_at.insert(provider_id.to_string(), Utc::now());
         self.rebuild_all_bots();
     }
 
     /// Rebuild the combined bots list from all providers
     fn rebuild_all_bots(&mut self) {
         self.all_bots.clear();
+        self.bot_providers.clear();
         for (provider_id, bots) in &self.provider_bots {
             for bot in bots {
                 // Clone bot and ensure it has provider info in the ID
                 let bot = bot.clone();
                 // The BotId should already contain the provider URL, but we can log it
                 log::debug!("Adding bot: {} from provider {}", bot.name, provider_id);
+                self.bot_providers.insert(bot.id.clone(), provider_id.clone());
                 self.all_bots.push(bot);
             }
         }
+        self.bots_revision += 1;
         log::info!("Total bots from all providers: {}", self.all_bots.len());
     }
 
+    /// Changes whenever the combined bot list changes, so callers can cache
+    /// anything derived from it
+    pub fn bots_revision(&self) -> u64 {
+        self.bots_revision
+    }
+
+    /// Changes whenever the clients are rebuilt, so holders of cloned clients
+    /// know to take new ones
+    pub fn clients_revision(&self) -> u64 {
+        self.clients_revision
+    }
+
     /// Get all bots from all providers
     pub fn get_all_bots(&self) -> &[Bot] {
         &self.all_bots
@@ -130,19 +213,123 @@ impl ProvidersManager {
     /// Clear all bots from all providers
     pub fn clear_all_bots(&mut self) {
         self.provider_bots.clear();
+        self.fetched_at.clear();
         self.all_bots.clear();
+        self.bot_providers.clear();
+        self.bots_revision += 1;
         log::info!("Cleared all bots from providers manager");
     }
 
     /// Get the provider ID for a given bot ID (by matching the provider string)
     pub fn get_provider_for_bot(&self, bot_id: &BotId) -> Option<&str> {
-        // Check for a match in our stored bots
+        self.bot_providers.get(bot_id).map(String::as_str)
+    }
+
+    /// Bot of a provider serving the given model, once the provider's models are loaded
+    pub fn bot_id_for_model(&self, provider_id: &str, model_name: &str) -> Option<&BotId> {
+        self.provider_bots.get(provider_id)?
+            .iter()
+            .map(|bot| &bot.id)
+            .find(|bot_id| bot_model_name(bot_id) == model_name)
+    }
+
+    /// Record the context windows a provider reported for its models
+    pub fn set_context_windows(&mut self, provider_id: &str, windows: HashMap<String, usize>) {
+        log::info!("Setting context windows of {} models for provider {}", windows.len(), provider_id);
+        self.context_windows.insert(provider_id.to_string(), windows);
+    }
+
+    /// Context window of a bot in tokens, if its provider reported one
+    pub fn context_window(&self, bot_id: &BotId) -> Option<usize> {
+        let provider_id = self.get_provider_for_bot(bot_id)?;
+        let windows = self.context_windows.get(provider_id)?;
+        windows.get(bot_model_name(bot_id)).copied()
+    }
+
+    /// Whether a bot accepts images attached to messages
+    pub fn supports_image_input(&self, bot_id: &BotId) -> bool {
+        crate::vision::supports_image_input(bot_model_name(bot_id))
+    }
+
+    fn model_cache_path() -> PathBuf {
+        crate::files::data_dir().join(MODEL_CACHE_FILENAME)
+    }
+
+    /// Persist the fetched bot lists to `~/.moly/model_cache.json`.
+    /// Entries of providers not fetched this session are kept.
+    pub fn save_cache(&self) {
+        let path = Self::model_cache_path();
+        let mut cache: HashMap<String, CachedProviderBots> = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+
         for (provider_id, bots) in &self.provider_bots {
-            if bots.iter().any(|b| &b.id == bot_id) {
-                return Some(provider_id);
+            let (Some(url), Some(fetched_at)) = (self.provider_urls.get(provider_id), self.fetched_at.get(provider_id)) else {
+                continue;
+            };
+            cache.insert(provider_id.clone(), CachedProviderBots {
+                url: url.clone(),
+                fetched_at: *fetched_at,
+                bots: bots.clone(),
+            });
+        }
+
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        match serde_json::to_string(&cache) {
+            Ok(json) => {
+                if let Err(e) = crate::files::write_atomic(&path, json) {
+                    log::error!("Failed to write model cache: {:?}", e);
+                }
+            }
+            Err(e) => log::error!("Failed to serialize model cache: {:?}", e),
+        }
+    }
+
+    /// Load cached bot lists for the configured providers.
+    ///
+    /// Entries older than `ttl`, or fetched from a different URL than the one
+    /// currently configured, are ignored. Returns the provider ids that were loaded.
+    pub fn load_cache(&mut self, ttl: Duration) -> Vec<String> {
+        let Ok(contents) = std::fs::read_to_string(Self::model_cache_path()) else {
+            return Vec::new();
+        };
+        let cache: HashMap<String, CachedProviderBots> = match serde_json::from_str(&contents) {
+            Ok(cache) => cache,
+            Err(e) => {
+                log::warn!("Ignoring unreadable model cache: {:?}", e);
+                return Vec::new();
             }
+        };
+
+        let max_age = chrono::Duration::from_std(ttl).unwrap_or(chrono::Duration::MAX);
+        let mut loaded = Vec::new();
+        for (provider_id, entry) in cache {
+            let url_matches = self.provider_urls.get(&provider_id) == Some(&entry.url);
+            if !url_matches || Utc::now() - entry.fetched_at > max_age || entry.bots.is_empty() {
+                continue;
+            }
+            self.provider_bots.insert(provider_id.clone(), entry.bots);
+            self.fetched_at.insert(provider_id.clone(), entry.fetched_at);
+            loaded.push(provider_id);
+        }
+
+        if !loaded.is_empty() {
+            log::info!("Loaded cached models for {} provider(s)", loaded.len());
+            self.rebuild_all_bots();
         }
-        None
+        loaded
+    }
+
+    /// Fetch the bot list of a provider without going through a ChatController
+    pub async fn fetch_bots(client: &ProviderClient) -> Result<Vec<Bot>, String> {
+        use moly_kit::aitk::protocol::BotClient;
+
+        client.bots().await.into_result().map_err(|errors| {
+            errors.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("; ")
+        })
     }
 
     /// Check if any providers are configured
@@ -150,8 +337,359 @@ impl ProvidersManager {
         !self.clients.is_empty()
     }
 
+    /// Record that a provider rate-limited a request, returning how long to wait
+    /// before retrying. None once `MAX_RATE_LIMIT_RETRIES` retries in a row were
+    /// rate-limited too; the next request then starts a fresh backoff.
+    pub fn note_rate_limited(&mut self, provider_id: &str, retry_after: Option<&str>) -> Option<Duration> {
+        let now = Instant::now();
+        // A rate limit long past doesn't count towards the backoff anymore
+        let attempts = match self.rate_limits.get(provider_id) {
+            Some(backoff) if now.saturating_duration_since(backoff.until) < rate_limit_delay(None, MAX_RATE_LIMIT_RETRIES) => backoff.attempts,
+            _ => 0,
+        };
+        if attempts >= MAX_RATE_LIMIT_RETRIES {
+            self.rate_limits.remove(provider_id);
+            return None;
+        }
+
+        let delay = rate_limit_delay(retry_after, attempts);
+        self.rate_limits.insert(provider_id.to_string(), RateLimitBackoff { attempts: attempts + 1, until: now + delay });
+        log::warn!("Provider {} rate limited, retry {} in {}s", provider_id, attempts + 1, delay.as_secs());
+        Some(delay)
+    }
+
+    /// Time left before a rate-limited provider should be sent requests again
+    pub fn rate_limit_remaining(&self, provider_id: &str) -> Option<Duration> {
+        let remaining = self.rate_limits.get(provider_id)?.until.saturating_duration_since(Instant::now());
+        (!remaining.is_zero()).then_some(remaining)
+    }
+
     /// Get list of configured provider IDs
     pub fn configured_provider_ids(&self) -> Vec<&str> {
         self.clients.keys().map(|s| s.as_str()).collect()
     }
+
+    /// Lightweight reachability check: lists models at `models_url` with the given key
+    pub async fn check_provider_reachable(
+        models_url: &str,
+        api_key: &str,
+        provider_type: &ProviderType,
+        custom_headers: &[(String, String)],
+        allow_invalid_certs: bool,
+        proxy: &ProxySettings,
+        timeout: Duration,
+    ) -> Result<(), String> {
+        let client = client_builder(proxy)
+            .timeout(timeout)
+            .danger_accept_invalid_certs(allow_invalid_certs)
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+        let mut request = client.get(models_url);
+        for (name, value) in provider_type.auth_headers(api_key) {
+            request = request.header(name, value);
+        }
+        for (name, value) in custom_headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        let response = send_with_rate_limit_retries(request)
+            .await
+            .map_err(|e| {
+                if e.is_timeout() {
+                    "Connection timed out".to_string()
+                } else if e.is_connect() {
+                    "Could not connect to server".to_string()
+                } else {
+                    format!("Request failed: {}", e)
+                }
+            })?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(format!("Server returned status: {}", response.status()))
+        }
+    }
+
+    /// Send a one-token chat request to check the provider accepts chats
+    ///
+    /// Posts to `completions_url`, the same chat URL the chat screen uses, with
+    /// the same headers, and asks for `max_tokens: 1` so the test costs as little
+    /// as possible. The body is valid in both the chat completions and Anthropic
+    /// Messages formats; the reply must be in the provider's format.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn test_chat_completion(
+        completions_url: &str,
+        api_key: &str,
+        provider_type: &ProviderType,
+        custom_headers: &[(String, String)],
+        model_id: &str,
+        allow_invalid_certs: bool,
+        proxy: &ProxySettings,
+        timeout: Duration,
+    ) -> Result<(), String> {
+        let client = client_builder(proxy)
+            .timeout(timeout)
+            .danger_accept_invalid_certs(allow_invalid_certs)
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+        let body = serde_json::json!({
+            "model": model_id,
+            "messages": [{ "role": "user", "content": "Reply with OK." }],
+            "max_tokens": 1,
+        });
+        let mut request = client.post(completions_url).json(&body);
+        for (name, value) in provider_type.auth_headers(api_key) {
+            request = request.header(name, value);
+        }
+        for (name, value) in custom_headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        let response = send_with_rate_limit_retries(request)
+            .await
+            .map_err(|e| {
+                if e.is_timeout() {
+                    "Connection timed out".to_string()
+                } else if e.is_connect() {
+                    "Could not connect to server".to_string()
+                } else {
+                    format!("Request failed: {}", e)
+                }
+            })?;
+
+        let status = response.status();
+        let reply: serde_json::Value = response.json().await.unwrap_or_default();
+        if !status.is_success() {
+            return match reply["error"]["message"].as_str() {
+                Some(message) => Err(format!("Server returned status: {}. {}", status, message)),
+                None => Err(format!("Server returned status: {}", status)),
+            };
+        }
+
+        let answered = match provider_type {
+            ProviderType::Anthropic => reply["content"].is_array(),
+            _ => reply["choices"].is_array(),
+        };
+        if answered {
+            Ok(())
+        } else {
+            Err("Unexpected reply from the chat endpoint".to_string())
+        }
+    }
+}
+
+/// Model name part of a bot id (`<len>;<model>@<provider>`)
+fn bot_model_name(bot_id: &BotId) -> &str {
+    let id = bot_id.as_str();
+    id.split_once(';')
+        .and_then(|(len, rest)| rest.get(..len.parse::<usize>().ok()?))
+        .unwrap_or(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use moly_kit::aitk::protocol::BotClient;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    const MODELS_LISTING: &str = r#"{"data":[{"id":"gpt-4o"}]}"#;
+    const ANTHROPIC_REPLY: &str = "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"OK\"}}\n\ndata: {\"type\":\"message_stop\"}\n\n";
+    const CHAT_REPLY: &str = "data: {\"choices\":[{\"delta\":{\"content\":\"OK\"}}]}\n\ndata: [DONE]\n\n";
+
+    /// Request line and body of a request answered by a test server
+    struct ServedRequest {
+        line: String,
+        body: String,
+    }
+
+    /// Answer one HTTP request with the models listing
+    fn serve_models_once() -> (u16, std::thread::JoinHandle<ServedRequest>) {
+        serve_once("application/json", MODELS_LISTING)
+    }
+
+    /// Answer one HTTP request with `body`
+    fn serve_once(content_type: &'static str, body: &'static str) -> (u16, std::thread::JoinHandle<ServedRequest>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            // Headers end at the first empty line
+            let mut header = String::new();
+            let mut content_length = 0;
+            while reader.read_line(&mut header).unwrap() > 2 {
+                if let Some((name, value)) = header.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+                header.clear();
+            }
+            let mut request_body = vec![0; content_length];
+            reader.read_exact(&mut request_body).unwrap();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                content_type,
+                body.len(),
+                body
+            ).unwrap();
+            ServedRequest {
+                line: request_line.trim_end().to_string(),
+                body: String::from_utf8(request_body).unwrap(),
+            }
+        });
+        (port, handle)
+    }
+
+    fn provider(id: &str, url: &str) -> ProviderPreferences {
+        ProviderPreferences {
+            api_key: Some("sk-test".to_string()),
+            ..ProviderPreferences::new(id, id, url)
+        }
+    }
+
+    fn list_models(manager: &ProvidersManager, provider_id: &str) -> Vec<String> {
+        let client = manager.get_client(provider_id).unwrap();
+        let rt = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        let bots = rt.block_on(ProvidersManager::fetch_bots(client)).unwrap();
+        bots.iter().map(|bot| bot.id.id().to_string()).collect()
+    }
+
+    /// Send a chat with the provider's client and return the text of the reply
+    fn chat(manager: &ProvidersManager, provider_id: &str) -> String {
+        let mut client = manager.clone_client(provider_id).unwrap();
+        let bot_id = BotId::new("gpt-4o", &manager.provider_urls[provider_id]);
+        let rt = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        let updates: Vec<_> = rt.block_on(client.send(&bot_id, &[], &[]).collect());
+        let content = updates.into_iter().last().unwrap().into_result().unwrap();
+        content.text
+    }
+
+    #[test]
+    fn provider_clients_use_the_configured_proxy_and_bypass_list() {
+        let (proxy_port, proxy_server) = serve_models_once();
+        let (local_port, local_server) = serve_models_once();
+        let proxy = ProxySettings {
+            enabled: true,
+            url: format!("http://127.0.0.1:{}", proxy_port),
+            no_proxy: "127.0.0.1".to_string(),
+            ..Default::default()
+        };
+        let remote = provider("remote", "http://models.example/v1");
+        let local = provider("local", &format!("http://127.0.0.1:{}/v1", local_port));
+
+        let mut manager = ProvidersManager::new();
+        manager.configure_providers(&[&remote, &local], &proxy);
+
+        assert_eq!(list_models(&manager, "remote"), ["gpt-4o"]);
+        // The proxy is asked for the full URL of the provider
+        assert_eq!(proxy_server.join().unwrap().line, "GET http://models.example/v1/models HTTP/1.1");
+
+        assert_eq!(list_models(&manager, "local"), ["gpt-4o"]);
+        // Hosts on the bypass list are reached directly
+        assert_eq!(local_server.join().unwrap().line, "GET /v1/models HTTP/1.1");
+    }
+
+    #[test]
+    fn chat_requests_go_to_the_chat_endpoint_exactly() {
+        let (default_port, default_server) = serve_once("text/event-stream", CHAT_REPLY);
+        let (relative_port, relative_server) = serve_once("text/event-stream", CHAT_REPLY);
+        let (absolute_port, absolute_server) = serve_once("text/event-stream", CHAT_REPLY);
+        let default = provider("default", &format!("http://127.0.0.1:{}/v1", default_port));
+        let relative = ProviderPreferences {
+            chat_endpoint: Some("/deployments/gpt-4o/chat?api-version=2024-06-01".to_string()),
+            ..provider("relative", &format!("http://127.0.0.1:{}/openai", relative_port))
+        };
+        let absolute = ProviderPreferences {
+            chat_endpoint: Some(format!("http://127.0.0.1:{}/llm/v2/generate", absolute_port)),
+            ..provider("absolute", "http://models.example/v1")
+        };
+
+        let mut manager = ProvidersManager::new();
+        manager.configure_providers(&[&default, &relative, &absolute], &ProxySettings::default());
+
+        assert_eq!(chat(&manager, "default"), "OK");
+        assert_eq!(default_server.join().unwrap().line, "POST /v1/chat/completions HTTP/1.1");
+
+        // Overrides replace the whole path, query included
+        assert_eq!(chat(&manager, "relative"), "OK");
+        assert_eq!(
+            relative_server.join().unwrap().line,
+            "POST /openai/deployments/gpt-4o/chat?api-version=2024-06-01 HTTP/1.1"
+        );
+        assert_eq!(chat(&manager, "absolute"), "OK");
+        assert_eq!(absolute_server.join().unwrap().line, "POST /llm/v2/generate HTTP/1.1");
+    }
+
+    #[test]
+    fn chat_requests_carry_the_generation_params() {
+        let (openai_port, openai_server) = serve_once("text/event-stream", CHAT_REPLY);
+        let (anthropic_port, anthropic_server) = serve_once("text/event-stream", ANTHROPIC_REPLY);
+        let openai = provider("openai", &format!("http://127.0.0.1:{}/v1", openai_port));
+        let anthropic = ProviderPreferences {
+            provider_type: ProviderType::Anthropic,
+            ..provider("anthropic", &format!("http://127.0.0.1:{}/v1", anthropic_port))
+        };
+
+        let mut manager = ProvidersManager::new();
+        manager.configure_providers(&[&openai, &anthropic], &ProxySettings::default());
+        manager.set_generation_params(GenerationParams {
+            temperature: Some(1.5),
+            top_p: Some(0.5),
+            max_tokens: Some(256),
+        });
+
+        assert_eq!(chat(&manager, "openai"), "OK");
+        let body: serde_json::Value = serde_json::from_str(&openai_server.join().unwrap().body).unwrap();
+        assert_eq!(body["model"], "gpt-4o");
+        assert_eq!(body["stream"], true);
+        assert_eq!(body["temperature"], 1.5);
+        assert_eq!(body["top_p"], 0.5);
+        assert_eq!(body["max_tokens"], 256);
+
+        // Anthropic takes temperatures up to 1.0, so this one is left to its default
+        assert_eq!(chat(&manager, "anthropic"), "OK");
+        let request = anthropic_server.join().unwrap();
+        assert_eq!(request.line, "POST /v1/messages HTTP/1.1");
+        let body: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+        assert!(body.get("temperature").is_none());
+        assert_eq!(body["top_p"], 0.5);
+        assert_eq!(body["max_tokens"], 256);
+    }
+
+    #[test]
+    fn anthropic_chat_test_expects_a_messages_api_reply() {
+        let test_chat = |port: u16| {
+            let anthropic = ProviderPreferences {
+                provider_type: ProviderType::Anthropic,
+                ..provider("anthropic", &format!("http://127.0.0.1:{}/v1", port))
+            };
+            let rt = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+            rt.block_on(ProvidersManager::test_chat_completion(
+                &anthropic.chat_request_url().unwrap(),
+                "sk-ant-test",
+                &anthropic.provider_type,
+                &[],
+                "claude-sonnet-4",
+                false,
+                &ProxySettings::default(),
+                Duration::from_secs(5),
+            ))
+        };
+
+        let (port, server) = serve_once("application/json", r#"{"type":"message","content":[{"type":"text","text":"OK"}]}"#);
+        assert_eq!(test_chat(port), Ok(()));
+        assert_eq!(server.join().unwrap().line, "POST /v1/messages HTTP/1.1");
+
+        // A chat completions reply means the endpoint isn't Anthropic's
+        let (port, server) = serve_once("application/json", r#"{"choices":[{"message":{"content":"OK"}}]}"#);
+        assert_eq!(test_chat(port), Err("Unexpected reply from the chat endpoint".to_string()));
+        server.join().unwrap();
+    }
 }
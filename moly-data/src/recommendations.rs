@@ -0,0 +1,164 @@
+//! Recommended models
+//!
+//! A small table of preferred chat models per provider, used to surface a
+//! sensible default at the top of the model selector. Each provider lists
+//! patterns in order of preference and the first one matching an available
+//! model wins. `*` matches any run of characters; matching ignores case.
+//!
+//! The built-in table can be overridden with `recommended_models.json` in the
+//! data directory (`~/.moly` unless `MOLY_DATA_DIR` is set),
+//! a map of provider id to patterns:
+//!
+//! ```json
+//! { "openai": ["gpt-5*", "gpt-4.1*"], "my-litellm": ["llama-3.3-70b*"] }
+//! ```
+//!
+//! Providers missing from the file keep their built-in patterns.
+//!
+//! Connection tests that send a real chat request pick the cheapest model
+//! they can instead, see [`cheapest_test_model`].
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+const RECOMMENDATIONS_FILENAME: &str = "recommended_models.json";
+
+/// Built-in patterns, most preferred first
+const BUILTIN_RECOMMENDATIONS: &[(&str, &[&str])] = &[
+    ("openai", &["gpt-5", "gpt-4.1", "gpt-4o"]),
+    ("anthropic", &["claude-sonnet-4*", "claude-3-7-sonnet*", "claude-3-5-sonnet*"]),
+    ("gemini", &["gemini-2.5-flash", "gemini-2.0-flash", "gemini-1.5-pro*"]),
+    ("deepseek", &["deepseek-chat"]),
+    ("groq", &["llama-3.3-70b*", "llama-3.1-8b*"]),
+    ("openrouter", &["openai/gpt-4o", "anthropic/claude-sonnet-4*", "meta-llama/llama-3.3-70b-instruct"]),
+    ("siliconflow", &["deepseek-ai/DeepSeek-V3*", "Qwen/Qwen2.5-72B-Instruct"]),
+    ("nvidia", &["meta/llama-3.3-70b-instruct", "meta/llama-3.1-70b-instruct"]),
+    ("ollama", &["llama3*", "qwen2.5*", "mistral*"]),
+];
+
+/// Small, cheap chat models across providers, cheapest first
+const TEST_MODEL_PATTERNS: &[&str] = &[
+    "*nano*", "*haiku*", "*flash-lite*", "*mini*", "*flash*", "*8b*", "*lite*", "*small*", "*3.5-turbo*",
+];
+
+/// Models that can't answer a plain chat request: embeddings, speech, images,
+/// and reasoning models that reject `max_tokens`
+const NON_CHAT_MODEL_PATTERNS: &[&str] = &[
+    "*embed*", "*whisper*", "*tts*", "*transcribe*", "*audio*", "*realtime*", "*dall-e*", "*image*",
+    "*moderation*", "*rerank*", "o1*", "o3*", "o4*",
+];
+
+/// The model a connection test should send its chat request to: the cheapest
+/// chat model among `models`, or else the first chat model
+pub fn cheapest_test_model<'a>(models: &[&'a str]) -> Option<&'a str> {
+    let chat_models: Vec<&str> = models.iter().copied()
+        .filter(|model| {
+            let name = model.strip_prefix("models/").unwrap_or(model);
+            !NON_CHAT_MODEL_PATTERNS.iter().any(|pattern| glob_matches(pattern, name))
+        })
+        .collect();
+    TEST_MODEL_PATTERNS.iter()
+        .find_map(|pattern| chat_models.iter().copied().find(|model| glob_matches(pattern, model)))
+        .or_else(|| chat_models.first().copied())
+}
+
+/// Recommended model patterns per provider
+#[derive(Clone, Debug, Default)]
+pub struct Recommendations {
+    patterns: HashMap<String, Vec<String>>,
+}
+
+impl Recommendations {
+    /// The built-in table only
+    pub fn builtin() -> Self {
+        let patterns = BUILTIN_RECOMMENDATIONS
+            .iter()
+            .map(|(provider_id, patterns)| {
+                (provider_id.to_string(), patterns.iter().map(|p| p.to_string()).collect())
+            })
+            .collect();
+        Self { patterns }
+    }
+
+    /// The built-in table with any overrides from the data directory applied
+    pub fn load() -> Self {
+        let mut recommendations = Self::builtin();
+        let path = Self::file_path();
+
+        let Ok(contents) = std::fs::read_to_string(&path) else {
+            return recommendations;
+        };
+        match serde_json::from_str::<HashMap<String, Vec<String>>>(&contents) {
+            Ok(overrides) => {
+                log::info!("Loaded model recommendations for {} providers from {:?}", overrides.len(), path);
+                recommendations.patterns.extend(overrides);
+            }
+            Err(e) => {
+                log::warn!("Ignoring invalid model recommendations file {:?}: {:?}", path, e);
+            }
+        }
+        recommendations
+    }
+
+    fn file_path() -> PathBuf {
+        crate::files::data_dir().join(RECOMMENDATIONS_FILENAME)
+    }
+
+    /// Patterns for a provider, most preferred first
+    pub fn patterns(&self, provider_id: &str) -> &[String] {
+        self.patterns.get(provider_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// The model to recommend among `models`, if any matches the provider's patterns.
+    /// A `models/` prefix (as used by Gemini) is ignored when matching.
+    pub fn recommend<'a>(&self, provider_id: &str, models: &[&'a str]) -> Option<&'a str> {
+        self.patterns(provider_id).iter().find_map(|pattern| {
+            models.iter().copied().find(|model| {
+                let name = model.strip_prefix("models/").unwrap_or(model);
+                glob_matches(pattern, name)
+            })
+        })
+    }
+}
+
+/// Case-insensitive match of `text` against `pattern`, where `*` matches any run
+pub(crate) fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and the text position it currently covers up to
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star_p, star_t)) = star {
+            // Let the last `*` swallow one more character
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tests_with_the_cheapest_chat_model() {
+        let models = ["gpt-4o", "text-embedding-3-small", "gpt-4o-mini-tts", "o4-mini", "gpt-4o-mini"];
+        assert_eq!(cheapest_test_model(&models), Some("gpt-4o-mini"));
+        assert_eq!(cheapest_test_model(&["llama3.3", "nomic-embed-text"]), Some("llama3.3"));
+        assert_eq!(cheapest_test_model(&["text-embedding-3-large"]), None);
+    }
+}
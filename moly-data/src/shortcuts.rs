@@ -0,0 +1,231 @@
+//! Keyboard shortcut registry
+//!
+//! Every rebindable shortcut is declared once in `SHORTCUTS`. Users can
+//! override a binding in preferences (`shortcut_bindings`, keyed by id);
+//! widgets check key events through `Preferences::shortcut_pressed` so the
+//! help overlay and the handlers always agree.
+//!
+//! Bindings are written as `+`-separated modifiers and a key, e.g.
+//! `"Mod+Shift+ArrowUp"`. `Mod` is Cmd on macOS and Ctrl elsewhere. Several
+//! alternatives can be given separated by `,` (e.g. `"F1, ?"`).
+
+use makepad_widgets::{KeyCode, KeyEvent};
+
+/// Where a shortcut applies, used to group the help overlay
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShortcutContext {
+    Global,
+    Chat,
+    Settings,
+}
+
+impl ShortcutContext {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ShortcutContext::Global => "Global",
+            ShortcutContext::Chat => "Chat",
+            ShortcutContext::Settings => "Settings",
+        }
+    }
+}
+
+/// A rebindable shortcut and its default binding
+#[derive(Clone, Copy, Debug)]
+pub struct ShortcutDef {
+    pub id: &'static str,
+    pub context: ShortcutContext,
+    pub description: &'static str,
+    pub default_binding: &'static str,
+}
+
+pub const SHORTCUT_SHOW_HELP: &str = "show_shortcuts";
+pub const SHORTCUT_REUSE_LAST_PROMPT: &str = "reuse_last_prompt";
+pub const SHORTCUT_CLOSE_DIALOG: &str = "close_dialog";
+pub const SHORTCUT_NEW_CHAT: &str = "new_chat";
+pub const SHORTCUT_GO_TO_CHAT: &str = "go_to_chat";
+pub const SHORTCUT_GO_TO_MODELS: &str = "go_to_models";
+pub const SHORTCUT_GO_TO_SETTINGS: &str = "go_to_settings";
+
+/// All shortcuts, in the order the help overlay lists them
+pub const SHORTCUTS: &[ShortcutDef] = &[
+    ShortcutDef {
+        id: SHORTCUT_SHOW_HELP,
+        context: ShortcutContext::Global,
+        description: "Show keyboard shortcuts",
+        default_binding: "F1, ?",
+    },
+    ShortcutDef {
+        id: SHORTCUT_NEW_CHAT,
+        context: ShortcutContext::Global,
+        description: "Start a new chat",
+        default_binding: "Mod+N",
+    },
+    ShortcutDef {
+        id: SHORTCUT_GO_TO_CHAT,
+        context: ShortcutContext::Global,
+        description: "Go to Chat",
+        default_binding: "Mod+1",
+    },
+    ShortcutDef {
+        id: SHORTCUT_GO_TO_MODELS,
+        context: ShortcutContext::Global,
+        description: "Go to Models",
+        default_binding: "Mod+2",
+    },
+    ShortcutDef {
+        id: SHORTCUT_GO_TO_SETTINGS,
+        context: ShortcutContext::Global,
+        description: "Go to Settings",
+        default_binding: "Mod+3",
+    },
+    ShortcutDef {
+        id: SHORTCUT_REUSE_LAST_PROMPT,
+        context: ShortcutContext::Chat,
+        description: "Put the last prompt back in the input",
+        default_binding: "Mod+Shift+ArrowUp",
+    },
+    ShortcutDef {
+        id: SHORTCUT_CLOSE_DIALOG,
+        context: ShortcutContext::Settings,
+        description: "Close the Add Provider dialog",
+        default_binding: "Escape",
+    },
+];
+
+/// Look up a shortcut definition by id
+pub fn shortcut_def(id: &str) -> Option<&'static ShortcutDef> {
+    SHORTCUTS.iter().find(|def| def.id == id)
+}
+
+/// A single parsed key combination
+#[derive(Clone, Debug, PartialEq)]
+struct KeyCombo {
+    primary: bool,
+    shift: bool,
+    alt: bool,
+    key_code: KeyCode,
+}
+
+impl KeyCombo {
+    fn parse(text: &str) -> Option<Self> {
+        let mut combo = KeyCombo { primary: false, shift: false, alt: false, key_code: KeyCode::Unknown };
+
+        // "?" is Shift+/ on the keyboards we support
+        if text == "?" {
+            combo.shift = true;
+            combo.key_code = KeyCode::Slash;
+            return Some(combo);
+        }
+
+        let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+        let key = parts.pop()?;
+        for modifier in parts {
+            match modifier.to_ascii_lowercase().as_str() {
+                "mod" | "ctrl" | "cmd" => combo.primary = true,
+                "shift" => combo.shift = true,
+                "alt" | "option" => combo.alt = true,
+                _ => return None,
+            }
+        }
+        combo.key_code = parse_key(key)?;
+        Some(combo)
+    }
+
+    fn matches(&self, event: &KeyEvent) -> bool {
+        event.key_code == self.key_code
+            && event.modifiers.is_primary() == self.primary
+            && event.modifiers.shift == self.shift
+            && event.modifiers.alt == self.alt
+    }
+}
+
+fn parse_key(key: &str) -> Option<KeyCode> {
+    let code = match key {
+        "Escape" | "Esc" => KeyCode::Escape,
+        "Enter" | "Return" => KeyCode::ReturnKey,
+        "Tab" => KeyCode::Tab,
+        "Space" => KeyCode::Space,
+        "ArrowUp" | "Up" => KeyCode::ArrowUp,
+        "ArrowDown" | "Down" => KeyCode::ArrowDown,
+        "ArrowLeft" | "Left" => KeyCode::ArrowLeft,
+        "ArrowRight" | "Right" => KeyCode::ArrowRight,
+        "/" => KeyCode::Slash,
+        "," => KeyCode::Comma,
+        "." => KeyCode::Period,
+        "F1" => KeyCode::F1,
+        "F2" => KeyCode::F2,
+        "F3" => KeyCode::F3,
+        "F4" => KeyCode::F4,
+        "F5" => KeyCode::F5,
+        "F6" => KeyCode::F6,
+        "F7" => KeyCode::F7,
+        "F8" => KeyCode::F8,
+        "F9" => KeyCode::F9,
+        "F10" => KeyCode::F10,
+        "F11" => KeyCode::F11,
+        "F12" => KeyCode::F12,
+        _ => {
+            let mut chars = key.chars();
+            let (Some(c), None) = (chars.next(), chars.next()) else { return None };
+            return letter_or_digit_key(c.to_ascii_uppercase());
+        }
+    };
+    Some(code)
+}
+
+fn letter_or_digit_key(c: char) -> Option<KeyCode> {
+    const LETTERS: [KeyCode; 26] = [
+        KeyCode::KeyA, KeyCode::KeyB, KeyCode::KeyC, KeyCode::KeyD, KeyCode::KeyE,
+        KeyCode::KeyF, KeyCode::KeyG, KeyCode::KeyH, KeyCode::KeyI, KeyCode::KeyJ,
+        KeyCode::KeyK, KeyCode::KeyL, KeyCode::KeyM, KeyCode::KeyN, KeyCode::KeyO,
+        KeyCode::KeyP, KeyCode::KeyQ, KeyCode::KeyR, KeyCode::KeyS, KeyCode::KeyT,
+        KeyCode::KeyU, KeyCode::KeyV, KeyCode::KeyW, KeyCode::KeyX, KeyCode::KeyY,
+        KeyCode::KeyZ,
+    ];
+    const DIGITS: [KeyCode; 10] = [
+        KeyCode::Key0, KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4,
+        KeyCode::Key5, KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+    ];
+    match c {
+        'A'..='Z' => Some(LETTERS[(c as u8 - b'A') as usize]),
+        '0'..='9' => Some(DIGITS[(c as u8 - b'0') as usize]),
+        _ => None,
+    }
+}
+
+/// Whether `event` matches any of the alternatives in `binding`
+pub fn binding_matches(binding: &str, event: &KeyEvent) -> bool {
+    binding
+        .split(',')
+        .filter_map(|alternative| KeyCombo::parse(alternative.trim()))
+        .any(|combo| combo.matches(event))
+}
+
+/// Whether every alternative in `binding` can be parsed
+pub fn is_valid_binding(binding: &str) -> bool {
+    binding.split(',').all(|alternative| KeyCombo::parse(alternative.trim()).is_some())
+}
+
+/// Human-readable form of a binding, e.g. "Ctrl+Shift+↑" or "⌘+Shift+↑"
+pub fn display_binding(binding: &str) -> String {
+    let primary = if cfg!(target_os = "macos") { "⌘" } else { "Ctrl" };
+    binding
+        .split(',')
+        .map(|alternative| {
+            alternative
+                .trim()
+                .split('+')
+                .map(|part| match part.trim() {
+                    "Mod" => primary,
+                    "ArrowUp" => "↑",
+                    "ArrowDown" => "↓",
+                    "ArrowLeft" => "←",
+                    "ArrowRight" => "→",
+                    other => other,
+                })
+                .collect::<Vec<_>>()
+                .join("+")
+        })
+        .collect::<Vec<_>>()
+        .join(" or ")
+}
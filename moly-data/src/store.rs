@@ -2,11 +2,17 @@ use makepad_widgets::*;
 use moly_kit::prelude::*;
 use std::sync::{Arc, Mutex};
 
-use crate::chats::Chats;
+use crate::chats::{ChatData, Chats};
+use crate::connectivity::Connectivity;
+use crate::downloads::Downloads;
+use crate::http::ProxySettings;
 use crate::mcp_servers::McpServersConfig;
-use crate::moly_client::MolyClient;
+use crate::moly_client::{MolyClient, ServerConnectionStatus};
 use crate::preferences::Preferences;
+use crate::providers::{ProviderConnectionStatus, ProviderId, ProviderPreferences};
 use crate::providers_manager::ProvidersManager;
+use crate::pricing::Pricing;
+use crate::recommendations::Recommendations;
 
 /// Actions that can be dispatched to modify the Store
 #[derive(Clone, Debug, DefaultNone)]
@@ -21,10 +27,27 @@ pub enum StoreAction {
     SetSidebarExpanded(bool),
     /// Navigate to a specific view
     Navigate(String),
+    /// The model selected in chat changed (None when nothing is selected)
+    ActiveModelChanged(Option<ActiveModel>),
+    /// The Moly Server connection status changed (posted by `MolyClient`)
+    ServerStatusChanged(ServerConnectionStatus),
+    /// The network went offline (false) or came back (true) (posted by `Connectivity`)
+    ConnectivityChanged(bool),
     /// No action
     None,
 }
 
+/// Model currently selected in chat
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActiveModel {
+    /// Display name of the model
+    pub name: String,
+    /// Provider serving the model
+    pub provider_id: String,
+    /// Path of the provider's icon, if it has one
+    pub icon_path: Option<String>,
+}
+
 /// Central state container for the application
 ///
 /// The Store holds all shared application state and is passed down
@@ -46,6 +69,15 @@ pub enum StoreAction {
 /// let store = scope.data.get_mut::<Store>().unwrap();
 /// // Modify store
 /// ```
+///
+/// Without any UI, e.g. from an integration test or a CLI:
+/// ```rust,ignore
+/// let preferences = Preferences::load();
+/// let chats = Chats::load(&preferences.chat_retention);
+/// let mut store = Store::new(preferences, chats);
+/// let provider_id = store.add_provider("Local", "http://localhost:8080/v1", None)?;
+/// let result = store.test_provider(&provider_id).await;
+/// ```
 pub struct Store {
     /// User preferences (persisted to disk)
     pub preferences: Preferences,
@@ -62,6 +94,24 @@ pub struct Store {
     /// Moly Server client for model discovery and downloads
     pub moly_client: MolyClient,
 
+    /// Whether the network is reachable, rechecked in the background
+    pub connectivity: Connectivity,
+
+    /// Active model downloads (shared with background tasks)
+    pub downloads: Downloads,
+
+    /// Recommended model patterns per provider, for the model selector
+    pub recommendations: Recommendations,
+
+    /// Input token prices of known models, for send cost estimates
+    pub pricing: Pricing,
+
+    /// Model currently selected in chat, reported via `StoreAction::ActiveModelChanged`
+    pub active_model: Option<ActiveModel>,
+
+    /// Result of a background provider check, applied by `apply_provider_check_result`
+    provider_check_result: Arc<Mutex<Option<(ProviderId, ProviderConnectionStatus)>>>,
+
     /// Whether the Store has been fully initialized
     pub initialized: bool,
 }
@@ -76,6 +126,12 @@ impl Default for Store {
             chat_controller: None,
             providers_manager: ProvidersManager::new(),
             moly_client: MolyClient::new(),
+            connectivity: Connectivity::default(),
+            downloads: Downloads::new(),
+            recommendations: Recommendations::builtin(),
+            pricing: Pricing::builtin(),
+            active_model: None,
+            provider_check_result: Arc::new(Mutex::new(None)),
             initialized: false,
         }
     }
@@ -85,7 +141,20 @@ impl Store {
     /// Create a new Store by loading preferences from disk
     pub fn load() -> Self {
         let preferences = Preferences::load();
+        let chats = Chats::load(&preferences.chat_retention);
+        let store = Self::new(preferences, chats);
+
+        // Pick up downloads left running by a previous session
+        store.restore_pending_downloads();
+        store.connectivity.start_monitoring();
 
+        store
+    }
+
+    /// Create a Store from already loaded state, without starting any
+    /// background work. Needs no widgets, so it also serves tests and
+    /// command-line tools; set `MOLY_DATA_DIR` to keep their data apart.
+    pub fn new(preferences: Preferences, chats: Chats) -> Self {
         // Create a ChatController with basic async spawner
         let chat_controller = ChatController::new_arc();
         {
@@ -96,13 +165,12 @@ impl Store {
         // Create ProvidersManager and configure with enabled providers
         let mut providers_manager = ProvidersManager::new();
         let enabled_providers: Vec<_> = preferences.get_enabled_providers();
-        providers_manager.configure_providers(&enabled_providers);
-
-        // Load chats from disk
-        let chats = Chats::load();
+        providers_manager.configure_providers(&enabled_providers, &preferences.proxy);
 
         // Create MolyClient for model discovery
-        let moly_client = MolyClient::new();
+        let mut moly_client = MolyClient::new();
+        moly_client.set_proxy(&preferences.proxy);
+        let connectivity = Connectivity::new(&preferences.proxy);
 
         Self {
             preferences,
@@ -110,14 +178,131 @@ impl Store {
             chat_controller: Some(chat_controller),
             providers_manager,
             moly_client,
+            connectivity,
+            downloads: Downloads::new(),
+            recommendations: Recommendations::load(),
+            pricing: Pricing::load(),
+            active_model: None,
+            provider_check_result: Arc::new(Mutex::new(None)),
             initialized: true,
         }
     }
 
-    /// Reconfigure providers manager when provider settings change
+    /// All chats, most recently accessed first
+    pub fn list_chats(&self) -> Vec<&ChatData> {
+        self.chats.get_sorted_chats()
+    }
+
+    /// All providers, in the order they are listed in Settings
+    pub fn providers(&self) -> &[ProviderPreferences] {
+        &self.preferences.providers_preferences
+    }
+
+    /// Add a custom provider, save it and configure its client if it's
+    /// enabled. Returns the new provider's id.
+    pub fn add_provider(&mut self, name: &str, url: &str, api_key: Option<String>) -> Result<ProviderId, String> {
+        let id = self.preferences.add_custom_provider(name, url, api_key)?;
+        self.reconfigure_providers();
+        Ok(id)
+    }
+
+    /// Check that a provider is reachable with its saved settings. The result
+    /// is returned, not persisted; see `Preferences::set_provider_connection_status`.
+    pub async fn test_provider(&self, provider_id: &str) -> Result<(), String> {
+        let provider = self.preferences.get_provider(&provider_id.to_string())
+            .ok_or_else(|| format!("Provider '{}' not found", provider_id))?;
+        check_provider(provider.clone(), self.preferences.proxy.clone()).await
+    }
+
+    /// Fetch pending downloads from Moly Server in the background and track
+    /// any in-progress or paused ones. Does nothing if the server is not running.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn restore_pending_downloads(&self) {
+        let moly_client = self.moly_client.clone();
+        let downloads = self.downloads.clone();
+
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+
+            rt.block_on(async {
+                match moly_client.get_pending_downloads().await {
+                    Ok(pending) => {
+                        let restored = downloads.restore_from_pending(&pending);
+                        if restored > 0 {
+                            log::info!("Restored {} pending download(s)", restored);
+                        }
+                    }
+                    Err(e) => {
+                        log::info!("Skipping download restore, Moly Server unavailable: {}", e);
+                    }
+                }
+            });
+        });
+    }
+
+    /// Restoring downloads is not supported on wasm
+    #[cfg(target_arch = "wasm32")]
+    pub fn restore_pending_downloads(&self) {}
+
+    /// Re-test only the active provider in the background.
+    /// The result is persisted once `apply_provider_check_result` picks it up.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn retest_active_provider(&self) {
+        let Some(provider_id) = self.providers_manager.active_provider_id() else { return };
+        let Some(provider) = self.preferences.get_provider(&provider_id.to_string()) else { return };
+        // Offline, the test could only fail and mark the provider broken
+        if !provider.has_api_key() || !self.is_online() {
+            return;
+        }
+
+        let provider_id = provider.id.clone();
+        let provider = provider.clone();
+        let proxy = self.preferences.proxy.clone();
+        let result_slot = self.provider_check_result.clone();
+
+        log::info!("Re-testing active provider {}", provider_id);
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+
+            let status = match rt.block_on(check_provider(provider, proxy)) {
+                Ok(()) => ProviderConnectionStatus::Connected,
+                Err(e) => ProviderConnectionStatus::Error(e),
+            };
+            if let Ok(mut guard) = result_slot.lock() {
+                *guard = Some((provider_id, status));
+            }
+        });
+    }
+
+    /// Provider re-tests are not supported on wasm
+    #[cfg(target_arch = "wasm32")]
+    pub fn retest_active_provider(&self) {}
+
+    /// Persist the result of a finished background provider check.
+    /// Returns true if a result was applied.
+    pub fn apply_provider_check_result(&mut self) -> bool {
+        let result = self.provider_check_result.lock().ok().and_then(|mut guard| guard.take());
+        if let Some((provider_id, status)) = result {
+            self.preferences.set_provider_connection_status(&provider_id, status);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Reconfigure providers manager when provider or proxy settings change
     pub fn reconfigure_providers(&mut self) {
+        self.moly_client.set_proxy(&self.preferences.proxy);
+        self.connectivity.set_proxy(&self.preferences.proxy);
+
         let enabled_providers: Vec<_> = self.preferences.get_enabled_providers();
-        self.providers_manager.configure_providers(&enabled_providers);
+        self.providers_manager.configure_providers(&enabled_providers, &self.preferences.proxy);
     }
 
     /// Get a reference to the ChatController
@@ -125,6 +310,11 @@ impl Store {
         self.chat_controller.clone()
     }
 
+    /// Whether the network was reachable at the last check
+    pub fn is_online(&self) -> bool {
+        self.connectivity.is_online()
+    }
+
     /// Check if dark mode is enabled
     pub fn is_dark_mode(&self) -> bool {
         self.preferences.dark_mode
@@ -183,6 +373,13 @@ impl Store {
             StoreAction::Navigate(view) => {
                 self.set_current_view(view);
             }
+            StoreAction::ActiveModelChanged(model) => {
+                self.active_model = model.clone();
+            }
+            // The status lives in `MolyClient`; the action only notifies widgets
+            StoreAction::ServerStatusChanged(_) => {}
+            // Likewise kept in `Connectivity`
+            StoreAction::ConnectivityChanged(_) => {}
             StoreAction::None => {}
         }
     }
@@ -230,7 +427,8 @@ impl Store {
             return tool_manager;
         }
 
-        let mcp_config = self.get_mcp_servers_config().clone();
+        // Resolve ${VAR} references only at launch so secrets never reach the saved config
+        let mcp_config = self.get_mcp_servers_config().resolve_env();
         tool_manager.set_dangerous_mode_enabled(mcp_config.dangerous_mode_enabled);
         let tool_manager_clone = tool_manager.clone();
 
@@ -258,3 +456,20 @@ impl Store {
         moly_kit::prelude::McpManagerClient::new()
     }
 }
+
+/// Check that a provider's models endpoint answers with its saved settings
+async fn check_provider(provider: ProviderPreferences, proxy: ProxySettings) -> Result<(), String> {
+    let api_key = provider.api_key.clone().filter(|key| !key.trim().is_empty())
+        .ok_or_else(|| "No API key provided".to_string())?;
+    let models_url = provider.models_url()
+        .unwrap_or_else(|| format!("{}/models", provider.url.trim_end_matches('/')));
+    ProvidersManager::check_provider_reachable(
+        &models_url,
+        api_key.trim(),
+        &provider.provider_type,
+        &provider.custom_headers,
+        provider.allow_invalid_certs,
+        &proxy,
+        provider.connection_timeout(),
+    ).await
+}
@@ -0,0 +1,14 @@
+//! Token estimates
+//!
+//! Rough token counts used to warn before large requests are sent. Tokenizers
+//! differ per model and none is bundled, so this assumes about four characters
+//! per token, which is close for English text and code with common BPE
+//! vocabularies and errs high for CJK text.
+
+/// Characters assumed per token
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Estimated number of tokens in `text`
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
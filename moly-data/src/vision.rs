@@ -0,0 +1,53 @@
+//! Image input
+//!
+//! Providers don't report which models accept images in their OpenAI-style
+//! model listings, so vision support is inferred from well-known model name
+//! patterns. Models that aren't recognized are treated as text-only.
+
+use std::path::Path;
+
+/// Largest image attached to a message; providers reject bigger payloads
+pub const MAX_IMAGE_BYTES: u64 = 20 * 1024 * 1024;
+
+/// Model name fragments of vision-capable model families
+const VISION_MODEL_PATTERNS: &[&str] = &[
+    "gpt-4o",
+    "gpt-4.1",
+    "gpt-4-turbo",
+    "gpt-5",
+    "o1",
+    "o3",
+    "o4",
+    "claude-3",
+    "claude-opus-4",
+    "claude-sonnet-4",
+    "gemini",
+    "vision",
+    "-vl",
+    "llava",
+    "pixtral",
+    "gemma-3",
+    "llama-4",
+];
+
+/// Whether a model is known to accept image input
+pub fn supports_image_input(model_name: &str) -> bool {
+    let name = model_name.to_lowercase();
+    // Strip an "org/" prefix so patterns matching the start of a name still apply
+    let name = name.rsplit('/').next().unwrap_or(&name);
+    VISION_MODEL_PATTERNS.iter().any(|pattern| {
+        name.contains(pattern) && (pattern.len() > 2 || name.starts_with(pattern))
+    })
+}
+
+/// MIME type of an image file by extension, None for unsupported formats
+pub fn image_mime_type(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    match extension.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        _ => None,
+    }
+}
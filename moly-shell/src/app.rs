@@ -1,7 +1,13 @@
 use makepad_widgets::*;
 
-use moly_data::Store;
+use moly_data::{Store, StoreAction, WindowGeometry};
+use moly_data::shortcuts::{
+    display_binding, ShortcutContext, SHORTCUTS, SHORTCUT_GO_TO_CHAT, SHORTCUT_GO_TO_MODELS,
+    SHORTCUT_GO_TO_SETTINGS, SHORTCUT_NEW_CHAT, SHORTCUT_SHOW_HELP,
+};
+use moly_mcp::McpAppAction;
 use moly_widgets::MolyApp;
+use moly_widgets::toast::{Toast, ToastStackWidgetRefExt};
 
 live_design! {
     use link::theme::*;
@@ -76,6 +82,8 @@ live_design! {
             pass: {
                 clear_color: #f5f7fa
             }
+            // Toasts float over the whole app
+            flow: Overlay
 
             body = <View> {
                 width: Fill, height: Fill
@@ -141,6 +149,40 @@ live_design! {
 
                     <View> { width: Fill } // Spacer
 
+                    // Model selected in chat, visible from every screen (click to open chat)
+                    active_model_indicator = <RoundedView> {
+                        visible: false
+                        width: Fit, height: Fit
+                        margin: {right: 12}
+                        padding: {left: 10, right: 10, top: 6, bottom: 6}
+                        flow: Right, spacing: 6
+                        align: {y: 0.5}
+                        cursor: Hand
+                        show_bg: true
+                        draw_bg: {
+                            instance dark_mode: 0.0
+                            border_radius: 6.0
+                            fn get_color(self) -> vec4 {
+                                return mix(#f1f5f9, #334155, self.dark_mode);
+                            }
+                        }
+
+                        active_model_icon = <Image> {
+                            width: 16, height: 16
+                            fit: Smallest
+                        }
+                        active_model_label = <Label> {
+                            text: ""
+                            draw_text: {
+                                instance dark_mode: 0.0
+                                fn get_color(self) -> vec4 {
+                                    return mix(#374151, #e2e8f0, self.dark_mode);
+                                }
+                                text_style: <THEME_FONT_REGULAR>{ font_size: 11.0 }
+                            }
+                        }
+                    }
+
                     // Theme toggle button
                     theme_toggle = <View> {
                         width: 40, height: Fit
@@ -160,6 +202,32 @@ live_design! {
                     }
                 }
 
+                // Shown while the network is unreachable
+                offline_banner = <View> {
+                    visible: false
+                    width: Fill, height: Fit
+                    padding: {left: 16, right: 16, top: 6, bottom: 6}
+                    align: {x: 0.5, y: 0.5}
+                    show_bg: true
+                    draw_bg: {
+                        instance dark_mode: 0.0
+                        fn pixel(self) -> vec4 {
+                            return mix(#fef3c7, #78350f, self.dark_mode);
+                        }
+                    }
+
+                    offline_label = <Label> {
+                        text: "You're offline. Chats, downloaded models and local providers are still available; online providers reconnect automatically."
+                        draw_text: {
+                            instance dark_mode: 0.0
+                            fn get_color(self) -> vec4 {
+                                return mix(#92400e, #fde68a, self.dark_mode);
+                            }
+                            text_style: <THEME_FONT_REGULAR>{ font_size: 11.0 }
+                        }
+                    }
+                }
+
                 // Content area
                 content = <View> {
                     width: Fill, height: Fill
@@ -302,6 +370,68 @@ live_design! {
                     }
                 }
             }
+
+            // Keyboard shortcut help (F1 or ?)
+            shortcuts_overlay = <View> {
+                visible: false
+                width: Fill, height: Fill
+                align: {x: 0.5, y: 0.5}
+                show_bg: true
+                draw_bg: {
+                    fn pixel(self) -> vec4 {
+                        return vec4(0.0, 0.0, 0.0, 0.5); // Semi-transparent backdrop
+                    }
+                }
+
+                shortcuts_card = <RoundedView> {
+                    width: 480, height: Fit
+                    padding: 24
+                    flow: Down, spacing: 12
+                    show_bg: true
+                    draw_bg: {
+                        instance dark_mode: 0.0
+                        border_radius: 8.0
+                        fn get_color(self) -> vec4 {
+                            return mix(#ffffff, #1e293b, self.dark_mode);
+                        }
+                    }
+
+                    shortcuts_title = <Label> {
+                        text: "Keyboard Shortcuts"
+                        draw_text: {
+                            instance dark_mode: 0.0
+                            fn get_color(self) -> vec4 {
+                                return mix(#1f2937, #f1f5f9, self.dark_mode);
+                            }
+                            text_style: <THEME_FONT_BOLD>{ font_size: 16.0 }
+                        }
+                    }
+                    shortcuts_text = <Label> {
+                        width: Fill
+                        text: ""
+                        draw_text: {
+                            wrap: Word
+                            instance dark_mode: 0.0
+                            fn get_color(self) -> vec4 {
+                                return mix(#374151, #e2e8f0, self.dark_mode);
+                            }
+                            text_style: <THEME_FONT_REGULAR>{ font_size: 12.0 }
+                        }
+                    }
+                    shortcuts_hint = <Label> {
+                        text: "Press Escape or click outside to close"
+                        draw_text: {
+                            instance dark_mode: 0.0
+                            fn get_color(self) -> vec4 {
+                                return mix(#6b7280, #94a3b8, self.dark_mode);
+                            }
+                            text_style: <THEME_FONT_REGULAR>{ font_size: 10.0 }
+                        }
+                    }
+                }
+            }
+
+            toast_stack = <ToastStack> {}
         }
     }
 }
@@ -315,6 +445,18 @@ enum NavigationTarget {
     Settings,
 }
 
+impl NavigationTarget {
+    /// Parse a view name as persisted in preferences and sent in `StoreAction::Navigate`
+    fn from_view_name(name: &str) -> Self {
+        match name {
+            "Models" => NavigationTarget::Models,
+            "Mcp" => NavigationTarget::Mcp,
+            "Settings" => NavigationTarget::Settings,
+            _ => NavigationTarget::Chat,
+        }
+    }
+}
+
 #[derive(Live)]
 pub struct App {
     #[live]
@@ -325,8 +467,28 @@ pub struct App {
     current_view: NavigationTarget,
     #[rust]
     initialized: bool,
+    /// When the active provider was last re-tested on focus (for debouncing)
+    #[rust]
+    last_focus_retest: Option<std::time::Instant>,
+    /// Navigation held back until the MCP screen's unsaved edits are resolved
+    #[rust]
+    pending_navigation: Option<NavigationTarget>,
+    /// Whether the keyboard shortcut help is showing
+    #[rust]
+    shortcuts_visible: bool,
+    /// Latest window geometry, saved once resizing or moving settles
+    #[rust]
+    pending_window_geometry: Option<WindowGeometry>,
+    #[rust]
+    window_geometry_timer: Timer,
 }
 
+/// Minimum time between provider re-tests triggered by focus changes
+const FOCUS_RETEST_DEBOUNCE: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Seconds without resizing or moving before the window geometry is saved
+const WINDOW_GEOMETRY_SAVE_DELAY: f64 = 1.0;
+
 impl LiveHook for App {
     fn after_new_from_doc(&mut self, _cx: &mut Cx) {
         if !self.initialized {
@@ -334,12 +496,7 @@ impl LiveHook for App {
             self.store = Store::load();
 
             // Set current_view from loaded preferences
-            self.current_view = match self.store.current_view() {
-                "Models" => NavigationTarget::Models,
-                "Mcp" => NavigationTarget::Mcp,
-                "Settings" => NavigationTarget::Settings,
-                _ => NavigationTarget::Chat,
-            };
+            self.current_view = NavigationTarget::from_view_name(self.store.current_view());
 
             self.initialized = true;
             ::log::info!("App initialized via LiveHook, store loaded from disk");
@@ -351,6 +508,7 @@ impl LiveRegister for App {
     fn live_register(cx: &mut Cx) {
         makepad_widgets::live_design(cx);
         moly_widgets::live_design(cx);
+        moly_widgets::toast::live_design(cx);
         // Register moly-kit widgets (Chat, Messages, PromptInput, etc.)
         moly_kit::widgets::live_design(cx);
         // Register app widgets from external app crates via MolyApp trait
@@ -368,6 +526,8 @@ impl MatchEvent for App {
         self.update_sidebar(cx);
         // Force apply view state on startup (bypass same-view check)
         self.apply_view_state(cx, self.current_view);
+        self.restore_session_state(cx);
+        self.restore_window_geometry(cx);
         ::log::info!("App initialized with Store");
     }
 
@@ -397,6 +557,48 @@ impl MatchEvent for App {
         if self.ui.view(ids!(settings_btn)).finger_down(&actions).is_some() {
             self.navigate_to(cx, NavigationTarget::Settings);
         }
+        if self.ui.view(ids!(active_model_indicator)).finger_down(&actions).is_some() {
+            self.navigate_to(cx, NavigationTarget::Chat);
+        }
+
+        // Toasts emitted by any screen
+        for action in actions.iter() {
+            if let Some(toast) = action.downcast_ref::<Toast>() {
+                self.ui.toast_stack(ids!(toast_stack)).show(cx, toast.clone());
+            }
+        }
+
+        // Model selection reported by the chat app, and navigation requested by any screen
+        for action in actions.iter() {
+            match action.cast() {
+                action @ StoreAction::ActiveModelChanged(_) => {
+                    self.store.handle_action(&action);
+                    self.update_active_model(cx);
+                }
+                StoreAction::Navigate(view) => {
+                    self.navigate_to(cx, NavigationTarget::from_view_name(&view));
+                }
+                StoreAction::ConnectivityChanged(online) => {
+                    self.ui.view(ids!(offline_banner)).set_visible(cx, !online);
+                    // Screens pick the change up on their next event, e.g. the chat refetches models
+                    self.ui.redraw(cx);
+                }
+                _ => {}
+            }
+        }
+
+        // Resolve a navigation held back by unsaved MCP edits
+        for action in actions.iter() {
+            match action.cast() {
+                McpAppAction::LeaveConfirmed => {
+                    if let Some(target) = self.pending_navigation.take() {
+                        self.switch_view(cx, target);
+                    }
+                }
+                McpAppAction::LeaveCancelled => self.pending_navigation = None,
+                McpAppAction::None => {}
+            }
+        }
     }
 }
 
@@ -406,6 +608,26 @@ impl AppMain for App {
         // IMPORTANT: ui.handle_event must be called BEFORE match_event
         // because actions are generated during handle_event and then
         // processed by match_event's handle_actions
+        if let Event::AppGotFocus = event {
+            self.maybe_retest_active_provider();
+        }
+
+        // Losing focus covers the app being killed rather than closed
+        if let Event::AppLostFocus | Event::Shutdown = event {
+            self.save_session_state();
+            self.save_window_geometry();
+        }
+
+        self.track_window_geometry(cx, event);
+
+        // Persist the result of a finished provider re-test
+        if self.store.apply_provider_check_result() {
+            self.ui.redraw(cx);
+        }
+
+        self.handle_shortcuts_overlay(cx, event);
+        self.handle_global_shortcuts(cx, event);
+
         let scope = &mut Scope::with_data(&mut self.store);
         self.ui.handle_event(cx, event, scope);
 
@@ -422,6 +644,26 @@ impl App {
             return;
         }
 
+        // Don't silently drop edits in the MCP editor
+        if self.current_view == NavigationTarget::Mcp {
+            let has_unsaved_changes = self.ui.widget(ids!(mcp_app))
+                .borrow::<moly_mcp::McpApp>()
+                .map_or(false, |mcp_app| mcp_app.has_unsaved_changes());
+            if has_unsaved_changes {
+                self.pending_navigation = Some(target);
+                if let Some(mut mcp_app) = self.ui.widget(ids!(mcp_app)).borrow_mut::<moly_mcp::McpApp>() {
+                    mcp_app.request_leave(cx);
+                }
+                return;
+            }
+        }
+
+        self.switch_view(cx, target);
+    }
+
+    /// Switch to a view and persist it, without any unsaved-changes checks
+    fn switch_view(&mut self, cx: &mut Cx, target: NavigationTarget) {
+        self.save_session_state();
         self.current_view = target;
 
         // Persist to Store
@@ -436,6 +678,82 @@ impl App {
         self.apply_view_state(cx, target);
     }
 
+    /// Persist each app's view state so the next launch reopens where the user left off
+    fn save_session_state(&mut self) {
+        let preferences = &mut self.store.preferences;
+        if let Some(chat_app) = self.ui.widget(ids!(chat_app)).borrow::<moly_chat::screen::ChatApp>() {
+            preferences.set_app_session_state(moly_chat::MolyChatApp::info().id, &chat_app.session_state());
+        }
+        if let Some(models_app) = self.ui.widget(ids!(models_app)).borrow::<moly_models::ModelsApp>() {
+            preferences.set_app_session_state(moly_models::MolyModelsApp::info().id, &models_app.session_state());
+        }
+        if let Some(settings_app) = self.ui.widget(ids!(settings_app)).borrow::<moly_settings::SettingsApp>() {
+            preferences.set_app_session_state(moly_settings::MolySettingsApp::info().id, &settings_app.session_state());
+        }
+    }
+
+    /// Hand each app its saved view state. Missing or unreadable state leaves the app's defaults.
+    fn restore_session_state(&mut self, cx: &mut Cx) {
+        let preferences = &self.store.preferences;
+        if let Some(state) = preferences.app_session_state(moly_chat::MolyChatApp::info().id) {
+            if let Some(mut chat_app) = self.ui.widget(ids!(chat_app)).borrow_mut::<moly_chat::screen::ChatApp>() {
+                chat_app.restore_session_state(state);
+            }
+        }
+        if let Some(state) = preferences.app_session_state(moly_models::MolyModelsApp::info().id) {
+            if let Some(mut models_app) = self.ui.widget(ids!(models_app)).borrow_mut::<moly_models::ModelsApp>() {
+                models_app.restore_session_state(cx, state);
+            }
+        }
+        if let Some(state) = preferences.app_session_state(moly_settings::MolySettingsApp::info().id) {
+            if let Some(mut settings_app) = self.ui.widget(ids!(settings_app)).borrow_mut::<moly_settings::SettingsApp>() {
+                settings_app.restore_session_state(state);
+            }
+        }
+    }
+
+    /// Reopen the window where it was last left, within `WindowGeometry::clamped`
+    fn restore_window_geometry(&mut self, cx: &mut Cx) {
+        let Some(geometry) = self.store.preferences.window_geometry else { return };
+        let ((width, height), position) = geometry.clamped();
+        self.ui.apply_over(cx, live! {
+            window: { inner_size: (vec2(width as f32, height as f32)) }
+        });
+        if let Some((x, y)) = position {
+            self.ui.apply_over(cx, live! {
+                window: { position: (vec2(x as f32, y as f32)) }
+            });
+        }
+    }
+
+    /// Note resizes and moves, saving once they settle. Fullscreen isn't remembered.
+    fn track_window_geometry(&mut self, cx: &mut Cx, event: &Event) {
+        if let Event::WindowGeomChange(geom_event) = event {
+            let geom = &geom_event.new_geom;
+            if !geom.is_fullscreen {
+                self.pending_window_geometry = Some(WindowGeometry {
+                    width: geom.inner_size.x,
+                    height: geom.inner_size.y,
+                    x: geom.position.x,
+                    y: geom.position.y,
+                });
+                cx.stop_timer(self.window_geometry_timer);
+                self.window_geometry_timer = cx.start_timeout(WINDOW_GEOMETRY_SAVE_DELAY);
+            }
+        }
+        if self.window_geometry_timer.is_event(event).is_some() {
+            self.save_window_geometry();
+        }
+    }
+
+    fn save_window_geometry(&mut self) {
+        if let Some(geometry) = self.pending_window_geometry.take() {
+            if self.store.preferences.window_geometry != Some(geometry) {
+                self.store.preferences.set_window_geometry(geometry);
+            }
+        }
+    }
+
     /// Apply UI state for the given view (visibility and button selection)
     fn apply_view_state(&mut self, cx: &mut Cx, target: NavigationTarget) {
         // Update app visibility
@@ -468,6 +786,109 @@ impl App {
         self.ui.redraw(cx);
     }
 
+    /// New chat and view switching shortcuts. They all need Cmd/Ctrl by default,
+    /// so they also apply while typing.
+    fn handle_global_shortcuts(&mut self, cx: &mut Cx, event: &Event) {
+        let Event::KeyDown(key_event) = event else { return };
+        if self.shortcuts_visible {
+            return;
+        }
+        let preferences = &self.store.preferences;
+
+        if preferences.shortcut_pressed(SHORTCUT_NEW_CHAT, key_event) {
+            self.navigate_to(cx, NavigationTarget::Chat);
+            // Navigation may be held back by unsaved MCP edits
+            if self.current_view == NavigationTarget::Chat {
+                if let Some(mut chat_app) = self.ui.widget(ids!(chat_app)).borrow_mut::<moly_chat::screen::ChatApp>() {
+                    chat_app.create_new_chat(cx, &mut Scope::with_data(&mut self.store));
+                }
+            }
+        } else if preferences.shortcut_pressed(SHORTCUT_GO_TO_CHAT, key_event) {
+            self.navigate_to(cx, NavigationTarget::Chat);
+        } else if preferences.shortcut_pressed(SHORTCUT_GO_TO_MODELS, key_event) {
+            self.navigate_to(cx, NavigationTarget::Models);
+        } else if preferences.shortcut_pressed(SHORTCUT_GO_TO_SETTINGS, key_event) {
+            self.navigate_to(cx, NavigationTarget::Settings);
+        }
+    }
+
+    /// Open the shortcut help on its shortcut; close it on Escape or a click outside the card
+    fn handle_shortcuts_overlay(&mut self, cx: &mut Cx, event: &Event) {
+        match event {
+            Event::KeyDown(key_event) if self.shortcuts_visible => {
+                if key_event.key_code == KeyCode::Escape {
+                    self.set_shortcuts_visible(cx, false);
+                }
+            }
+            Event::KeyDown(key_event) => {
+                // Plain keys like "?" must still be typeable in text inputs
+                let typing = !cx.has_key_focus(Area::Empty);
+                let has_modifier = key_event.modifiers.is_primary() || key_event.modifiers.alt;
+                let is_function_key = matches!(key_event.key_code,
+                    KeyCode::F1 | KeyCode::F2 | KeyCode::F3 | KeyCode::F4 | KeyCode::F5 | KeyCode::F6 |
+                    KeyCode::F7 | KeyCode::F8 | KeyCode::F9 | KeyCode::F10 | KeyCode::F11 | KeyCode::F12);
+                if (!typing || has_modifier || is_function_key)
+                    && self.store.preferences.shortcut_pressed(SHORTCUT_SHOW_HELP, key_event)
+                {
+                    self.set_shortcuts_visible(cx, true);
+                }
+            }
+            Event::FingerDown(finger_event) if self.shortcuts_visible => {
+                let card = self.ui.view(ids!(shortcuts_card)).area().rect(cx);
+                if !card.contains(finger_event.abs) {
+                    self.set_shortcuts_visible(cx, false);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn set_shortcuts_visible(&mut self, cx: &mut Cx, visible: bool) {
+        self.shortcuts_visible = visible;
+        if visible {
+            let text = self.shortcuts_help_text();
+            self.ui.label(ids!(shortcuts_text)).set_text(cx, &text);
+        }
+        self.ui.view(ids!(shortcuts_overlay)).set_visible(cx, visible);
+        self.ui.redraw(cx);
+    }
+
+    /// Shortcuts grouped by context, with the user's current bindings
+    fn shortcuts_help_text(&self) -> String {
+        let contexts = [ShortcutContext::Global, ShortcutContext::Chat, ShortcutContext::Settings];
+        contexts.iter()
+            .map(|context| {
+                let lines: Vec<String> = SHORTCUTS.iter()
+                    .filter(|def| def.context == *context)
+                    .map(|def| {
+                        let binding = display_binding(&self.store.preferences.shortcut_binding(def.id));
+                        format!("    {}  —  {}", binding, def.description)
+                    })
+                    .collect();
+                format!("{}\n{}", context.label(), lines.join("\n"))
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    /// Show the chat's selected model in the header
+    fn update_active_model(&mut self, cx: &mut Cx) {
+        let indicator = self.ui.view(ids!(active_model_indicator));
+        let Some(model) = self.store.active_model.clone() else {
+            indicator.set_visible(cx, false);
+            return;
+        };
+
+        indicator.set_visible(cx, true);
+        self.ui.label(ids!(active_model_label)).set_text(cx, &model.name);
+
+        let icon = self.ui.image(ids!(active_model_icon));
+        let icon_loaded = model.icon_path
+            .map_or(false, |path| icon.load_image_file_by_path(cx, std::path::Path::new(&path)).is_ok());
+        icon.set_visible(cx, icon_loaded);
+        self.ui.redraw(cx);
+    }
+
     fn update_theme(&mut self, cx: &mut Cx) {
         let dark_mode_value = if self.store.is_dark_mode() { 1.0 } else { 0.0 };
 
@@ -489,6 +910,32 @@ impl App {
         self.ui.icon(ids!(theme_toggle.theme_icon)).apply_over(cx, live! {
             draw_icon: { dark_mode: (dark_mode_value) }
         });
+        self.ui.view(ids!(active_model_indicator)).apply_over(cx, live! {
+            draw_bg: { dark_mode: (dark_mode_value) }
+        });
+        self.ui.view(ids!(offline_banner)).apply_over(cx, live! {
+            draw_bg: { dark_mode: (dark_mode_value) }
+        });
+        self.ui.label(ids!(offline_label)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode_value) }
+        });
+
+        // Shortcut help
+        self.ui.view(ids!(shortcuts_card)).apply_over(cx, live! {
+            draw_bg: { dark_mode: (dark_mode_value) }
+        });
+        self.ui.label(ids!(shortcuts_title)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode_value) }
+        });
+        self.ui.label(ids!(shortcuts_text)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode_value) }
+        });
+        self.ui.label(ids!(shortcuts_hint)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode_value) }
+        });
+        self.ui.label(ids!(active_model_label)).apply_over(cx, live! {
+            draw_text: { dark_mode: (dark_mode_value) }
+        });
 
         self.ui.view(ids!(sidebar)).apply_over(cx, live! {
             draw_bg: { dark_mode: (dark_mode_value) }
@@ -552,6 +999,18 @@ impl App {
         self.ui.redraw(cx);
     }
 
+    /// Re-test the active provider after the app regains focus (debounced, opt-out via preferences)
+    fn maybe_retest_active_provider(&mut self) {
+        if !self.store.preferences.retest_provider_on_focus {
+            return;
+        }
+        if self.last_focus_retest.map_or(false, |last| last.elapsed() < FOCUS_RETEST_DEBOUNCE) {
+            return;
+        }
+        self.last_focus_retest = Some(std::time::Instant::now());
+        self.store.retest_active_provider();
+    }
+
     fn update_sidebar(&mut self, cx: &mut Cx) {
         let expanded = self.store.is_sidebar_expanded();
         let width = if expanded { 250.0 } else { 60.0 };
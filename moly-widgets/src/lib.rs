@@ -1,7 +1,9 @@
 pub mod theme;
 pub mod app_trait;
+pub mod toast;
 
 pub use app_trait::{MolyApp, AppInfo, AppRegistry};
+pub use toast::{Toast, ToastAction, ToastLevel};
 
 use makepad_widgets::*;
 
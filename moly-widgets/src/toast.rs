@@ -0,0 +1,281 @@
+//! # Toasts - Transient Notifications
+//!
+//! Any screen can show a toast by emitting it as an action:
+//!
+//! ```rust,ignore
+//! use moly_widgets::toast::Toast;
+//!
+//! cx.action(Toast::success("Configuration saved"));
+//! cx.action(Toast::info("Chat deleted").with_action("Undo", "undo_delete_chat"));
+//! ```
+//!
+//! The shell forwards these to its `ToastStack`, which shows up to
+//! `MAX_VISIBLE_TOASTS` at once and dismisses each after its duration or when
+//! closed. Clicking a toast's action button emits `ToastAction::ActionClicked`
+//! with the key the toast was created with.
+
+use makepad_widgets::*;
+use std::time::{Duration, Instant};
+
+live_design! {
+    use link::theme::*;
+    use link::shaders::*;
+    use link::widgets::*;
+
+    ToastLinkButton = <Button> {
+        width: Fit, height: Fit
+        padding: {left: 4, right: 4, top: 2, bottom: 2}
+        draw_bg: {
+            fn pixel(self) -> vec4 {
+                return vec4(0.0, 0.0, 0.0, 0.0);
+            }
+        }
+        draw_text: {
+            instance hover: 0.0
+            fn get_color(self) -> vec4 {
+                return mix(#ffffff, #e0e7ff, self.hover);
+            }
+            text_style: <THEME_FONT_BOLD>{ font_size: 11.0 }
+        }
+    }
+
+    ToastItem = <RoundedView> {
+        visible: false
+        width: 340, height: Fit
+        padding: {left: 14, right: 8, top: 10, bottom: 10}
+        flow: Right, spacing: 8
+        align: {y: 0.5}
+        show_bg: true
+        draw_bg: {
+            // level: 0=info, 1=success, 2=error
+            instance level: 0.0
+            border_radius: 6.0
+            fn get_color(self) -> vec4 {
+                let info = #1f2937;
+                let success = #047857;
+                let error = #b91c1c;
+                return mix(mix(info, success, clamp(self.level, 0.0, 1.0)), error, clamp(self.level - 1.0, 0.0, 1.0));
+            }
+        }
+
+        message = <Label> {
+            width: Fill
+            text: ""
+            draw_text: {
+                wrap: Word
+                color: #ffffff
+                text_style: <THEME_FONT_REGULAR>{ font_size: 11.0 }
+            }
+        }
+        action_button = <ToastLinkButton> { visible: false }
+        dismiss_button = <ToastLinkButton> { text: "×" }
+    }
+
+    pub ToastStack = {{ToastStack}} {
+        width: Fill, height: Fill
+        flow: Down, spacing: 8
+        align: {x: 1.0, y: 1.0}
+        padding: 20
+
+        toast_0 = <ToastItem> {}
+        toast_1 = <ToastItem> {}
+        toast_2 = <ToastItem> {}
+    }
+}
+
+/// Number of toasts shown at once; older ones are dropped first
+pub const MAX_VISIBLE_TOASTS: usize = 3;
+
+/// How long a toast stays up unless it sets its own duration
+pub const DEFAULT_TOAST_DURATION: Duration = Duration::from_secs(4);
+
+/// Severity of a toast, which sets its color
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum ToastLevel {
+    #[default]
+    Info,
+    Success,
+    Error,
+}
+
+/// A transient notification. Emit it with `cx.action(...)` to show it.
+#[derive(Clone, Debug)]
+pub struct Toast {
+    pub message: String,
+    pub level: ToastLevel,
+    /// Optional button label and the key reported when it is clicked
+    pub action: Option<(String, String)>,
+    pub duration: Duration,
+}
+
+impl Toast {
+    pub fn new(level: ToastLevel, message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            level,
+            action: None,
+            duration: DEFAULT_TOAST_DURATION,
+        }
+    }
+
+    pub fn info(message: impl Into<String>) -> Self {
+        Self::new(ToastLevel::Info, message)
+    }
+
+    pub fn success(message: impl Into<String>) -> Self {
+        Self::new(ToastLevel::Success, message)
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::new(ToastLevel::Error, message)
+    }
+
+    /// Add a button (e.g. "Undo"); clicking it emits `ToastAction::ActionClicked(key)`
+    pub fn with_action(mut self, label: impl Into<String>, key: impl Into<String>) -> Self {
+        self.action = Some((label.into(), key.into()));
+        self
+    }
+
+    /// Keep the toast up for `duration` instead of the default
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+}
+
+/// Actions emitted by the ToastStack
+#[derive(Clone, Debug, DefaultNone)]
+pub enum ToastAction {
+    None,
+    /// The action button of a toast was clicked, with the toast's action key
+    ActionClicked(String),
+}
+
+/// A toast currently on screen
+struct ActiveToast {
+    toast: Toast,
+    expires_at: Instant,
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct ToastStack {
+    #[deref]
+    view: View,
+
+    /// Visible toasts, oldest first
+    #[rust]
+    toasts: Vec<ActiveToast>,
+
+    /// Checks for expired toasts while any are showing
+    #[rust]
+    expiry_timer: Timer,
+}
+
+impl Widget for ToastStack {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+        self.widget_match_event(cx, event, scope);
+
+        if self.expiry_timer.is_event(event).is_some() {
+            let now = Instant::now();
+            let before = self.toasts.len();
+            self.toasts.retain(|active| active.expires_at > now);
+            if self.toasts.len() != before {
+                self.sync_slots(cx);
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl WidgetMatchEvent for ToastStack {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, _scope: &mut Scope) {
+        let slots = [ids!(toast_0), ids!(toast_1), ids!(toast_2)];
+
+        for (index, slot) in slots.iter().enumerate() {
+            let Some(active) = self.toasts.get(index) else { break };
+            let item = self.view.view(*slot);
+
+            if item.button(ids!(action_button)).clicked(actions) {
+                if let Some((_, key)) = &active.toast.action {
+                    cx.action(ToastAction::ActionClicked(key.clone()));
+                }
+                self.dismiss(cx, index);
+                return;
+            }
+            if item.button(ids!(dismiss_button)).clicked(actions) {
+                self.dismiss(cx, index);
+                return;
+            }
+        }
+    }
+}
+
+impl ToastStack {
+    /// Show a toast, dropping the oldest one if the stack is full
+    pub fn show(&mut self, cx: &mut Cx, toast: Toast) {
+        if self.toasts.len() == MAX_VISIBLE_TOASTS {
+            self.toasts.remove(0);
+        }
+        let expires_at = Instant::now() + toast.duration;
+        self.toasts.push(ActiveToast { toast, expires_at });
+        self.sync_slots(cx);
+    }
+
+    fn dismiss(&mut self, cx: &mut Cx, index: usize) {
+        if index < self.toasts.len() {
+            self.toasts.remove(index);
+            self.sync_slots(cx);
+        }
+    }
+
+    /// Copy the visible toasts into the fixed slots and (re)arm the expiry timer
+    fn sync_slots(&mut self, cx: &mut Cx) {
+        let slots = [ids!(toast_0), ids!(toast_1), ids!(toast_2)];
+
+        for (index, slot) in slots.iter().enumerate() {
+            let item = self.view.view(*slot);
+            let Some(active) = self.toasts.get(index) else {
+                item.set_visible(cx, false);
+                continue;
+            };
+
+            let level = match active.toast.level {
+                ToastLevel::Info => 0.0,
+                ToastLevel::Success => 1.0,
+                ToastLevel::Error => 2.0,
+            };
+            item.set_visible(cx, true);
+            item.apply_over(cx, live! {
+                draw_bg: { level: (level) }
+            });
+            item.label(ids!(message)).set_text(cx, &active.toast.message);
+
+            let action_button = item.button(ids!(action_button));
+            action_button.set_visible(cx, active.toast.action.is_some());
+            if let Some((label, _)) = &active.toast.action {
+                action_button.set_text(cx, label);
+            }
+        }
+
+        if self.toasts.is_empty() {
+            cx.stop_timer(self.expiry_timer);
+            self.expiry_timer = Timer::empty();
+        } else if self.expiry_timer.is_empty() {
+            self.expiry_timer = cx.start_interval(0.25);
+        }
+        self.view.redraw(cx);
+    }
+}
+
+impl ToastStackRef {
+    /// Show a toast, dropping the oldest one if the stack is full
+    pub fn show(&self, cx: &mut Cx, toast: Toast) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.show(cx, toast);
+        }
+    }
+}